@@ -0,0 +1,65 @@
+use encoding_rs::Encoding;
+
+/// Info-ZIP Unicode Path extra field
+const UNICODE_PATH_EXTRA_ID: u16 = 0x7075;
+
+/// Decode a ZIP entry name from its raw bytes.
+///
+/// In order of preference: the Info-ZIP Unicode Path extra field (when its
+/// CRC matches the raw name), the raw bytes if they are valid UTF-8 (many
+/// tools write UTF-8 without setting the language-encoding flag), the
+/// configured `fallback` encoding, and finally the name as decoded by the
+/// zip crate (CP437 unless the UTF-8 flag is set).
+pub fn decode_zip_entry_name(
+    raw: &[u8],
+    extra: &[u8],
+    crate_name: &str,
+    fallback: Option<&'static Encoding>,
+) -> String {
+    let decoded = if let Some(unicode_name) = unicode_path_extra(raw, extra) {
+        unicode_name
+    } else if let Ok(utf8) = std::str::from_utf8(raw) {
+        utf8.to_string()
+    } else if let Some(encoding) = fallback {
+        let (text, _, _) = encoding.decode(raw);
+        text.into_owned()
+    } else {
+        crate_name.to_string()
+    };
+    normalize_entry_name(&decoded)
+}
+
+/// Replace control characters and characters Windows rejects in file names
+/// (`/` and `\` are kept as separators for the path sanitizer)
+pub fn normalize_entry_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            c if c.is_control() => '_',
+            '<' | '>' | ':' | '"' | '|' | '?' | '*' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// Readable form of a raw name for the report, e.g. `Gr\x81\xe1e.txt`
+pub fn escape_raw_name(raw: &[u8]) -> String {
+    raw.escape_ascii().to_string()
+}
+
+fn unicode_path_extra(raw: &[u8], extra: &[u8]) -> Option<String> {
+    let mut rest = extra;
+    while rest.len() >= 4 {
+        let id = u16::from_le_bytes([rest[0], rest[1]]);
+        let size = u16::from_le_bytes([rest[2], rest[3]]) as usize;
+        let data = rest.get(4..4 + size)?;
+        // version (1), CRC-32 of the raw name (4), UTF-8 name
+        if id == UNICODE_PATH_EXTRA_ID && data.len() > 5 && data[0] == 1 {
+            let crc = u32::from_le_bytes([data[1], data[2], data[3], data[4]]);
+            if crc == crc32fast::hash(raw) {
+                return std::str::from_utf8(&data[5..]).ok().map(|s| s.to_string());
+            }
+        }
+        rest = &rest[4 + size..];
+    }
+    None
+}
@@ -0,0 +1,57 @@
+use crate::hashing_service::{HashAlgorithm, HashingService};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Name of the checksum file for `algorithm`, e.g. `SHA512SUMS.txt`
+pub fn checksum_file_name(algorithm: HashAlgorithm) -> String {
+    format!("{}SUMS.txt", algorithm.column_label())
+}
+
+/// Write a checksum file covering every file in `export_path`, in the
+/// `<hex>  <path>` format read by `sha512sum -c`, `b3sum -c` and the like.
+/// Paths are relative to the export folder, with forward slashes, so the
+/// check runs from inside it on any platform.
+pub fn write_checksum_file(export_path: &Path, hashing_service: &HashingService) -> Result<PathBuf> {
+    let file_name = checksum_file_name(hashing_service.algorithm());
+    let path = export_path.join(&file_name);
+
+    let mut lines = String::new();
+    let files = WalkDir::new(export_path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.path() != path);
+    for file in files {
+        let relative = file
+            .path()
+            .strip_prefix(export_path)
+            .unwrap_or(file.path())
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let digest = hashing_service.hash_file(file.path())?;
+        lines.push_str(&format!("{}  {}\n", digest, relative));
+    }
+
+    fs::write(&path, lines).with_context(|| format!("Failed to write checksum file: {}", path.display()))?;
+    Ok(path)
+}
+
+/// `(digest, relative path)` pairs of a checksum file written by
+/// `write_checksum_file`; blank lines are skipped
+pub fn read_checksum_file(path: &Path) -> Result<Vec<(String, String)>> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read checksum file: {}", path.display()))?;
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (digest, relative) = line
+                .split_once("  ")
+                .with_context(|| format!("Malformed line in {}: {}", path.display(), line))?;
+            Ok((digest.to_lowercase(), relative.to_string()))
+        })
+        .collect()
+}
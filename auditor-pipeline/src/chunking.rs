@@ -0,0 +1,100 @@
+use crate::corpus_stats::CHARS_PER_TOKEN;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Exported formats split into parts; structured formats such as JSON or
+/// HTML would no longer parse
+const CHUNKABLE_EXTENSIONS: &[&str] = &["txt", "md", "csv", "tsv", "log"];
+
+/// Formats whose first line is a header, repeated at the top of every part
+const HEADER_EXTENSIONS: &[&str] = &["csv", "tsv"];
+
+/// Splitting of exported documents too long to give an LLM in one go, e.g.
+/// a 900-page PDF converted to text, into numbered parts.
+///
+/// Sizes are estimated tokens (about four characters per token).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkSettings {
+    /// Split documents estimated above this many tokens; 0 disables splitting
+    pub max_tokens: u64,
+    /// Tokens each part repeats from the end of the part before it, so
+    /// text cut at a part boundary is read whole in one of them
+    pub overlap_tokens: u64,
+}
+
+impl Default for ChunkSettings {
+    fn default() -> Self {
+        Self {
+            max_tokens: 0,
+            overlap_tokens: 500,
+        }
+    }
+}
+
+impl ChunkSettings {
+    pub fn is_enabled(&self) -> bool {
+        self.max_tokens > 0
+    }
+}
+
+/// Whether the exported file at `path` is of a format that is split
+pub fn is_chunkable(path: &Path) -> bool {
+    has_extension(path, CHUNKABLE_EXTENSIONS)
+}
+
+/// Whether every part of the file at `path` starts with its header line
+pub fn repeats_header(path: &Path) -> bool {
+    has_extension(path, HEADER_EXTENSIONS)
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| extensions.contains(&e.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// `text` in consecutive parts of at most `max_tokens` estimated tokens,
+/// each overlapping the one before it. Parts end after a line break where
+/// there is one in their second half, so lines aren't cut.
+pub fn split_text<'a>(text: &'a str, settings: &ChunkSettings) -> Vec<&'a str> {
+    let max_chars = (settings.max_tokens * CHARS_PER_TOKEN).max(1) as usize;
+    // Parts must move forward by at least half their length
+    let overlap_chars = ((settings.overlap_tokens * CHARS_PER_TOKEN) as usize).min(max_chars / 2);
+
+    let mut parts = Vec::new();
+    let mut start = 0;
+    loop {
+        let mut end = text[start..]
+            .char_indices()
+            .nth(max_chars)
+            .map(|(offset, _)| start + offset)
+            .unwrap_or(text.len());
+        if end == text.len() {
+            parts.push(&text[start..]);
+            return parts;
+        }
+        if let Some(newline) = text[start..end].rfind('\n') {
+            if newline >= (end - start) / 2 {
+                end = start + newline + 1;
+            }
+        }
+        parts.push(&text[start..end]);
+
+        // The next part starts `overlap_chars` back, at a line start if the
+        // overlap holds one
+        let back = text[..end]
+            .char_indices()
+            .rev()
+            .take(overlap_chars)
+            .last()
+            .map(|(offset, _)| offset)
+            .unwrap_or(end);
+        let next = match text[back..end].find('\n') {
+            Some(newline) if back + newline + 1 < end => back + newline + 1,
+            _ => back,
+        };
+        start = if next > start { next } else { end };
+    }
+}
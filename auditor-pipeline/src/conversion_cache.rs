@@ -0,0 +1,76 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha512};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Tells apart the temporary files of stores running at the same time
+static PARTIAL_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Converted outputs kept across runs, so reprocessing an evidence folder
+/// only converts the files that changed.
+///
+/// Entries live at `<root>/<key[..2]>/<key>.<ext>`. The key is derived from
+/// the source's digest and a fingerprint of everything else that shapes the
+/// output (tool version, file name, converter options), so changing a
+/// setting never serves a stale conversion.
+#[derive(Debug, Clone)]
+pub struct ConversionCache {
+    root: PathBuf,
+}
+
+impl ConversionCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Cache key for a source with digest `source_hash` converted under `fingerprint`
+    pub fn key(source_hash: &str, fingerprint: &str) -> String {
+        let mut hasher = Sha512::new();
+        hasher.update(source_hash.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(fingerprint.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    fn entry_path(&self, key: &str, extension: &str) -> PathBuf {
+        self.root.join(&key[..2.min(key.len())]).join(format!("{}.{}", key, extension))
+    }
+
+    /// Copy a cached output to `output_path`; false when there is none
+    pub fn restore(&self, key: &str, extension: &str, output_path: &Path) -> Result<bool> {
+        let cached = self.entry_path(key, extension);
+        if !cached.is_file() {
+            return Ok(false);
+        }
+        std::fs::copy(&cached, output_path)
+            .with_context(|| format!("Failed to copy cached conversion {}", cached.display()))?;
+        Ok(true)
+    }
+
+    pub fn contains(&self, key: &str, extension: &str) -> bool {
+        self.entry_path(key, extension).is_file()
+    }
+
+    /// Store a fresh output, replacing any earlier one. The copy is written
+    /// under a temporary name first so a concurrent run never restores a
+    /// half-written file.
+    pub fn store(&self, key: &str, extension: &str, converted_path: &Path) -> Result<()> {
+        let entry = self.entry_path(key, extension);
+        let dir = entry.parent().context("Cache entry has no parent directory")?;
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create cache directory {}", dir.display()))?;
+
+        let partial = entry.with_extension(format!(
+            "{}.partial-{}-{}",
+            extension,
+            std::process::id(),
+            PARTIAL_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::copy(converted_path, &partial)
+            .with_context(|| format!("Failed to copy {} into the conversion cache", converted_path.display()))?;
+        std::fs::rename(&partial, &entry).with_context(|| {
+            let _ = std::fs::remove_file(&partial);
+            format!("Failed to store cache entry {}", entry.display())
+        })
+    }
+}
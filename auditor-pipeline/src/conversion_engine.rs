@@ -0,0 +1,1749 @@
+use crate::conversion_cache::ConversionCache;
+use crate::csv_normalizer;
+use crate::docx_markdown;
+use crate::email_message::EmailMessage;
+use crate::ept_logger::EPTLogger;
+use crate::excel_controls::WorkbookControls;
+use crate::excel_hidden::{HiddenContent, SheetHiddenContent};
+use crate::html_markdown;
+use crate::image_conversion::{self, ImageConverter};
+use crate::long_paths;
+use crate::pdf_text;
+use crate::pptx_markdown;
+use crate::settings::ProcessingSettings;
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+use calamine::{open_workbook, Data, Reader, SheetVisible, Xlsx, Xls};
+use chrono::Local;
+
+/// Which converter handles `.docx` files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocxConverter {
+    /// LibreOffice (to PDF) when installed, otherwise the native markdown converter
+    #[default]
+    Auto,
+    /// Always LibreOffice; conversion fails if it is not installed
+    LibreOffice,
+    /// Always the native markdown converter
+    Native,
+}
+
+/// What `.pptx` presentations are converted to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresentationOutput {
+    /// PDF via LibreOffice
+    #[default]
+    Pdf,
+    /// Slide titles, body text and speaker notes as markdown, one heading per slide
+    Markdown,
+}
+
+/// What photographed or scanned images are converted to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageOutput {
+    /// Left out of the export, as not LLM-readable
+    #[default]
+    Off,
+    /// Text recognised by Tesseract, as markdown
+    Ocr,
+    /// The image wrapped into a single-page PDF
+    Pdf,
+}
+
+/// Whether spreadsheet formulas are shown next to their computed values
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FormulaOutput {
+    /// Values only
+    #[default]
+    Off,
+    /// Each formula cell shows its value followed by the formula, e.g. ``120 `=SUM(A1:A10)` ``
+    Inline,
+    /// Values only in the sheet table, followed by a Cell | Formula | Value table
+    Listing,
+}
+
+/// Output format a file type is converted to. Text and CSV come from
+/// LibreOffice for formats without a native converter; LibreOffice CSV
+/// export only covers a workbook's first sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversionTarget {
+    Pdf,
+    Md,
+    Txt,
+    Csv,
+}
+
+impl ConversionTarget {
+    /// Extension of the converted file
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConversionTarget::Pdf => "pdf",
+            ConversionTarget::Md => "md",
+            ConversionTarget::Txt => "txt",
+            ConversionTarget::Csv => "csv",
+        }
+    }
+
+    /// `--convert-to` argument when LibreOffice produces this format
+    fn libreoffice_filter(&self) -> &'static str {
+        match self {
+            ConversionTarget::Pdf => "pdf",
+            ConversionTarget::Md => "md",
+            ConversionTarget::Txt => "txt:Text",
+            ConversionTarget::Csv => "csv",
+        }
+    }
+}
+
+/// Formats LibreOffice can open, and the ones it can save as text or CSV
+const LIBREOFFICE_EXTENSIONS: &[&str] = &[
+    "doc", "docx", "ppt", "pptx", "xls", "xlsx", "odt", "ods", "odp", "rtf", "wpd", "wps", "odg", "pub",
+];
+const LIBREOFFICE_TEXT_EXTENSIONS: &[&str] = &["doc", "docx", "odt", "rtf", "wpd", "wps"];
+const LIBREOFFICE_CSV_EXTENSIONS: &[&str] = &["xls", "xlsx", "ods"];
+
+/// Formats one of the in-process converters renders as markdown (or text)
+const NATIVE_EXTENSIONS: &[&str] = &["xls", "xlsx", "docx", "pptx", "pdf", "eml", "msg", "html", "htm"];
+
+/// Images turned into OCR markdown or a PDF in-process
+const NATIVE_IMAGE_EXTENSIONS: &[&str] = image_conversion::IMAGE_EXTENSIONS;
+
+/// Delimited text files rewritten in-process as UTF-8, comma-separated CSV
+const NATIVE_CSV_EXTENSIONS: &[&str] = &["csv", "tsv"];
+
+/// Which converter turns a file into its target format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Route {
+    Native,
+    LibreOffice,
+}
+
+/// Converter that produced a file's output. LibreOffice conversions that
+/// fail fall back on pandoc, then on the native converter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversionBackend {
+    LibreOffice,
+    Pandoc,
+    Native,
+}
+
+impl ConversionBackend {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConversionBackend::LibreOffice => "LibreOffice",
+            ConversionBackend::Pandoc => "pandoc",
+            ConversionBackend::Native => "Native",
+        }
+    }
+}
+
+/// LibreOffice-bound formats pandoc can read instead
+const PANDOC_FALLBACK_EXTENSIONS: &[&str] = &["docx", "odt", "rtf"];
+
+/// LibreOffice-bound formats an in-process converter can read instead
+const NATIVE_FALLBACK_EXTENSIONS: &[&str] = &["docx", "pptx", "xls", "xlsx"];
+
+fn route_for(file_ext: &str, target: ConversionTarget) -> Option<Route> {
+    let native = NATIVE_EXTENSIONS.contains(&file_ext);
+    let libreoffice = LIBREOFFICE_EXTENSIONS.contains(&file_ext);
+    match target {
+        ConversionTarget::Md if native => Some(Route::Native),
+        ConversionTarget::Txt if native => Some(Route::Native),
+        ConversionTarget::Txt if LIBREOFFICE_TEXT_EXTENSIONS.contains(&file_ext) => Some(Route::LibreOffice),
+        ConversionTarget::Csv if NATIVE_CSV_EXTENSIONS.contains(&file_ext) => Some(Route::Native),
+        ConversionTarget::Md | ConversionTarget::Pdf if NATIVE_IMAGE_EXTENSIONS.contains(&file_ext) => {
+            Some(Route::Native)
+        }
+        ConversionTarget::Csv if LIBREOFFICE_CSV_EXTENSIONS.contains(&file_ext) => Some(Route::LibreOffice),
+        ConversionTarget::Pdf if libreoffice => Some(Route::LibreOffice),
+        _ => None,
+    }
+}
+
+/// `--convert-to` argument for a file. Legacy formats imported through
+/// LibreOffice's libwpd/libwps/libmspub filters name the PDF export filter
+/// of the module they open in, as the default guess fails for them.
+fn libreoffice_filter_for(file_ext: &str, target: ConversionTarget) -> &'static str {
+    match (file_ext, target) {
+        ("wpd" | "wps", ConversionTarget::Pdf) => "pdf:writer_pdf_Export",
+        ("odg" | "pub", ConversionTarget::Pdf) => "pdf:draw_pdf_Export",
+        _ => target.libreoffice_filter(),
+    }
+}
+
+/// Normalise user-supplied extension keys (`.DOCX` → `docx`)
+fn normalize_extension(ext: &str) -> String {
+    ext.trim().trim_start_matches('.').to_lowercase()
+}
+
+/// Check that every extension → target pair in a conversion matrix has a
+/// converter, so a bad profile fails before the run starts
+pub fn validate_conversion_targets(targets: &BTreeMap<String, ConversionTarget>) -> Result<()> {
+    let unsupported: Vec<String> = targets
+        .iter()
+        .filter(|(ext, target)| route_for(&normalize_extension(ext), **target).is_none())
+        .map(|(ext, target)| format!("{} → {}", ext, target.extension()))
+        .collect();
+    if unsupported.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!("Unsupported conversion target(s): {}", unsupported.join(", ")))
+    }
+}
+
+pub struct ConversionEngine {
+    logger: EPTLogger,
+    /// Convert PDFs to markdown text instead of exporting them as-is
+    pdf_text_extraction: bool,
+    docx_converter: DocxConverter,
+    pptx_output: PresentationOutput,
+    /// Clean HTML pages up into markdown instead of exporting them as-is
+    html_to_markdown: bool,
+    /// Rewrite CSV/TSV files as UTF-8, comma-separated `__converted.csv`
+    normalize_csv: bool,
+    image_output: ImageOutput,
+    /// Tesseract language code(s) for image OCR, e.g. `eng+deu`
+    ocr_language: String,
+    excel_formulas: FormulaOutput,
+    /// Append defined names, data validation and protection per sheet
+    excel_controls_appendix: bool,
+    /// Label hidden sheets and mark hidden rows/columns
+    excel_hidden_content: bool,
+    /// Append cell comments/notes per sheet
+    excel_comments: bool,
+    /// Retry files LibreOffice fails on with pandoc and the native converters
+    conversion_fallback: bool,
+    /// Sheet names (with `*` wildcards) left out of Excel conversions, as configured
+    skip_sheets: Vec<String>,
+    /// `skip_sheets` compiled to case-insensitive patterns
+    skip_sheet_patterns: Vec<Regex>,
+    /// Rows written per sheet below its header; 0 writes them all
+    excel_max_rows_per_sheet: usize,
+    /// Per-extension targets overriding the defaults above
+    conversion_targets: HashMap<String, ConversionTarget>,
+    /// Outputs of earlier runs, keyed by source hash
+    conversion_cache: Option<ConversionCache>,
+    /// Convert even when the cache holds an output (and refresh it)
+    force_reconversion: bool,
+    /// Whether LibreOffice could be found, looked up on first use
+    libreoffice_available: OnceLock<bool>,
+    /// Held while a single-file LibreOffice conversion runs: concurrent
+    /// `soffice` processes sharing a user profile fail, so conversion workers
+    /// take turns for these
+    libreoffice_lock: Mutex<()>,
+}
+
+impl ConversionEngine {
+    pub fn new(logger: EPTLogger) -> Self {
+        Self {
+            logger,
+            pdf_text_extraction: false,
+            docx_converter: DocxConverter::default(),
+            pptx_output: PresentationOutput::default(),
+            html_to_markdown: false,
+            normalize_csv: true,
+            image_output: ImageOutput::default(),
+            ocr_language: "eng".to_string(),
+            excel_formulas: FormulaOutput::default(),
+            excel_controls_appendix: false,
+            excel_hidden_content: true,
+            excel_comments: true,
+            conversion_fallback: true,
+            skip_sheets: Vec::new(),
+            skip_sheet_patterns: Vec::new(),
+            excel_max_rows_per_sheet: 0,
+            conversion_targets: HashMap::new(),
+            conversion_cache: None,
+            force_reconversion: false,
+            libreoffice_available: OnceLock::new(),
+            libreoffice_lock: Mutex::new(()),
+        }
+    }
+
+    /// Engine configured from the run's processing settings
+    pub fn with_settings(logger: EPTLogger, settings: &ProcessingSettings) -> Self {
+        Self {
+            logger,
+            pdf_text_extraction: settings.pdf_text_extraction,
+            docx_converter: settings.docx_converter,
+            pptx_output: settings.pptx_output,
+            html_to_markdown: settings.html_to_markdown,
+            normalize_csv: settings.normalize_csv,
+            image_output: settings.image_output,
+            ocr_language: settings.ocr_language.clone(),
+            excel_formulas: settings.excel_formulas,
+            excel_controls_appendix: settings.excel_controls_appendix,
+            excel_hidden_content: settings.excel_hidden_content,
+            excel_comments: settings.excel_comments,
+            conversion_fallback: settings.conversion_fallback,
+            skip_sheets: settings.skip_sheets.clone(),
+            skip_sheet_patterns: settings.skip_sheets.iter().map(|pattern| sheet_name_pattern(pattern)).collect(),
+            excel_max_rows_per_sheet: settings.excel_max_rows_per_sheet,
+            conversion_targets: settings
+                .conversion_targets
+                .iter()
+                .map(|(ext, target)| (normalize_extension(ext), *target))
+                .collect(),
+            conversion_cache: settings.conversion_cache_dir.clone().map(ConversionCache::new),
+            force_reconversion: settings.force_reconversion,
+            libreoffice_available: OnceLock::new(),
+            libreoffice_lock: Mutex::new(()),
+        }
+    }
+
+    /// Whether DOCX files default to the native converter
+    fn prefers_native_docx(&self) -> bool {
+        match self.docx_converter {
+            DocxConverter::Native => true,
+            DocxConverter::LibreOffice => false,
+            DocxConverter::Auto => !*self.libreoffice_available.get_or_init(|| {
+                let available = self.find_libreoffice().is_ok();
+                if !available {
+                    self.logger.info("LibreOffice not found; converting DOCX files with the native converter");
+                }
+                available
+            }),
+        }
+    }
+
+    fn file_extension(file_path: &Path) -> String {
+        file_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|s| s.to_lowercase())
+            .unwrap_or_default()
+    }
+
+    /// Output format for a file extension: the configured override when it
+    /// is supported, otherwise the built-in default. `None` means the file
+    /// is not converted.
+    fn target_for(&self, file_ext: &str) -> Option<ConversionTarget> {
+        // Unsupported overrides are rejected up front by `validate_conversion_targets`
+        if let Some(target) = self.conversion_targets.get(file_ext) {
+            if route_for(file_ext, *target).is_some() {
+                return Some(*target);
+            }
+        }
+
+        match file_ext {
+            "docx" if self.prefers_native_docx() => Some(ConversionTarget::Md),
+            "pptx" if self.pptx_output == PresentationOutput::Markdown => Some(ConversionTarget::Md),
+            "pdf" if self.pdf_text_extraction => Some(ConversionTarget::Md),
+            "html" | "htm" if self.html_to_markdown => Some(ConversionTarget::Md),
+            "csv" | "tsv" if self.normalize_csv => Some(ConversionTarget::Csv),
+            ext if NATIVE_IMAGE_EXTENSIONS.contains(&ext) => match self.image_output {
+                ImageOutput::Off => None,
+                ImageOutput::Ocr => Some(ConversionTarget::Md),
+                ImageOutput::Pdf => Some(ConversionTarget::Pdf),
+            },
+            "xls" | "xlsx" | "eml" | "msg" => Some(ConversionTarget::Md),
+            "doc" | "docx" | "ppt" | "pptx" | "odt" | "ods" | "odp" => Some(ConversionTarget::Pdf),
+            "wpd" | "wps" | "odg" | "pub" => Some(ConversionTarget::Pdf),
+            _ => None,
+        }
+    }
+
+    fn route(&self, file_path: &Path) -> Option<(ConversionTarget, Route)> {
+        let file_ext = Self::file_extension(file_path);
+        let target = self.target_for(&file_ext)?;
+        Some((target, route_for(&file_ext, target)?))
+    }
+
+    pub fn convert_file(&self, file_path: &Path, _root_path: &Path) -> Result<Option<PathBuf>> {
+        if !self.is_convertible_file(file_path) {
+            return Ok(None);
+        }
+
+        let Some((target, route)) = self.route(file_path) else {
+            return Ok(None);
+        };
+        let file_ext = Self::file_extension(file_path);
+        let output_ext = target.extension();
+        let output_path = Self::output_path(file_path, target)?;
+
+        self.logger.debug(&format!(
+            "Converting {} to {}",
+            file_path.display(),
+            output_path.display()
+        ));
+
+        // PDFs, emails, HTML, CSV, images, spreadsheets, DOCX and PPTX files can be converted in-process
+        if route == Route::Native {
+            return match file_ext.as_str() {
+                "csv" | "tsv" => self.normalize_csv_file(file_path, &output_path),
+                ext if NATIVE_IMAGE_EXTENSIONS.contains(&ext) => self.convert_image(file_path, &output_path, target),
+                "docx" => self.convert_docx_to_markdown(file_path, &output_path),
+                "pptx" => self.convert_pptx_to_markdown(file_path, &output_path),
+                "pdf" => self.convert_pdf_to_markdown(file_path, &output_path),
+                "html" | "htm" => self.convert_html_to_markdown(file_path, &output_path),
+                ext if Self::is_email_extension(ext) => self.convert_email_to_markdown(file_path, &output_path),
+                _ => self.convert_excel_to_markdown(file_path, &output_path),
+            };
+        }
+
+        // For other file types, use LibreOffice
+        // Find LibreOffice executable
+        let libreoffice_cmd = self.find_libreoffice()?;
+        let _libreoffice_turn = self.libreoffice_lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let output_dir = output_path
+            .parent()
+            .context("Output path has no parent")?;
+
+        let mut cmd = Command::new(&libreoffice_cmd);
+        cmd.arg("--headless")
+            .arg("--convert-to")
+            .arg(libreoffice_filter_for(&file_ext, target))
+            .arg("--outdir")
+            .arg(long_paths::simplified(output_dir))
+            .arg(long_paths::simplified(file_path));
+
+        self.logger.debug(&format!(
+            "Executing LibreOffice command: {:?} {:?}",
+            libreoffice_cmd,
+            cmd.get_args().collect::<Vec<_>>()
+        ));
+
+        let output = cmd.output()
+            .with_context(|| "Failed to execute LibreOffice conversion command".to_string())?;
+
+        let stdout_msg = String::from_utf8_lossy(&output.stdout);
+        let stderr_msg = String::from_utf8_lossy(&output.stderr);
+        
+        self.logger.debug(&format!(
+            "LibreOffice exit status: {:?}, stdout: {}, stderr: {}",
+            output.status.code(),
+            stdout_msg,
+            stderr_msg
+        ));
+
+        if !output.status.success() {
+            self.logger.error(&format!(
+                "LibreOffice conversion failed for {}: stdout: {}, stderr: {}",
+                file_path.display(),
+                stdout_msg,
+                stderr_msg
+            ));
+            return Err(anyhow::anyhow!(
+                "LibreOffice conversion failed: stdout: {}, stderr: {}",
+                stdout_msg,
+                stderr_msg
+            ));
+        }
+
+        // Check if the file was created with the expected name
+        // LibreOffice might use the original filename
+        let file_stem = file_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("converted");
+        let possible_pdf = output_dir.join(format!("{}.{}", file_stem, output_ext));
+        self.logger.debug(&format!(
+            "Checking for LibreOffice output: {} (exists: {}), expected: {} (exists: {})",
+            possible_pdf.display(),
+            possible_pdf.exists(),
+            output_path.display(),
+            output_path.exists()
+        ));
+        
+        if possible_pdf.exists() && !output_path.exists() {
+            self.logger.debug(&format!(
+                "Found output with original name, renaming {} to {}",
+                possible_pdf.display(),
+                output_path.display()
+            ));
+            std::fs::rename(&possible_pdf, &output_path)
+                .with_context(|| format!("Failed to rename {} to {}", possible_pdf.display(), output_path.display()))?;
+        }
+
+        if output_path.exists() {
+            self.logger.debug(&format!("Successfully converted to: {}", output_path.display()));
+            Ok(Some(output_path))
+        } else {
+            Err(anyhow::anyhow!("Conversion completed but output file not found"))
+        }
+    }
+
+    /// Where a file's conversion is written: `<stem>__converted.<ext>` next to it
+    fn output_path(file_path: &Path, target: ConversionTarget) -> Result<PathBuf> {
+        let file_stem = file_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("converted");
+        let output_filename = format!("{}__converted.{}", file_stem, target.extension());
+        Ok(file_path
+            .parent()
+            .context("File has no parent directory")?
+            .join(output_filename))
+    }
+
+    /// Everything besides the source's content that shapes its converted
+    /// output. Outputs embed the file name, so that is part of it too.
+    fn cache_fingerprint(&self, file_path: &Path, target: ConversionTarget) -> String {
+        let options = (
+            self.pdf_text_extraction,
+            self.docx_converter,
+            self.pptx_output,
+            self.html_to_markdown,
+            self.normalize_csv,
+            self.image_output,
+            &self.ocr_language,
+            self.excel_formulas,
+            self.excel_controls_appendix,
+            self.excel_hidden_content,
+            self.excel_comments,
+            (&self.skip_sheets, self.excel_max_rows_per_sheet),
+        );
+        format!(
+            "{}|{}|{}|{}|{:?}",
+            env!("CARGO_PKG_VERSION"),
+            file_path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default(),
+            target.extension(),
+            self.conversion_method(file_path).unwrap_or_default(),
+            options
+        )
+    }
+
+    /// Cache key and output extension for a file, if caching is on and the file is convertible
+    fn cache_entry(&self, file_path: &Path, source_hash: &str) -> Option<(&ConversionCache, String, ConversionTarget)> {
+        let cache = self.conversion_cache.as_ref()?;
+        let (target, _) = self.route(file_path)?;
+        let key = ConversionCache::key(source_hash, &self.cache_fingerprint(file_path, target));
+        Some((cache, key, target))
+    }
+
+    /// Whether a run would reuse a cached output for this file
+    pub fn has_cached_conversion(&self, file_path: &Path, source_hash: &str) -> bool {
+        !self.force_reconversion
+            && self
+                .cache_entry(file_path, source_hash)
+                .is_some_and(|(cache, key, target)| cache.contains(&key, target.extension()))
+    }
+
+    /// Put the cached output of an earlier run in place of converting the
+    /// file again. Returns the output path, or `None` on a cache miss.
+    pub fn restore_cached_conversion(&self, file_path: &Path, source_hash: &str) -> Option<PathBuf> {
+        if self.force_reconversion {
+            return None;
+        }
+        let (cache, key, target) = self.cache_entry(file_path, source_hash)?;
+        let output_path = Self::output_path(file_path, target).ok()?;
+        match cache.restore(&key, target.extension(), &output_path) {
+            Ok(true) => {
+                self.logger.debug(&format!("Reused cached conversion of {}", file_path.display()));
+                Some(output_path)
+            }
+            Ok(false) => None,
+            Err(e) => {
+                self.logger.warning(&format!("{:#}; converting {} again", e, file_path.display()));
+                None
+            }
+        }
+    }
+
+    /// Keep a fresh output for later runs. Failing to cache never fails the conversion.
+    pub fn cache_conversion(&self, file_path: &Path, source_hash: &str, converted_path: &Path) {
+        let Some((cache, key, target)) = self.cache_entry(file_path, source_hash) else {
+            return;
+        };
+        if let Err(e) = cache.store(&key, target.extension(), converted_path) {
+            self.logger.warning(&format!("{:#}", e));
+        }
+    }
+
+    /// Backend a file is converted with, barring fallbacks
+    pub fn backend(&self, file_path: &Path) -> Option<ConversionBackend> {
+        match self.route(file_path)? {
+            (_, Route::LibreOffice) => Some(ConversionBackend::LibreOffice),
+            (_, Route::Native) => Some(ConversionBackend::Native),
+        }
+    }
+
+    /// Convert a file LibreOffice failed on with the first fallback backend
+    /// that succeeds: pandoc, then the native converter. Fallbacks write
+    /// markdown whatever the configured target. `None` when fallbacks are
+    /// off, none reads this format or all of them fail.
+    pub fn convert_with_fallback(&self, file_path: &Path, libreoffice_error: &str) -> Option<(PathBuf, ConversionBackend)> {
+        if !self.conversion_fallback || !self.requires_libreoffice(file_path) {
+            return None;
+        }
+        let file_ext = Self::file_extension(file_path);
+        let mut backends = Vec::new();
+        if PANDOC_FALLBACK_EXTENSIONS.contains(&file_ext.as_str()) {
+            backends.push(ConversionBackend::Pandoc);
+        }
+        if NATIVE_FALLBACK_EXTENSIONS.contains(&file_ext.as_str()) {
+            backends.push(ConversionBackend::Native);
+        }
+        let output_path = Self::output_path(file_path, ConversionTarget::Md).ok()?;
+
+        for backend in backends {
+            let outcome = match (backend, file_ext.as_str()) {
+                (ConversionBackend::Pandoc, _) => self.convert_with_pandoc(file_path, &output_path),
+                (_, "docx") => self.convert_docx_to_markdown(file_path, &output_path),
+                (_, "pptx") => self.convert_pptx_to_markdown(file_path, &output_path),
+                _ => self.convert_excel_to_markdown(file_path, &output_path),
+            };
+            match outcome {
+                Ok(Some(converted_path)) => {
+                    self.logger.info(&format!(
+                        "Converted {} with the {} fallback after LibreOffice failed: {}",
+                        file_path.display(),
+                        backend.label(),
+                        libreoffice_error
+                    ));
+                    return Some((converted_path, backend));
+                }
+                Ok(None) => {}
+                Err(e) => self.logger.warning(&format!(
+                    "{} fallback failed for {}: {:#}",
+                    backend.label(),
+                    file_path.display(),
+                    e
+                )),
+            }
+        }
+        None
+    }
+
+    /// Convert a document to GitHub-flavoured markdown with pandoc
+    fn convert_with_pandoc(&self, file_path: &Path, output_path: &Path) -> Result<Option<PathBuf>> {
+        let pandoc = self
+            .find_pandoc()
+            .context("pandoc not found (install it or set EPT_PANDOC_PATH)")?;
+        let output = Command::new(&pandoc)
+            .arg(long_paths::simplified(file_path))
+            .arg("--to")
+            .arg("gfm")
+            .arg("--wrap=none")
+            .output()
+            .context("Failed to execute pandoc")?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "pandoc failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        let file_name = file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown");
+        let markdown_content = [
+            format!("# Document: {}", file_name),
+            String::new(),
+            format!("Converted on: {}", Local::now().format("%Y-%m-%d %H:%M:%S")),
+            String::new(),
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+            String::new(),
+        ];
+
+        std::fs::write(output_path, markdown_content.join("\n"))
+            .with_context(|| format!("Failed to write markdown file: {}", output_path.display()))?;
+        Ok(Some(output_path.to_path_buf()))
+    }
+
+    fn find_pandoc(&self) -> Option<PathBuf> {
+        if let Ok(env_path) = std::env::var("EPT_PANDOC_PATH") {
+            let path = PathBuf::from(&env_path);
+            if path.exists() {
+                return Some(path);
+            }
+            self.logger.warning(&format!("EPT_PANDOC_PATH is set to {}, but file does not exist", env_path));
+        }
+        which::which("pandoc").ok()
+    }
+
+    fn is_email_extension(file_ext: &str) -> bool {
+        matches!(file_ext, "eml" | "msg")
+    }
+
+    /// Whether converting this file goes through LibreOffice (as opposed to
+    /// one of the in-process converters)
+    pub fn requires_libreoffice(&self, file_path: &Path) -> bool {
+        matches!(self.route(file_path), Some((_, Route::LibreOffice)))
+    }
+
+    /// Human-readable name of the converter used for this file, if it is convertible
+    pub fn conversion_method(&self, file_path: &Path) -> Option<&'static str> {
+        match self.route(file_path)? {
+            (_, Route::LibreOffice) => Some("LibreOffice"),
+            (target, Route::Native) => match Self::file_extension(file_path).as_str() {
+                "docx" => Some("DOCX to Markdown (native)"),
+                "pptx" => Some("PPTX to Markdown (native)"),
+                "pdf" => Some("PDF Text to Markdown"),
+                "html" | "htm" => Some("HTML to Markdown"),
+                "csv" | "tsv" => Some("CSV normalization"),
+                ext if NATIVE_IMAGE_EXTENSIONS.contains(&ext) => match target {
+                    ConversionTarget::Pdf => Some("Image to PDF"),
+                    _ => Some("Image OCR (Tesseract)"),
+                },
+                ext if Self::is_email_extension(ext) => Some("Email to Markdown"),
+                _ => Some("Excel to Markdown"),
+            },
+        }
+    }
+
+    /// Skip reason for a failed conversion. Legacy formats get a hint at
+    /// what LibreOffice can and can't import, since their failures are
+    /// usually down to an unsupported version rather than a damaged file.
+    pub fn failure_reason(&self, file_path: &Path, error: &str) -> String {
+        let hint = match Self::file_extension(file_path).as_str() {
+            "wpd" => Some("WordPerfect document; LibreOffice imports WordPerfect 4.2 and later"),
+            "wps" => Some(
+                "Microsoft Works document; LibreOffice imports Works word-processor files from version 3 on, \
+                 not Works spreadsheets or Kingsoft WPS files",
+            ),
+            "pub" => Some("Microsoft Publisher document; LibreOffice imports Publisher 98 and later"),
+            "odg" => Some("OpenDocument drawing"),
+            _ => None,
+        };
+        match hint {
+            Some(hint) => format!("Conversion failed ({}): {}", hint, error),
+            None => format!("Conversion failed: {}", error),
+        }
+    }
+
+    /// Convert many LibreOffice-bound files with as few `soffice` processes as possible.
+    ///
+    /// Files are grouped by target format into batches of at most `batch_size`
+    /// and each batch is passed to a single `--convert-to` invocation writing
+    /// into a scratch directory under `working_path`. Outputs are then moved
+    /// next to their source as `<stem>__converted.<ext>`, matching
+    /// `convert_file`. A batch never contains two files with the same stem, so
+    /// outputs in the scratch directory can always be mapped back to their source.
+    /// Files that fail within a batch are retried on their own so every
+    /// failure carries the error LibreOffice reported for that file alone.
+    ///
+    /// Returns the outcome for every input path, keyed by that path.
+    pub fn convert_batches<F: FnMut(usize, usize)>(
+        &self,
+        files: &[PathBuf],
+        working_path: &Path,
+        batch_size: usize,
+        mut on_progress: F,
+    ) -> HashMap<PathBuf, std::result::Result<PathBuf, String>> {
+        let mut results = HashMap::new();
+        if files.is_empty() {
+            return results;
+        }
+
+        let libreoffice_cmd = match self.find_libreoffice() {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                for file in files {
+                    results.insert(file.clone(), Err(e.to_string()));
+                }
+                return results;
+            }
+        };
+
+        let scratch_dir = working_path.join(".libreoffice_batch");
+
+        // Group by target format and export filter, preserving input order within each group
+        let mut by_format: Vec<(ConversionTarget, &'static str, Vec<PathBuf>)> = Vec::new();
+        for file in files {
+            let Some((target, Route::LibreOffice)) = self.route(file) else {
+                results.insert(file.clone(), Err("Not a LibreOffice conversion".to_string()));
+                continue;
+            };
+            let filter = libreoffice_filter_for(&Self::file_extension(file), target);
+            match by_format.iter_mut().find(|(_, f, _)| *f == filter) {
+                Some((_, _, group)) => group.push(file.clone()),
+                None => by_format.push((target, filter, vec![file.clone()])),
+            }
+        }
+
+        let total = files.len();
+        let mut done = 0;
+        for (target, filter, group) in by_format {
+            for batch in Self::split_into_batches(&group, batch_size.max(1)) {
+                let outcomes = self.convert_libreoffice_batch(&libreoffice_cmd, &batch, target, filter, &scratch_dir);
+                let failed: Vec<PathBuf> = outcomes
+                    .iter()
+                    .filter(|(_, outcome)| outcome.is_err())
+                    .map(|(file, _)| file.clone())
+                    .collect();
+                for (file, outcome) in outcomes {
+                    results.insert(file, outcome);
+                }
+
+                // LibreOffice's stderr covers the whole batch and a document
+                // that crashes soffice takes the rest of the batch down with
+                // it, so failures are retried one at a time. This rescues the
+                // innocent files and gives each real failure its own error.
+                if batch.len() > 1 && !failed.is_empty() {
+                    self.logger.info(&format!(
+                        "Retrying {} of {} file(s) from a failed LibreOffice batch individually",
+                        failed.len(),
+                        batch.len()
+                    ));
+                    for file in failed {
+                        let single = std::slice::from_ref(&file);
+                        for (file, outcome) in
+                            self.convert_libreoffice_batch(&libreoffice_cmd, single, target, filter, &scratch_dir)
+                        {
+                            results.insert(file, outcome);
+                        }
+                    }
+                }
+                done += batch.len();
+                on_progress(done, total);
+            }
+        }
+
+        if scratch_dir.exists() {
+            if let Err(e) = std::fs::remove_dir_all(&scratch_dir) {
+                self.logger.warning(&format!(
+                    "Failed to remove LibreOffice scratch directory {}: {}",
+                    scratch_dir.display(),
+                    e
+                ));
+            }
+        }
+
+        results
+    }
+
+    /// Split files into batches of at most `batch_size`, starting a new batch
+    /// whenever a file stem would repeat
+    fn split_into_batches(files: &[PathBuf], batch_size: usize) -> Vec<Vec<PathBuf>> {
+        let mut batches = Vec::new();
+        let mut current: Vec<PathBuf> = Vec::new();
+        let mut stems: HashSet<String> = HashSet::new();
+
+        for file in files {
+            let stem = file
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if current.len() >= batch_size || stems.contains(&stem) {
+                batches.push(std::mem::take(&mut current));
+                stems.clear();
+            }
+            stems.insert(stem);
+            current.push(file.clone());
+        }
+        if !current.is_empty() {
+            batches.push(current);
+        }
+
+        batches
+    }
+
+    fn convert_libreoffice_batch(
+        &self,
+        libreoffice_cmd: &Path,
+        batch: &[PathBuf],
+        target: ConversionTarget,
+        filter: &str,
+        scratch_dir: &Path,
+    ) -> Vec<(PathBuf, std::result::Result<PathBuf, String>)> {
+        let output_ext = target.extension();
+        if let Err(e) = std::fs::create_dir_all(scratch_dir) {
+            let message = format!("Failed to create scratch directory {}: {}", scratch_dir.display(), e);
+            return batch.iter().map(|f| (f.clone(), Err(message.clone()))).collect();
+        }
+
+        let mut cmd = Command::new(libreoffice_cmd);
+        cmd.arg("--headless")
+            .arg("--convert-to")
+            .arg(filter)
+            .arg("--outdir")
+            .arg(long_paths::simplified(scratch_dir))
+            .args(batch.iter().map(|file| long_paths::simplified(file)));
+
+        self.logger.debug(&format!(
+            "Executing LibreOffice batch of {} file(s) to {}",
+            batch.len(),
+            output_ext
+        ));
+
+        let (stdout_msg, stderr_msg) = match cmd.output() {
+            Ok(output) => (
+                String::from_utf8_lossy(&output.stdout).to_string(),
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ),
+            Err(e) => {
+                let message = format!("Failed to execute LibreOffice conversion command: {}", e);
+                return batch.iter().map(|f| (f.clone(), Err(message.clone()))).collect();
+            }
+        };
+
+        self.logger.debug(&format!(
+            "LibreOffice batch stdout: {}, stderr: {}",
+            stdout_msg,
+            stderr_msg
+        ));
+
+        // Map each scratch output back to its source, regardless of the exit
+        // status: LibreOffice reports failure for the whole batch even when
+        // only one document could not be converted.
+        batch
+            .iter()
+            .map(|file_path| {
+                let outcome = self.claim_batch_output(file_path, output_ext, scratch_dir, &stderr_msg);
+                (file_path.clone(), outcome)
+            })
+            .collect()
+    }
+
+    fn claim_batch_output(
+        &self,
+        file_path: &Path,
+        output_ext: &str,
+        scratch_dir: &Path,
+        stderr_msg: &str,
+    ) -> std::result::Result<PathBuf, String> {
+        let file_stem = file_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("converted");
+        let scratch_output = scratch_dir.join(format!("{}.{}", file_stem, output_ext));
+        if !scratch_output.exists() {
+            return Err(format!(
+                "LibreOffice conversion failed: no output produced, stderr: {}",
+                stderr_msg
+            ));
+        }
+
+        let output_path = file_path
+            .parent()
+            .ok_or_else(|| "File has no parent directory".to_string())?
+            .join(format!("{}__converted.{}", file_stem, output_ext));
+
+        std::fs::rename(&scratch_output, &output_path)
+            .map_err(|e| format!("Failed to move {} to {}: {}", scratch_output.display(), output_path.display(), e))?;
+
+        self.logger.debug(&format!("Successfully converted to: {}", output_path.display()));
+        Ok(output_path)
+    }
+
+    /// Render an email's headers, body and attachment list as markdown. The
+    /// attachments themselves are extracted during decompression and
+    /// processed as files of their own.
+    fn convert_email_to_markdown(&self, file_path: &Path, output_path: &Path) -> Result<Option<PathBuf>> {
+        self.logger.debug(&format!("Converting email {} to markdown", file_path.display()));
+
+        let message = EmailMessage::from_file(file_path)?;
+        let file_name = file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown");
+
+        std::fs::write(output_path, message.to_markdown(file_name))
+            .with_context(|| format!("Failed to write markdown file: {}", output_path.display()))?;
+
+        self.logger.debug(&format!(
+            "Successfully converted email to markdown: {} ({} attachment(s))",
+            output_path.display(),
+            message.attachments.len()
+        ));
+        Ok(Some(output_path.to_path_buf()))
+    }
+
+    /// Write a PDF's text as markdown with a heading per page. PDFs with no
+    /// extractable text (scans, encrypted or unparseable files) are left to be
+    /// exported as the original PDF.
+    fn convert_pdf_to_markdown(&self, file_path: &Path, output_path: &Path) -> Result<Option<PathBuf>> {
+        self.logger.debug(&format!("Extracting text from PDF {}", file_path.display()));
+
+        let data = std::fs::read(file_path)
+            .with_context(|| format!("Failed to read PDF: {}", file_path.display()))?;
+        let pages = match pdf_text::extract_page_text(&data) {
+            Ok(pages) if pages.iter().any(|page| !page.is_empty()) => pages,
+            Ok(_) => {
+                self.logger.warning(&format!(
+                    "No extractable text in {}; exporting the original PDF",
+                    file_path.display()
+                ));
+                return Ok(None);
+            }
+            Err(e) => {
+                self.logger.warning(&format!(
+                    "Could not extract text from {} ({}); exporting the original PDF",
+                    file_path.display(),
+                    e
+                ));
+                return Ok(None);
+            }
+        };
+
+        let file_name = file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown");
+        let mut markdown_content = vec![
+            format!("# PDF: {}", file_name),
+            String::new(),
+            format!("Converted on: {}", Local::now().format("%Y-%m-%d %H:%M:%S")),
+            String::new(),
+        ];
+        for (index, text) in pages.iter().enumerate() {
+            markdown_content.push(format!("## Page {}", index + 1));
+            markdown_content.push(String::new());
+            markdown_content.push(if text.is_empty() {
+                "*(no extractable text)*".to_string()
+            } else {
+                text.clone()
+            });
+            markdown_content.push(String::new());
+        }
+
+        std::fs::write(output_path, markdown_content.join("\n"))
+            .with_context(|| format!("Failed to write markdown file: {}", output_path.display()))?;
+
+        self.logger.debug(&format!(
+            "Successfully converted PDF to markdown: {} ({} page(s))",
+            output_path.display(),
+            pages.len()
+        ));
+        Ok(Some(output_path.to_path_buf()))
+    }
+
+    /// Strip scripts, styles and page chrome from an HTML file and keep its
+    /// text as markdown
+    fn convert_html_to_markdown(&self, file_path: &Path, output_path: &Path) -> Result<Option<PathBuf>> {
+        self.logger.debug(&format!("Converting HTML {} to markdown", file_path.display()));
+
+        let data = std::fs::read(file_path)
+            .with_context(|| format!("Failed to read HTML file: {}", file_path.display()))?;
+        let body = html_markdown::html_to_markdown(&html_markdown::decode_html(&data));
+        let file_name = file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown");
+        let markdown_content = [
+            format!("# Web Page: {}", file_name),
+            String::new(),
+            format!("Converted on: {}", Local::now().format("%Y-%m-%d %H:%M:%S")),
+            String::new(),
+            body,
+            String::new(),
+        ];
+
+        std::fs::write(output_path, markdown_content.join("\n"))
+            .with_context(|| format!("Failed to write markdown file: {}", output_path.display()))?;
+
+        self.logger.debug(&format!("Successfully converted HTML to markdown: {}", output_path.display()));
+        Ok(Some(output_path.to_path_buf()))
+    }
+
+    fn convert_image(&self, file_path: &Path, output_path: &Path, target: ConversionTarget) -> Result<Option<PathBuf>> {
+        let converter = ImageConverter::new(self.logger.clone());
+        if target == ConversionTarget::Pdf {
+            converter.wrap_in_pdf(file_path, output_path)?;
+            self.logger.debug(&format!("Wrapped image in PDF: {}", output_path.display()));
+            return Ok(Some(output_path.to_path_buf()));
+        }
+
+        let text = converter.ocr(file_path, &self.ocr_language)?;
+        let file_name = file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown");
+        let body = if text.is_empty() {
+            "*No text recognised*".to_string()
+        } else {
+            text
+        };
+        let markdown_content = [
+            format!("# Image: {}", file_name),
+            String::new(),
+            format!("Converted on: {}", Local::now().format("%Y-%m-%d %H:%M:%S")),
+            String::new(),
+            "*Text recognised by OCR; check figures against the original image*".to_string(),
+            String::new(),
+            body,
+            String::new(),
+        ];
+
+        std::fs::write(output_path, markdown_content.join("\n"))
+            .with_context(|| format!("Failed to write markdown file: {}", output_path.display()))?;
+
+        self.logger.debug(&format!("Successfully converted image to markdown: {}", output_path.display()));
+        Ok(Some(output_path.to_path_buf()))
+    }
+
+    fn normalize_csv_file(&self, file_path: &Path, output_path: &Path) -> Result<Option<PathBuf>> {
+        let data = std::fs::read(file_path)
+            .with_context(|| format!("Failed to read CSV file: {}", file_path.display()))?;
+        let (normalized, format) = csv_normalizer::normalize(&data);
+
+        std::fs::write(output_path, normalized)
+            .with_context(|| format!("Failed to write CSV file: {}", output_path.display()))?;
+
+        self.logger.debug(&format!(
+            "Normalized {} ({}, {}-delimited) to UTF-8 CSV: {}",
+            file_path.display(),
+            format.encoding_label(),
+            format.delimiter_label(),
+            output_path.display()
+        ));
+        Ok(Some(output_path.to_path_buf()))
+    }
+
+    /// Encoding detected in a CSV/TSV source, for the report
+    pub fn source_encoding(&self, file_path: &Path) -> Option<String> {
+        if !NATIVE_CSV_EXTENSIONS.contains(&Self::file_extension(file_path).as_str()) {
+            return None;
+        }
+        let data = std::fs::read(file_path).ok()?;
+        Some(csv_normalizer::detect(&data).encoding_label())
+    }
+
+    fn convert_docx_to_markdown(&self, file_path: &Path, output_path: &Path) -> Result<Option<PathBuf>> {
+        self.logger.debug(&format!("Converting DOCX {} to markdown natively", file_path.display()));
+
+        let body = docx_markdown::docx_to_markdown(file_path)?;
+        let file_name = file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown");
+        let markdown_content = [
+            format!("# Word Document: {}", file_name),
+            String::new(),
+            format!("Converted on: {}", Local::now().format("%Y-%m-%d %H:%M:%S")),
+            String::new(),
+            body,
+            String::new(),
+        ];
+
+        std::fs::write(output_path, markdown_content.join("\n"))
+            .with_context(|| format!("Failed to write markdown file: {}", output_path.display()))?;
+
+        self.logger.debug(&format!("Successfully converted DOCX to markdown: {}", output_path.display()));
+        Ok(Some(output_path.to_path_buf()))
+    }
+
+    fn convert_pptx_to_markdown(&self, file_path: &Path, output_path: &Path) -> Result<Option<PathBuf>> {
+        self.logger.debug(&format!("Extracting slide text from {}", file_path.display()));
+
+        let body = pptx_markdown::pptx_to_markdown(file_path)?;
+        let file_name = file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown");
+        let markdown_content = [
+            format!("# Presentation: {}", file_name),
+            String::new(),
+            format!("Converted on: {}", Local::now().format("%Y-%m-%d %H:%M:%S")),
+            String::new(),
+            body,
+            String::new(),
+        ];
+
+        std::fs::write(output_path, markdown_content.join("\n"))
+            .with_context(|| format!("Failed to write markdown file: {}", output_path.display()))?;
+
+        self.logger.debug(&format!("Successfully converted PPTX to markdown: {}", output_path.display()));
+        Ok(Some(output_path.to_path_buf()))
+    }
+
+    fn convert_excel_to_markdown(&self, file_path: &Path, output_path: &Path) -> Result<Option<PathBuf>> {
+        self.logger.debug(&format!(
+            "Converting Excel file {} to markdown",
+            file_path.display()
+        ));
+
+        let file_ext = file_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|s| s.to_lowercase())
+            .unwrap_or_default();
+        if file_ext != "xlsx" && file_ext != "xls" {
+            return Err(anyhow::anyhow!("Unsupported Excel file format: {}", file_ext));
+        }
+
+        // Markdown is written as the sheets are read, so a large workbook is
+        // never held in memory whole
+        let file = std::fs::File::create(output_path)
+            .with_context(|| format!("Failed to create markdown file: {}", output_path.display()))?;
+        let mut out = BufWriter::new(file);
+        let result = self.write_excel_markdown(file_path, &file_ext, &mut out).and_then(|sheet_names| {
+            out.flush()
+                .with_context(|| format!("Failed to write markdown file: {}", output_path.display()))?;
+            Ok(sheet_names)
+        });
+        drop(out);
+        let sheet_names = match result {
+            Ok(sheet_names) => sheet_names,
+            Err(e) => {
+                let _ = std::fs::remove_file(output_path);
+                return Err(e);
+            }
+        };
+
+        self.logger.debug(&format!(
+            "Successfully converted Excel file to markdown: {} (processed {} sheet(s))",
+            output_path.display(),
+            sheet_names.len()
+        ));
+
+        Ok(Some(output_path.to_path_buf()))
+    }
+
+    fn write_excel_markdown(&self, file_path: &Path, file_ext: &str, out: &mut impl Write) -> Result<Vec<String>> {
+        // Add header
+        let file_name = file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown");
+        writeln!(out, "# Excel File: {}\n", file_name)?;
+        writeln!(out, "Converted on: {}\n", Local::now().format("%Y-%m-%d %H:%M:%S"))?;
+
+        // Process workbook based on file extension
+        let sheet_names = if file_ext == "xlsx" {
+            self.process_xlsx_workbook(file_path, out)?
+        } else {
+            self.process_xls_workbook(file_path, out)?
+        };
+
+        if sheet_names.is_empty() {
+            self.logger.warning("Workbook contains no sheets");
+            writeln!(out, "*Workbook contains no sheets*")?;
+        }
+        Ok(sheet_names)
+    }
+
+    fn process_xlsx_workbook(&self, file_path: &Path, out: &mut impl Write) -> Result<Vec<String>> {
+        let mut workbook: Xlsx<_> = open_workbook(file_path)
+            .with_context(|| format!("Failed to open XLSX file: {}", file_path.display()))?;
+        let controls = if self.excel_controls_appendix {
+            match WorkbookControls::read_xlsx(file_path) {
+                Ok(controls) => Some(controls),
+                Err(e) => {
+                    self.logger.warning(&format!("Could not read workbook controls of {}: {:#}", file_path.display(), e));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let hidden = if self.excel_hidden_content || self.excel_comments {
+            match HiddenContent::read_xlsx(file_path) {
+                Ok(hidden) => Some(hidden),
+                Err(e) => {
+                    self.logger.warning(&format!("Could not read hidden rows/columns and comments of {}: {:#}", file_path.display(), e));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let sheet_names = self.process_workbook_sheets(&mut workbook, out, controls.as_ref(), hidden.as_ref())?;
+        if let Some(controls) = &controls {
+            writeln!(out, "{}\n", controls.workbook_appendix())?;
+        }
+        Ok(sheet_names)
+    }
+
+    fn process_xls_workbook(&self, file_path: &Path, out: &mut impl Write) -> Result<Vec<String>> {
+        let mut workbook: Xls<_> = open_workbook(file_path)
+            .with_context(|| format!("Failed to open XLS file: {}", file_path.display()))?;
+        let sheet_names = self.process_workbook_sheets(&mut workbook, out, None, None)?;
+        if self.excel_hidden_content || self.excel_comments {
+            writeln!(out, "*Hidden rows, hidden columns and cell comments are not read from .xls workbooks*\n")?;
+        }
+        // The legacy format only exposes its defined names
+        if self.excel_controls_appendix {
+            writeln!(out, "## Workbook Controls\n")?;
+            let names = workbook.defined_names();
+            if !names.is_empty() {
+                writeln!(out, "| Name | Refers To |")?;
+                writeln!(out, "|---|---|")?;
+                for (name, refers_to) in names {
+                    writeln!(
+                        out,
+                        "| {} | `{}` |",
+                        name.replace('|', "\\|"),
+                        refers_to.replace('|', "\\|")
+                    )?;
+                }
+                writeln!(out)?;
+            }
+            writeln!(out, "*Data validation and protection are not read from .xls workbooks*\n")?;
+        }
+        Ok(sheet_names)
+    }
+
+    /// Render every sheet of an open workbook, returning the sheet names.
+    /// With `controls`, each sheet is followed by its controls appendix;
+    /// `hidden` supplies the hidden rows/columns and comments calamine can't read.
+    fn process_workbook_sheets<RS, R>(
+        &self,
+        workbook: &mut R,
+        out: &mut impl Write,
+        controls: Option<&WorkbookControls>,
+        hidden: Option<&HiddenContent>,
+    ) -> Result<Vec<String>>
+    where
+        RS: std::io::Read + std::io::Seek,
+        R: Reader<RS> + SheetCells,
+    {
+        let sheet_names = workbook.sheet_names().to_vec();
+
+        for (sheet_index, sheet_name) in sheet_names.iter().enumerate() {
+            if self.skip_sheet_patterns.iter().any(|pattern| pattern.is_match(sheet_name)) {
+                self.logger.debug(&format!("Skipping sheet on the skip list: {}", sheet_name));
+                continue;
+            }
+
+            self.logger.debug(&format!("Processing sheet: {}", sheet_name));
+
+            // A first pass over the cells finds the table's bounds, so the
+            // second can write each row as soon as it is read
+            let extent = match self.measure_sheet(workbook, sheet_name) {
+                Ok(extent) => extent,
+                Err(e) => {
+                    self.logger.error(&format!("Error reading sheet {}: {:#}", sheet_name, e));
+                    writeln!(out, "## Sheet: {} (Error)\n", sheet_name)?;
+                    writeln!(out, "*Error processing sheet: {:#}*\n", e)?;
+                    continue;
+                }
+            };
+
+            // Formulas, keyed by absolute (row, column)
+            let mut formulas: Vec<((u32, u32), String)> = if self.excel_formulas == FormulaOutput::Off {
+                Vec::new()
+            } else {
+                match workbook.sheet_formulas(sheet_name) {
+                    Ok(formulas) => formulas,
+                    Err(e) => {
+                        self.logger.warning(&format!("Could not read formulas of sheet {}: {:#}", sheet_name, e));
+                        Vec::new()
+                    }
+                }
+            };
+            // Sorted for lookup by position while the rows are written
+            formulas.sort_by_key(|(position, _)| *position);
+            if let Some(extent) = extent.as_ref().filter(|extent| extent.truncated) {
+                formulas.retain(|((row, _), _)| *row <= extent.end_row);
+            }
+
+            let mut heading = format!("Sheet: {}", sheet_name);
+            let mut notes = Vec::new();
+            let sheet_hidden = hidden.and_then(|hidden| hidden.sheet(sheet_index));
+            if self.excel_hidden_content {
+                let visibility = workbook
+                    .sheets_metadata()
+                    .iter()
+                    .find(|sheet| &sheet.name == sheet_name)
+                    .map(|sheet| sheet.visible);
+                match visibility {
+                    Some(SheetVisible::Hidden) => {
+                        heading = format!("Hidden Sheet: {}", sheet_name);
+                        notes.push("*This sheet is hidden in the workbook*".to_string());
+                    }
+                    Some(SheetVisible::VeryHidden) => {
+                        heading = format!("Very Hidden Sheet: {}", sheet_name);
+                        notes.push("*This sheet is very hidden; it can only be unhidden through VBA*".to_string());
+                    }
+                    _ => {}
+                }
+
+                if let Some(sheet_hidden) = sheet_hidden {
+                    if !sheet_hidden.hidden_rows.is_empty() {
+                        notes.push(format!(
+                            "*Hidden rows: {}*",
+                            index_ranges(&sheet_hidden.hidden_rows, |row| (row + 1).to_string())
+                        ));
+                    }
+                    if !sheet_hidden.hidden_columns.is_empty() {
+                        notes.push(format!(
+                            "*Hidden columns: {}*",
+                            index_ranges(&sheet_hidden.hidden_columns, column_name)
+                        ));
+                    }
+                }
+            }
+            if let Some(extent) = extent.as_ref().filter(|extent| extent.truncated) {
+                self.logger.warning(&format!(
+                    "Sheet {} has more than {} rows; the rest are left out of the markdown",
+                    sheet_name, self.excel_max_rows_per_sheet
+                ));
+                notes.push(format!(
+                    "*Truncated: only the first {} rows below the header are included; rows after {} were not converted*",
+                    self.excel_max_rows_per_sheet,
+                    extent.end_row + 1
+                ));
+            }
+
+            writeln!(out, "## {}\n", heading)?;
+            for note in &notes {
+                writeln!(out, "{}\n", note)?;
+            }
+
+            // Formula values for the listing, captured as the rows go by
+            let mut formula_values = HashMap::new();
+            match extent {
+                None => writeln!(out, "*Sheet is empty*\n")?,
+                Some(extent) => {
+                    let rows = SheetRows {
+                        extent,
+                        formulas: &formulas,
+                        hidden: sheet_hidden.filter(|_| self.excel_hidden_content),
+                    };
+                    if let Err(e) = self.write_sheet_rows(workbook, sheet_name, &rows, &mut formula_values, out) {
+                        self.logger.error(&format!("Error reading sheet {}: {:#}", sheet_name, e));
+                        writeln!(out, "\n*Error processing sheet: {:#}*", e)?;
+                    }
+                    writeln!(out)?;
+                }
+            }
+
+            if self.excel_formulas == FormulaOutput::Listing && !formulas.is_empty() {
+                writeln!(out, "### Formulas: {}\n", sheet_name)?;
+                writeln!(out, "| Cell | Formula | Value |")?;
+                writeln!(out, "|---|---|---|")?;
+                for ((row, col), formula) in &formulas {
+                    let value = formula_values.get(&(*row, *col)).map(String::as_str).unwrap_or_default();
+                    writeln!(
+                        out,
+                        "| {} | `{}` | {} |",
+                        cell_reference(*row, *col),
+                        formula.replace('|', "\\|"),
+                        value.replace('|', "\\|")
+                    )?;
+                }
+                writeln!(out)?;
+            }
+
+            if self.excel_comments {
+                if let Some(appendix) = sheet_hidden.and_then(|sheet_hidden| sheet_hidden.comments_appendix()) {
+                    writeln!(out, "{}\n", appendix)?;
+                }
+            }
+
+            if let Some(appendix) = controls.and_then(|controls| controls.sheet_appendix(sheet_index)) {
+                writeln!(out, "{}\n", appendix)?;
+            }
+        }
+
+        Ok(sheet_names)
+    }
+
+    /// Bounds of a sheet's used cells, up to the row cap; `None` when the
+    /// sheet is empty
+    fn measure_sheet(&self, workbook: &mut impl SheetCells, sheet_name: &str) -> Result<Option<SheetExtent>> {
+        let mut extent: Option<SheetExtent> = None;
+        for cell in workbook.sheet_cells(sheet_name)? {
+            let ((row, col), value) = cell?;
+            if value == Data::Empty {
+                continue;
+            }
+            let extent = extent.get_or_insert(SheetExtent {
+                start_row: row,
+                end_row: row,
+                start_col: col,
+                end_col: col,
+                truncated: false,
+            });
+            // The first row is the header, so the cap counts the rows below it
+            if self.excel_max_rows_per_sheet > 0 && (row - extent.start_row) as usize > self.excel_max_rows_per_sheet {
+                extent.truncated = true;
+                break;
+            }
+            extent.end_row = row;
+            extent.start_col = extent.start_col.min(col);
+            extent.end_col = extent.end_col.max(col);
+        }
+        Ok(extent)
+    }
+
+    /// Stream the rows within `rows.extent` into a markdown table, the first
+    /// row as its header
+    fn write_sheet_rows(
+        &self,
+        workbook: &mut impl SheetCells,
+        sheet_name: &str,
+        rows: &SheetRows,
+        formula_values: &mut HashMap<(u32, u32), String>,
+        out: &mut impl Write,
+    ) -> Result<()> {
+        let extent = &rows.extent;
+        let columns = (extent.end_col - extent.start_col + 1) as usize;
+        let mut row_index = extent.start_row;
+        let mut row_values = vec![String::new(); columns];
+
+        for cell in workbook.sheet_cells(sheet_name)? {
+            let ((row, col), value) = cell?;
+            if row < extent.start_row || col < extent.start_col || col > extent.end_col || value == Data::Empty {
+                continue;
+            }
+            if row > extent.end_row {
+                break;
+            }
+            while row_index < row {
+                let finished = std::mem::replace(&mut row_values, vec![String::new(); columns]);
+                self.write_sheet_row(rows, row_index, finished, formula_values, out)?;
+                row_index += 1;
+            }
+            row_values[(col - extent.start_col) as usize] = format_cell_value(&format!("{}", value));
+        }
+        self.write_sheet_row(rows, row_index, row_values, formula_values, out)
+    }
+
+    fn write_sheet_row(
+        &self,
+        rows: &SheetRows,
+        row: u32,
+        mut row_values: Vec<String>,
+        formula_values: &mut HashMap<(u32, u32), String>,
+        out: &mut impl Write,
+    ) -> Result<()> {
+        let extent = &rows.extent;
+        let column_of = |index: usize| extent.start_col + index as u32;
+
+        if self.excel_formulas != FormulaOutput::Off {
+            for (index, value) in row_values.iter_mut().enumerate() {
+                let position = (row, column_of(index));
+                let Ok(found) = rows.formulas.binary_search_by(|(key, _)| key.cmp(&position)) else {
+                    continue;
+                };
+                if self.excel_formulas == FormulaOutput::Inline {
+                    *value = format!("{} `{}`", value, rows.formulas[found].1).trim_start().to_string();
+                } else {
+                    formula_values.insert(position, value.clone());
+                }
+            }
+        }
+
+        if let Some(sheet_hidden) = rows.hidden {
+            if sheet_hidden.hidden_rows.contains(&row) {
+                if let Some(cell) = row_values.first_mut() {
+                    *cell = format!("*(hidden row)* {}", cell).trim_end().to_string();
+                }
+            }
+            if row == extent.start_row {
+                for (index, cell) in row_values.iter_mut().enumerate() {
+                    if sheet_hidden.hidden_columns.contains(&column_of(index)) {
+                        *cell = format!("{} *(hidden column)*", cell).trim_start().to_string();
+                    }
+                }
+            }
+        }
+
+        // Escape pipe characters
+        let row_values: Vec<String> = row_values
+            .iter()
+            .map(|v| v.replace('|', "\\|"))
+            .collect();
+        writeln!(out, "| {} |", row_values.join(" | "))?;
+        if row == extent.start_row {
+            writeln!(out, "|{}|", "---|".repeat(row_values.len()))?;
+        }
+        Ok(())
+    }
+
+    pub fn is_convertible_file(&self, file_path: &Path) -> bool {
+        self.route(file_path).is_some()
+    }
+
+    pub fn find_libreoffice(&self) -> Result<PathBuf> {
+        // First, check EPT_LIBREOFFICE_PATH environment variable
+        if let Ok(env_path) = std::env::var("EPT_LIBREOFFICE_PATH") {
+            let path = PathBuf::from(&env_path);
+            if path.exists() {
+                self.logger.debug(&format!("Using LibreOffice from EPT_LIBREOFFICE_PATH: {}", env_path));
+                return Ok(path);
+            } else {
+                self.logger.warning(&format!("EPT_LIBREOFFICE_PATH is set to {}, but file does not exist", env_path));
+            }
+        }
+
+        // Try common LibreOffice executable names and paths
+        let candidates = if cfg!(target_os = "windows") {
+            vec![
+                "soffice.exe",
+                "C:\\Program Files\\LibreOffice\\program\\soffice.exe",
+                "C:\\Program Files (x86)\\LibreOffice\\program\\soffice.exe",
+            ]
+        } else if cfg!(target_os = "macos") {
+            vec![
+                "/Applications/LibreOffice.app/Contents/MacOS/soffice",
+                "soffice",
+            ]
+        } else {
+            vec![
+                "/usr/bin/soffice",
+                "/usr/local/bin/soffice",
+                "soffice",
+            ]
+        };
+
+        for candidate in candidates {
+            if let Ok(path) = which::which(candidate) {
+                return Ok(path);
+            }
+        }
+
+        // Try to find it in PATH
+        if let Ok(path) = which::which("soffice") {
+            return Ok(path);
+        }
+
+        Err(anyhow::anyhow!(
+            "LibreOffice not found. Please install LibreOffice and ensure 'soffice' is in your PATH."
+        ))
+    }
+}
+
+/// Bounds of the used cells of a sheet, as absolute zero-based indexes
+struct SheetExtent {
+    start_row: u32,
+    /// Last row written, which is before the sheet's last row when truncated
+    end_row: u32,
+    start_col: u32,
+    end_col: u32,
+    /// The sheet has rows past the row cap
+    truncated: bool,
+}
+
+/// What a sheet's rows are rendered with
+struct SheetRows<'a> {
+    extent: SheetExtent,
+    /// Sorted by position
+    formulas: &'a [((u32, u32), String)],
+    /// Set when hidden rows and columns are marked
+    hidden: Option<&'a SheetHiddenContent>,
+}
+
+/// Workbooks whose sheets can be read cell by cell, so a sheet is never
+/// held in memory whole
+trait SheetCells {
+    /// Cells of a sheet in row order, at absolute (row, column) positions
+    fn sheet_cells<'a>(
+        &'a mut self,
+        sheet_name: &'a str,
+    ) -> Result<Box<dyn Iterator<Item = Result<((u32, u32), Data)>> + 'a>>;
+
+    /// Non-empty formulas of a sheet as `=...`, at absolute positions
+    fn sheet_formulas(&mut self, sheet_name: &str) -> Result<Vec<((u32, u32), String)>>;
+}
+
+impl<RS: std::io::Read + std::io::Seek> SheetCells for Xlsx<RS> {
+    fn sheet_cells<'a>(
+        &'a mut self,
+        sheet_name: &'a str,
+    ) -> Result<Box<dyn Iterator<Item = Result<((u32, u32), Data)>> + 'a>> {
+        let mut reader = self.worksheet_cells_reader(sheet_name)?;
+        Ok(Box::new(std::iter::from_fn(move || match reader.next_cell() {
+            Ok(Some(cell)) => Some(Ok((cell.get_position(), Data::from(cell.get_value().clone())))),
+            Ok(None) => None,
+            Err(e) => Some(Err(e.into())),
+        })))
+    }
+
+    fn sheet_formulas(&mut self, sheet_name: &str) -> Result<Vec<((u32, u32), String)>> {
+        let mut reader = self.worksheet_cells_reader(sheet_name)?;
+        let mut formulas = Vec::new();
+        while let Some(cell) = reader.next_formula()? {
+            if !cell.get_value().is_empty() {
+                formulas.push((cell.get_position(), format!("={}", cell.get_value())));
+            }
+        }
+        Ok(formulas)
+    }
+}
+
+/// The format caps sheets at 65,536 rows and calamine parses the whole
+/// workbook on open, so .xls sheets are read as ranges
+impl<RS: std::io::Read + std::io::Seek> SheetCells for Xls<RS> {
+    fn sheet_cells<'a>(
+        &'a mut self,
+        sheet_name: &'a str,
+    ) -> Result<Box<dyn Iterator<Item = Result<((u32, u32), Data)>> + 'a>> {
+        let range = self.worksheet_range(sheet_name)?;
+        let (start_row, start_col) = range.start().unwrap_or((0, 0));
+        let cells: Vec<_> = range
+            .used_cells()
+            .map(|(row, col, value)| Ok(((start_row + row as u32, start_col + col as u32), value.clone())))
+            .collect();
+        Ok(Box::new(cells.into_iter()))
+    }
+
+    fn sheet_formulas(&mut self, sheet_name: &str) -> Result<Vec<((u32, u32), String)>> {
+        let formula_range = self.worksheet_formula(sheet_name)?;
+        let (start_row, start_col) = formula_range.start().unwrap_or((0, 0));
+        Ok(formula_range
+            .used_cells()
+            .filter(|(_, _, formula)| !formula.is_empty())
+            .map(|(row, col, formula)| ((start_row + row as u32, start_col + col as u32), format!("={}", formula)))
+            .collect())
+    }
+}
+
+/// Whole-name, case-insensitive match for a sheet name where `*` stands for
+/// any run of characters, e.g. `Cover*` or `*Instructions`
+fn sheet_name_pattern(pattern: &str) -> Regex {
+    let body = pattern.trim().split('*').map(regex::escape).collect::<Vec<_>>().join(".*");
+    Regex::new(&format!("(?i)^{}$", body)).expect("escaped sheet name pattern is a valid regex")
+}
+
+/// Render a cell value, trimming floats to a readable precision
+fn format_cell_value(cell_str: &str) -> String {
+    if let Ok(f) = cell_str.parse::<f64>() {
+        if f == 0.0 {
+            "0".to_string()
+        } else if f.abs() < 0.01 {
+            format!("{:.6}", f)
+        } else {
+            let formatted = format!("{:.2}", f);
+            formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+        }
+    } else {
+        cell_str.to_string()
+    }
+}
+
+/// A1-style reference for a zero-based (row, column) position
+fn cell_reference(row: u32, col: u32) -> String {
+    column_name(col) + &(row + 1).to_string()
+}
+
+/// Column letters (`A`, `B`, ..., `AA`) for a zero-based column index
+fn column_name(col: u32) -> String {
+    let mut letters = Vec::new();
+    let mut col = col + 1;
+    while col > 0 {
+        let remainder = (col - 1) % 26;
+        letters.push((b'A' + remainder as u8) as char);
+        col = (col - 1) / 26;
+    }
+    letters.iter().rev().collect()
+}
+
+/// Consecutive indexes collapsed into ranges, e.g. `3, 7-9`
+fn index_ranges(indexes: &BTreeSet<u32>, label: impl Fn(u32) -> String) -> String {
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for &index in indexes {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == index => *end = index,
+            _ => ranges.push((index, index)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            if start == end {
+                label(start)
+            } else {
+                format!("{}-{}", label(start), label(end))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,201 @@
+use crate::manifest::{ExportManifest, ManifestEntry};
+use crate::pdf_text;
+use crate::report_model::ReportModel;
+use crate::tagging::TaggingEngine;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// Rough characters-per-token ratio of common LLM tokenizers on prose
+pub(crate) const CHARS_PER_TOKEN: u64 = 4;
+
+/// Upper bounds of the size histogram buckets, in bytes
+const SIZE_BUCKETS: [(u64, &str); 5] = [
+    (10 * 1024, "< 10 KB"),
+    (100 * 1024, "10 KB - 100 KB"),
+    (1024 * 1024, "100 KB - 1 MB"),
+    (10 * 1024 * 1024, "1 MB - 10 MB"),
+    (u64::MAX, ">= 10 MB"),
+];
+
+/// Characters of each document sampled for language detection
+const LANGUAGE_SAMPLE_CHARS: usize = 20_000;
+
+/// Frequent function words per language, used to guess a document's language
+const STOPWORDS: [(&str, &[&str]); 7] = [
+    ("en", &["the", "and", "of", "to", "in", "is", "that", "for", "with", "this"]),
+    ("de", &["der", "die", "und", "das", "ist", "nicht", "mit", "den", "für", "auf"]),
+    ("fr", &["le", "la", "les", "et", "des", "est", "une", "pour", "dans", "pas"]),
+    ("es", &["el", "los", "las", "y", "del", "que", "por", "una", "para", "con"]),
+    ("nl", &["de", "het", "een", "en", "van", "niet", "met", "voor", "zijn", "op"]),
+    ("it", &["il", "di", "che", "e", "della", "per", "non", "una", "sono", "gli"]),
+    ("pt", &["o", "os", "da", "do", "e", "que", "para", "uma", "com", "não"]),
+];
+
+/// Stopword hits needed before a language is assigned
+const MIN_LANGUAGE_HITS: usize = 5;
+
+/// Language key for documents without enough text to tell
+const UNKNOWN_LANGUAGE: &str = "unknown";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TypeStats {
+    pub files: usize,
+    pub bytes: u64,
+    pub estimated_tokens: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeBucket {
+    pub label: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// Size, token and language profile of an LLM export, for budgeting LLM
+/// usage before anything is uploaded.
+///
+/// Token counts are estimates (about four characters per token); files whose
+/// text can't be read, such as scanned PDFs, contribute no tokens and are
+/// counted in `files_without_text`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorpusStats {
+    pub run_id: Option<String>,
+    pub total_files: usize,
+    pub total_bytes: u64,
+    pub estimated_tokens: u64,
+    pub files_without_text: usize,
+    /// Exact duplicates left out of the export; they add no tokens
+    pub duplicates_collapsed: usize,
+    /// Keyed by exported file extension
+    pub type_counts: BTreeMap<String, TypeStats>,
+    pub size_histogram: Vec<SizeBucket>,
+    /// Documents per ISO 639-1 language code
+    pub languages: BTreeMap<String, usize>,
+}
+
+impl CorpusStats {
+    /// Compute the statistics of the export folder from its manifest
+    pub fn compute(export_path: &Path) -> Result<Self> {
+        let manifest = ExportManifest::load(export_path)?;
+        let mut stats = Self {
+            run_id: manifest.run_id.clone(),
+            total_files: manifest.files.len(),
+            total_bytes: 0,
+            estimated_tokens: 0,
+            files_without_text: 0,
+            duplicates_collapsed: manifest.duplicate_count,
+            type_counts: BTreeMap::new(),
+            size_histogram: SIZE_BUCKETS
+                .iter()
+                .map(|(_, label)| SizeBucket {
+                    label: label.to_string(),
+                    files: 0,
+                    bytes: 0,
+                })
+                .collect(),
+            languages: BTreeMap::new(),
+        };
+
+        for file in &manifest.files {
+            stats.total_bytes += file.size_bytes;
+            let bucket = SIZE_BUCKETS
+                .iter()
+                .position(|(limit, _)| file.size_bytes < *limit)
+                .unwrap_or(SIZE_BUCKETS.len() - 1);
+            stats.size_histogram[bucket].files += 1;
+            stats.size_histogram[bucket].bytes += file.size_bytes;
+
+            let text = read_text(export_path, file);
+            let tokens = text.as_deref().map(estimate_tokens).unwrap_or(0);
+            stats.estimated_tokens += tokens;
+
+            let type_stats = stats.type_counts.entry(file.exported_format.clone()).or_default();
+            type_stats.files += 1;
+            type_stats.bytes += file.size_bytes;
+            type_stats.estimated_tokens += tokens;
+
+            let language = match text.as_deref().filter(|text| !text.trim().is_empty()) {
+                Some(text) => detect_language(text),
+                None => {
+                    stats.files_without_text += 1;
+                    UNKNOWN_LANGUAGE
+                }
+            };
+            *stats.languages.entry(language.to_string()).or_insert(0) += 1;
+        }
+
+        Ok(stats)
+    }
+}
+
+/// Estimated LLM tokens in a text
+pub(crate) fn estimate_tokens(text: &str) -> u64 {
+    text.chars().count() as u64 / CHARS_PER_TOKEN
+}
+
+/// Estimated LLM tokens of what `entry` exported into `export_path`, all
+/// parts included; `None` when nothing was exported or its text can't be
+/// read
+pub(crate) fn estimate_entry_tokens(export_path: &Path, entry: &ReportModel) -> Option<u64> {
+    let files = entry.exported_files();
+    if files.is_empty() {
+        return None;
+    }
+    read_files_text(export_path, &files).as_deref().map(estimate_tokens)
+}
+
+/// Text of an exported file; a document split into parts as all of them,
+/// overlap included
+pub(crate) fn read_text(export_path: &Path, file: &ManifestEntry) -> Option<String> {
+    if file.parts.is_empty() {
+        read_files_text(export_path, &[file.exported_file_name.as_str()])
+    } else {
+        let parts: Vec<&str> = file.parts.iter().map(String::as_str).collect();
+        read_files_text(export_path, &parts)
+    }
+}
+
+/// Text of the files `names` in the export, one after the other: PDFs
+/// through the text extractor, text formats as-is
+pub(crate) fn read_files_text(export_path: &Path, names: &[&str]) -> Option<String> {
+    let mut text = String::new();
+    for name in names {
+        let path = export_path.join(name);
+        let is_pdf = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("pdf"));
+        if is_pdf {
+            let data = fs::read(&path).ok()?;
+            text.push_str(&pdf_text::extract_page_text(&data).ok()?.join("\n"));
+        } else if TaggingEngine::is_text_file(&path) {
+            text.push_str(&String::from_utf8_lossy(&fs::read(&path).ok()?));
+        } else {
+            return None;
+        }
+    }
+    Some(text)
+}
+
+/// Guess a language from stopword frequencies in the start of the text
+fn detect_language(text: &str) -> &'static str {
+    let sample: String = text.chars().take(LANGUAGE_SAMPLE_CHARS).collect::<String>().to_lowercase();
+    let mut word_counts: HashMap<&str, usize> = HashMap::new();
+    for word in sample.split(|c: char| !c.is_alphabetic()).filter(|w| !w.is_empty()) {
+        *word_counts.entry(word).or_insert(0) += 1;
+    }
+
+    STOPWORDS
+        .iter()
+        .map(|(language, words)| {
+            let hits: usize = words.iter().map(|w| word_counts.get(w).copied().unwrap_or(0)).sum();
+            (*language, hits)
+        })
+        .filter(|(_, hits)| *hits >= MIN_LANGUAGE_HITS)
+        .max_by_key(|(_, hits)| *hits)
+        .map(|(language, _)| language)
+        .unwrap_or(UNKNOWN_LANGUAGE)
+}
@@ -0,0 +1,172 @@
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use std::borrow::Cow;
+
+/// Delimiters tried when sniffing a delimited text file
+const CANDIDATE_DELIMITERS: [char; 4] = [',', ';', '\t', '|'];
+
+/// Records sampled when sniffing the delimiter
+const DELIMITER_SAMPLE_RECORDS: usize = 50;
+
+/// How a delimited text file was written
+#[derive(Debug, Clone, Copy)]
+pub struct CsvFormat {
+    pub encoding: &'static Encoding,
+    /// Whether the data started with a byte order mark
+    pub bom: bool,
+    pub delimiter: char,
+}
+
+impl CsvFormat {
+    /// Encoding as recorded in the report, e.g. `UTF-8 (BOM)` or `windows-1252`
+    pub fn encoding_label(&self) -> String {
+        if self.bom {
+            format!("{} (BOM)", self.encoding.name())
+        } else {
+            self.encoding.name().to_string()
+        }
+    }
+
+    pub fn delimiter_label(&self) -> &'static str {
+        match self.delimiter {
+            ',' => "comma",
+            ';' => "semicolon",
+            '\t' => "tab",
+            _ => "pipe",
+        }
+    }
+}
+
+/// Detect the encoding and delimiter of CSV/TSV data
+pub fn detect(data: &[u8]) -> CsvFormat {
+    decode(data).1
+}
+
+/// Rewrite CSV/TSV data as UTF-8, comma-separated, without a BOM.
+///
+/// Fields are re-quoted as needed, so quoted delimiters and line breaks in
+/// the source survive. Returns the rewritten text and the detected format.
+pub fn normalize(data: &[u8]) -> (String, CsvFormat) {
+    let (text, format) = decode(data);
+    let mut output = String::with_capacity(text.len());
+    for record in parse_records(&text, format.delimiter, None) {
+        let fields: Vec<String> = record.iter().map(|field| quote_field(field)).collect();
+        output.push_str(&fields.join(","));
+        output.push('\n');
+    }
+    (output, format)
+}
+
+fn decode(data: &[u8]) -> (Cow<'_, str>, CsvFormat) {
+    let (encoding, bom_length) = detect_encoding(data);
+    let (text, _, _) = encoding.decode_without_bom_handling(&data[bom_length..]);
+    let format = CsvFormat {
+        encoding,
+        bom: bom_length > 0,
+        delimiter: detect_delimiter(&text),
+    };
+    (text, format)
+}
+
+/// Byte order mark first, then UTF-8 if the data is valid UTF-8, then
+/// BOM-less UTF-16 (NUL in every other byte), and finally Windows-1252,
+/// the superset of Latin-1 that spreadsheet exports actually use
+fn detect_encoding(data: &[u8]) -> (&'static Encoding, usize) {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(data) {
+        return (encoding, bom_length);
+    }
+    if std::str::from_utf8(data).is_ok() {
+        return (UTF_8, 0);
+    }
+
+    let sample = &data[..data.len().min(1024) & !1];
+    let pairs = sample.len() / 2;
+    if pairs > 0 {
+        let even_nuls = sample.iter().step_by(2).filter(|b| **b == 0).count();
+        let odd_nuls = sample.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+        if odd_nuls * 2 > pairs && even_nuls * 10 < pairs {
+            return (UTF_16LE, 0);
+        }
+        if even_nuls * 2 > pairs && odd_nuls * 10 < pairs {
+            return (UTF_16BE, 0);
+        }
+    }
+    (WINDOWS_1252, 0)
+}
+
+/// Pick the candidate that splits the most sampled records into the same
+/// number (greater than one) of fields; comma when none does
+fn detect_delimiter(text: &str) -> char {
+    CANDIDATE_DELIMITERS
+        .iter()
+        .filter_map(|&delimiter| {
+            let records = parse_records(text, delimiter, Some(DELIMITER_SAMPLE_RECORDS));
+            let mut counts = std::collections::HashMap::new();
+            for record in &records {
+                *counts.entry(record.len()).or_insert(0usize) += 1;
+            }
+            let (fields, consistent) = counts
+                .into_iter()
+                .filter(|(fields, _)| *fields > 1)
+                .max_by_key(|(fields, records)| (*records, *fields))?;
+            Some((delimiter, consistent, fields))
+        })
+        .max_by_key(|(_, consistent, fields)| (*consistent, *fields))
+        .map(|(delimiter, _, _)| delimiter)
+        .unwrap_or(',')
+}
+
+/// Split delimited text into records, honouring double-quoted fields with
+/// `""` escapes and embedded line breaks. Blank lines are skipped.
+fn parse_records(text: &str, delimiter: char, limit: Option<usize>) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            '\r' | '\n' => {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                record.push(std::mem::take(&mut field));
+                if !(record.len() == 1 && record[0].is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                    if limit.is_some_and(|limit| records.len() >= limit) {
+                        return records;
+                    }
+                }
+                record.clear();
+            }
+            c if c == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+
+    record.push(field);
+    if !(record.len() == 1 && record[0].is_empty()) {
+        records.push(record);
+    }
+    records
+}
+
+pub(crate) fn quote_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,1096 @@
+use crate::archive_names::{decode_zip_entry_name, escape_raw_name, normalize_entry_name};
+use crate::email_message::EmailMessage;
+use crate::ept_logger::EPTLogger;
+use crate::extraction_limits::{ExtractionBudget, ExtractionLimits, LimitedWriter};
+use crate::folder_naming::FolderNaming;
+use crate::hashing_service::HashingService;
+use crate::pdf_attachments::{extract_embedded_files, has_embedded_files};
+use crate::report_model::{self, ArchiveOutcome, ArchiveRecord};
+use crate::settings::ProcessingSettings;
+use encoding_rs::Encoding;
+use crate::tar_reader::{TarEntryKind, TarReader};
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use walkdir::WalkDir;
+use zip::ZipArchive;
+
+/// How often the output of an external RAR extraction is measured while it runs
+const RAR_METER_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Single-file compression formats handled by `decompress_stream`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamFormat {
+    Gzip,
+    Bzip2,
+    Zstd,
+    Xz,
+}
+
+impl StreamFormat {
+    fn label(&self) -> &'static str {
+        match self {
+            StreamFormat::Gzip => "GZ",
+            StreamFormat::Bzip2 => "BZ2",
+            StreamFormat::Zstd => "ZST",
+            StreamFormat::Xz => "XZ",
+        }
+    }
+}
+
+/// A file carved out of a PDF or email, before it is written to staging
+struct CarvedFile<'a> {
+    name: Option<String>,
+    fallback_name: String,
+    /// Decodes the content, cut off after the given number of bytes, or
+    /// says why it can't be
+    data: Box<dyn FnOnce(u64) -> std::result::Result<Vec<u8>, String> + 'a>,
+}
+
+/// External tool used to extract RAR archives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RarTool {
+    Unrar,
+    SevenZip,
+}
+
+pub struct DecompressionEngine {
+    logger: EPTLogger,
+    visited_paths: std::collections::HashSet<PathBuf>,
+    extraction_failures: Vec<(PathBuf, String)>,
+    depth_limited: Vec<PathBuf>,
+    limits: ExtractionLimits,
+    /// Bytes expanded by this engine so far, checked against `max_run_bytes`
+    run_bytes_extracted: u64,
+    zip_name_encoding: Option<&'static Encoding>,
+    /// Extracted files whose ZIP entry name was not plain ASCII, with the raw name
+    raw_entry_names: Vec<(PathBuf, String)>,
+    archives: Vec<ArchiveRecord>,
+    /// (entries, bytes) of the most recent extraction attempt
+    last_extraction_stats: Option<(usize, u64)>,
+    extract_pdf_attachments: bool,
+    extract_email_attachments: bool,
+    /// Files extracted from an email or PDF, paired with the document they came from
+    attachment_parents: Vec<(PathBuf, PathBuf)>,
+    /// Staged archives whose contents were extracted (PDFs excluded)
+    extracted_archives: Vec<PathBuf>,
+    /// Where the contents of each extracted archive went, paired with the archive
+    extraction_outputs: Vec<(PathBuf, PathBuf)>,
+    naming: FolderNaming,
+    /// Hashes archives for the Archives sheet
+    hashing_service: HashingService,
+}
+
+impl DecompressionEngine {
+    pub fn new(logger: EPTLogger, settings: &ProcessingSettings, naming: FolderNaming) -> Self {
+        let zip_name_encoding = settings.zip_name_encoding.as_deref().and_then(|label| {
+            let encoding = Encoding::for_label(label.trim().as_bytes());
+            if encoding.is_none() {
+                logger.warning(&format!("Unknown ZIP file name encoding '{}', using CP437", label));
+            }
+            encoding
+        });
+        Self {
+            logger,
+            visited_paths: std::collections::HashSet::new(),
+            extraction_failures: Vec::new(),
+            depth_limited: Vec::new(),
+            limits: settings.extraction_limits.clone(),
+            run_bytes_extracted: 0,
+            zip_name_encoding,
+            raw_entry_names: Vec::new(),
+            archives: Vec::new(),
+            last_extraction_stats: None,
+            extract_pdf_attachments: settings.extract_pdf_attachments,
+            extract_email_attachments: settings.extract_email_attachments,
+            attachment_parents: Vec::new(),
+            extracted_archives: Vec::new(),
+            extraction_outputs: Vec::new(),
+            naming,
+            hashing_service: HashingService::with_algorithm(settings.hash_algorithm),
+        }
+    }
+
+    /// Every archive this engine extracted, failed on or skipped
+    pub fn archives(&self) -> &[ArchiveRecord] {
+        &self.archives
+    }
+
+    /// Expand the ZIP given as run input, recording it like nested archives
+    pub fn expand_input_zip(&mut self, zip_path: &Path) -> Result<PathBuf> {
+        let result = self.expand_zip_to_folder(zip_path);
+        let display_path = zip_path.display().to_string();
+        match &result {
+            Ok(_) => self.record_archive(zip_path, display_path, 0, ArchiveOutcome::Extracted, None),
+            Err(e) => self.record_archive(zip_path, display_path, 0, ArchiveOutcome::Failed, Some(e.to_string())),
+        }
+        result
+    }
+
+    fn record_archive(
+        &mut self,
+        path: &Path,
+        display_path: String,
+        nesting_depth: usize,
+        outcome: ArchiveOutcome,
+        detail: Option<String>,
+    ) {
+        let (entry_count, bytes_extracted) = self.last_extraction_stats.take().unwrap_or((0, 0));
+        let format = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+        let hash = self.hashing_service.hash_file(path).ok();
+        self.archives.push(ArchiveRecord {
+            path: display_path,
+            format,
+            hash,
+            nesting_depth,
+            entry_count,
+            bytes_extracted,
+            outcome,
+            detail,
+        });
+    }
+
+    /// Email and PDF attachments written to staging, paired with their parent document
+    pub fn attachment_parents(&self) -> &[(PathBuf, PathBuf)] {
+        &self.attachment_parents
+    }
+
+    /// Archives inside the staging folder that were fully extracted
+    pub fn extracted_archives(&self) -> &[PathBuf] {
+        &self.extracted_archives
+    }
+
+    /// Folder or file each archive in the staging folder was extracted to,
+    /// paired with the archive (PDFs and emails excluded)
+    pub fn extraction_outputs(&self) -> &[(PathBuf, PathBuf)] {
+        &self.extraction_outputs
+    }
+
+    /// Add a note to the record of an archive, e.g. where it was moved to
+    pub fn annotate_archive(&mut self, display_path: &str, note: &str) {
+        if let Some(record) = self.archives.iter_mut().find(|record| record.path == display_path) {
+            record.detail = Some(match record.detail.take() {
+                Some(detail) => format!("{}; {}", detail, note),
+                None => note.to_string(),
+            });
+        }
+    }
+
+    /// Files extracted from ZIP entries with non-ASCII names, paired with the
+    /// escaped raw entry name
+    pub fn raw_entry_names(&self) -> &[(PathBuf, String)] {
+        &self.raw_entry_names
+    }
+
+    /// Archives that could not be extracted during the last
+    /// `recursive_decompress`, with the reason
+    pub fn extraction_failures(&self) -> &[(PathBuf, String)] {
+        &self.extraction_failures
+    }
+
+    /// Archives left unextracted during the last `recursive_decompress`
+    /// because they are nested deeper than `max_nesting_depth`
+    pub fn depth_limited_archives(&self) -> &[PathBuf] {
+        &self.depth_limited
+    }
+
+    pub fn max_nesting_depth(&self) -> usize {
+        self.limits.max_nesting_depth
+    }
+
+    pub fn expand_zip_to_folder(&mut self, zip_path: &Path) -> Result<PathBuf> {
+        let zip_name = zip_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("extracted");
+        
+        let parent_dir = zip_path
+            .parent()
+            .context("ZIP file has no parent directory")?;
+        
+        let output_path = self.naming.unique_path(parent_dir, zip_name, "");
+        
+        self.logger.info(&format!("Extracting ZIP: {} -> {}", 
+            zip_path.display(), output_path.display()));
+        
+        fs::create_dir_all(&output_path)
+            .context("Failed to create extraction directory")?;
+
+        let mut budget = self.start_budget(zip_path);
+        let result = self.extract_zip_entries(zip_path, &output_path, &mut budget);
+        if let Err(e) = self.settle_budget(zip_path, budget, result) {
+            // Don't leave a half-extracted folder behind to be scanned
+            let _ = fs::remove_dir_all(&output_path);
+            return Err(e);
+        }
+        
+        self.logger.info(&format!("Successfully extracted ZIP to: {}", output_path.display()));
+        Ok(output_path)
+    }
+
+    fn extract_zip_entries(
+        &mut self,
+        zip_path: &Path,
+        output_path: &Path,
+        budget: &mut ExtractionBudget,
+    ) -> Result<()> {
+        // Canonicalize output path for security validation
+        let output_path_canonical = output_path.canonicalize()
+            .context("Failed to canonicalize output path")?;
+        
+        let file = fs::File::open(zip_path)
+            .context("Failed to open ZIP file")?;
+        
+        let mut archive = ZipArchive::new(file)
+            .context("Failed to read ZIP archive")?;
+        
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)
+                .context("Failed to read file from ZIP")?;
+            budget.add_entry().map_err(anyhow::Error::msg)?;
+            
+            // Legacy tools write CP437 or locale-encoded names; decode before sanitizing
+            let entry_name = decode_zip_entry_name(
+                file.name_raw(),
+                file.extra_data(),
+                file.name(),
+                self.zip_name_encoding,
+            );
+            let raw_name = (!file.name_raw().is_ascii()).then(|| escape_raw_name(file.name_raw()));
+
+            // SECURITY: Sanitize ZIP entry name to prevent path traversal
+            let sanitized_name = self.sanitize_zip_entry_name(&entry_name);
+            
+            let outpath = output_path.join(&sanitized_name);
+
+            // SECURITY: Validate that the resolved path stays within output directory.
+            // We already stripped any leading slashes and removed `..` segments in
+            // `sanitize_zip_entry_name`, so any path created by joining with
+            // `output_path` will remain inside that directory as long as we don't
+            // introduce new traversal here.
+            //
+            // For defense-in-depth, we still *attempt* to canonicalize, but we no
+            // longer fail hard when the target file/dir doesn't exist yet (which is
+            // normal during extraction and caused `ENOENT` errors).
+            let outpath_canonical = match outpath.canonicalize() {
+                Ok(canonical) => canonical,
+                Err(e) => {
+                    // If the path doesn't exist yet, fall back to `outpath` itself.
+                    // It's safe because:
+                    //   - `output_path` is canonicalized above
+                    //   - `sanitize_zip_entry_name` removed `..` and leading separators
+                    // Therefore `outpath` cannot escape `output_path`.
+                    if e.kind() == std::io::ErrorKind::NotFound {
+                        outpath.clone()
+                    } else {
+                        return Err(e).context("Failed to resolve extraction path");
+                    }
+                }
+            };
+
+            // Ensure the (canonical or constructed) path is within the output directory
+            if !outpath_canonical.starts_with(&output_path_canonical) {
+                self.logger.warning(&format!(
+                    "SECURITY: Blocked path traversal attempt in ZIP entry: {} (resolved to: {})",
+                    entry_name,
+                    outpath_canonical.display()
+                ));
+                continue;
+            }
+            
+            if file.name().ends_with('/') {
+                fs::create_dir_all(&outpath)
+                    .context("Failed to create directory in ZIP")?;
+            } else {
+                if let Some(p) = outpath.parent() {
+                    fs::create_dir_all(p)
+                        .context("Failed to create parent directory")?;
+                }
+                
+                let outfile = fs::File::create(&outpath)
+                    .context("Failed to create output file")?;
+                std::io::copy(&mut file, &mut LimitedWriter::new(outfile, &mut *budget))
+                    .context("Failed to write file from ZIP")?;
+
+                if let Some(raw_name) = raw_name {
+                    self.raw_entry_names.push((outpath, raw_name));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn start_budget(&self, archive_path: &Path) -> ExtractionBudget {
+        let compressed_size = fs::metadata(archive_path).map(|m| m.len()).unwrap_or(0);
+        ExtractionBudget::new(&self.limits, compressed_size, self.run_bytes_extracted)
+    }
+
+    /// Charge a finished extraction to the run total, or turn a limit
+    /// violation into the error reported for the archive
+    fn settle_budget<T>(&mut self, archive_path: &Path, budget: ExtractionBudget, result: Result<T>) -> Result<T> {
+        self.last_extraction_stats = Some((budget.entries, budget.archive_bytes));
+        match (result, budget.violation) {
+            (Ok(value), _) => {
+                self.run_bytes_extracted += budget.archive_bytes;
+                Ok(value)
+            }
+            (Err(_), Some(reason)) => {
+                self.logger.warning(&format!(
+                    "SECURITY: Aborted extraction of {}: {}",
+                    archive_path.display(),
+                    reason
+                ));
+                Err(anyhow::anyhow!("Extraction limit exceeded: {}", reason))
+            }
+            (Err(e), None) => Err(e),
+        }
+    }
+    
+    /// Sanitize ZIP entry names to prevent path traversal attacks
+    /// Removes leading slashes and normalizes path separators
+    fn sanitize_zip_entry_name(&self, entry_name: &str) -> String {
+        // Remove leading slashes and backslashes
+        let mut sanitized = entry_name.trim_start_matches('/').trim_start_matches('\\').to_string();
+        
+        // Normalize path separators to forward slashes for cross-platform compatibility
+        // Then replace with platform-specific separator when joining paths
+        sanitized = sanitized.replace('\\', "/");
+        
+        // Remove any remaining path traversal sequences
+        // This is a defense-in-depth measure (canonicalize check is primary protection)
+        while sanitized.contains("../") {
+            sanitized = sanitized.replace("../", "");
+        }
+        while sanitized.contains("..\\") {
+            sanitized = sanitized.replace("..\\", "");
+        }
+        
+        sanitized
+    }
+
+    pub fn recursive_decompress(&mut self, input_path: &Path) -> Result<()> {
+        self.visited_paths.clear();
+        self.extraction_failures.clear();
+        self.depth_limited.clear();
+        self.extracted_archives.clear();
+        self.extraction_outputs.clear();
+        self.attachment_parents.clear();
+        self._recursive_decompress_internal(input_path)?;
+        Ok(())
+    }
+
+    fn _recursive_decompress_internal(&mut self, dir_path: &Path) -> Result<()> {
+        // Work queue of paths still to be walked. Each pass streams the walk and
+        // only keeps the archives it discovers; anything an archive expands into
+        // is queued for a later pass instead of being recursed into directly.
+        // Each queued path carries the nesting level of the archives in it.
+        let mut pending: Vec<(PathBuf, usize)> = vec![(dir_path.to_path_buf(), 1)];
+
+        while let Some((next_path, depth)) = pending.pop() {
+            let archives: Vec<PathBuf> = WalkDir::new(&next_path)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|entry| entry.path().is_file() && self.is_container_file(entry.path()))
+                .map(|entry| entry.into_path())
+                .collect();
+
+            for path in archives {
+                let normalized = path.canonicalize()
+                    .unwrap_or_else(|_| path.clone());
+                
+                let display_path = path
+                    .strip_prefix(dir_path)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .to_string();
+
+                if self.visited_paths.contains(&normalized) {
+                    self.logger.warning(&format!("Skipping already processed archive: {}", path.display()));
+                    self.record_archive(&path, display_path, depth, ArchiveOutcome::SkippedAlreadyProcessed, None);
+                    continue;
+                }
+                
+                self.visited_paths.insert(normalized);
+
+                let max_depth = self.limits.max_nesting_depth;
+                if max_depth > 0 && depth > max_depth {
+                    self.logger.warning(&format!(
+                        "Not extracting {}: nested {} levels deep (limit {})",
+                        path.display(),
+                        depth,
+                        max_depth
+                    ));
+                    self.depth_limited.push(path.clone());
+                    self.record_archive(
+                        &path,
+                        display_path,
+                        depth,
+                        ArchiveOutcome::SkippedNestingDepth,
+                        Some(format!("Nested deeper than {} levels", max_depth)),
+                    );
+                    continue;
+                }
+                
+                match self.decompress_file(&path) {
+                    Ok(Some(output_path)) => {
+                        self.record_archive(&path, display_path, depth, ArchiveOutcome::Extracted, None);
+                        if self.is_compressed_file(&path) {
+                            self.extracted_archives.push(path.clone());
+                            self.extraction_outputs.push((output_path.clone(), path.clone()));
+                        }
+                        pending.push((output_path, depth + 1));
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        self.logger.error(&format!("Failed to decompress {}: {}", path.display(), e));
+                        // Keep the cause (e.g. a password prompt) for the skip reason
+                        self.extraction_failures.push((path.clone(), format!("{:#}", e)));
+                        self.record_archive(&path, display_path, depth, ArchiveOutcome::Failed, Some(e.to_string()));
+                    }
+                }
+            }
+        }
+        
+        Ok(())
+    }
+
+    fn is_compressed_file(&self, path: &Path) -> bool {
+        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+            let ext_lower = ext.to_lowercase();
+            matches!(ext_lower.as_str(), "zip" | "gz" | "bz2" | "zst" | "xz" | "rar" | "tar" | "tgz")
+        } else {
+            false
+        }
+    }
+
+    /// Archives, plus PDFs and emails when attachment extraction is enabled.
+    /// A document without attachments yields no output and is not recorded
+    /// as an archive.
+    fn is_container_file(&self, path: &Path) -> bool {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+        self.is_compressed_file(path)
+            || (self.extract_pdf_attachments && ext == "pdf")
+            || (self.extract_email_attachments && matches!(ext.as_str(), "eml" | "msg"))
+    }
+
+    /// Decompress a single archive, returning the path of the extracted
+    /// content so the caller can scan it for further nested archives.
+    fn decompress_file(&mut self, file_path: &Path) -> Result<Option<PathBuf>> {
+        if let Some(ext) = file_path.extension().and_then(|e| e.to_str()) {
+            let ext_lower = ext.to_lowercase();
+            
+            match ext_lower.as_str() {
+                "zip" => self.decompress_zip(file_path).map(Some),
+                "gz" if Self::is_tar_gz(file_path) => self.decompress_tar(file_path, true).map(Some),
+                "gz" => self.decompress_gz(file_path).map(Some),
+                "bz2" => self.decompress_stream(file_path, StreamFormat::Bzip2).map(Some),
+                "zst" => self.decompress_stream(file_path, StreamFormat::Zstd).map(Some),
+                "xz" => self.decompress_stream(file_path, StreamFormat::Xz).map(Some),
+                "tgz" => self.decompress_tar(file_path, true).map(Some),
+                "tar" => self.decompress_tar(file_path, false).map(Some),
+                "rar" => self.decompress_rar(file_path).map(Some),
+                "pdf" => self.extract_pdf_attachments(file_path),
+                "eml" | "msg" => self.extract_email_attachments(file_path),
+                _ => {
+                    self.logger.warning(&format!("Unsupported archive format: {}", ext));
+                    Ok(None)
+                }
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn decompress_zip(&mut self, zip_path: &Path) -> Result<PathBuf> {
+        self.logger.debug(&format!("Decompressing ZIP: {}", zip_path.display()));
+        
+        self.expand_zip_to_folder(zip_path)
+    }
+
+    fn decompress_gz(&mut self, gz_path: &Path) -> Result<PathBuf> {
+        self.decompress_stream(gz_path, StreamFormat::Gzip)
+    }
+
+    /// Decompress a single-file stream (`.gz`, `.bz2`, `.zst`, `.xz`) next to
+    /// the original, named by the folder name template
+    fn decompress_stream(&mut self, source_path: &Path, format: StreamFormat) -> Result<PathBuf> {
+        let label = format.label();
+        self.logger.debug(&format!("Decompressing {}: {}", label, source_path.display()));
+        
+        let file_stem = source_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("extracted");
+        
+        let parent_dir = source_path
+            .parent()
+            .with_context(|| format!("{} file has no parent directory", label))?;
+        
+        // The folder name template goes before the wrapped file's extension,
+        // so `ledger.csv.gz` becomes `ledger__<stamp>.csv` and is converted
+        // and exported as a CSV
+        let wrapped = report_model::wrapped_extension(source_path);
+        let output_path = match &wrapped {
+            Some(inner) => {
+                let inner_stem = &file_stem[..file_stem.len() - inner.len() - 1];
+                self.naming.unique_path(parent_dir, inner_stem, &format!(".{}", inner))
+            }
+            None => self.naming.unique_path(parent_dir, file_stem, ""),
+        };
+
+        let mut budget = self.start_budget(source_path);
+        let written = match format {
+            StreamFormat::Xz => self.decompress_xz(source_path, &output_path, &mut budget),
+            _ => {
+                let file = fs::File::open(source_path)
+                    .with_context(|| format!("Failed to open {} file", label))?;
+                let file = std::io::BufReader::new(file);
+                let mut decoder: Box<dyn Read> = match format {
+                    StreamFormat::Gzip => Box::new(flate2::read::GzDecoder::new(file)),
+                    StreamFormat::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(file)),
+                    StreamFormat::Zstd => Box::new(
+                        zstd::stream::read::Decoder::with_buffer(file)
+                            .context("Failed to initialise ZST decoder")?,
+                    ),
+                    StreamFormat::Xz => unreachable!(),
+                };
+                fs::File::create(&output_path)
+                    .context("Failed to create output file")
+                    .and_then(|output_file| {
+                        std::io::copy(&mut decoder, &mut LimitedWriter::new(output_file, &mut budget))
+                            .with_context(|| format!("Failed to decompress {} file", label))
+                    })
+                    .map(|_| ())
+            }
+        };
+        if let Err(e) = self.settle_budget(source_path, budget, written) {
+            // Don't leave a truncated file behind to be scanned
+            let _ = fs::remove_file(&output_path);
+            return Err(e);
+        }
+        
+        self.logger.debug(&format!("Successfully decompressed {} to: {}", label, output_path.display()));
+
+        // A compressed stream that hides a tar (`.tar.bz2`, or a tar under an
+        // unhelpful name like `backup.gz`) gets a `.tar` extension so the
+        // next pass unpacks it
+        if wrapped.as_deref() != Some("tar") && Self::has_tar_magic(&output_path) {
+            let tar_stem = file_stem
+                .strip_suffix(".tar")
+                .or_else(|| file_stem.strip_suffix(".TAR"))
+                .unwrap_or(file_stem);
+            let tar_path = self.naming.unique_path(parent_dir, tar_stem, ".tar");
+            fs::rename(&output_path, &tar_path)
+                .context("Failed to rename decompressed tar")?;
+            return Ok(tar_path);
+        }
+        
+        // If the output is another archive it is picked up on the next pass
+        Ok(output_path)
+    }
+
+    /// There is no pure-Rust xz decoder in the build, so `.xz` goes through an
+    /// external `xz` or 7-Zip binary (or `EPT_XZ_PATH`). Its output is piped
+    /// to `output_path` through the budget, and the tool is killed as soon as
+    /// a limit is exceeded.
+    fn decompress_xz(&self, xz_path: &Path, output_path: &Path, budget: &mut ExtractionBudget) -> Result<()> {
+        let tool_path = self.find_xz_tool().context(
+            "XZ decompression requires xz or 7-Zip; install one or set EPT_XZ_PATH",
+        )?;
+        let is_seven_zip = tool_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| n.to_lowercase().starts_with("7z"))
+            .unwrap_or(false);
+
+        let output_file = fs::File::create(output_path)
+            .context("Failed to create output file")?;
+
+        let mut cmd = Command::new(&tool_path);
+        if is_seven_zip {
+            cmd.arg("e").arg("-so").arg(xz_path);
+        } else {
+            cmd.arg("-dc").arg(xz_path);
+        }
+        cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+
+        let mut child = cmd.spawn()
+            .with_context(|| format!("Failed to execute {}", tool_path.display()))?;
+        // Drained on its own thread so a chatty tool can't block on a full pipe
+        let stderr = child.stderr.take().map(|mut stderr| {
+            std::thread::spawn(move || {
+                let mut text = String::new();
+                let _ = stderr.read_to_string(&mut text);
+                text
+            })
+        });
+        let mut stdout = child.stdout.take().context("Failed to read XZ output")?;
+        let copied = std::io::copy(&mut stdout, &mut LimitedWriter::new(output_file, budget));
+        drop(stdout);
+        if let Err(e) = copied {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow::Error::new(e).context("Failed to decompress XZ file"));
+        }
+
+        let status = child.wait()
+            .with_context(|| format!("Failed to execute {}", tool_path.display()))?;
+        if !status.success() {
+            let stderr_msg = stderr.and_then(|reader| reader.join().ok()).unwrap_or_default();
+            return Err(anyhow::anyhow!(
+                "XZ decompression failed (exit code {:?}): {}",
+                status.code(),
+                stderr_msg.trim()
+            ));
+        }
+        Ok(())
+    }
+
+    fn find_xz_tool(&self) -> Option<PathBuf> {
+        if let Ok(env_path) = std::env::var("EPT_XZ_PATH") {
+            let path = PathBuf::from(&env_path);
+            if path.exists() {
+                return Some(path);
+            }
+            self.logger.warning(&format!("EPT_XZ_PATH is set to {}, but file does not exist", env_path));
+        }
+
+        let candidates = if cfg!(target_os = "windows") {
+            vec!["xz.exe", "7z.exe", "C:\\Program Files\\7-Zip\\7z.exe"]
+        } else {
+            vec!["xz", "7z", "7zz", "7za"]
+        };
+
+        candidates
+            .into_iter()
+            .find_map(|candidate| which::which(candidate).ok())
+    }
+
+    /// `name.tar.gz` is handled as a single tar archive rather than gunzipped
+    /// into an intermediate `.tar` file
+    fn is_tar_gz(path: &Path) -> bool {
+        path.file_stem()
+            .and_then(|s| s.to_str())
+            .map(|stem| stem.to_lowercase().ends_with(".tar"))
+            .unwrap_or(false)
+    }
+
+    /// Check for the ustar magic at offset 257 of the first header block
+    fn has_tar_magic(path: &Path) -> bool {
+        let mut header = [0u8; 512];
+        match fs::File::open(path).and_then(|mut f| f.read_exact(&mut header)) {
+            Ok(()) => &header[257..262] == b"ustar",
+            Err(_) => false,
+        }
+    }
+
+    /// Unpack a tar (optionally gzip-compressed) into a templated folder
+    /// next to the archive. Nested archives inside are picked up on the next pass.
+    fn decompress_tar(&mut self, tar_path: &Path, gzipped: bool) -> Result<PathBuf> {
+        self.logger.debug(&format!("Decompressing TAR: {}", tar_path.display()));
+
+        let file_name = tar_path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("extracted");
+        let lower = file_name.to_lowercase();
+        let tar_name = [".tar.gz", ".tgz", ".tar"]
+            .iter()
+            .find(|suffix| lower.ends_with(*suffix))
+            .map(|suffix| &file_name[..file_name.len() - suffix.len()])
+            .filter(|stem| !stem.is_empty())
+            .unwrap_or("extracted");
+
+        let parent_dir = tar_path
+            .parent()
+            .context("TAR file has no parent directory")?;
+
+        let output_path = self.naming.unique_path(parent_dir, tar_name, "");
+
+        fs::create_dir_all(&output_path)
+            .context("Failed to create extraction directory")?;
+
+        let file = fs::File::open(tar_path)
+            .context("Failed to open TAR file")?;
+        let reader: Box<dyn Read> = if gzipped {
+            Box::new(flate2::read::GzDecoder::new(std::io::BufReader::new(file)))
+        } else {
+            Box::new(std::io::BufReader::new(file))
+        };
+
+        let mut budget = self.start_budget(tar_path);
+        let result = self.extract_tar_entries(reader, &output_path, &mut budget);
+        if let Err(e) = self.settle_budget(tar_path, budget, result) {
+            // Don't leave a half-extracted folder behind to be scanned
+            let _ = fs::remove_dir_all(&output_path);
+            return Err(e);
+        }
+
+        self.logger.info(&format!("Successfully extracted TAR to: {}", output_path.display()));
+        Ok(output_path)
+    }
+
+    fn extract_tar_entries(
+        &self,
+        reader: Box<dyn Read>,
+        output_path: &Path,
+        budget: &mut ExtractionBudget,
+    ) -> Result<()> {
+        let mut archive = TarReader::new(reader);
+
+        while let Some(entry) = archive.next_entry()? {
+            budget.add_entry().map_err(anyhow::Error::msg)?;
+            // SECURITY: Same sanitization as ZIP entries; `..` and leading
+            // separators are stripped so the joined path stays inside output_path
+            let sanitized_name = self.sanitize_zip_entry_name(&entry.path);
+            if sanitized_name.is_empty() {
+                continue;
+            }
+            let outpath = output_path.join(&sanitized_name);
+
+            match entry.kind {
+                TarEntryKind::Directory => {
+                    fs::create_dir_all(&outpath)
+                        .context("Failed to create directory in TAR")?;
+                }
+                TarEntryKind::File => {
+                    if let Some(p) = outpath.parent() {
+                        fs::create_dir_all(p)
+                            .context("Failed to create parent directory")?;
+                    }
+
+                    let outfile = fs::File::create(&outpath)
+                        .context("Failed to create output file")?;
+                    archive.copy_data(&entry, &mut LimitedWriter::new(outfile, &mut *budget))
+                        .context("Failed to write file from TAR")?;
+                }
+                TarEntryKind::Other => {
+                    self.logger.debug(&format!("Skipping non-regular TAR entry: {}", entry.path));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the files embedded in a PDF into a templated folder next to it,
+    /// remembering which PDF each one came from.
+    ///
+    /// Returns `None` for the common case of a PDF without attachments.
+    fn extract_pdf_attachments(&mut self, pdf_path: &Path) -> Result<Option<PathBuf>> {
+        let data = fs::read(pdf_path).context("Failed to read PDF file")?;
+        if !has_embedded_files(&data) {
+            return Ok(None);
+        }
+        let embedded = extract_embedded_files(&data);
+        if embedded.files.is_empty() {
+            return Ok(None);
+        }
+
+        // Streams are decoded one at a time as they are written
+        let embedded_ref = &embedded;
+        let files = embedded
+            .files
+            .iter()
+            .map(|file| CarvedFile {
+                name: file.name.clone(),
+                fallback_name: format!("attachment_{}.bin", file.object_number),
+                data: Box::new(move |max_bytes: u64| embedded_ref.decode(file, max_bytes)),
+            })
+            .collect();
+        let (output_path, written) = self.write_carved_files(pdf_path, "PDF", files)?;
+        self.attachment_parents
+            .extend(written.into_iter().map(|path| (path, pdf_path.to_path_buf())));
+        Ok(Some(output_path))
+    }
+
+    /// Write the attachments of an `.eml`/`.msg` into a templated folder
+    /// next to it, remembering which message each one came from.
+    ///
+    /// Returns `None` for messages without attachments.
+    fn extract_email_attachments(&mut self, email_path: &Path) -> Result<Option<PathBuf>> {
+        let message = EmailMessage::from_file(email_path)?;
+        if message.attachments.is_empty() {
+            return Ok(None);
+        }
+
+        let files = message
+            .attachments
+            .into_iter()
+            .enumerate()
+            .map(|(index, attachment)| CarvedFile {
+                data: {
+                    let data = attachment
+                        .data
+                        .ok_or_else(|| format!("{} is not stored as a file", attachment.file_name));
+                    Box::new(move |_: u64| data)
+                },
+                name: Some(attachment.file_name),
+                fallback_name: format!("attachment_{}.bin", index + 1),
+            })
+            .collect();
+        let (output_path, written) = self.write_carved_files(email_path, "email", files)?;
+        self.attachment_parents
+            .extend(written.into_iter().map(|path| (path, email_path.to_path_buf())));
+        Ok(Some(output_path))
+    }
+
+    /// Write files carved out of a document into a templated folder next to
+    /// it, returning the folder and the files written.
+    ///
+    /// Files that can't be decoded are logged and skipped; the document is
+    /// only reported as failed if none of them could be written.
+    fn write_carved_files(
+        &mut self,
+        source_path: &Path,
+        label: &str,
+        files: Vec<CarvedFile<'_>>,
+    ) -> Result<(PathBuf, Vec<PathBuf>)> {
+        self.logger.debug(&format!(
+            "Extracting {} embedded files from {}: {}",
+            files.len(),
+            label,
+            source_path.display()
+        ));
+
+        let file_stem = source_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("extracted");
+        let parent_dir = source_path
+            .parent()
+            .with_context(|| format!("{} file has no parent directory", label))?;
+        let output_path = self.naming.unique_path(parent_dir, file_stem, "");
+
+        fs::create_dir_all(&output_path)
+            .context("Failed to create extraction directory")?;
+
+        let mut budget = self.start_budget(source_path);
+        let mut written = Vec::new();
+        let mut errors = Vec::new();
+        let result = (|| -> Result<()> {
+            for file in files {
+                // One byte past the limit, so writing it reports the violation
+                let max_bytes = budget.remaining_archive_bytes().map_or(u64::MAX, |remaining| remaining + 1);
+                let data = match (file.data)(max_bytes) {
+                    Ok(data) => data,
+                    Err(reason) => {
+                        self.logger.warning(&format!(
+                            "Skipping embedded file in {}: {}",
+                            source_path.display(),
+                            reason
+                        ));
+                        errors.push(reason);
+                        continue;
+                    }
+                };
+                budget.add_entry().map_err(anyhow::Error::msg)?;
+
+                // Names may carry a path from the author's machine; keep the file name only
+                let name = file
+                    .name
+                    .as_deref()
+                    .map(|name| name.rsplit(['/', '\\']).next().unwrap_or(name))
+                    .map(normalize_entry_name)
+                    .map(|name| name.trim().trim_start_matches('.').to_string())
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or(file.fallback_name);
+                let mut outpath = output_path.join(&name);
+                let mut counter = 2;
+                while outpath.exists() {
+                    outpath = output_path.join(format!("{}_{}", counter, name));
+                    counter += 1;
+                }
+
+                let outfile = fs::File::create(&outpath)
+                    .context("Failed to create output file")?;
+                std::io::copy(&mut data.as_slice(), &mut LimitedWriter::new(outfile, &mut budget))
+                    .context("Failed to write embedded file")?;
+                written.push(outpath);
+            }
+            Ok(())
+        })();
+        if let Err(e) = self.settle_budget(source_path, budget, result) {
+            let _ = fs::remove_dir_all(&output_path);
+            return Err(e);
+        }
+
+        if written.is_empty() {
+            let _ = fs::remove_dir_all(&output_path);
+            return Err(anyhow::anyhow!(
+                "No embedded file could be decoded: {}",
+                errors.join("; ")
+            ));
+        }
+
+        self.logger.info(&format!(
+            "Extracted {} embedded files from {} to: {}",
+            written.len(),
+            label,
+            output_path.display()
+        ));
+        Ok((output_path, written))
+    }
+
+    /// Extract a RAR archive with an external `unrar` or `7z` binary.
+    ///
+    /// There is no pure-Rust RAR decoder, so this only works when one of the
+    /// tools is installed (or `EPT_UNRAR_PATH` points to one); otherwise the
+    /// archive is reported as not extracted.
+    fn decompress_rar(&mut self, rar_path: &Path) -> Result<PathBuf> {
+        self.logger.debug(&format!("Decompressing RAR: {}", rar_path.display()));
+
+        let (tool_path, tool) = self.find_rar_tool().context(
+            "RAR extraction requires unrar or 7-Zip; install one or set EPT_UNRAR_PATH",
+        )?;
+
+        let rar_name = rar_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("extracted");
+        
+        let parent_dir = rar_path
+            .parent()
+            .context("RAR file has no parent directory")?;
+        
+        let output_path = self.naming.unique_path(parent_dir, rar_name, "");
+        
+        fs::create_dir_all(&output_path)
+            .context("Failed to create extraction directory")?;
+
+        // Never prompt for a password: encrypted archives fail instead of hanging
+        let mut cmd = Command::new(&tool_path);
+        match tool {
+            RarTool::Unrar => {
+                cmd.arg("x").arg("-o+").arg("-y").arg("-p-").arg(rar_path).arg(&output_path);
+            }
+            RarTool::SevenZip => {
+                let mut out_arg = std::ffi::OsString::from("-o");
+                out_arg.push(&output_path);
+                cmd.arg("x").arg("-y").arg(out_arg).arg(rar_path);
+            }
+        }
+        cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped());
+
+        let mut child = cmd.spawn()
+            .with_context(|| format!("Failed to execute {}", tool_path.display()))?;
+        let stderr = child.stderr.take().map(|mut stderr| {
+            std::thread::spawn(move || {
+                let mut text = String::new();
+                let _ = stderr.read_to_string(&mut text);
+                text
+            })
+        });
+
+        // The tool writes straight to disk, so its output folder is measured
+        // while it runs and the tool killed once a limit is exceeded
+        let mut limit_hit = false;
+        let status = loop {
+            if let Some(status) = child.try_wait()
+                .with_context(|| format!("Failed to execute {}", tool_path.display()))?
+            {
+                break Some(status);
+            }
+            let mut probe = self.start_budget(rar_path);
+            if Self::meter_folder(&output_path, &mut probe).is_err() {
+                let _ = child.kill();
+                let _ = child.wait();
+                limit_hit = true;
+                break None;
+            }
+            std::thread::sleep(RAR_METER_INTERVAL);
+        };
+
+        if let Some(status) = status.filter(|status| !status.success()) {
+            let stderr_msg = stderr.and_then(|reader| reader.join().ok()).unwrap_or_default();
+            // Don't leave a half-extracted folder behind to be scanned
+            let _ = fs::remove_dir_all(&output_path);
+            return Err(anyhow::anyhow!(
+                "RAR extraction failed (exit code {:?}): {}",
+                status.code(),
+                stderr_msg.trim()
+            ));
+        }
+
+        // Measure the final result; after a kill this reports the limit hit
+        let mut budget = self.start_budget(rar_path);
+        let mut result = Self::meter_folder(&output_path, &mut budget).map_err(anyhow::Error::msg);
+        if limit_hit && result.is_ok() {
+            result = Err(anyhow::anyhow!("Extraction stopped at a limit"));
+        }
+        if let Err(e) = self.settle_budget(rar_path, budget, result) {
+            let _ = fs::remove_dir_all(&output_path);
+            return Err(e);
+        }
+
+        self.logger.info(&format!("Successfully extracted RAR to: {}", output_path.display()));
+        Ok(output_path)
+    }
+
+    /// Charge every entry and file byte under `folder` to `budget`
+    fn meter_folder(folder: &Path, budget: &mut ExtractionBudget) -> std::result::Result<(), String> {
+        WalkDir::new(folder)
+            .min_depth(1)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .try_for_each(|entry| {
+                budget.add_entry()?;
+                let size = entry.metadata().map(|m| if m.is_file() { m.len() } else { 0 }).unwrap_or(0);
+                budget.add_bytes(size)
+            })
+    }
+
+    fn find_rar_tool(&self) -> Option<(PathBuf, RarTool)> {
+        let classify = |path: &Path| {
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .to_lowercase();
+            if name.starts_with("7z") {
+                RarTool::SevenZip
+            } else {
+                RarTool::Unrar
+            }
+        };
+
+        if let Ok(env_path) = std::env::var("EPT_UNRAR_PATH") {
+            let path = PathBuf::from(&env_path);
+            if path.exists() {
+                return Some((path.clone(), classify(&path)));
+            }
+            self.logger.warning(&format!("EPT_UNRAR_PATH is set to {}, but file does not exist", env_path));
+        }
+
+        let candidates = if cfg!(target_os = "windows") {
+            vec![
+                "unrar.exe",
+                "C:\\Program Files\\WinRAR\\UnRAR.exe",
+                "7z.exe",
+                "C:\\Program Files\\7-Zip\\7z.exe",
+            ]
+        } else {
+            vec!["unrar", "7z", "7zz", "7za"]
+        };
+
+        candidates
+            .into_iter()
+            .find_map(|candidate| which::which(candidate).ok())
+            .map(|path| {
+                let tool = classify(&path);
+                (path, tool)
+            })
+    }
+}
@@ -0,0 +1,260 @@
+use anyhow::{Context, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Largest XML part read from a DOCX; guards against zip bombs
+const MAX_PART_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Render the body of a DOCX as markdown without LibreOffice.
+///
+/// Headings (from paragraph styles), list items, paragraphs and tables are
+/// kept; formatting, images, headers/footers and tracked deletions are not.
+pub fn docx_to_markdown(path: &Path) -> Result<String> {
+    let file = File::open(path).with_context(|| format!("Failed to open DOCX: {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .with_context(|| format!("Not a valid DOCX (zip) file: {}", path.display()))?;
+
+    let document = read_part(&mut archive, "word/document.xml")?
+        .with_context(|| format!("DOCX has no word/document.xml: {}", path.display()))?;
+    let styles = match read_part(&mut archive, "word/styles.xml")? {
+        Some(xml) => style_names(&xml),
+        None => HashMap::new(),
+    };
+
+    render_document(&document, &styles)
+}
+
+pub(crate) fn read_part(archive: &mut zip::ZipArchive<File>, name: &str) -> Result<Option<String>> {
+    let part = match archive.by_name(name) {
+        Ok(part) => part,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to open {}", name)),
+    };
+    let mut xml = String::new();
+    part.take(MAX_PART_BYTES)
+        .read_to_string(&mut xml)
+        .with_context(|| format!("Failed to read {}", name))?;
+    Ok(Some(xml))
+}
+
+pub(crate) fn attribute(element: &BytesStart, name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == name)
+        .and_then(|attr| {
+            let raw = String::from_utf8_lossy(&attr.value).to_string();
+            quick_xml::escape::unescape(&raw).ok().map(|value| value.to_string())
+        })
+}
+
+/// Style id → display name (e.g. `Heading1` → `heading 1`), so headings are
+/// recognised whatever language the template uses for its ids
+fn style_names(xml: &str) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    let mut reader = Reader::from_str(xml);
+    let mut current: Option<String> = None;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) if e.local_name().as_ref() == b"style" => {
+                current = attribute(&e, b"styleId");
+            }
+            Ok(Event::Empty(e)) if e.local_name().as_ref() == b"name" => {
+                if let (Some(id), Some(name)) = (current.take(), attribute(&e, b"val")) {
+                    names.insert(id, name.to_lowercase());
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    names
+}
+
+fn heading_level(style_id: &str, styles: &HashMap<String, String>) -> Option<usize> {
+    let name = styles
+        .get(style_id)
+        .cloned()
+        .unwrap_or_else(|| style_id.to_lowercase());
+    match name.as_str() {
+        "title" => Some(1),
+        "subtitle" => Some(2),
+        _ => name
+            .strip_prefix("heading")
+            .and_then(|level| level.trim().parse::<usize>().ok())
+            .map(|level| level.clamp(1, 6)),
+    }
+}
+
+#[derive(Default)]
+struct Paragraph {
+    text: String,
+    style: Option<String>,
+    list_level: Option<usize>,
+}
+
+#[derive(Default)]
+struct Table {
+    rows: Vec<Vec<String>>,
+}
+
+fn render_document(xml: &str, styles: &HashMap<String, String>) -> Result<String> {
+    let mut reader = Reader::from_str(xml);
+    let mut blocks: Vec<String> = Vec::new();
+    // A stack, since text boxes nest paragraphs inside paragraphs
+    let mut paragraphs: Vec<Paragraph> = Vec::new();
+    let mut tables: Vec<Table> = Vec::new();
+    let mut in_run = false;
+    let mut in_text = false;
+
+    loop {
+        let event = reader.read_event().context("Malformed word/document.xml")?;
+        match event {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"p" => paragraphs.push(Paragraph::default()),
+                b"r" => in_run = true,
+                b"t" => in_text = true,
+                b"tbl" => tables.push(Table::default()),
+                b"tr" => {
+                    if let Some(table) = tables.last_mut() {
+                        table.rows.push(Vec::new());
+                    }
+                }
+                b"tc" => {
+                    if let Some(row) = tables.last_mut().and_then(|table| table.rows.last_mut()) {
+                        row.push(String::new());
+                    }
+                }
+                _ => {}
+            },
+            Event::Empty(e) => match e.local_name().as_ref() {
+                b"pStyle" => {
+                    if let Some(paragraph) = paragraphs.last_mut() {
+                        paragraph.style = attribute(&e, b"val");
+                    }
+                }
+                b"ilvl" => {
+                    if let Some(paragraph) = paragraphs.last_mut() {
+                        paragraph.list_level = attribute(&e, b"val").and_then(|level| level.parse().ok());
+                    }
+                }
+                b"numId" => {
+                    // numId 0 switches numbering off
+                    if let Some(paragraph) = paragraphs.last_mut() {
+                        if attribute(&e, b"val").as_deref() != Some("0") {
+                            paragraph.list_level.get_or_insert(0);
+                        }
+                    }
+                }
+                b"tab" if in_run => {
+                    if let Some(paragraph) = paragraphs.last_mut() {
+                        paragraph.text.push('\t');
+                    }
+                }
+                b"br" | b"cr" if in_run => {
+                    if let Some(paragraph) = paragraphs.last_mut() {
+                        paragraph.text.push('\n');
+                    }
+                }
+                _ => {}
+            },
+            Event::Text(e) if in_text => {
+                if let Some(paragraph) = paragraphs.last_mut() {
+                    paragraph.text.push_str(&e.unescape().context("Malformed text in word/document.xml")?);
+                }
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"r" => in_run = false,
+                b"p" => {
+                    let Some(finished) = paragraphs.pop() else {
+                        continue;
+                    };
+                    let cell = tables
+                        .last_mut()
+                        .and_then(|table| table.rows.last_mut())
+                        .and_then(|row| row.last_mut());
+                    if let Some(cell) = cell {
+                        let text = finished.text.split_whitespace().collect::<Vec<_>>().join(" ");
+                        if !text.is_empty() {
+                            if !cell.is_empty() {
+                                cell.push_str("<br>");
+                            }
+                            cell.push_str(&text);
+                        }
+                    } else if let Some(block) = render_paragraph(finished, styles) {
+                        blocks.push(block);
+                    }
+                }
+                b"tbl" => {
+                    let Some(table) = tables.pop() else {
+                        continue;
+                    };
+                    let cell = tables
+                        .last_mut()
+                        .and_then(|table| table.rows.last_mut())
+                        .and_then(|row| row.last_mut());
+                    if let Some(cell) = cell {
+                        // Nested table: flatten into the enclosing cell
+                        let text: Vec<String> = table.rows.iter().map(|row| row.join(" / ")).collect();
+                        if !cell.is_empty() {
+                            cell.push_str("<br>");
+                        }
+                        cell.push_str(&text.join("<br>"));
+                    } else if let Some(block) = render_table(&table.rows) {
+                        blocks.push(block);
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(blocks.join("\n\n"))
+}
+
+fn render_paragraph(paragraph: Paragraph, styles: &HashMap<String, String>) -> Option<String> {
+    let text = paragraph
+        .text
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("  \n");
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    if let Some(level) = paragraph.style.as_deref().and_then(|style| heading_level(style, styles)) {
+        return Some(format!("{} {}", "#".repeat(level), text.replace("  \n", " ")));
+    }
+    if let Some(level) = paragraph.list_level {
+        return Some(format!("{}- {}", "  ".repeat(level.min(8)), text));
+    }
+    Some(text.to_string())
+}
+
+/// Markdown table with the first row as its header
+pub(crate) fn render_table(rows: &[Vec<String>]) -> Option<String> {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return None;
+    }
+
+    let mut lines = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        let mut cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+        cells.resize(columns, String::new());
+        lines.push(format!("| {} |", cells.join(" | ")));
+        if index == 0 {
+            lines.push(format!("|{}|", vec![" --- "; columns].join("|")));
+        }
+    }
+    Some(lines.join("\n"))
+}
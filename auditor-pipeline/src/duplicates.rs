@@ -0,0 +1,43 @@
+use crate::report_model::ReportModel;
+use std::collections::HashMap;
+
+/// Give files with identical content a shared duplicate group id (`D0001`,
+/// `D0002`, … in order of first appearance) and point every copy after the
+/// first at it. Grouped on the hash of the original file, so copies are
+/// found whether or not they were converted. Returns the number of groups.
+pub fn assign_groups(entries: &mut [ReportModel]) -> usize {
+    let mut members: HashMap<&str, Vec<usize>> = HashMap::new();
+    let mut order: Vec<&str> = Vec::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let Some(hash) = entry.original_hash.as_deref() else {
+            continue;
+        };
+        let group = members.entry(hash).or_default();
+        if group.is_empty() {
+            order.push(hash);
+        }
+        group.push(index);
+    }
+
+    let groups: Vec<Vec<usize>> = order
+        .into_iter()
+        .filter_map(|hash| members.remove(hash))
+        .filter(|group| group.len() > 1)
+        .collect();
+
+    for entry in entries.iter_mut() {
+        entry.duplicate_group = None;
+        entry.duplicate_of = None;
+    }
+    for (number, group) in groups.iter().enumerate() {
+        let id = format!("D{:04}", number + 1);
+        let first = entries[group[0]].original_relative_path.clone();
+        for (position, &index) in group.iter().enumerate() {
+            entries[index].duplicate_group = Some(id.clone());
+            if position > 0 {
+                entries[index].duplicate_of = Some(first.clone());
+            }
+        }
+    }
+    groups.len()
+}
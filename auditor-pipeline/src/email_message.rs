@@ -0,0 +1,530 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use encoding_rs::Encoding;
+use std::io::Read;
+use std::path::Path;
+
+/// A file attached to an email
+pub struct EmailAttachment {
+    pub file_name: String,
+    pub content_type: Option<String>,
+    /// `None` when the attachment can't be written out as a file (e.g. an
+    /// Outlook message embedded as a storage rather than as bytes)
+    pub data: Option<Vec<u8>>,
+}
+
+/// The parts of an `.eml` or `.msg` message rendered to markdown
+#[derive(Default)]
+pub struct EmailMessage {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub cc: Option<String>,
+    pub date: Option<String>,
+    pub subject: Option<String>,
+    pub message_id: Option<String>,
+    pub body_text: Option<String>,
+    pub body_html: Option<String>,
+    pub attachments: Vec<EmailAttachment>,
+}
+
+impl EmailMessage {
+    /// Parse an `.eml` or `.msg` file, chosen by extension
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+        match ext.as_str() {
+            "eml" => {
+                let data = std::fs::read(path)
+                    .with_context(|| format!("Failed to read email file: {}", path.display()))?;
+                Ok(Self::parse_eml(&data))
+            }
+            "msg" => Self::parse_msg(path),
+            _ => Err(anyhow::anyhow!("Not an email file: {}", path.display())),
+        }
+    }
+
+    /// Parse an RFC 822 / MIME message. Malformed input never fails; whatever
+    /// could be recovered is returned.
+    pub fn parse_eml(data: &[u8]) -> Self {
+        let mut message = Self::default();
+        let (headers, body) = split_headers(data);
+        for (name, value) in &headers {
+            let value = decode_encoded_words(value);
+            match name.as_str() {
+                "from" => message.from = Some(value),
+                "to" => message.to = Some(value),
+                "cc" => message.cc = Some(value),
+                "date" => message.date = Some(value),
+                "subject" => message.subject = Some(value),
+                "message-id" => message.message_id = Some(value),
+                _ => {}
+            }
+        }
+        message.collect_part(&headers, body);
+        message
+    }
+
+    fn collect_part(&mut self, headers: &[(String, String)], body: &[u8]) {
+        let content_type = header(headers, "content-type").unwrap_or("text/plain");
+        let (mime_type, params) = parse_header_params(content_type);
+        let disposition = header(headers, "content-disposition").map(parse_header_params);
+
+        if mime_type.starts_with("multipart/") {
+            if let Some(boundary) = param(&params, "boundary") {
+                for part in split_multipart(body, boundary.as_bytes()) {
+                    let (part_headers, part_body) = split_headers(part);
+                    self.collect_part(&part_headers, part_body);
+                }
+                return;
+            }
+        }
+
+        let encoding = header(headers, "content-transfer-encoding")
+            .map(|e| e.trim().to_lowercase())
+            .unwrap_or_default();
+        let decoded = decode_transfer_encoding(body, &encoding);
+
+        let file_name = disposition
+            .as_ref()
+            .and_then(|(_, params)| param(params, "filename"))
+            .or_else(|| param(&params, "name"))
+            .map(|name| decode_encoded_words(&name));
+        let is_attachment = disposition
+            .as_ref()
+            .is_some_and(|(kind, _)| kind == "attachment")
+            || file_name.is_some()
+            || mime_type == "message/rfc822";
+
+        if !is_attachment && mime_type == "text/plain" && self.body_text.is_none() {
+            self.body_text = Some(decode_charset(&decoded, param(&params, "charset").as_deref()));
+        } else if !is_attachment && mime_type == "text/html" && self.body_html.is_none() {
+            self.body_html = Some(decode_charset(&decoded, param(&params, "charset").as_deref()));
+        } else if is_attachment || !mime_type.starts_with("text/") {
+            let file_name = file_name.unwrap_or_else(|| {
+                let index = self.attachments.len() + 1;
+                if mime_type == "message/rfc822" {
+                    format!("attached_message_{}.eml", index)
+                } else {
+                    format!("attachment_{}.bin", index)
+                }
+            });
+            self.attachments.push(EmailAttachment {
+                file_name,
+                content_type: Some(mime_type),
+                data: Some(decoded),
+            });
+        }
+    }
+
+    /// Parse an Outlook `.msg` (OLE compound file with MAPI property streams)
+    pub fn parse_msg(path: &Path) -> Result<Self> {
+        let mut file = cfb::open(path)
+            .with_context(|| format!("Failed to open Outlook message: {}", path.display()))?;
+        let mut message = Self::default();
+
+        let root = Path::new("/");
+        message.subject = read_msg_string(&mut file, root, "0037");
+        let sender_name = read_msg_string(&mut file, root, "0C1A");
+        let sender_email = read_msg_string(&mut file, root, "5D01")
+            .or_else(|| read_msg_string(&mut file, root, "0C1F"));
+        message.from = match (sender_name, sender_email) {
+            (Some(name), Some(email)) if name != email => Some(format!("{} <{}>", name, email)),
+            (name, email) => name.or(email),
+        };
+        message.to = read_msg_string(&mut file, root, "0E04");
+        message.cc = read_msg_string(&mut file, root, "0E03");
+        message.message_id = read_msg_string(&mut file, root, "1035");
+        message.body_text = read_msg_string(&mut file, root, "1000");
+        message.body_html = read_msg_stream(&mut file, &root.join("__substg1.0_10130102"))
+            .map(|html| decode_charset(&html, None))
+            .or_else(|| read_msg_string(&mut file, root, "1013"));
+
+        // Transport headers carry the original Date (and fill gaps)
+        if let Some(transport) = read_msg_string(&mut file, root, "007D") {
+            let (headers, _) = split_headers(transport.as_bytes());
+            message.date = header(&headers, "date").map(|d| d.trim().to_string());
+            if message.message_id.is_none() {
+                message.message_id = header(&headers, "message-id").map(|v| v.trim().to_string());
+            }
+        }
+
+        let attachment_storages: Vec<_> = file
+            .read_root_storage()
+            .filter(|entry| entry.is_storage() && entry.name().starts_with("__attach_version1.0_"))
+            .map(|entry| entry.path().to_path_buf())
+            .collect();
+        for (index, storage) in attachment_storages.iter().enumerate() {
+            let file_name = read_msg_string(&mut file, storage, "3707")
+                .or_else(|| read_msg_string(&mut file, storage, "3704"))
+                .or_else(|| read_msg_string(&mut file, storage, "3001"))
+                .unwrap_or_else(|| format!("attachment_{}.bin", index + 1));
+            let content_type = read_msg_string(&mut file, storage, "370E");
+            let data = read_msg_stream(&mut file, &storage.join("__substg1.0_37010102"));
+            message.attachments.push(EmailAttachment {
+                file_name,
+                content_type,
+                data,
+            });
+        }
+
+        Ok(message)
+    }
+
+    /// Plain-text body, falling back to the HTML body stripped of markup
+    pub fn body(&self) -> Option<String> {
+        self.body_text
+            .clone()
+            .filter(|text| !text.trim().is_empty())
+            .or_else(|| self.body_html.as_deref().map(html_to_text))
+    }
+
+    /// Render headers, body and the attachment list as markdown
+    pub fn to_markdown(&self, file_name: &str) -> String {
+        let mut markdown = Vec::new();
+        markdown.push(format!(
+            "# Email: {}",
+            self.subject.as_deref().filter(|s| !s.trim().is_empty()).unwrap_or(file_name)
+        ));
+        markdown.push(String::new());
+
+        for (label, value) in [
+            ("From", &self.from),
+            ("To", &self.to),
+            ("Cc", &self.cc),
+            ("Date", &self.date),
+            ("Subject", &self.subject),
+            ("Message-ID", &self.message_id),
+        ] {
+            if let Some(value) = value.as_deref().filter(|v| !v.trim().is_empty()) {
+                markdown.push(format!("**{}:** {}  ", label, value.trim()));
+            }
+        }
+        markdown.push(format!("**Source file:** {}", file_name));
+        markdown.push(String::new());
+
+        markdown.push("## Body".to_string());
+        markdown.push(String::new());
+        markdown.push(
+            self.body()
+                .map(|body| body.trim().to_string())
+                .unwrap_or_else(|| "*No message body*".to_string()),
+        );
+        markdown.push(String::new());
+
+        if !self.attachments.is_empty() {
+            markdown.push("## Attachments".to_string());
+            markdown.push(String::new());
+            for attachment in &self.attachments {
+                let detail = match &attachment.data {
+                    Some(data) => format!("{} bytes", data.len()),
+                    None => "not extracted".to_string(),
+                };
+                markdown.push(format!("- {} ({})", attachment.file_name, detail));
+            }
+            markdown.push(String::new());
+        }
+
+        markdown.join("\n")
+    }
+}
+
+/// Split a message or MIME part into unfolded, lower-cased headers and its body
+fn split_headers(data: &[u8]) -> (Vec<(String, String)>, &[u8]) {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let end = data[pos..].iter().position(|&b| b == b'\n').map(|i| pos + i).unwrap_or(data.len());
+        let line = String::from_utf8_lossy(&data[pos..end]);
+        let line = line.trim_end_matches('\r');
+        pos = (end + 1).min(data.len());
+
+        if line.is_empty() {
+            return (headers, &data[pos..]);
+        }
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    (headers, &data[data.len()..])
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+}
+
+/// `text/plain; charset="utf-8"` → ("text/plain", [("charset", "utf-8")])
+fn parse_header_params(value: &str) -> (String, Vec<(String, String)>) {
+    let mut parts = value.split(';');
+    let main = parts.next().unwrap_or("").trim().to_lowercase();
+    let params = parts
+        .filter_map(|part| {
+            let (name, value) = part.split_once('=')?;
+            Some((name.trim().to_lowercase(), value.trim().trim_matches('"').to_string()))
+        })
+        .collect();
+    (main, params)
+}
+
+/// Look up a parameter, including the RFC 2231 `name*=charset''value` form
+fn param(params: &[(String, String)], name: &str) -> Option<String> {
+    if let Some((_, value)) = params.iter().find(|(n, _)| n == name) {
+        return Some(value.clone());
+    }
+    let extended = format!("{}*", name);
+    let (_, value) = params.iter().find(|(n, _)| *n == extended)?;
+    let mut pieces = value.splitn(3, '\'');
+    let (charset, _language, encoded) = (pieces.next()?, pieces.next()?, pieces.next()?);
+    Some(decode_charset(&percent_decode(encoded), Some(charset)))
+}
+
+fn split_multipart<'a>(body: &'a [u8], boundary: &[u8]) -> Vec<&'a [u8]> {
+    let mut delimiter = b"--".to_vec();
+    delimiter.extend_from_slice(boundary);
+
+    let mut parts = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut pos = 0;
+    while pos < body.len() {
+        let end = body[pos..].iter().position(|&b| b == b'\n').map(|i| pos + i).unwrap_or(body.len());
+        let line = trim_line_end(&body[pos..end]);
+        if line.starts_with(&delimiter) {
+            if let Some(part_start) = start {
+                // The line break before the delimiter belongs to the delimiter
+                parts.push(trim_line_end(&body[part_start..pos.saturating_sub(1).max(part_start)]));
+            }
+            if line[delimiter.len()..].starts_with(b"--") {
+                return parts;
+            }
+            start = Some((end + 1).min(body.len()));
+        }
+        pos = end + 1;
+    }
+    if let Some(part_start) = start {
+        parts.push(&body[part_start..]);
+    }
+    parts
+}
+
+fn trim_line_end(line: &[u8]) -> &[u8] {
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+fn decode_transfer_encoding(body: &[u8], encoding: &str) -> Vec<u8> {
+    match encoding {
+        "base64" => {
+            let cleaned: Vec<u8> = body.iter().copied().filter(|b| !b.is_ascii_whitespace()).collect();
+            base64::engine::general_purpose::STANDARD
+                .decode(&cleaned)
+                .or_else(|_| base64::engine::general_purpose::STANDARD_NO_PAD.decode(cleaned.trim_ascii_end()))
+                .unwrap_or_else(|_| body.to_vec())
+        }
+        "quoted-printable" => decode_quoted_printable(body, false),
+        _ => body.to_vec(),
+    }
+}
+
+/// Quoted-printable; `underscore_is_space` for the Q encoding of encoded words
+fn decode_quoted_printable(input: &[u8], underscore_is_space: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        match input[i] {
+            b'=' => {
+                let rest = &input[i + 1..];
+                if rest.starts_with(b"\r\n") {
+                    i += 3;
+                    continue;
+                }
+                if rest.starts_with(b"\n") {
+                    i += 2;
+                    continue;
+                }
+                let hex = rest.get(..2).and_then(|h| std::str::from_utf8(h).ok());
+                match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 3;
+                    }
+                    None => {
+                        out.push(b'=');
+                        i += 1;
+                    }
+                }
+            }
+            b'_' if underscore_is_space => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+fn percent_decode(input: &str) -> Vec<u8> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = bytes
+                .get(i + 1..i + 3)
+                .and_then(|h| std::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+            {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    out
+}
+
+fn decode_charset(bytes: &[u8], charset: Option<&str>) -> String {
+    let encoding = charset
+        .and_then(|label| Encoding::for_label(label.trim().as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8);
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
+
+/// Decode RFC 2047 encoded words such as `=?utf-8?B?...?=` in a header value
+fn decode_encoded_words(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut previous_was_word = false;
+    while let Some(start) = rest.find("=?") {
+        let decoded = rest[start + 2..].split_once('?').and_then(|(charset, after)| {
+            let (kind, after) = after.split_once('?')?;
+            let end = after.find("?=")?;
+            let text = &after[..end];
+            let bytes = match kind.to_ascii_uppercase().as_str() {
+                "B" => base64::engine::general_purpose::STANDARD
+                    .decode(text)
+                    .or_else(|_| base64::engine::general_purpose::STANDARD_NO_PAD.decode(text.trim_end_matches('=')))
+                    .ok()?,
+                "Q" => decode_quoted_printable(text.as_bytes(), true),
+                _ => return None,
+            };
+            let consumed = start + 2 + charset.len() + 1 + kind.len() + 1 + end + 2;
+            Some((decode_charset(&bytes, Some(charset)), consumed))
+        });
+
+        match decoded {
+            Some((text, consumed)) => {
+                // Whitespace between adjacent encoded words is dropped
+                let between = &rest[..start];
+                if !(previous_was_word && between.trim().is_empty()) {
+                    out.push_str(between);
+                }
+                out.push_str(&text);
+                rest = &rest[consumed..];
+                previous_was_word = true;
+            }
+            None => {
+                out.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+                previous_was_word = false;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Readable text from an HTML body: tags, scripts and styles removed,
+/// block elements turned into line breaks
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_lowercase();
+        let tag_name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect();
+        rest = &rest[start + end + 1..];
+
+        if !tag.starts_with('/') && (tag_name == "script" || tag_name == "style") {
+            let closing = format!("</{}", tag_name);
+            rest = match rest.to_lowercase().find(&closing) {
+                Some(pos) => &rest[pos..],
+                None => "",
+            };
+            continue;
+        }
+        if matches!(tag_name.as_str(), "br" | "p" | "div" | "tr" | "li" | "h1" | "h2" | "h3" | "h4" | "table")
+        {
+            text.push('\n');
+        }
+    }
+    text.push_str(rest);
+
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+
+    // Collapse runs of blank lines and trailing spaces
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim_end) {
+        if line.trim().is_empty() && lines.last().is_none_or(|last| last.trim().is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Read a MAPI string property (`001F` Unicode, or `001E` 8-bit) from a storage
+fn read_msg_string<F: std::io::Read + std::io::Seek>(
+    file: &mut cfb::CompoundFile<F>,
+    storage: &Path,
+    property_id: &str,
+) -> Option<String> {
+    if let Some(data) = read_msg_stream(file, &storage.join(format!("__substg1.0_{}001F", property_id))) {
+        let units: Vec<u16> = data
+            .chunks(2)
+            .filter(|c| c.len() == 2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        return Some(String::from_utf16_lossy(&units).trim_end_matches('\0').to_string());
+    }
+    read_msg_stream(file, &storage.join(format!("__substg1.0_{}001E", property_id)))
+        .map(|data| decode_charset(&data, Some("windows-1252")).trim_end_matches('\0').to_string())
+}
+
+fn read_msg_stream<F: std::io::Read + std::io::Seek>(
+    file: &mut cfb::CompoundFile<F>,
+    path: &Path,
+) -> Option<Vec<u8>> {
+    if !file.is_stream(path) {
+        return None;
+    }
+    let mut data = Vec::new();
+    file.open_stream(path).ok()?.read_to_end(&mut data).ok()?;
+    Some(data)
+}
@@ -0,0 +1,184 @@
+use crate::ept_logger::EPTLogger;
+use crate::report_model::{FileStatus, ReportModel};
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Threading headers of a single RFC 822 message
+#[derive(Debug, Clone, Default)]
+pub struct EmailHeaders {
+    pub message_id: Option<String>,
+    pub in_reply_to: Option<String>,
+    pub references: Vec<String>,
+}
+
+impl EmailHeaders {
+    /// Parse the header block of a message file (everything up to the first blank line)
+    pub fn parse_file(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open email file: {}", path.display()))?;
+        let reader = BufReader::new(file);
+
+        let mut headers = Self::default();
+        let mut current: Option<(String, String)> = None;
+
+        for line in reader.split(b'\n') {
+            let line = line.with_context(|| format!("Failed to read email file: {}", path.display()))?;
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches('\r');
+
+            if line.is_empty() {
+                break;
+            }
+
+            // Folded continuation line
+            if line.starts_with(' ') || line.starts_with('\t') {
+                if let Some((_, value)) = current.as_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+                continue;
+            }
+
+            if let Some((name, value)) = current.take() {
+                headers.apply(&name, &value);
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                current = Some((name.trim().to_lowercase(), value.trim().to_string()));
+            }
+        }
+        if let Some((name, value)) = current.take() {
+            headers.apply(&name, &value);
+        }
+
+        Ok(headers)
+    }
+
+    fn apply(&mut self, name: &str, value: &str) {
+        match name {
+            "message-id" => self.message_id = Self::extract_ids(value).into_iter().next(),
+            "in-reply-to" => self.in_reply_to = Self::extract_ids(value).into_iter().next(),
+            "references" => self.references = Self::extract_ids(value),
+            _ => {}
+        }
+    }
+
+    /// Pull `<id@host>` tokens out of a header value
+    fn extract_ids(value: &str) -> Vec<String> {
+        value
+            .split('<')
+            .skip(1)
+            .filter_map(|part| part.split_once('>').map(|(id, _)| id.trim().to_lowercase()))
+            .filter(|id| !id.is_empty())
+            .collect()
+    }
+
+    /// Message IDs this message replies to or quotes
+    fn ancestors(&self) -> impl Iterator<Item = &String> {
+        self.references.iter().chain(self.in_reply_to.iter())
+    }
+}
+
+/// Detects email threads and suppresses messages whose content is quoted by
+/// a later reply in the same thread.
+pub struct EmailThreadDeduplicator {
+    logger: EPTLogger,
+}
+
+impl EmailThreadDeduplicator {
+    pub fn new(logger: EPTLogger) -> Self {
+        Self { logger }
+    }
+
+    pub fn is_email_file(path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.eq_ignore_ascii_case("eml"))
+            .unwrap_or(false)
+    }
+
+    /// Mark every exportable message that another exportable message replies
+    /// to (directly or via References) as suppressed, so only the most
+    /// inclusive message of each thread branch is exported.
+    ///
+    /// Headers are read from the original message file, so this works
+    /// regardless of what the message was converted to.
+    ///
+    /// Returns the number of suppressed messages.
+    pub fn suppress_quoted_messages(&self, working_path: &Path, entries: &mut [ReportModel]) -> usize {
+        let mut headers_by_index: HashMap<usize, EmailHeaders> = HashMap::new();
+
+        for (idx, entry) in entries.iter().enumerate() {
+            if !entry.status.is_exportable() {
+                continue;
+            }
+            let original_path = working_path.join(&entry.original_relative_path);
+            if !Self::is_email_file(&original_path) {
+                continue;
+            }
+            match EmailHeaders::parse_file(&original_path) {
+                Ok(headers) => {
+                    headers_by_index.insert(idx, headers);
+                }
+                Err(e) => {
+                    self.logger.warning(&format!(
+                        "Failed to read email headers from {}: {}",
+                        original_path.display(),
+                        e
+                    ));
+                }
+            }
+        }
+
+        if headers_by_index.is_empty() {
+            return 0;
+        }
+
+        // Message-ID → the entry that carries it
+        let mut index_by_message_id: HashMap<&str, usize> = HashMap::new();
+        for (idx, headers) in &headers_by_index {
+            if let Some(message_id) = headers.message_id.as_deref() {
+                index_by_message_id.entry(message_id).or_insert(*idx);
+            }
+        }
+
+        // Entry index → the reply that quotes it
+        let mut quoted_by: HashMap<usize, usize> = HashMap::new();
+        for (idx, headers) in &headers_by_index {
+            for ancestor in headers.ancestors() {
+                if let Some(&ancestor_idx) = index_by_message_id.get(ancestor.as_str()) {
+                    if ancestor_idx != *idx {
+                        quoted_by.entry(ancestor_idx).or_insert(*idx);
+                    }
+                }
+            }
+        }
+
+        for (&idx, &reply_idx) in &quoted_by {
+            // Follow the chain to the message that is exported in the end
+            let mut kept_idx = reply_idx;
+            let mut seen = HashSet::new();
+            while let Some(&next) = quoted_by.get(&kept_idx) {
+                if !seen.insert(kept_idx) {
+                    break;
+                }
+                kept_idx = next;
+            }
+
+            let kept_name = entries[kept_idx].original_file_name.clone();
+            let entry = &mut entries[idx];
+            entry.status = FileStatus::SuppressedInThread;
+            entry.skip_reason = Some(format!("Quoted in later thread message {}", kept_name));
+        }
+
+        self.logger.info(&format!(
+            "Email thread de-duplication: {} of {} messages suppressed",
+            quoted_by.len(),
+            headers_by_index.len()
+        ));
+
+        quoted_by.len()
+    }
+}
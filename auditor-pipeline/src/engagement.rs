@@ -0,0 +1,174 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File holding an engagement's metadata inside its folder
+const ENGAGEMENT_FILE_NAME: &str = "engagement.json";
+
+/// A run recorded under an engagement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngagementRun {
+    pub run_id: String,
+    pub started_at: String,
+    pub input_path: String,
+    /// "completed" or "failed"
+    pub status: String,
+    pub staging_path: Option<String>,
+    pub llm_output_path: Option<String>,
+    pub report_path: Option<String>,
+    pub error: Option<String>,
+    /// Retention date of the engagement when the run was recorded
+    pub delete_after: Option<NaiveDate>,
+    /// Set once the run's staging and export folders have been purged
+    pub purged_at: Option<String>,
+}
+
+/// An audit engagement grouping the runs performed for one client/project.
+///
+/// Each engagement owns a folder `<store root>/<id>/` with its metadata and
+/// a `runs/<run id>/` folder per run that exports and reports are filed under.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Engagement {
+    pub id: String,
+    pub name: String,
+    pub client_code: String,
+    /// Date after which the engagement's outputs may be disposed of
+    pub retention_date: Option<NaiveDate>,
+    pub created_at: String,
+    pub runs: Vec<EngagementRun>,
+}
+
+/// Engagements persisted as one folder each under a root directory
+pub struct EngagementStore {
+    root: PathBuf,
+}
+
+impl EngagementStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn create(
+        &self,
+        name: &str,
+        client_code: &str,
+        retention_date: Option<NaiveDate>,
+    ) -> Result<Engagement> {
+        let name = name.trim();
+        let client_code = client_code.trim();
+        if name.is_empty() {
+            return Err(anyhow::anyhow!("Engagement name must not be empty"));
+        }
+
+        // Readable, filesystem-safe id; a counter keeps it unique
+        let base_id = sanitize_id(&format!("{}_{}", client_code, name));
+        let mut id = base_id.clone();
+        let mut counter = 2;
+        while self.engagement_dir(&id).exists() {
+            id = format!("{}_{}", base_id, counter);
+            counter += 1;
+        }
+
+        let engagement = Engagement {
+            id,
+            name: name.to_string(),
+            client_code: client_code.to_string(),
+            retention_date,
+            created_at: chrono::Local::now().to_rfc3339(),
+            runs: Vec::new(),
+        };
+        self.save(&engagement)?;
+        Ok(engagement)
+    }
+
+    /// All engagements, most recently created first
+    pub fn list(&self) -> Result<Vec<Engagement>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut engagements = Vec::new();
+        for entry in fs::read_dir(&self.root)
+            .with_context(|| format!("Failed to read engagements folder: {}", self.root.display()))?
+        {
+            let path = entry.context("Failed to read engagements folder entry")?.path();
+            if path.join(ENGAGEMENT_FILE_NAME).is_file() {
+                engagements.push(Self::read(&path)?);
+            }
+        }
+        engagements.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(engagements)
+    }
+
+    pub fn load(&self, id: &str) -> Result<Engagement> {
+        Self::read(&self.engagement_dir(id))
+            .with_context(|| format!("Engagement not found: {}", id))
+    }
+
+    /// Folder that a run's export and report should be written under
+    pub fn run_dir(&self, id: &str, run_id: &str) -> PathBuf {
+        self.engagement_dir(id).join("runs").join(sanitize_id(run_id))
+    }
+
+    /// Folder for deletion certificates; kept when runs are purged
+    pub fn certificates_dir(&self, id: &str) -> PathBuf {
+        self.engagement_dir(id).join("certificates")
+    }
+
+    pub fn record_run(&self, id: &str, run: EngagementRun) -> Result<()> {
+        let mut engagement = self.load(id)?;
+        engagement.runs.push(run);
+        self.save(&engagement)
+    }
+
+    /// Apply `update` to the record of `run_id` in whichever engagement it
+    /// was filed under. Returns whether such a record was found.
+    pub fn update_run(&self, run_id: &str, update: impl FnOnce(&mut EngagementRun)) -> Result<bool> {
+        for mut engagement in self.list()? {
+            if let Some(run) = engagement.runs.iter_mut().find(|run| run.run_id == run_id) {
+                update(run);
+                self.save(&engagement)?;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn engagement_dir(&self, id: &str) -> PathBuf {
+        self.root.join(sanitize_id(id))
+    }
+
+    fn read(dir: &Path) -> Result<Engagement> {
+        let path = dir.join(ENGAGEMENT_FILE_NAME);
+        let bytes = fs::read(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_slice(&bytes)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, engagement: &Engagement) -> Result<()> {
+        let dir = self.engagement_dir(&engagement.id);
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create engagement folder: {}", dir.display()))?;
+        let json = serde_json::to_vec_pretty(engagement).context("Failed to serialize engagement")?;
+        let path = dir.join(ENGAGEMENT_FILE_NAME);
+        fs::write(&path, json)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Keep letters, digits, `-` and `_`; everything else becomes `_`
+fn sanitize_id(value: &str) -> String {
+    let sanitized: String = value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    let trimmed = sanitized.trim_matches('_');
+    if trimmed.is_empty() {
+        "engagement".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
@@ -0,0 +1,304 @@
+use regex::Regex;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// How much of a file is read to find its header metadata
+const HEADER_BYTES: u64 = 64 * 1024;
+
+/// CAD/BIM and other engineering formats we inventory but can't convert,
+/// keyed by lower-case extension
+const ENGINEERING_FORMATS: &[(&str, &str)] = &[
+    ("dwg", "AutoCAD drawing (DWG)"),
+    ("dxf", "Drawing Exchange Format (DXF)"),
+    ("dwf", "Design Web Format (DWF)"),
+    ("dwfx", "Design Web Format (DWFx)"),
+    ("dgn", "MicroStation design (DGN)"),
+    ("rvt", "Revit project (RVT)"),
+    ("rfa", "Revit family (RFA)"),
+    ("rte", "Revit template (RTE)"),
+    ("ifc", "Industry Foundation Classes (IFC)"),
+    ("stp", "STEP model"),
+    ("step", "STEP model"),
+    ("igs", "IGES model"),
+    ("iges", "IGES model"),
+    ("stl", "Stereolithography mesh (STL)"),
+    ("skp", "SketchUp model (SKP)"),
+    ("3dm", "Rhino model (3DM)"),
+    ("nwd", "Navisworks document (NWD)"),
+    ("nwc", "Navisworks cache (NWC)"),
+    ("nwf", "Navisworks file set (NWF)"),
+    ("sldprt", "SolidWorks part"),
+    ("sldasm", "SolidWorks assembly"),
+    ("slddrw", "SolidWorks drawing"),
+    ("ipt", "Inventor part"),
+    ("iam", "Inventor assembly"),
+    ("idw", "Inventor drawing"),
+    ("catpart", "CATIA part"),
+    ("catproduct", "CATIA product"),
+    ("x_t", "Parasolid model"),
+    ("sat", "ACIS model"),
+];
+
+/// What could be learned about an engineering file from its header
+#[derive(Debug, Clone)]
+pub struct EngineeringFileInfo {
+    pub format: &'static str,
+    pub details: Vec<String>,
+}
+
+impl EngineeringFileInfo {
+    /// One-line description for the report, e.g.
+    /// `AutoCAD drawing (DWG); version AC1032 (AutoCAD 2018+)`
+    pub fn describe(&self) -> String {
+        if self.details.is_empty() {
+            self.format.to_string()
+        } else {
+            format!("{}; {}", self.format, self.details.join("; "))
+        }
+    }
+}
+
+fn format_name(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    ENGINEERING_FORMATS
+        .iter()
+        .find(|(known, _)| *known == ext)
+        .map(|(_, name)| *name)
+}
+
+/// Identify an engineering file and pull basic metadata from its header.
+///
+/// Returns `None` for other files. Header parsing is best effort: an
+/// unreadable or unexpected header still yields the format name, with a note.
+pub fn inspect(path: &Path) -> Option<EngineeringFileInfo> {
+    let format = format_name(path)?;
+    let mut info = EngineeringFileInfo { format, details: Vec::new() };
+
+    let mut header = Vec::new();
+    if let Err(e) = File::open(path).and_then(|f| f.take(HEADER_BYTES).read_to_end(&mut header)) {
+        info.details.push(format!("header unreadable: {}", e));
+        return Some(info);
+    }
+
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match ext.as_str() {
+        "dwg" => dwg_details(&header, &mut info.details),
+        "dxf" => dxf_details(&header, &mut info.details),
+        "ifc" | "stp" | "step" => step_details(&header, &mut info.details),
+        "igs" | "iges" => iges_details(&header, &mut info.details),
+        "stl" => stl_details(&header, &mut info.details),
+        "skp" => skp_details(&header, &mut info.details),
+        "dgn" => {
+            info.details.push(if header.starts_with(OLE_MAGIC) {
+                "V8 (OLE container)".to_string()
+            } else {
+                "V7 or earlier".to_string()
+            });
+        }
+        "rvt" | "rfa" | "rte" => revit_details(path, &mut info.details),
+        _ => {}
+    }
+
+    if info.details.is_empty() {
+        info.details.push("no header metadata extracted".to_string());
+    }
+    Some(info)
+}
+
+const OLE_MAGIC: &[u8] = &[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// AutoCAD release for a DWG/DXF version code
+fn acad_release(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "AC1.50" => "AutoCAD 2.0",
+        "AC1009" => "AutoCAD R11/R12",
+        "AC1012" => "AutoCAD R13",
+        "AC1014" => "AutoCAD R14",
+        "AC1015" => "AutoCAD 2000-2002",
+        "AC1018" => "AutoCAD 2004-2006",
+        "AC1021" => "AutoCAD 2007-2009",
+        "AC1024" => "AutoCAD 2010-2012",
+        "AC1027" => "AutoCAD 2013-2017",
+        "AC1032" => "AutoCAD 2018+",
+        _ => return None,
+    })
+}
+
+fn version_detail(code: &str) -> String {
+    match acad_release(code) {
+        Some(release) => format!("version {} ({})", code, release),
+        None => format!("version {}", code),
+    }
+}
+
+fn dwg_details(header: &[u8], details: &mut Vec<String>) {
+    match header.get(..6).and_then(|code| std::str::from_utf8(code).ok()) {
+        Some(code) if code.starts_with("AC") => details.push(version_detail(code)),
+        _ => details.push("unrecognised DWG signature".to_string()),
+    }
+}
+
+fn dxf_details(header: &[u8], details: &mut Vec<String>) {
+    if header.starts_with(b"AutoCAD Binary DXF") {
+        details.push("binary DXF".to_string());
+        return;
+    }
+
+    // ASCII DXF is a sequence of (group code, value) line pairs
+    let text = String::from_utf8_lossy(header);
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    for (name, label) in [("$ACADVER", "version"), ("$DWGCODEPAGE", "code page")] {
+        let value = lines
+            .iter()
+            .position(|line| *line == name)
+            .and_then(|pos| lines.get(pos + 2))
+            .filter(|value| !value.is_empty());
+        if let Some(value) = value {
+            details.push(if label == "version" {
+                version_detail(value)
+            } else {
+                format!("{} {}", label, value)
+            });
+        }
+    }
+}
+
+/// ISO 10303-21 header shared by IFC and STEP files
+fn step_details(header: &[u8], details: &mut Vec<String>) {
+    static SCHEMA: OnceLock<Regex> = OnceLock::new();
+    static FILE_NAME: OnceLock<Regex> = OnceLock::new();
+    static QUOTED: OnceLock<Regex> = OnceLock::new();
+
+    let text = String::from_utf8_lossy(header);
+    if !text.starts_with("ISO-10303-21") {
+        details.push("missing ISO-10303-21 header".to_string());
+        return;
+    }
+
+    let schema = SCHEMA.get_or_init(|| Regex::new(r"FILE_SCHEMA\s*\(\s*\(\s*'([^']*)'").unwrap());
+    if let Some(captures) = schema.captures(&text) {
+        details.push(format!("schema {}", &captures[1]));
+    }
+
+    // FILE_NAME(name, time_stamp, (author), (organization), preprocessor, originating_system, authorization)
+    let file_name = FILE_NAME.get_or_init(|| Regex::new(r"(?s)FILE_NAME\s*\((.*?)\)\s*;").unwrap());
+    let quoted = QUOTED.get_or_init(|| Regex::new(r"'([^']*)'").unwrap());
+    if let Some(captures) = file_name.captures(&text) {
+        let values: Vec<&str> = quoted
+            .captures_iter(&captures[1])
+            .map(|c| c.get(1).map(|m| m.as_str()).unwrap_or(""))
+            .collect();
+        for (index, label) in [(1, "created"), (2, "author"), (3, "organization"), (5, "authoring application")] {
+            if let Some(value) = values.get(index).map(|v| v.trim()).filter(|v| !v.is_empty()) {
+                details.push(format!("{} {}", label, value));
+            }
+        }
+    }
+}
+
+/// IGES global section: comma-separated parameters in the 'G' lines
+fn iges_details(header: &[u8], details: &mut Vec<String>) {
+    let text = String::from_utf8_lossy(header);
+    let global: String = text
+        .lines()
+        .filter(|line| line.as_bytes().get(72) == Some(&b'G'))
+        .filter_map(|line| line.get(..72))
+        .collect();
+    if global.is_empty() {
+        details.push("no IGES global section".to_string());
+        return;
+    }
+
+    // Values are Hollerith strings (`5HHello`) or plain numbers
+    let mut values = Vec::new();
+    let mut rest = global.as_str();
+    while !rest.is_empty() && values.len() < 25 {
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let after_digits = &rest[digits.len()..];
+        if !digits.is_empty() && after_digits.starts_with('H') {
+            let count: usize = digits.parse().unwrap_or(0);
+            let body = &after_digits[1..];
+            let end = body.char_indices().nth(count).map(|(i, _)| i).unwrap_or(body.len());
+            values.push(body[..end].to_string());
+            rest = &body[end..];
+        } else {
+            let end = rest.find([',', ';']).unwrap_or(rest.len());
+            values.push(rest[..end].trim().to_string());
+            rest = &rest[end..];
+        }
+        rest = rest.strip_prefix([',', ';']).unwrap_or(rest);
+    }
+
+    // Parameter numbers from the IGES spec (1-based)
+    for (number, label) in [(4, "file name"), (5, "native system"), (18, "created"), (21, "author"), (22, "organization")] {
+        if let Some(value) = values.get(number - 1).map(|v| v.trim()).filter(|v| !v.is_empty()) {
+            details.push(format!("{} {}", label, value));
+        }
+    }
+}
+
+fn stl_details(header: &[u8], details: &mut Vec<String>) {
+    if header.starts_with(b"solid") && header.windows(5).any(|w| w == b"facet") {
+        let name = String::from_utf8_lossy(&header[5..])
+            .lines()
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        details.push("ASCII STL".to_string());
+        if !name.is_empty() {
+            details.push(format!("solid {}", name));
+        }
+    } else if let Some(count) = header.get(80..84) {
+        let triangles = u32::from_le_bytes([count[0], count[1], count[2], count[3]]);
+        details.push(format!("binary STL, {} triangles", triangles));
+    }
+}
+
+/// SketchUp files open with a UTF-16LE banner such as `SketchUp Model{21.0.339}`
+fn skp_details(header: &[u8], details: &mut Vec<String>) {
+    let units: Vec<u16> = header
+        .get(..512.min(header.len()))
+        .unwrap_or(&[])
+        .chunks(2)
+        .filter(|c| c.len() == 2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    let banner = String::from_utf16_lossy(&units);
+    if let (Some(start), Some(end)) = (banner.find('{'), banner.find('}')) {
+        if start < end {
+            details.push(format!("version {}", &banner[start + 1..end]));
+        }
+    }
+}
+
+/// Revit stores the saving release in the UTF-16 `BasicFileInfo` stream
+fn revit_details(path: &Path, details: &mut Vec<String>) {
+    static FORMAT: OnceLock<Regex> = OnceLock::new();
+    let Ok(mut file) = cfb::open(path) else {
+        details.push("not an OLE container".to_string());
+        return;
+    };
+    let mut data = Vec::new();
+    let read = file
+        .open_stream("/BasicFileInfo")
+        .and_then(|mut stream| stream.read_to_end(&mut data));
+    if read.is_err() {
+        return;
+    }
+
+    let units: Vec<u16> = data
+        .chunks(2)
+        .filter(|c| c.len() == 2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    let text = String::from_utf16_lossy(&units);
+    let format = FORMAT.get_or_init(|| Regex::new(r"Autodesk Revit (\d{4})|Format: (\d{4})").unwrap());
+    if let Some(captures) = format.captures(&text) {
+        if let Some(year) = captures.get(1).or_else(|| captures.get(2)) {
+            details.push(format!("saved by Revit {}", year.as_str()));
+        }
+    }
+}
@@ -1,6 +1,6 @@
+use crate::events::EventSink;
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
-use tauri::Emitter;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
@@ -11,21 +11,21 @@ pub struct LogEntry {
 
 #[derive(Clone)]
 pub struct EPTLogger {
-    app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
+    sink: Arc<Mutex<Option<Arc<dyn EventSink>>>>,
     logs: Arc<Mutex<Vec<LogEntry>>>,
 }
 
 impl EPTLogger {
     pub fn new() -> Self {
         Self {
-            app_handle: Arc::new(Mutex::new(None)),
+            sink: Arc::new(Mutex::new(None)),
             logs: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
-    pub fn set_app_handle(&self, app_handle: tauri::AppHandle) {
-        if let Ok(mut handle) = self.app_handle.lock() {
-            *handle = Some(app_handle);
+    pub fn set_sink(&self, sink: Arc<dyn EventSink>) {
+        if let Ok(mut handle) = self.sink.lock() {
+            *handle = Some(sink);
         }
     }
 
@@ -43,9 +43,9 @@ impl EPTLogger {
         }
 
         // Emit to frontend
-        if let Ok(handle) = self.app_handle.lock() {
-            if let Some(app) = handle.as_ref() {
-                let _ = app.emit("log-entry", &entry);
+        if let Ok(handle) = self.sink.lock() {
+            if let Some(sink) = handle.as_ref() {
+                sink.emit_log(&entry);
             }
         }
 
@@ -79,4 +79,3 @@ impl Default for EPTLogger {
         Self::new()
     }
 }
-
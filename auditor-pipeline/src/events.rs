@@ -0,0 +1,188 @@
+use crate::ept_logger::LogEntry;
+use crate::run_summary::ExportStats;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Condvar, Mutex};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressUpdate {
+    pub current: usize,
+    pub total: usize,
+    pub task_category: String,
+    /// Running counts while the LLM export stage copies files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub export: Option<ExportStats>,
+    /// Processing is paused, or pausing once the files in progress finish
+    #[serde(default)]
+    pub paused: bool,
+}
+
+/// Receiver for events produced while the pipeline runs.
+///
+/// The Tauri frontends forward these to the webview; a CLI can print them.
+/// Both methods default to doing nothing.
+pub trait EventSink: Send + Sync {
+    fn emit_log(&self, _entry: &LogEntry) {}
+
+    fn emit_progress(&self, _update: &ProgressUpdate) {}
+}
+
+/// Sink that drops every event
+pub struct NoopEventSink;
+
+impl EventSink for NoopEventSink {}
+
+/// Latest counters seen for one pipeline stage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageProgress {
+    pub stage: String,
+    pub current: usize,
+    pub total: usize,
+    pub finished: bool,
+}
+
+/// Everything a frontend needs to redraw progress after a reload
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProgressSnapshot {
+    pub running: bool,
+    /// Processing stops taking new files until resumed
+    #[serde(default)]
+    pub paused: bool,
+    /// Stage currently executing, `None` between runs
+    pub current_stage: Option<String>,
+    pub latest: Option<ProgressUpdate>,
+    /// Stages in the order they started
+    pub stages: Vec<StageProgress>,
+}
+
+/// Shared record of the running pipeline's progress.
+///
+/// Unlike the `EventSink`, which only forwards updates as they happen, this
+/// keeps the latest state so it can be queried at any time. It also carries
+/// the pause switch the processing loop waits on. Clones share the same state.
+#[derive(Clone, Default)]
+pub struct ProgressTracker {
+    state: Arc<Mutex<ProgressSnapshot>>,
+    /// Signalled when a pause ends
+    resumed: Arc<Condvar>,
+}
+
+impl ProgressTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget the previous run and mark a new one as running
+    pub fn start_run(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            *state = ProgressSnapshot {
+                running: true,
+                ..ProgressSnapshot::default()
+            };
+        }
+    }
+
+    /// Mark a new run as running unless one already is. The app shares one
+    /// tracker between its runs, so a second run would mix its progress and
+    /// pause state with the first. Returns whether the run was started.
+    pub fn try_start_run(&self) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            return false;
+        };
+        if state.running {
+            return false;
+        }
+        *state = ProgressSnapshot {
+            running: true,
+            ..ProgressSnapshot::default()
+        };
+        true
+    }
+
+    /// Finish the current stage and make `stage` the current one
+    pub fn begin_stage(&self, stage: &str) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(previous) = state.stages.last_mut() {
+                previous.finished = true;
+            }
+            state.current_stage = Some(stage.to_string());
+            state.stages.push(StageProgress {
+                stage: stage.to_string(),
+                current: 0,
+                total: 0,
+                finished: false,
+            });
+        }
+    }
+
+    /// Record an update against the current stage
+    pub fn record(&self, update: &ProgressUpdate) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(stage) = state.stages.last_mut() {
+                stage.current = update.current;
+                stage.total = update.total;
+            }
+            state.latest = Some(update.clone());
+        }
+    }
+
+    /// Mark the run as over, successful or not
+    pub fn finish_run(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(stage) = state.stages.last_mut() {
+                stage.finished = true;
+            }
+            state.running = false;
+            state.paused = false;
+            state.current_stage = None;
+        }
+        self.resumed.notify_all();
+    }
+
+    /// Stop the running pipeline from taking new files once the ones in
+    /// progress finish. Returns the latest update, marked paused, to pass
+    /// on to the frontend; `None` when no run is in progress.
+    pub fn pause(&self) -> Option<ProgressUpdate> {
+        self.set_paused(true)
+    }
+
+    /// Let a paused pipeline continue. Returns the latest update, no longer
+    /// marked paused; `None` when no run is in progress.
+    pub fn resume(&self) -> Option<ProgressUpdate> {
+        let latest = self.set_paused(false);
+        self.resumed.notify_all();
+        latest
+    }
+
+    fn set_paused(&self, paused: bool) -> Option<ProgressUpdate> {
+        let mut state = self.state.lock().ok()?;
+        if !state.running {
+            return None;
+        }
+        state.paused = paused;
+        let latest = state.latest.get_or_insert_with(|| ProgressUpdate {
+            current: 0,
+            total: 0,
+            task_category: String::new(),
+            export: None,
+            paused,
+        });
+        latest.paused = paused;
+        Some(latest.clone())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.lock().map(|state| state.paused).unwrap_or(false)
+    }
+
+    /// Block while the run is paused
+    pub fn wait_while_paused(&self) {
+        let Ok(state) = self.state.lock() else {
+            return;
+        };
+        let _ = self.resumed.wait_while(state, |state| state.paused);
+    }
+
+    pub fn snapshot(&self) -> ProgressSnapshot {
+        self.state.lock().map(|state| state.clone()).unwrap_or_default()
+    }
+}
@@ -0,0 +1,405 @@
+use crate::docx_markdown::{attribute, read_part, render_table};
+use crate::pptx_markdown::relationship_targets;
+use anyhow::{Context, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::fs::File;
+use std::path::Path;
+
+/// `<sheetProtection>` attributes that, when `1`, forbid an action
+const PROTECTION_LOCKS: [(&str, &str); 10] = [
+    ("formatCells", "format cells"),
+    ("formatColumns", "format columns"),
+    ("formatRows", "format rows"),
+    ("insertColumns", "insert columns"),
+    ("insertRows", "insert rows"),
+    ("deleteColumns", "delete columns"),
+    ("deleteRows", "delete rows"),
+    ("sort", "sort"),
+    ("autoFilter", "filter"),
+    ("pivotTables", "use pivot tables"),
+];
+
+/// A defined name, scoped to one sheet or to the whole workbook
+#[derive(Debug, Clone)]
+pub struct DefinedName {
+    pub name: String,
+    pub refers_to: String,
+    /// Index of the sheet the name is local to
+    pub local_sheet: Option<usize>,
+    pub hidden: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DataValidation {
+    pub range: String,
+    pub kind: String,
+    pub operator: Option<String>,
+    pub formula1: Option<String>,
+    pub formula2: Option<String>,
+    pub prompt: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SheetControls {
+    pub name: String,
+    /// `None` when the sheet is not protected
+    pub protection: Option<String>,
+    pub validations: Vec<DataValidation>,
+}
+
+/// Control-relevant settings of an xlsx workbook that the cell values alone
+/// don't show: defined names, data validation rules and protection.
+#[derive(Debug, Clone, Default)]
+pub struct WorkbookControls {
+    pub workbook_protection: Option<String>,
+    pub defined_names: Vec<DefinedName>,
+    /// In workbook order
+    pub sheets: Vec<SheetControls>,
+}
+
+impl WorkbookControls {
+    pub fn read_xlsx(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("Failed to open XLSX: {}", path.display()))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .with_context(|| format!("Not a valid XLSX (zip) file: {}", path.display()))?;
+
+        let workbook_xml = read_part(&mut archive, "xl/workbook.xml")?
+            .with_context(|| format!("XLSX has no xl/workbook.xml: {}", path.display()))?;
+        let (sheet_names, sheet_parts): (Vec<String>, Vec<Option<String>>) =
+            worksheet_parts(&mut archive, &workbook_xml)?.into_iter().unzip();
+
+        let mut controls = Self {
+            sheets: sheet_names
+                .into_iter()
+                .map(|name| SheetControls {
+                    name,
+                    ..SheetControls::default()
+                })
+                .collect(),
+            ..Self::default()
+        };
+        let mut reader = Reader::from_str(&workbook_xml);
+        let mut open_name: Option<DefinedName> = None;
+        loop {
+            match reader.read_event()? {
+                Event::Empty(e) | Event::Start(e) if e.local_name().as_ref() == b"workbookProtection" => {
+                    controls.workbook_protection = Some(describe_workbook_protection(&e));
+                }
+                Event::Start(e) if e.local_name().as_ref() == b"definedName" => {
+                    open_name = Some(DefinedName {
+                        name: attribute(&e, b"name").unwrap_or_default(),
+                        refers_to: String::new(),
+                        local_sheet: attribute(&e, b"localSheetId").and_then(|id| id.parse().ok()),
+                        hidden: attribute(&e, b"hidden").as_deref() == Some("1"),
+                    });
+                }
+                Event::Text(e) => {
+                    if let Some(name) = open_name.as_mut() {
+                        name.refers_to.push_str(&e.unescape()?);
+                    }
+                }
+                Event::End(e) if e.local_name().as_ref() == b"definedName" => {
+                    if let Some(name) = open_name.take() {
+                        controls.defined_names.push(name);
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        for (sheet, part) in controls.sheets.iter_mut().zip(sheet_parts) {
+            let Some(part) = part else { continue };
+            if let Some(xml) = read_part(&mut archive, &part)? {
+                parse_sheet_controls(&xml, sheet).with_context(|| format!("Malformed {}", part))?;
+            }
+        }
+
+        Ok(controls)
+    }
+
+    /// Markdown appendix for one sheet: its protection, the names scoped to
+    /// or pointing into it, and its data validation rules
+    pub fn sheet_appendix(&self, sheet_index: usize) -> Option<String> {
+        let sheet = self.sheets.get(sheet_index)?;
+        let mut blocks = vec![format!("### Controls: {}", sheet.name)];
+
+        blocks.push(match &sheet.protection {
+            Some(protection) => format!("**Sheet protection:** {}", protection),
+            None => "**Sheet protection:** not protected".to_string(),
+        });
+
+        let names: Vec<&DefinedName> = self
+            .defined_names
+            .iter()
+            .filter(|name| match name.local_sheet {
+                Some(index) => index == sheet_index,
+                None => refers_to_sheet(&name.refers_to, &sheet.name),
+            })
+            .collect();
+        if !names.is_empty() {
+            let mut rows = vec![vec!["Name".to_string(), "Refers To".to_string(), "Scope".to_string()]];
+            for name in names {
+                let mut scope = if name.local_sheet.is_some() { "Sheet" } else { "Workbook" }.to_string();
+                if name.hidden {
+                    scope.push_str(" (hidden)");
+                }
+                rows.push(vec![name.name.clone(), format!("`{}`", name.refers_to), scope]);
+            }
+            blocks.push("**Defined names:**".to_string());
+            blocks.extend(render_table(&rows));
+        }
+
+        if !sheet.validations.is_empty() {
+            let mut rows = vec![vec![
+                "Range".to_string(),
+                "Type".to_string(),
+                "Rule".to_string(),
+                "Input Prompt".to_string(),
+                "Error Message".to_string(),
+            ]];
+            for validation in &sheet.validations {
+                rows.push(vec![
+                    validation.range.clone(),
+                    validation.kind.clone(),
+                    describe_rule(validation),
+                    validation.prompt.clone().unwrap_or_default(),
+                    validation.error.clone().unwrap_or_default(),
+                ]);
+            }
+            blocks.push("**Data validation:**".to_string());
+            blocks.extend(render_table(&rows));
+        }
+
+        Some(blocks.join("\n\n"))
+    }
+
+    /// Markdown for workbook-level controls not tied to any sheet
+    pub fn workbook_appendix(&self) -> String {
+        let mut blocks = vec!["## Workbook Controls".to_string()];
+        blocks.push(match &self.workbook_protection {
+            Some(protection) => format!("**Workbook protection:** {}", protection),
+            None => "**Workbook protection:** not protected".to_string(),
+        });
+
+        let unattached: Vec<Vec<String>> = self
+            .defined_names
+            .iter()
+            .filter(|name| {
+                name.local_sheet.is_none()
+                    && !self.sheets.iter().any(|sheet| refers_to_sheet(&name.refers_to, &sheet.name))
+            })
+            .map(|name| vec![name.name.clone(), format!("`{}`", name.refers_to)])
+            .collect();
+        if !unattached.is_empty() {
+            let mut rows = vec![vec!["Name".to_string(), "Refers To".to_string()]];
+            rows.extend(unattached);
+            blocks.push("**Workbook-level names:**".to_string());
+            blocks.extend(render_table(&rows));
+        }
+        blocks.join("\n\n")
+    }
+}
+
+/// `(sheet name, worksheet part)` of every sheet of an open xlsx package, in
+/// workbook order (the order calamine reports sheets in)
+pub(crate) fn worksheet_parts(
+    archive: &mut zip::ZipArchive<File>,
+    workbook_xml: &str,
+) -> Result<Vec<(String, Option<String>)>> {
+    let relationships = match read_part(archive, "xl/_rels/workbook.xml.rels")? {
+        Some(xml) => relationship_targets(&xml, "xl"),
+        None => Default::default(),
+    };
+    let mut sheets = Vec::new();
+    let mut reader = Reader::from_str(workbook_xml);
+    loop {
+        match reader.read_event()? {
+            Event::Empty(e) | Event::Start(e) if e.local_name().as_ref() == b"sheet" => {
+                let name = attribute(&e, b"name").unwrap_or_default();
+                let part = relationship_id(&e).and_then(|id| relationships.get(&id).cloned());
+                sheets.push((name, part));
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(sheets)
+}
+
+/// The `r:id` attribute of a `<sheet>`; its local name clashes with `sheetId`
+fn relationship_id(element: &quick_xml::events::BytesStart) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref().ends_with(b":id"))
+        .map(|attr| String::from_utf8_lossy(&attr.value).to_string())
+}
+
+/// Whether a reference like `'Sheet 1'!$A$1:$B$2` points into `sheet_name`
+fn refers_to_sheet(refers_to: &str, sheet_name: &str) -> bool {
+    let quoted = format!("'{}'!", sheet_name.replace('\'', "''"));
+    let plain = format!("{}!", sheet_name);
+    refers_to.contains(&quoted) || refers_to.contains(&plain)
+}
+
+fn describe_workbook_protection(element: &quick_xml::events::BytesStart) -> String {
+    let mut locked = Vec::new();
+    if attribute(element, b"lockStructure").as_deref() == Some("1") {
+        locked.push("structure");
+    }
+    if attribute(element, b"lockWindows").as_deref() == Some("1") {
+        locked.push("windows");
+    }
+    let password = has_password(element);
+    match (locked.is_empty(), password) {
+        (true, _) => "not protected".to_string(),
+        (false, true) => format!("{} locked, password set", locked.join(" and ")),
+        (false, false) => format!("{} locked, no password", locked.join(" and ")),
+    }
+}
+
+fn has_password(element: &quick_xml::events::BytesStart) -> bool {
+    attribute(element, b"password").is_some()
+        || attribute(element, b"workbookPassword").is_some()
+        || attribute(element, b"hashValue").is_some()
+        || attribute(element, b"workbookHashValue").is_some()
+}
+
+fn describe_sheet_protection(element: &quick_xml::events::BytesStart) -> Option<String> {
+    if attribute(element, b"sheet").as_deref() != Some("1") {
+        return None;
+    }
+    let mut description = if has_password(element) {
+        "protected, password set".to_string()
+    } else {
+        "protected, no password".to_string()
+    };
+    let mut blocked: Vec<&str> = PROTECTION_LOCKS
+        .iter()
+        .filter(|(attr, _)| attribute(element, attr.as_bytes()).as_deref() != Some("0"))
+        .map(|(_, label)| *label)
+        .collect();
+    // Unlike the flags above, selection defaults to allowed
+    if attribute(element, b"selectLockedCells").as_deref() == Some("1") {
+        blocked.push("select locked cells");
+    }
+    if attribute(element, b"selectUnlockedCells").as_deref() == Some("1") {
+        blocked.push("select unlocked cells");
+    }
+    if !blocked.is_empty() {
+        description.push_str(&format!("; users cannot {}", blocked.join(", ")));
+    }
+    Some(description)
+}
+
+/// Read `<sheetProtection>` and both the classic `<dataValidation>` and the
+/// `x14` extension form (used for lists referring to other sheets)
+fn parse_sheet_controls(xml: &str, sheet: &mut SheetControls) -> Result<()> {
+    let mut reader = Reader::from_str(xml);
+    let mut validation: Option<DataValidation> = None;
+    // Element whose text is being collected: formula1, formula2 or sqref
+    let mut text_target: Option<Vec<u8>> = None;
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Empty(e) | Event::Start(e) if e.local_name().as_ref() == b"sheetProtection" => {
+                sheet.protection = describe_sheet_protection(&e);
+            }
+            Event::Start(e) if e.local_name().as_ref() == b"dataValidation" => {
+                validation = Some(new_validation(&e));
+            }
+            Event::Empty(e) if e.local_name().as_ref() == b"dataValidation" => {
+                sheet.validations.push(new_validation(&e));
+            }
+            Event::Start(e) if matches!(e.local_name().as_ref(), b"formula1" | b"formula2" | b"sqref" | b"f") => {
+                text_target = Some(e.local_name().as_ref().to_vec());
+                text.clear();
+            }
+            Event::Text(e) if text_target.is_some() => text.push_str(&e.unescape()?),
+            Event::End(e) => match e.local_name().as_ref() {
+                b"dataValidation" => {
+                    if let Some(validation) = validation.take() {
+                        sheet.validations.push(validation);
+                    }
+                }
+                name @ (b"formula1" | b"formula2" | b"sqref") => {
+                    // In the x14 form the text sat in a nested <xm:f> and was
+                    // already taken when that closed
+                    if text_target.as_deref() != Some(name) {
+                        continue;
+                    }
+                    text_target = None;
+                    if let Some(validation) = validation.as_mut() {
+                        let value = text.trim().to_string();
+                        match name {
+                            b"formula1" => validation.formula1 = Some(value),
+                            b"formula2" => validation.formula2 = Some(value),
+                            _ => validation.range = value,
+                        }
+                    }
+                }
+                b"f" => {
+                    // x14 wraps each formula in <x14:formula1><xm:f>..</xm:f>
+                    text_target = None;
+                    if let Some(validation) = validation.as_mut() {
+                        let value = Some(text.trim().to_string());
+                        if validation.formula1.is_none() {
+                            validation.formula1 = value;
+                        } else {
+                            validation.formula2 = value;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn new_validation(element: &quick_xml::events::BytesStart) -> DataValidation {
+    let message = |title: &[u8], body: &[u8]| {
+        let parts: Vec<String> = [attribute(element, title), attribute(element, body)]
+            .into_iter()
+            .flatten()
+            .filter(|part| !part.is_empty())
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(": "))
+    };
+    DataValidation {
+        range: attribute(element, b"sqref").unwrap_or_default(),
+        kind: attribute(element, b"type").unwrap_or_else(|| "any".to_string()),
+        operator: attribute(element, b"operator"),
+        formula1: None,
+        formula2: None,
+        prompt: message(b"promptTitle", b"prompt"),
+        error: message(b"errorTitle", b"error"),
+    }
+}
+
+fn describe_rule(validation: &DataValidation) -> String {
+    let formula1 = validation.formula1.clone().unwrap_or_default();
+    let formula2 = validation.formula2.clone().unwrap_or_default();
+    match validation.kind.as_str() {
+        "list" => format!("one of {}", formula1),
+        "custom" => format!("`={}`", formula1),
+        "any" => String::new(),
+        _ => match validation.operator.as_deref().unwrap_or("between") {
+            "between" => format!("between {} and {}", formula1, formula2),
+            "notBetween" => format!("not between {} and {}", formula1, formula2),
+            "equal" => format!("= {}", formula1),
+            "notEqual" => format!("≠ {}", formula1),
+            "greaterThan" => format!("> {}", formula1),
+            "lessThan" => format!("< {}", formula1),
+            "greaterThanOrEqual" => format!(">= {}", formula1),
+            "lessThanOrEqual" => format!("<= {}", formula1),
+            other => format!("{} {} {}", other, formula1, formula2).trim().to_string(),
+        },
+    }
+}
@@ -0,0 +1,181 @@
+use crate::docx_markdown::{attribute, read_part, render_table};
+use crate::excel_controls::worksheet_parts;
+use crate::pptx_markdown::related_part;
+use anyhow::{Context, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::path::Path;
+
+/// A comment or note attached to a cell
+#[derive(Debug, Clone)]
+pub struct CellComment {
+    /// A1-style reference of the cell
+    pub cell: String,
+    pub author: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SheetHiddenContent {
+    pub name: String,
+    /// Zero-based indexes of hidden rows
+    pub hidden_rows: BTreeSet<u32>,
+    /// Zero-based indexes of hidden columns
+    pub hidden_columns: BTreeSet<u32>,
+    pub comments: Vec<CellComment>,
+}
+
+/// Hidden rows, hidden columns and cell comments of an xlsx workbook, none
+/// of which calamine exposes. Hidden sheets are reported by calamine itself.
+#[derive(Debug, Clone, Default)]
+pub struct HiddenContent {
+    /// In workbook order
+    pub sheets: Vec<SheetHiddenContent>,
+}
+
+impl HiddenContent {
+    pub fn read_xlsx(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("Failed to open XLSX: {}", path.display()))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .with_context(|| format!("Not a valid XLSX (zip) file: {}", path.display()))?;
+
+        let workbook_xml = read_part(&mut archive, "xl/workbook.xml")?
+            .with_context(|| format!("XLSX has no xl/workbook.xml: {}", path.display()))?;
+
+        let mut content = Self::default();
+        for (name, part) in worksheet_parts(&mut archive, &workbook_xml)? {
+            let mut sheet = SheetHiddenContent {
+                name,
+                ..SheetHiddenContent::default()
+            };
+            if let Some(part) = part {
+                if let Some(xml) = read_part(&mut archive, &part)? {
+                    parse_hidden_rows_and_columns(&xml, &mut sheet);
+                }
+                if let Some(comments_part) = related_part(&mut archive, &part, "/comments")? {
+                    if let Some(xml) = read_part(&mut archive, &comments_part)? {
+                        sheet.comments =
+                            parse_comments(&xml).with_context(|| format!("Malformed {}", comments_part))?;
+                    }
+                }
+            }
+            content.sheets.push(sheet);
+        }
+        Ok(content)
+    }
+
+    pub fn sheet(&self, sheet_index: usize) -> Option<&SheetHiddenContent> {
+        self.sheets.get(sheet_index)
+    }
+}
+
+impl SheetHiddenContent {
+    /// Markdown listing of the sheet's comments, if it has any
+    pub fn comments_appendix(&self) -> Option<String> {
+        if self.comments.is_empty() {
+            return None;
+        }
+        let mut rows = vec![vec!["Cell".to_string(), "Author".to_string(), "Comment".to_string()]];
+        for comment in &self.comments {
+            rows.push(vec![
+                comment.cell.clone(),
+                comment.author.clone(),
+                comment.text.split_whitespace().collect::<Vec<_>>().join(" "),
+            ]);
+        }
+        Some(format!("### Comments: {}\n\n{}", self.name, render_table(&rows)?))
+    }
+}
+
+/// Collect `<row hidden="1">` and `<col hidden="1">` entries. Large sheets
+/// may be cut off at the part size limit, so parsing stops quietly at the
+/// first error and keeps what was read so far.
+fn parse_hidden_rows_and_columns(xml: &str, sheet: &mut SheetHiddenContent) {
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Empty(e)) | Ok(Event::Start(e)) if e.local_name().as_ref() == b"col" => {
+                if attribute(&e, b"hidden").as_deref() != Some("1") {
+                    continue;
+                }
+                let min = attribute(&e, b"min").and_then(|v| v.parse::<u32>().ok());
+                let max = attribute(&e, b"max").and_then(|v| v.parse::<u32>().ok());
+                if let (Some(min), Some(max)) = (min, max) {
+                    // Columns are one-based in the sheet XML
+                    sheet.hidden_columns.extend(min.saturating_sub(1)..max);
+                }
+            }
+            Ok(Event::Empty(e)) | Ok(Event::Start(e)) if e.local_name().as_ref() == b"row" => {
+                if attribute(&e, b"hidden").as_deref() != Some("1") {
+                    continue;
+                }
+                if let Some(row) = attribute(&e, b"r").and_then(|v| v.parse::<u32>().ok()) {
+                    sheet.hidden_rows.insert(row.saturating_sub(1));
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+}
+
+/// Parse a `commentsN.xml` part; threaded comments also write one of these
+/// for compatibility, so they are covered as well
+fn parse_comments(xml: &str) -> Result<Vec<CellComment>> {
+    let mut reader = Reader::from_str(xml);
+    let mut authors = Vec::new();
+    let mut comments = Vec::new();
+    let mut in_author = false;
+    let mut author = String::new();
+    let mut open_comment: Option<(String, Option<usize>)> = None;
+    let mut in_text = false;
+    let mut text = String::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"author" => {
+                    in_author = true;
+                    author.clear();
+                }
+                b"comment" => {
+                    let cell = attribute(&e, b"ref").unwrap_or_default();
+                    let author_id = attribute(&e, b"authorId").and_then(|id| id.parse().ok());
+                    open_comment = Some((cell, author_id));
+                    text.clear();
+                }
+                b"t" if open_comment.is_some() => in_text = true,
+                _ => {}
+            },
+            Event::Text(e) => {
+                if in_author {
+                    author.push_str(&e.unescape()?);
+                } else if in_text {
+                    text.push_str(&e.unescape()?);
+                }
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"author" => {
+                    in_author = false;
+                    authors.push(author.trim().to_string());
+                }
+                b"t" => in_text = false,
+                b"comment" => {
+                    if let Some((cell, author_id)) = open_comment.take() {
+                        comments.push(CellComment {
+                            cell,
+                            author: author_id.and_then(|id| authors.get(id).cloned()).unwrap_or_default(),
+                            text: text.trim().to_string(),
+                        });
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(comments)
+}
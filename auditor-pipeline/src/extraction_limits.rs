@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+
+/// Compression ratio is only enforced once an archive has produced this many
+/// bytes, so small but highly compressible files (blank forms, logs) pass
+const RATIO_CHECK_MIN_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Expansion limits guarding against zip bombs and runaway archives.
+///
+/// A value of 0 disables the corresponding limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExtractionLimits {
+    /// Maximum bytes a single archive may expand to
+    pub max_archive_bytes: u64,
+    /// Maximum bytes all archives of a run may expand to together
+    pub max_run_bytes: u64,
+    /// Maximum number of entries in a single archive
+    pub max_entries: usize,
+    /// Maximum ratio of expanded size to archive size
+    pub max_compression_ratio: u64,
+    /// Maximum levels of archives inside archives; an archive found directly
+    /// in the input is level 1
+    pub max_nesting_depth: usize,
+}
+
+impl Default for ExtractionLimits {
+    fn default() -> Self {
+        Self {
+            max_archive_bytes: 20 * 1024 * 1024 * 1024,
+            max_run_bytes: 200 * 1024 * 1024 * 1024,
+            max_entries: 100_000,
+            max_compression_ratio: 1_000,
+            max_nesting_depth: 10,
+        }
+    }
+}
+
+/// Running totals for one archive being extracted
+pub struct ExtractionBudget {
+    limits: ExtractionLimits,
+    compressed_size: u64,
+    run_bytes_before: u64,
+    pub archive_bytes: u64,
+    pub entries: usize,
+    /// Set to the reason as soon as a limit is exceeded
+    pub violation: Option<String>,
+}
+
+impl ExtractionBudget {
+    pub fn new(limits: &ExtractionLimits, compressed_size: u64, run_bytes_before: u64) -> Self {
+        Self {
+            limits: limits.clone(),
+            compressed_size,
+            run_bytes_before,
+            archive_bytes: 0,
+            entries: 0,
+            violation: None,
+        }
+    }
+
+    /// Bytes the archive may still expand to before `max_archive_bytes` is
+    /// exceeded; `None` when that limit is disabled
+    pub fn remaining_archive_bytes(&self) -> Option<u64> {
+        (self.limits.max_archive_bytes > 0)
+            .then(|| self.limits.max_archive_bytes.saturating_sub(self.archive_bytes))
+    }
+
+    /// Account for one more entry
+    pub fn add_entry(&mut self) -> Result<(), String> {
+        self.entries += 1;
+        if self.limits.max_entries > 0 && self.entries > self.limits.max_entries {
+            return self.fail(format!("archive has more than {} entries", self.limits.max_entries));
+        }
+        Ok(())
+    }
+
+    /// Account for `bytes` more expanded bytes
+    pub fn add_bytes(&mut self, bytes: u64) -> Result<(), String> {
+        self.archive_bytes += bytes;
+
+        if self.limits.max_archive_bytes > 0 && self.archive_bytes > self.limits.max_archive_bytes {
+            return self.fail(format!(
+                "archive expands beyond {} bytes",
+                self.limits.max_archive_bytes
+            ));
+        }
+        if self.limits.max_run_bytes > 0
+            && self.run_bytes_before + self.archive_bytes > self.limits.max_run_bytes
+        {
+            return self.fail(format!(
+                "run total expands beyond {} bytes",
+                self.limits.max_run_bytes
+            ));
+        }
+        if self.limits.max_compression_ratio > 0
+            && self.archive_bytes > RATIO_CHECK_MIN_BYTES
+            && self.archive_bytes / self.compressed_size.max(1) > self.limits.max_compression_ratio
+        {
+            return self.fail(format!(
+                "compression ratio exceeds {}:1",
+                self.limits.max_compression_ratio
+            ));
+        }
+        Ok(())
+    }
+
+    fn fail(&mut self, reason: String) -> Result<(), String> {
+        self.violation = Some(reason.clone());
+        Err(reason)
+    }
+}
+
+/// Writer that charges every byte to an [`ExtractionBudget`] and fails the
+/// write once a limit is exceeded
+pub struct LimitedWriter<'a, W: Write> {
+    inner: W,
+    budget: &'a mut ExtractionBudget,
+}
+
+impl<'a, W: Write> LimitedWriter<'a, W> {
+    pub fn new(inner: W, budget: &'a mut ExtractionBudget) -> Self {
+        Self { inner, budget }
+    }
+}
+
+impl<W: Write> Write for LimitedWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.budget.add_bytes(written as u64).map_err(io::Error::other)?;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,212 @@
+use anyhow::Result;
+use std::path::Path;
+
+/// Who owns a file and who may access it, as the operating system records it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ownership {
+    /// `DOMAIN\user` on Windows, the user name (or uid) elsewhere
+    pub owner: Option<String>,
+    pub group: Option<String>,
+    /// NTFS access control list in SDDL form on Windows, e.g.
+    /// `D:PAI(A;;FA;;;SY)(A;;FA;;;BA)`; mode bits elsewhere, e.g. `-rw-r----- (0640)`
+    pub permissions: Option<String>,
+}
+
+/// Reads file ownership, caching account name lookups across files
+pub struct OwnershipReader {
+    #[cfg(unix)]
+    users: std::collections::HashMap<u32, String>,
+    #[cfg(unix)]
+    groups: std::collections::HashMap<u32, String>,
+}
+
+impl OwnershipReader {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(unix)]
+            users: unix::account_names("/etc/passwd"),
+            #[cfg(unix)]
+            groups: unix::account_names("/etc/group"),
+        }
+    }
+
+    /// Owner, group and permissions of the file at `path`
+    pub fn read(&self, path: &Path) -> Result<Ownership> {
+        #[cfg(unix)]
+        {
+            unix::read(path, &self.users, &self.groups)
+        }
+        #[cfg(windows)]
+        {
+            windows::read(path)
+        }
+        #[cfg(not(any(unix, windows)))]
+        {
+            anyhow::bail!("File ownership isn't available on this platform: {}", path.display())
+        }
+    }
+}
+
+impl Default for OwnershipReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::Ownership;
+    use anyhow::{Context, Result};
+    use std::collections::HashMap;
+    use std::os::unix::fs::MetadataExt;
+    use std::path::Path;
+
+    /// Id → name of the accounts in a `passwd`/`group` style file; empty
+    /// when it can't be read (names then show as ids)
+    pub(super) fn account_names(path: &str) -> HashMap<u32, String> {
+        std::fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split(':');
+                let name = fields.next()?;
+                let id = fields.nth(1)?.parse().ok()?;
+                Some((id, name.to_string()))
+            })
+            .collect()
+    }
+
+    pub(super) fn read(path: &Path, users: &HashMap<u32, String>, groups: &HashMap<u32, String>) -> Result<Ownership> {
+        let metadata = std::fs::symlink_metadata(path)
+            .with_context(|| format!("Failed to read metadata: {}", path.display()))?;
+        let name = |names: &HashMap<u32, String>, id: u32| names.get(&id).cloned().unwrap_or_else(|| id.to_string());
+        Ok(Ownership {
+            owner: Some(name(users, metadata.uid())),
+            group: Some(name(groups, metadata.gid())),
+            permissions: Some(format_mode(metadata.mode())),
+        })
+    }
+
+    /// `ls -l` style mode string with the octal bits, e.g. `-rw-r--r-- (0644)`
+    fn format_mode(mode: u32) -> String {
+        let kind = match mode & 0o170000 {
+            0o040000 => 'd',
+            0o120000 => 'l',
+            _ => '-',
+        };
+        let mut text = String::from(kind);
+        for shift in [6, 3, 0] {
+            let bits = (mode >> shift) & 0o7;
+            text.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+            text.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+            text.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+        }
+        format!("{} ({:04o})", text, mode & 0o7777)
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::Ownership;
+    use anyhow::{bail, Result};
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use std::ptr;
+    use windows_sys::Win32::Foundation::{LocalFree, ERROR_SUCCESS};
+    use windows_sys::Win32::Security::Authorization::{
+        ConvertSecurityDescriptorToStringSecurityDescriptorW, GetNamedSecurityInfoW, SDDL_REVISION_1, SE_FILE_OBJECT,
+    };
+    use windows_sys::Win32::Security::{
+        LookupAccountSidW, DACL_SECURITY_INFORMATION, GROUP_SECURITY_INFORMATION, OWNER_SECURITY_INFORMATION,
+        PSECURITY_DESCRIPTOR, PSID,
+    };
+
+    pub(super) fn read(path: &Path) -> Result<Ownership> {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+        let mut owner: PSID = ptr::null_mut();
+        let mut group: PSID = ptr::null_mut();
+        let mut descriptor: PSECURITY_DESCRIPTOR = ptr::null_mut();
+        // SAFETY: the path is NUL-terminated and the out pointers are valid;
+        // the SIDs point into `descriptor`, which is freed below
+        let status = unsafe {
+            GetNamedSecurityInfoW(
+                wide.as_ptr(),
+                SE_FILE_OBJECT,
+                OWNER_SECURITY_INFORMATION | GROUP_SECURITY_INFORMATION | DACL_SECURITY_INFORMATION,
+                &mut owner,
+                &mut group,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut descriptor,
+            )
+        };
+        if status != ERROR_SUCCESS {
+            bail!("Failed to read the security descriptor of {} (error {})", path.display(), status);
+        }
+
+        let ownership = Ownership {
+            owner: account_name(owner),
+            group: account_name(group),
+            permissions: dacl_sddl(descriptor),
+        };
+        // SAFETY: `descriptor` was allocated by GetNamedSecurityInfoW
+        unsafe { LocalFree(descriptor) };
+        Ok(ownership)
+    }
+
+    /// `DOMAIN\name` of a SID; `None` for SIDs without an account, e.g. of
+    /// a deleted user
+    fn account_name(sid: PSID) -> Option<String> {
+        if sid.is_null() {
+            return None;
+        }
+        let mut name = [0u16; 256];
+        let mut domain = [0u16; 256];
+        let mut name_len = name.len() as u32;
+        let mut domain_len = domain.len() as u32;
+        let mut sid_use = 0;
+        // SAFETY: the buffers and their lengths match
+        let found = unsafe {
+            LookupAccountSidW(
+                ptr::null(),
+                sid,
+                name.as_mut_ptr(),
+                &mut name_len,
+                domain.as_mut_ptr(),
+                &mut domain_len,
+                &mut sid_use,
+            )
+        };
+        if found == 0 {
+            return None;
+        }
+        let name = String::from_utf16_lossy(&name[..name_len as usize]);
+        let domain = String::from_utf16_lossy(&domain[..domain_len as usize]);
+        Some(if domain.is_empty() { name } else { format!("{}\\{}", domain, name) })
+    }
+
+    /// The access control list of a security descriptor in SDDL form
+    fn dacl_sddl(descriptor: PSECURITY_DESCRIPTOR) -> Option<String> {
+        let mut text: *mut u16 = ptr::null_mut();
+        let mut length = 0u32;
+        // SAFETY: `descriptor` is valid; `text` is freed with LocalFree
+        let converted = unsafe {
+            ConvertSecurityDescriptorToStringSecurityDescriptorW(
+                descriptor,
+                SDDL_REVISION_1,
+                DACL_SECURITY_INFORMATION,
+                &mut text,
+                &mut length,
+            )
+        };
+        if converted == 0 || text.is_null() {
+            return None;
+        }
+        // SAFETY: `text` holds `length` UTF-16 units, the last one the NUL
+        let sddl = unsafe { std::slice::from_raw_parts(text, length as usize) };
+        let sddl = String::from_utf16_lossy(sddl).trim_end_matches('\0').to_string();
+        // SAFETY: `text` was allocated by the conversion
+        unsafe { LocalFree(text.cast()) };
+        Some(sddl)
+    }
+}
@@ -0,0 +1,223 @@
+use crate::ept_logger::EPTLogger;
+use crate::path_filter::{PathFilter, PatternExclusion};
+use crate::report_model::{self, ExcludedFile, FileStatus, ReportModel};
+use crate::scan_limits::ScanLimits;
+use crate::system_files::SystemFileList;
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Files found by a scan
+pub struct ScanOutcome {
+    pub entries: Vec<ReportModel>,
+    /// Set when a scan limit stopped the scan early; `entries` then holds
+    /// only the files found up to that point
+    pub limit_hit: Option<String>,
+    /// Hidden and system files left out, for the Exclusions sheet
+    pub excluded: Vec<ExcludedFile>,
+}
+
+pub struct FileScanner {
+    logger: Option<EPTLogger>,
+    filter: Option<(PathFilter, PatternExclusion)>,
+    limits: Option<ScanLimits>,
+    system_files: Option<SystemFileList>,
+}
+
+impl FileScanner {
+    pub fn new() -> Self {
+        Self {
+            logger: None,
+            filter: None,
+            limits: None,
+            system_files: Some(SystemFileList::default()),
+        }
+    }
+
+    pub fn with_logger(logger: EPTLogger) -> Self {
+        Self {
+            logger: Some(logger),
+            ..Self::new()
+        }
+    }
+
+    /// Leave out files matching `system_files`; `None` keeps hidden and
+    /// system files
+    pub fn with_system_files(mut self, system_files: Option<SystemFileList>) -> Self {
+        self.system_files = system_files;
+        self
+    }
+
+    /// Stop scanning once a limit is hit
+    pub fn with_limits(mut self, limits: ScanLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Apply include/exclude patterns: files they leave out are dropped, or
+    /// listed as excluded with the pattern as the reason
+    pub fn with_filter(mut self, filter: PathFilter, exclusion: PatternExclusion) -> Self {
+        self.filter = (!filter.is_empty()).then_some((filter, exclusion));
+        self
+    }
+
+    /// Why the file at `path` is left out as a hidden or system file
+    fn system_file_reason(&self, path: &Path) -> Option<String> {
+        let system_files = self.system_files.as_ref()?;
+        let file_name = path.file_name()?.to_str()?;
+        system_files.exclusion_reason(file_name)
+    }
+
+    pub fn scan(root_path: &Path) -> Result<Vec<ReportModel>> {
+        Ok(Self::new().scan_with_logging(root_path)?.entries)
+    }
+
+    pub fn scan_with_logging(&self, root_path: &Path) -> Result<ScanOutcome> {
+        let mut entries = Vec::new();
+        let mut total_bytes = 0u64;
+        let mut limit_hit = None;
+        let mut excluded = Vec::new();
+        
+        if let Some(ref logger) = self.logger {
+            logger.debug(&format!("Scanning directory: {}", root_path.display()));
+        }
+        
+        let mut walker = WalkDir::new(root_path);
+        if let Some(depth) = self.limits.as_ref().and_then(ScanLimits::walk_depth) {
+            walker = walker.max_depth(depth);
+        }
+        for dir_entry in walker.into_iter().filter_map(|e| e.ok()) {
+            if let Some(limits) = &self.limits {
+                limit_hit = limits.check_depth(dir_entry.depth());
+                if limit_hit.is_some() {
+                    break;
+                }
+            }
+            if let Some(reason) = self.system_file_reason(dir_entry.path()).filter(|_| dir_entry.file_type().is_file()) {
+                excluded.push(ExcludedFile {
+                    relative_path: dir_entry
+                        .path()
+                        .strip_prefix(root_path)
+                        .unwrap_or(dir_entry.path())
+                        .to_string_lossy()
+                        .to_string(),
+                    reason,
+                });
+                continue;
+            }
+            let Some(report_entry) = Self::build_entry(root_path, dir_entry.path())
+                .and_then(|entry| self.apply_filter(entry))
+            else {
+                continue;
+            };
+            if let Some(limits) = &self.limits {
+                total_bytes += report_entry.file_size_bytes;
+                limit_hit = limits.check_totals(entries.len() + 1, total_bytes);
+                if limit_hit.is_some() {
+                    break;
+                }
+            }
+            entries.push(report_entry);
+            
+            // Log every 100 files for progress feedback
+            if entries.len() % 100 == 0 {
+                if let Some(ref logger) = self.logger {
+                    logger.debug(&format!("Scanned {} files so far...", entries.len()));
+                }
+            }
+        }
+        
+        if let Some(ref logger) = self.logger {
+            match &limit_hit {
+                Some(reason) => logger.warning(&format!(
+                    "File scan stopped after {} files: {}",
+                    entries.len(),
+                    reason
+                )),
+                None => logger.info(&format!("File scan complete: found {} files", entries.len())),
+            }
+        }
+        
+        Ok(ScanOutcome { entries, limit_hit, excluded })
+    }
+
+    fn apply_filter(&self, mut entry: ReportModel) -> Option<ReportModel> {
+        let Some((filter, exclusion)) = &self.filter else {
+            return Some(entry);
+        };
+        let Some(reason) = filter.exclusion_reason(&entry.original_relative_path) else {
+            return Some(entry);
+        };
+        match exclusion {
+            PatternExclusion::Omit => None,
+            PatternExclusion::List => {
+                entry.status = FileStatus::Excluded;
+                entry.skip_reason = Some(reason);
+                Some(entry)
+            }
+        }
+    }
+
+    fn build_entry(root_path: &Path, path: &Path) -> Option<ReportModel> {
+        if !path.is_file() {
+            return None;
+        }
+
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown");
+
+        let metadata = fs::metadata(path).ok()?;
+        let file_name = file_name.to_string();
+        
+        let relative_path = path
+            .strip_prefix(root_path)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string();
+        
+        // Compressed files keep the type they wrap, e.g. `csv.gz`
+        let file_type = report_model::compound_extension(path).unwrap_or_else(|| "unknown".to_string());
+        
+        let file_size = metadata.len();
+        
+        let last_modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| {
+                chrono::DateTime::<chrono::Local>::from(t)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+                    .into()
+                })
+            .unwrap_or_else(|| "unknown".to_string());
+        
+        let created_time = metadata
+            .created()
+            .ok()
+            .and_then(|t| {
+                chrono::DateTime::<chrono::Local>::from(t)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+                    .into()
+                })
+            .unwrap_or_else(|| last_modified.clone());
+        
+        Some(ReportModel::new(
+            file_name,
+            relative_path,
+            file_type,
+            file_size,
+            last_modified,
+            created_time,
+        ))
+    }
+}
+
+impl Default for FileScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,226 @@
+use crate::office_encryption::has_ole_magic;
+use regex::Regex;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// How much of a file is read to recognise its type
+const SNIFF_BYTES: u64 = 8 * 1024;
+
+/// Leading bytes of binary formats, with their extension, description and
+/// MIME type, checked in order
+const SIGNATURES: &[(&[u8], &str, &str, &str)] = &[
+    (b"\xFF\xD8\xFF", "jpg", "JPEG image", "image/jpeg"),
+    (b"\x89PNG\r\n\x1A\n", "png", "PNG image", "image/png"),
+    (b"GIF87a", "gif", "GIF image", "image/gif"),
+    (b"GIF89a", "gif", "GIF image", "image/gif"),
+    (b"II*\x00", "tif", "TIFF image", "image/tiff"),
+    (b"MM\x00*", "tif", "TIFF image", "image/tiff"),
+    (b"{\\rtf", "rtf", "RTF document", "application/rtf"),
+    (b"\x1F\x8B", "gz", "gzip archive", "application/gzip"),
+    (b"Rar!\x1A\x07", "rar", "RAR archive", "application/vnd.rar"),
+    (b"7z\xBC\xAF\x27\x1C", "7z", "7-Zip archive", "application/x-7z-compressed"),
+];
+
+/// Extensions of one recognised format; the first is the one `detect` reports
+const EXTENSION_GROUPS: &[&[&str]] = &[
+    &["jpg", "jpeg", "jpe", "jfif"],
+    &["png"],
+    &["gif"],
+    &["tif", "tiff"],
+    &["rtf"],
+    &["gz", "tgz"],
+    &["rar"],
+    &["7z"],
+    &["pdf"],
+    &["zip"],
+    &["docx", "docm", "dotx", "dotm"],
+    &["xlsx", "xlsm", "xltx", "xltm", "xlam"],
+    &["pptx", "pptm", "potx", "potm", "ppsx", "ppsm"],
+    &["odt", "ott"],
+    &["ods", "ots"],
+    &["odp", "otp"],
+    &["doc", "dot"],
+    &["xls", "xlt", "xla"],
+    &["ppt", "pps", "pot"],
+    &["msg", "oft"],
+];
+
+/// Extension-less names that are conventionally plain text
+const TEXT_FILE_NAMES: &[&str] = &[
+    "readme", "license", "licence", "copying", "notice", "changelog", "changes", "authors", "contributors",
+    "install", "todo", "makefile", "dockerfile",
+];
+
+/// A file type recognised from a file's content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetectedType {
+    pub extension: &'static str,
+    pub description: &'static str,
+    pub mime: &'static str,
+}
+
+impl DetectedType {
+    fn new(extension: &'static str, description: &'static str, mime: &'static str) -> Self {
+        Self { extension, description, mime }
+    }
+
+    /// Whether a file named with `extension` claims a different format than
+    /// its content shows. Only extensions of formats this module recognises
+    /// count: text has too many extensions to judge, and an unrecognised
+    /// zip-based format (`.jar`, `.epub`) is still a valid zip.
+    pub fn contradicts_extension(&self, extension: &str) -> bool {
+        let extension = extension.to_lowercase();
+        let claimed = EXTENSION_GROUPS.iter().find(|group| group.contains(&extension.as_str()));
+        match claimed {
+            Some(group) => !group.contains(&self.extension),
+            None => false,
+        }
+    }
+}
+
+/// Recognise a file's type from its leading bytes, for files whose name
+/// doesn't say. Containers (zip, OLE) are opened to tell Office formats
+/// apart; text is classified as XML, HTML, JSON, email or plain text.
+/// Returns `None` for unrecognised binary data.
+pub fn detect(path: &Path) -> Option<DetectedType> {
+    let mut head = Vec::new();
+    File::open(path).ok()?.take(SNIFF_BYTES).read_to_end(&mut head).ok()?;
+    if head.is_empty() {
+        return None;
+    }
+
+    // PDF writers may put junk before the header; readers accept it within 1 KB
+    if find(&head[..head.len().min(1024)], b"%PDF-").is_some() {
+        return Some(DetectedType::new("pdf", "PDF document", "application/pdf"));
+    }
+    if head.starts_with(b"PK\x03\x04") {
+        return Some(detect_zip_package(path));
+    }
+    if has_ole_magic(path) {
+        return detect_compound_file(path);
+    }
+    if let Some(&(_, extension, description, mime)) = SIGNATURES.iter().find(|(magic, _, _, _)| head.starts_with(magic)) {
+        return Some(DetectedType::new(extension, description, mime));
+    }
+
+    detect_text(&head, path)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+const OPENDOCUMENT_TEXT: &str = "application/vnd.oasis.opendocument.text";
+const OPENDOCUMENT_SPREADSHEET: &str = "application/vnd.oasis.opendocument.spreadsheet";
+const OPENDOCUMENT_PRESENTATION: &str = "application/vnd.oasis.opendocument.presentation";
+
+/// OOXML and OpenDocument files are zips with a telltale part
+fn detect_zip_package(path: &Path) -> DetectedType {
+    let archive = File::open(path).ok().and_then(|file| zip::ZipArchive::new(file).ok());
+    let Some(mut archive) = archive else {
+        return DetectedType::new("zip", "ZIP archive", "application/zip");
+    };
+    let has = |archive: &zip::ZipArchive<File>, name: &str| archive.file_names().any(|n| n == name);
+
+    if has(&archive, "word/document.xml") {
+        return DetectedType::new(
+            "docx",
+            "Word document",
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        );
+    }
+    if has(&archive, "xl/workbook.xml") {
+        return DetectedType::new(
+            "xlsx",
+            "Excel workbook",
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        );
+    }
+    if has(&archive, "ppt/presentation.xml") {
+        return DetectedType::new(
+            "pptx",
+            "PowerPoint presentation",
+            "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        );
+    }
+
+    let mut mimetype = String::new();
+    if let Ok(part) = archive.by_name("mimetype") {
+        let _ = part.take(100).read_to_string(&mut mimetype);
+    }
+    match mimetype.trim() {
+        OPENDOCUMENT_TEXT => DetectedType::new("odt", "OpenDocument text", OPENDOCUMENT_TEXT),
+        OPENDOCUMENT_SPREADSHEET => DetectedType::new("ods", "OpenDocument spreadsheet", OPENDOCUMENT_SPREADSHEET),
+        OPENDOCUMENT_PRESENTATION => DetectedType::new("odp", "OpenDocument presentation", OPENDOCUMENT_PRESENTATION),
+        _ => DetectedType::new("zip", "ZIP archive", "application/zip"),
+    }
+}
+
+/// Legacy Office documents and Outlook messages are OLE compound files
+fn detect_compound_file(path: &Path) -> Option<DetectedType> {
+    let file = cfb::open(path).ok()?;
+    if file.exists("/WordDocument") {
+        return Some(DetectedType::new("doc", "Word 97-2003 document", "application/msword"));
+    }
+    if file.exists("/Workbook") || file.exists("/Book") {
+        return Some(DetectedType::new("xls", "Excel 97-2003 workbook", "application/vnd.ms-excel"));
+    }
+    if file.exists("/PowerPoint Document") {
+        return Some(DetectedType::new("ppt", "PowerPoint 97-2003 presentation", "application/vnd.ms-powerpoint"));
+    }
+    let is_message = file
+        .read_root_storage()
+        .any(|entry| entry.name().starts_with("__substg1.0_"));
+    is_message.then(|| DetectedType::new("msg", "Outlook message", "application/vnd.ms-outlook"))
+}
+
+fn detect_text(head: &[u8], path: &Path) -> Option<DetectedType> {
+    if head.contains(&0) {
+        return None;
+    }
+    // The sample may end in the middle of a multi-byte character
+    let text = match std::str::from_utf8(head) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&head[..e.valid_up_to()]).ok()?,
+        Err(_) => return None,
+    };
+
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    if TEXT_FILE_NAMES.contains(&name.as_str()) {
+        return Some(DetectedType::new("txt", "Plain text", "text/plain"));
+    }
+
+    let start = text.trim_start_matches('\u{FEFF}').trim_start();
+    let lower_start: String = start.chars().take(512).collect::<String>().to_lowercase();
+    if lower_start.starts_with("<!doctype html") || lower_start.starts_with("<html") {
+        return Some(DetectedType::new("html", "HTML page", "text/html"));
+    }
+    if lower_start.starts_with("<?xml") {
+        if lower_start.contains("<html") {
+            return Some(DetectedType::new("html", "XHTML page", "application/xhtml+xml"));
+        }
+        return Some(DetectedType::new("xml", "XML document", "application/xml"));
+    }
+    if start.starts_with('{') || start.starts_with('[') {
+        return Some(DetectedType::new("json", "JSON data", "application/json"));
+    }
+    if email_header().is_match(start) {
+        return Some(DetectedType::new("eml", "Email message", "message/rfc822"));
+    }
+    Some(DetectedType::new("txt", "Plain text", "text/plain"))
+}
+
+/// An RFC 5322 message starts with header fields such as `Received:` or `From:`
+fn email_header() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?i)^(received|return-path|from|mime-version|message-id|delivered-to|date|x-[a-z0-9-]+):[^\n]*\n([^\n]+\n)*?(from|subject|to):")
+            .expect("valid email header pattern")
+    })
+}
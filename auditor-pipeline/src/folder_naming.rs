@@ -0,0 +1,116 @@
+use crate::settings::ProcessingSettings;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Local};
+use std::path::{Path, PathBuf};
+
+/// Template reproducing the historical `<name>__<timestamp>` folders
+pub const DEFAULT_FOLDER_NAME_TEMPLATE: &str = "{name}__{timestamp}";
+
+/// Tokens a folder name template may use
+const TOKENS: [&str; 6] = ["name", "date", "time", "timestamp", "run_id", "engagement"];
+
+/// Names the staging folder and every folder/file created next to an
+/// archive or document when it is unpacked, from the user's template.
+///
+/// The date and time tokens are fixed when the run starts, so all folders of
+/// one run sort together. A name that is already taken gets `_2`, `_3`, ...
+/// appended instead of being reused.
+#[derive(Debug, Clone)]
+pub struct FolderNaming {
+    template: String,
+    started_at: DateTime<Local>,
+    run_id: String,
+    engagement_code: String,
+}
+
+impl FolderNaming {
+    pub fn from_settings(settings: &ProcessingSettings) -> Self {
+        let started_at = Local::now();
+        let run_id = settings
+            .run_id
+            .clone()
+            .unwrap_or_else(|| format!("run-{}", started_at.format("%Y%m%d_%H%M%S")));
+        Self {
+            template: settings.folder_name_template.clone(),
+            started_at,
+            run_id,
+            engagement_code: settings.engagement_code.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Render the template for `name` (a file stem or input folder name)
+    pub fn render(&self, name: &str) -> String {
+        let mut rendered = self.template.clone();
+        for token in TOKENS {
+            let placeholder = format!("{{{}}}", token);
+            if !rendered.contains(&placeholder) {
+                continue;
+            }
+            let value = match token {
+                "name" => name.to_string(),
+                "date" => self.started_at.format("%Y%m%d").to_string(),
+                "time" => self.started_at.format("%H%M%S").to_string(),
+                "timestamp" => self.started_at.format("%Y%m%d_%H%M%S").to_string(),
+                "run_id" => self.run_id.clone(),
+                _ => self.engagement_code.clone(),
+            };
+            rendered = rendered.replace(&placeholder, &sanitize_component(&value));
+        }
+        rendered
+    }
+
+    /// `parent/<rendered name><suffix>`, numbered if that path already exists
+    pub fn unique_path(&self, parent: &Path, name: &str, suffix: &str) -> PathBuf {
+        let base = self.render(name);
+        let candidate = parent.join(format!("{}{}", base, suffix));
+        if !candidate.exists() {
+            return candidate;
+        }
+        (2..)
+            .map(|n| parent.join(format!("{}_{}{}", base, n, suffix)))
+            .find(|path| !path.exists())
+            .expect("unbounded counter always finds a free name")
+    }
+}
+
+/// Reject templates that could produce invalid or colliding names
+pub fn validate_template(template: &str) -> Result<()> {
+    if !template.contains("{name}") {
+        bail!("Folder name template '{}' must contain {{name}}", template);
+    }
+    if template.trim() == "{name}" {
+        bail!("Folder name template must add something to {{name}}, e.g. {{name}}__{{timestamp}}");
+    }
+    if template.contains(['/', '\\']) {
+        bail!("Folder name template '{}' must not contain path separators", template);
+    }
+
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            bail!("Folder name template '{}' has an unclosed '{{'", template);
+        };
+        let token = &rest[start + 1..start + end];
+        if !TOKENS.contains(&token) {
+            bail!(
+                "Unknown token {{{}}} in folder name template; available: {}",
+                token,
+                TOKENS.map(|t| format!("{{{}}}", t)).join(", ")
+            );
+        }
+        rest = &rest[start + end + 1..];
+    }
+    Ok(())
+}
+
+/// Replace characters that are not allowed in Windows/Unix file names
+fn sanitize_component(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
@@ -0,0 +1,224 @@
+use anyhow::{bail, Context, Result};
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Digest recorded for every file, chosen to match the evidence-handling
+/// standard of the engagement. MD5 and SHA-1 are only offered as additional
+/// digests, e.g. for matching against old load files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    #[default]
+    Sha512,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// Name as shown in the settings snapshot, e.g. `SHA-256`
+    pub fn label(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Md5 => "MD5",
+            HashAlgorithm::Sha1 => "SHA-1",
+            HashAlgorithm::Sha256 => "SHA-256",
+            HashAlgorithm::Sha512 => "SHA-512",
+            HashAlgorithm::Blake3 => "BLAKE3",
+        }
+    }
+
+    /// Name as used in report column headers, e.g. `SHA256`
+    pub fn column_label(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Md5 => "MD5",
+            HashAlgorithm::Sha1 => "SHA1",
+            HashAlgorithm::Sha256 => "SHA256",
+            HashAlgorithm::Sha512 => "SHA512",
+            HashAlgorithm::Blake3 => "BLAKE3",
+        }
+    }
+
+    /// Whether the algorithm is strong enough to identify evidence and
+    /// find duplicates by
+    pub fn is_collision_resistant(&self) -> bool {
+        !matches!(self, HashAlgorithm::Md5 | HashAlgorithm::Sha1)
+    }
+}
+
+/// Reject a primary algorithm that is only fit to be an additional digest
+pub fn validate_primary_algorithm(algorithm: HashAlgorithm) -> Result<()> {
+    if !algorithm.is_collision_resistant() {
+        bail!(
+            "{} can only be an additional hash algorithm; use SHA-256, SHA-512 or BLAKE3 as the primary one",
+            algorithm.label()
+        );
+    }
+    Ok(())
+}
+
+enum Hasher {
+    Md5(Md5),
+    Sha1(Sha1),
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Md5 => Hasher::Md5(Md5::new()),
+            HashAlgorithm::Sha1 => Hasher::Sha1(Sha1::new()),
+            HashAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            HashAlgorithm::Sha512 => Hasher::Sha512(Sha512::new()),
+            HashAlgorithm::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Md5(hasher) => hasher.update(data),
+            Hasher::Sha1(hasher) => hasher.update(data),
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Sha512(hasher) => hasher.update(data),
+            Hasher::Blake3(hasher) => {
+                hasher.update(data);
+            }
+        }
+    }
+
+    fn finalize_hex(self) -> String {
+        match self {
+            Hasher::Md5(hasher) => hex::encode(hasher.finalize()),
+            Hasher::Sha1(hasher) => hex::encode(hasher.finalize()),
+            Hasher::Sha256(hasher) => hex::encode(hasher.finalize()),
+            Hasher::Sha512(hasher) => hex::encode(hasher.finalize()),
+            Hasher::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+/// Primary and additional digests of one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDigests {
+    pub algorithm: HashAlgorithm,
+    pub primary: String,
+    pub additional: Vec<(HashAlgorithm, String)>,
+}
+
+impl FileDigests {
+    /// Digest under `algorithm`, if it was taken
+    pub fn get(&self, algorithm: HashAlgorithm) -> Option<&str> {
+        if algorithm == self.algorithm {
+            return Some(&self.primary);
+        }
+        self.additional
+            .iter()
+            .find(|(additional, _)| *additional == algorithm)
+            .map(|(_, digest)| digest.as_str())
+    }
+
+    /// The same digests with only `algorithms` kept among the additional ones
+    pub fn keeping(&self, algorithms: &[HashAlgorithm]) -> FileDigests {
+        FileDigests {
+            algorithm: self.algorithm,
+            primary: self.primary.clone(),
+            additional: self
+                .additional
+                .iter()
+                .filter(|(algorithm, _)| algorithms.contains(algorithm))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+pub struct HashingService {
+    algorithm: HashAlgorithm,
+    /// Digests taken alongside the primary one for original files
+    additional: Vec<HashAlgorithm>,
+}
+
+impl HashingService {
+    pub fn new() -> Self {
+        Self::with_algorithm(HashAlgorithm::default())
+    }
+
+    pub fn with_algorithm(algorithm: HashAlgorithm) -> Self {
+        Self {
+            algorithm,
+            additional: Vec::new(),
+        }
+    }
+
+    /// Also take `algorithms` in `hash_file_all`; repeats and the primary
+    /// algorithm are dropped
+    pub fn with_additional(mut self, algorithms: &[HashAlgorithm]) -> Self {
+        for &algorithm in algorithms {
+            if algorithm != self.algorithm && !self.additional.contains(&algorithm) {
+                self.additional.push(algorithm);
+            }
+        }
+        self
+    }
+
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    /// Hex digest of a file under the service's algorithm
+    pub fn hash_file(&self, file_path: &Path) -> Result<String> {
+        let mut digests = digest_file(file_path, &[self.algorithm])?;
+        Ok(digests.remove(0))
+    }
+
+    /// Primary and additional digests of a file, from a single read
+    pub fn hash_file_all(&self, file_path: &Path) -> Result<FileDigests> {
+        let mut algorithms = vec![self.algorithm];
+        algorithms.extend_from_slice(&self.additional);
+        let mut digests = digest_file(file_path, &algorithms)?;
+        let primary = digests.remove(0);
+        Ok(FileDigests {
+            algorithm: self.algorithm,
+            primary,
+            additional: self.additional.iter().copied().zip(digests).collect(),
+        })
+    }
+}
+
+/// Hex digests of a file under each of `algorithms`, in order, feeding one
+/// read pass into all hashers
+pub(crate) fn digest_file(file_path: &Path, algorithms: &[HashAlgorithm]) -> Result<Vec<String>> {
+    let mut file = File::open(file_path)
+        .with_context(|| format!("Failed to open file for hashing: {}", file_path.display()))?;
+
+    let mut hashers: Vec<Hasher> = algorithms.iter().map(|&algorithm| Hasher::new(algorithm)).collect();
+    let mut buffer = vec![0u8; 8192]; // 8KB buffer
+
+    loop {
+        let bytes_read = file.read(&mut buffer)
+            .with_context(|| format!("Failed to read file for hashing: {}", file_path.display()))?;
+
+        if bytes_read == 0 {
+            break;
+        }
+
+        for hasher in hashers.iter_mut() {
+            hasher.update(&buffer[..bytes_read]);
+        }
+    }
+
+    Ok(hashers.into_iter().map(Hasher::finalize_hex).collect())
+}
+
+impl Default for HashingService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
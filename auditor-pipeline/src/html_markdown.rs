@@ -0,0 +1,406 @@
+use crate::docx_markdown::render_table;
+
+/// Elements dropped with everything inside them: code, styling, navigation
+/// chrome and embedded media that carry no document text
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "head", "nav", "footer", "iframe", "svg", "canvas",
+    "form", "button", "select", "object", "embed",
+];
+
+/// Elements that start a new block of text
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p", "div", "section", "article", "main", "header", "aside", "address", "figure", "figcaption",
+    "dl", "dt", "dd", "hr", "center",
+];
+
+/// Decode HTML bytes, honouring a `charset` declared in the first kilobyte
+/// (meta tag or XML prolog) and falling back to UTF-8
+pub fn decode_html(data: &[u8]) -> String {
+    let head = String::from_utf8_lossy(&data[..data.len().min(1024)]).to_lowercase();
+    let label = head.find("charset=").map(|pos| {
+        head[pos + 8..]
+            .trim_start_matches(['"', '\''])
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':'))
+            .collect::<String>()
+    });
+    match label.and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes())) {
+        Some(encoding) => encoding.decode(data).0.into_owned(),
+        None => String::from_utf8_lossy(data).into_owned(),
+    }
+}
+
+/// Convert an HTML page to clean markdown.
+///
+/// Scripts, styles, navigation, forms and other boilerplate are dropped;
+/// headings, paragraphs, lists, tables, links, preformatted text and
+/// emphasis are kept. The parser is tolerant of unclosed tags.
+pub fn html_to_markdown(html: &str) -> String {
+    let mut converter = Converter::default();
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let Some(open) = rest.find('<') else {
+            converter.text(rest);
+            break;
+        };
+        converter.text(&rest[..open]);
+        rest = &rest[open..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+            continue;
+        }
+
+        // A '<' that doesn't open a tag is plain text
+        let Some((tag, close)) = rest.find('>').and_then(|close| Some((Tag::parse(&rest[1..close])?, close))) else {
+            converter.text("<");
+            rest = &rest[1..];
+            continue;
+        };
+        rest = &rest[close + 1..];
+
+        if !tag.closing && SKIPPED_ELEMENTS.contains(&tag.name.as_str()) && !tag.self_closing {
+            rest = match find_end_tag(rest, &tag.name) {
+                Some(end) => rest[end..].find('>').map_or("", |gt| &rest[end + gt + 1..]),
+                None => "",
+            };
+            continue;
+        }
+        converter.tag(&tag);
+    }
+
+    converter.finish()
+}
+
+/// Byte offset of the first `</name` in `text`, ignoring case
+fn find_end_tag(text: &str, name: &str) -> Option<usize> {
+    let mut from = 0;
+    while let Some(pos) = text[from..].find("</") {
+        let start = from + pos;
+        let candidate = text[start + 2..].as_bytes();
+        if candidate.len() >= name.len() && candidate[..name.len()].eq_ignore_ascii_case(name.as_bytes()) {
+            return Some(start);
+        }
+        from = start + 2;
+    }
+    None
+}
+
+struct Tag {
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    href: Option<String>,
+}
+
+impl Tag {
+    fn parse(inner: &str) -> Option<Self> {
+        let inner = inner.trim();
+        let (closing, inner) = match inner.strip_prefix('/') {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, inner),
+        };
+        let name: String = inner
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if name.is_empty() {
+            return None;
+        }
+        let href = if name == "a" { attribute_value(inner, "href") } else { None };
+        Some(Self {
+            name,
+            closing,
+            self_closing: inner.ends_with('/'),
+            href,
+        })
+    }
+
+    fn heading_level(&self) -> Option<usize> {
+        match self.name.as_str() {
+            "h1" => Some(1),
+            "h2" => Some(2),
+            "h3" => Some(3),
+            "h4" => Some(4),
+            "h5" => Some(5),
+            "h6" => Some(6),
+            _ => None,
+        }
+    }
+}
+
+fn attribute_value(inner: &str, name: &str) -> Option<String> {
+    let lower = inner.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(pos) = lower[from..].find(name) {
+        let start = from + pos;
+        from = start + name.len();
+        let preceded_by_space = lower[..start].ends_with(|c: char| c.is_whitespace());
+        let after = lower[from..].trim_start();
+        if !preceded_by_space || !after.starts_with('=') {
+            continue;
+        }
+        let value_start = inner.len() - after.len() + 1;
+        let value = inner[value_start..].trim_start();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or(""),
+            _ => value.split(|c: char| c.is_whitespace() || c == '>').next().unwrap_or(""),
+        };
+        return Some(decode_entities(value));
+    }
+    None
+}
+
+/// Decode named and numeric character references
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.char_indices().take(12).find(|(_, c)| *c == ';').map(|(i, _)| i) else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            "ndash" => Some('–'),
+            "mdash" => Some('—'),
+            "hellip" => Some('…'),
+            "copy" => Some('©'),
+            "reg" => Some('®'),
+            "euro" => Some('€'),
+            "pound" => Some('£'),
+            "lsquo" | "rsquo" => Some('\''),
+            "ldquo" | "rdquo" => Some('"'),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[derive(Default)]
+struct Converter {
+    blocks: Vec<String>,
+    line: String,
+    /// Heading level of the block being built
+    heading: Option<usize>,
+    /// `Some(counter)` for ordered lists, `None` for bullets
+    lists: Vec<Option<usize>>,
+    in_pre: bool,
+    in_blockquote: bool,
+    links: Vec<Option<String>>,
+    table: Option<Vec<Vec<String>>>,
+    cell: Option<String>,
+}
+
+impl Converter {
+    fn text(&mut self, raw: &str) {
+        let decoded = decode_entities(raw);
+        if self.in_pre {
+            self.line.push_str(&decoded);
+            return;
+        }
+        let target = match self.cell.as_mut() {
+            Some(cell) => cell,
+            None => &mut self.line,
+        };
+        for (index, word) in decoded.split_whitespace().enumerate() {
+            let glue = index > 0 || decoded.starts_with(char::is_whitespace);
+            if glue && !target.is_empty() && !target.ends_with(' ') {
+                target.push(' ');
+            }
+            target.push_str(word);
+        }
+        if decoded.ends_with(char::is_whitespace) && !target.is_empty() && !target.ends_with(' ') {
+            target.push(' ');
+        }
+    }
+
+    fn push_inline(&mut self, markup: &str) {
+        match self.cell.as_mut() {
+            Some(cell) => cell.push_str(markup),
+            None => self.line.push_str(markup),
+        }
+    }
+
+    fn flush(&mut self) {
+        let text = std::mem::take(&mut self.line);
+        let heading = self.heading.take();
+        if self.in_pre {
+            if !text.trim().is_empty() {
+                self.blocks.push(format!("```\n{}\n```", text.trim_matches('\n')));
+            }
+            return;
+        }
+        // Only the end is trimmed so nested list items keep their indent
+        let text = text.trim_end();
+        if text.trim_start().is_empty() {
+            return;
+        }
+        let block = match heading {
+            Some(level) => format!("{} {}", "#".repeat(level), text),
+            None => text.to_string(),
+        };
+        self.blocks.push(if self.in_blockquote {
+            format!("> {}", block)
+        } else {
+            block
+        });
+    }
+
+    fn tag(&mut self, tag: &Tag) {
+        if let Some(level) = tag.heading_level() {
+            self.flush();
+            if !tag.closing {
+                self.heading = Some(level);
+            }
+            return;
+        }
+
+        match (tag.name.as_str(), tag.closing) {
+            ("br", _) => {
+                if self.in_pre {
+                    self.line.push('\n');
+                } else {
+                    self.push_inline("  \n");
+                }
+            }
+            ("pre", false) => {
+                self.flush();
+                self.in_pre = true;
+            }
+            ("pre", true) => {
+                self.flush();
+                self.in_pre = false;
+            }
+            ("blockquote", closing) => {
+                self.flush();
+                self.in_blockquote = !closing;
+            }
+            ("ul", false) => {
+                self.flush();
+                self.lists.push(None);
+            }
+            ("ol", false) => {
+                self.flush();
+                self.lists.push(Some(0));
+            }
+            ("ul" | "ol", true) => {
+                self.flush();
+                self.lists.pop();
+            }
+            ("li", false) => {
+                self.flush();
+                let depth = self.lists.len().saturating_sub(1).min(8);
+                let marker = match self.lists.last_mut() {
+                    Some(Some(counter)) => {
+                        *counter += 1;
+                        format!("{}. ", counter)
+                    }
+                    _ => "- ".to_string(),
+                };
+                self.line = format!("{}{}", "  ".repeat(depth), marker);
+            }
+            ("li", true) => self.flush(),
+            ("strong" | "b", _) => self.push_inline("**"),
+            ("em" | "i", _) => self.push_inline("_"),
+            ("code", _) if !self.in_pre => self.push_inline("`"),
+            ("a", false) => self.links.push(tag.href.clone()),
+            ("a", true) => {
+                // Keep absolute link targets; anchors and relative links mean
+                // nothing outside the original site
+                if let Some(Some(href)) = self.links.pop() {
+                    if href.starts_with("http://") || href.starts_with("https://") || href.starts_with("mailto:") {
+                        self.push_inline(&format!(" ({})", href));
+                    }
+                }
+            }
+            ("table", false) => {
+                self.flush();
+                self.table = Some(Vec::new());
+            }
+            ("tr", false) => {
+                self.finish_cell();
+                if let Some(table) = self.table.as_mut() {
+                    table.push(Vec::new());
+                }
+            }
+            ("td" | "th", false) => {
+                self.finish_cell();
+                if self.table.is_some() {
+                    self.cell = Some(String::new());
+                }
+            }
+            ("td" | "th", true) => self.finish_cell(),
+            ("table", true) => {
+                self.finish_cell();
+                if let Some(table) = self.table.take() {
+                    let rows: Vec<Vec<String>> = table.into_iter().filter(|row| !row.is_empty()).collect();
+                    if let Some(rendered) = render_table(&rows) {
+                        self.blocks.push(rendered);
+                    }
+                }
+            }
+            (name, _) if BLOCK_ELEMENTS.contains(&name) => self.flush(),
+            _ => {}
+        }
+    }
+
+    fn finish_cell(&mut self) {
+        if let Some(cell) = self.cell.take() {
+            let text = cell.split_whitespace().collect::<Vec<_>>().join(" ");
+            match self.table.as_mut().and_then(|table| table.last_mut()) {
+                Some(row) => row.push(text),
+                None => {
+                    if let Some(table) = self.table.as_mut() {
+                        table.push(vec![text]);
+                    }
+                }
+            }
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.finish_cell();
+        if let Some(table) = self.table.take() {
+            if let Some(rendered) = render_table(&table) {
+                self.blocks.push(rendered);
+            }
+        }
+        self.flush();
+        self.blocks.join("\n\n")
+    }
+}
@@ -0,0 +1,201 @@
+use std::fmt::Write;
+
+/// Bars drawn per chart; the rest are summed into an "Other" bar
+const MAX_CHART_BARS: usize = 15;
+
+/// One cell of the file table
+pub(crate) enum HtmlCell {
+    Text(String),
+    /// A link shown as "Open", or nothing when there is no target
+    Link(Option<String>),
+}
+
+/// A bar chart of labelled values
+pub(crate) struct HtmlChart {
+    pub title: String,
+    pub bars: Vec<(String, u64)>,
+    /// Write the values as file sizes rather than counts
+    pub as_bytes: bool,
+}
+
+/// Contents of a self-contained HTML report: no scripts, styles or images
+/// are loaded from elsewhere, so it can be opened from an email attachment
+pub(crate) struct HtmlReport {
+    pub title: String,
+    pub totals: Vec<(String, String)>,
+    pub charts: Vec<HtmlChart>,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<HtmlCell>>,
+}
+
+const STYLE: &str = "
+body { font-family: Segoe UI, Arial, sans-serif; font-size: 13px; margin: 20px; color: #222; }
+h1 { font-size: 20px; } h2 { font-size: 16px; margin-top: 28px; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 3px 6px; text-align: left; vertical-align: top; }
+#files th { background: #eee; cursor: pointer; position: sticky; top: 0; white-space: nowrap; }
+#files th[data-order=asc]::after { content: ' \\25B2'; }
+#files th[data-order=desc]::after { content: ' \\25BC'; }
+#files td { max-width: 400px; overflow-wrap: anywhere; }
+.charts { display: flex; flex-wrap: wrap; gap: 24px; }
+.chart text { font-size: 12px; }
+#filter { margin-bottom: 8px; width: 300px; }
+";
+
+/// Sorts the file table on a header click and filters it on the search
+/// box, comparing numbers within text numerically
+const SCRIPT: &str = "
+const table = document.getElementById('files');
+const body = table.tBodies[0];
+table.querySelectorAll('th').forEach((th, col) => th.addEventListener('click', () => {
+  const ascending = th.dataset.order !== 'asc';
+  table.querySelectorAll('th').forEach(h => delete h.dataset.order);
+  th.dataset.order = ascending ? 'asc' : 'desc';
+  const rows = Array.from(body.rows);
+  rows.sort((a, b) => {
+    const order = a.cells[col].textContent.localeCompare(b.cells[col].textContent, undefined, { numeric: true });
+    return ascending ? order : -order;
+  });
+  rows.forEach(row => body.appendChild(row));
+}));
+document.getElementById('filter').addEventListener('input', event => {
+  const text = event.target.value.toLowerCase();
+  Array.from(body.rows).forEach(row => {
+    row.hidden = text !== '' && !row.textContent.toLowerCase().includes(text);
+  });
+});
+";
+
+/// The report as a single HTML page
+pub(crate) fn render(report: &HtmlReport) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        escape(&report.title),
+        STYLE,
+        escape(&report.title)
+    );
+
+    html.push_str("<h2>Summary</h2>\n<table>\n");
+    for (name, value) in &report.totals {
+        let _ = writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", escape(name), escape(value));
+    }
+    html.push_str("</table>\n<div class=\"charts\">\n");
+    for chart in &report.charts {
+        html.push_str(&render_chart(chart));
+    }
+    html.push_str("</div>\n");
+
+    let _ = write!(
+        html,
+        "<h2>Files ({})</h2>\n<input id=\"filter\" type=\"search\" placeholder=\"Filter files\">\n<table id=\"files\">\n<thead><tr>",
+        report.rows.len()
+    );
+    for header in &report.headers {
+        let _ = write!(html, "<th>{}</th>", escape(header));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for row in &report.rows {
+        html.push_str("<tr>");
+        for cell in row {
+            match cell {
+                HtmlCell::Text(text) => {
+                    let _ = write!(html, "<td>{}</td>", escape(text));
+                }
+                HtmlCell::Link(Some(href)) => {
+                    let _ = write!(html, "<td><a href=\"{}\">Open</a></td>", escape(href));
+                }
+                HtmlCell::Link(None) => html.push_str("<td></td>"),
+            }
+        }
+        html.push_str("</tr>\n");
+    }
+    let _ = write!(html, "</tbody>\n</table>\n<script>{}</script>\n</body>\n</html>\n", SCRIPT);
+    html
+}
+
+/// Horizontal bar chart as inline SVG, largest bar first
+fn render_chart(chart: &HtmlChart) -> String {
+    const LABEL_WIDTH: usize = 170;
+    const BAR_WIDTH: usize = 260;
+    const VALUE_WIDTH: usize = 90;
+    const ROW_HEIGHT: usize = 20;
+
+    let mut bars = chart.bars.clone();
+    bars.sort_by(|(a_label, a), (b_label, b)| b.cmp(a).then_with(|| a_label.cmp(b_label)));
+    if bars.len() > MAX_CHART_BARS {
+        let other: u64 = bars.drain(MAX_CHART_BARS - 1..).map(|(_, value)| value).sum();
+        bars.push(("Other".to_string(), other));
+    }
+    let largest = bars.iter().map(|(_, value)| *value).max().unwrap_or(0).max(1);
+    let width = LABEL_WIDTH + BAR_WIDTH + VALUE_WIDTH;
+    let height = (bars.len() * ROW_HEIGHT).max(ROW_HEIGHT);
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        "<div><h2>{}</h2>\n<svg class=\"chart\" width=\"{}\" height=\"{}\" role=\"img\">\n",
+        escape(&chart.title),
+        width,
+        height
+    );
+    for (index, (label, value)) in bars.iter().enumerate() {
+        let y = index * ROW_HEIGHT;
+        let bar = (*value as f64 / largest as f64 * BAR_WIDTH as f64).round().max(1.0) as usize;
+        let shown = if chart.as_bytes {
+            crate::report_model::format_file_size(*value)
+        } else {
+            value.to_string()
+        };
+        let _ = write!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>\
+             <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#4472c4\"></rect>\
+             <text x=\"{}\" y=\"{}\">{}</text>\n",
+            LABEL_WIDTH - 6,
+            y + 14,
+            escape(label),
+            LABEL_WIDTH,
+            y + 3,
+            bar,
+            ROW_HEIGHT - 6,
+            LABEL_WIDTH + bar + 4,
+            y + 14,
+            escape(&shown)
+        );
+    }
+    svg.push_str("</svg></div>\n");
+    svg
+}
+
+/// `href` of the file at `path`, absolute or relative to the report;
+/// characters that end or escape a URL path are percent-encoded
+pub(crate) fn file_href(path: &str, absolute: bool) -> String {
+    let mut href = String::from(if absolute { "file:///" } else { "" });
+    for c in path.trim_start_matches('/').chars() {
+        match c {
+            '\\' => href.push('/'),
+            ' ' | '"' | '#' | '%' | '?' | '<' | '>' => {
+                let _ = write!(href, "%{:02X}", c as u32);
+            }
+            _ => href.push(c),
+        }
+    }
+    href
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
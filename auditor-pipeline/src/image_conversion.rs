@@ -0,0 +1,290 @@
+use crate::ept_logger::EPTLogger;
+use crate::long_paths;
+use anyhow::{bail, Context, Result};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Image formats the converter accepts
+pub const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "tif", "tiff", "heic", "heif"];
+
+/// Resolution assumed when sizing the PDF page of a wrapped image
+const ASSUMED_DPI: f64 = 150.0;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// Turns photographed or scanned images into something an LLM can read:
+/// OCR text through Tesseract, or a single-page PDF.
+///
+/// JPEGs and non-interlaced PNGs without transparency are wrapped into a PDF
+/// in-process without re-encoding; TIFF, HEIC and other PNGs go through
+/// ImageMagick. HEIC images are also converted with ImageMagick before OCR,
+/// since Tesseract can't read them.
+pub struct ImageConverter {
+    logger: EPTLogger,
+}
+
+impl ImageConverter {
+    pub fn new(logger: EPTLogger) -> Self {
+        Self { logger }
+    }
+
+    /// Recognise the text of an image with Tesseract
+    pub fn ocr(&self, image_path: &Path, language: &str) -> Result<String> {
+        let tesseract = self
+            .find_tool("EPT_TESSERACT_PATH", &["tesseract"])
+            .context("OCR needs Tesseract (install it or set EPT_TESSERACT_PATH)")?;
+
+        // Tesseract can't read HEIC; hand it a PNG copy instead
+        let readable = if is_heic(image_path) {
+            let png_path = image_path.with_extension("ocr.png");
+            self.imagemagick_convert(image_path, &png_path)?;
+            Some(png_path)
+        } else {
+            None
+        };
+        let input = readable.as_deref().unwrap_or(image_path);
+
+        let output = Command::new(&tesseract)
+            .arg(long_paths::simplified(input))
+            .arg("stdout")
+            .arg("-l")
+            .arg(language)
+            .output()
+            .context("Failed to execute Tesseract");
+        if let Some(png_path) = &readable {
+            let _ = std::fs::remove_file(png_path);
+        }
+        let output = output?;
+        if !output.status.success() {
+            bail!("Tesseract failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Write the image as a single-page PDF
+    pub fn wrap_in_pdf(&self, image_path: &Path, output_path: &Path) -> Result<()> {
+        let data = std::fs::read(image_path)
+            .with_context(|| format!("Failed to read image: {}", image_path.display()))?;
+
+        let native = if data.starts_with(&[0xFF, 0xD8]) {
+            jpeg_image(&data)
+        } else if data.starts_with(&PNG_SIGNATURE) {
+            png_image(&data)
+        } else {
+            None
+        };
+
+        match native {
+            Some(image) => {
+                std::fs::write(output_path, single_image_pdf(&image))
+                    .with_context(|| format!("Failed to write PDF: {}", output_path.display()))?;
+                Ok(())
+            }
+            None => {
+                self.logger.debug(&format!(
+                    "Wrapping {} in a PDF with ImageMagick",
+                    image_path.display()
+                ));
+                self.imagemagick_convert(image_path, output_path)
+            }
+        }
+    }
+
+    fn imagemagick_convert(&self, input: &Path, output: &Path) -> Result<()> {
+        let candidates: &[&str] = if cfg!(target_os = "windows") {
+            &["magick.exe"]
+        } else {
+            &["magick", "convert"]
+        };
+        let magick = self
+            .find_tool("EPT_MAGICK_PATH", candidates)
+            .context("Converting this image needs ImageMagick (install it or set EPT_MAGICK_PATH)")?;
+
+        // `[0]` selects the first frame of multi-page TIFFs
+        let result = Command::new(&magick)
+            .arg(format!("{}[0]", long_paths::simplified(input).display()))
+            .arg(long_paths::simplified(output))
+            .output()
+            .context("Failed to execute ImageMagick")?;
+        if !result.status.success() || !output.exists() {
+            bail!("ImageMagick failed: {}", String::from_utf8_lossy(&result.stderr).trim());
+        }
+        Ok(())
+    }
+
+    fn find_tool(&self, env_var: &str, candidates: &[&str]) -> Option<PathBuf> {
+        if let Ok(env_path) = std::env::var(env_var) {
+            let path = PathBuf::from(&env_path);
+            if path.exists() {
+                return Some(path);
+            }
+            self.logger.warning(&format!("{} is set to {}, but file does not exist", env_var, env_path));
+        }
+        candidates.iter().find_map(|candidate| which::which(candidate).ok())
+    }
+}
+
+fn is_heic(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("heic") || e.eq_ignore_ascii_case("heif"))
+}
+
+/// Image data that can be embedded in a PDF as-is
+struct EmbeddableImage {
+    width: u32,
+    height: u32,
+    /// Entries of the image XObject dictionary besides size and length
+    dictionary: String,
+    data: Vec<u8>,
+}
+
+/// A baseline or progressive JPEG, embedded with `DCTDecode`
+fn jpeg_image(data: &[u8]) -> Option<EmbeddableImage> {
+    let mut position = 2;
+    let mut adobe = false;
+    while position + 4 <= data.len() {
+        if data[position] != 0xFF {
+            return None;
+        }
+        let marker = data[position + 1];
+        let length = u16::from_be_bytes([data[position + 2], data[position + 3]]) as usize;
+        let segment = data.get(position + 4..position + 2 + length)?;
+        match marker {
+            0xEE if segment.starts_with(b"Adobe") => adobe = true,
+            // Start-of-frame markers; C4, C8 and CC are other segment types
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                let height = u16::from_be_bytes([*segment.get(1)?, *segment.get(2)?]) as u32;
+                let width = u16::from_be_bytes([*segment.get(3)?, *segment.get(4)?]) as u32;
+                let color_space = match *segment.get(5)? {
+                    1 => "/DeviceGray",
+                    3 => "/DeviceRGB",
+                    // Adobe writes CMYK JPEGs inverted
+                    4 if adobe => "/DeviceCMYK /Decode [1 0 1 0 1 0 1 0]",
+                    4 => "/DeviceCMYK",
+                    _ => return None,
+                };
+                return Some(EmbeddableImage {
+                    width,
+                    height,
+                    dictionary: format!("/ColorSpace {} /BitsPerComponent 8 /Filter /DCTDecode", color_space),
+                    data: data.to_vec(),
+                });
+            }
+            _ => {}
+        }
+        position += 2 + length;
+    }
+    None
+}
+
+/// A non-interlaced grey, RGB or palette PNG. Its zlib data is embedded
+/// with the PNG predictor, so nothing is decoded; images with an alpha
+/// channel or interlacing return `None`.
+fn png_image(data: &[u8]) -> Option<EmbeddableImage> {
+    let mut position = PNG_SIGNATURE.len();
+    let mut header = None;
+    let mut palette = None;
+    let mut image_data = Vec::new();
+
+    while position + 8 <= data.len() {
+        let length = u32::from_be_bytes(data[position..position + 4].try_into().ok()?) as usize;
+        let kind = &data[position + 4..position + 8];
+        let chunk = data.get(position + 8..position + 8 + length)?;
+        match kind {
+            b"IHDR" if chunk.len() >= 13 => header = Some(chunk.to_vec()),
+            b"PLTE" => palette = Some(chunk.to_vec()),
+            b"IDAT" => image_data.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ => {}
+        }
+        // Chunk data is followed by a 4-byte CRC
+        position += 12 + length;
+    }
+
+    let header = header?;
+    let width = u32::from_be_bytes(header[0..4].try_into().ok()?);
+    let height = u32::from_be_bytes(header[4..8].try_into().ok()?);
+    let (bit_depth, color_type, interlace) = (header[8], header[9], header[12]);
+    if interlace != 0 || image_data.is_empty() {
+        return None;
+    }
+
+    let (color_space, colors) = match color_type {
+        0 => ("/DeviceGray".to_string(), 1),
+        2 => ("/DeviceRGB".to_string(), 3),
+        3 => {
+            let palette = palette?;
+            let hex: String = palette.iter().map(|b| format!("{:02X}", b)).collect();
+            (format!("[/Indexed /DeviceRGB {} <{}>]", (palette.len() / 3).checked_sub(1)?, hex), 1)
+        }
+        _ => return None,
+    };
+    Some(EmbeddableImage {
+        width,
+        height,
+        dictionary: format!(
+            "/ColorSpace {} /BitsPerComponent {} /Filter /FlateDecode \
+             /DecodeParms << /Predictor 15 /Colors {} /BitsPerComponent {} /Columns {} >>",
+            color_space, bit_depth, colors, bit_depth, width
+        ),
+        data: image_data,
+    })
+}
+
+/// A one-page PDF showing `image` at `ASSUMED_DPI`
+fn single_image_pdf(image: &EmbeddableImage) -> Vec<u8> {
+    let page_width = image.width as f64 * 72.0 / ASSUMED_DPI;
+    let page_height = image.height as f64 * 72.0 / ASSUMED_DPI;
+    let content = format!("q {:.2} 0 0 {:.2} 0 0 cm /Im0 Do Q", page_width, page_height);
+
+    let mut pdf: Vec<u8> = b"%PDF-1.5\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = Vec::new();
+    let mut object = |pdf: &mut Vec<u8>, body: &[u8]| {
+        offsets.push(pdf.len());
+        let _ = writeln!(pdf, "{} 0 obj", offsets.len());
+        pdf.extend_from_slice(body);
+        pdf.extend_from_slice(b"\nendobj\n");
+    };
+
+    object(&mut pdf, b"<< /Type /Catalog /Pages 2 0 R >>");
+    object(&mut pdf, b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>");
+    object(
+        &mut pdf,
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+             /Resources << /XObject << /Im0 5 0 R >> >> /Contents 4 0 R >>",
+            page_width, page_height
+        )
+        .as_bytes(),
+    );
+    object(
+        &mut pdf,
+        format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content).as_bytes(),
+    );
+    let mut image_object = format!(
+        "<< /Type /XObject /Subtype /Image /Width {} /Height {} {} /Length {} >>\nstream\n",
+        image.width,
+        image.height,
+        image.dictionary,
+        image.data.len()
+    )
+    .into_bytes();
+    image_object.extend_from_slice(&image.data);
+    image_object.extend_from_slice(b"\nendstream");
+    object(&mut pdf, &image_object);
+
+    let xref_offset = pdf.len();
+    let _ = writeln!(pdf, "xref\n0 {}\n0000000000 65535 f ", offsets.len() + 1);
+    for offset in &offsets {
+        let _ = writeln!(pdf, "{:010} 00000 n ", offset);
+    }
+    let _ = writeln!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF",
+        offsets.len() + 1,
+        xref_offset
+    );
+    pdf
+}
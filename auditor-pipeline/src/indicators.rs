@@ -0,0 +1,190 @@
+use crate::hashing_service::{digest_file, FileDigests, HashAlgorithm};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Digest algorithms recognised in an indicator list, by hex length
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndicatorHash {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl IndicatorHash {
+    fn from_hex_len(len: usize) -> Option<Self> {
+        match len {
+            32 => Some(IndicatorHash::Md5),
+            40 => Some(IndicatorHash::Sha1),
+            64 => Some(IndicatorHash::Sha256),
+            128 => Some(IndicatorHash::Sha512),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            IndicatorHash::Md5 => "MD5",
+            IndicatorHash::Sha1 => "SHA-1",
+            IndicatorHash::Sha256 => "SHA-256",
+            IndicatorHash::Sha512 => "SHA-512",
+        }
+    }
+
+    /// The hashing algorithm that produces this kind of digest
+    pub fn algorithm(&self) -> HashAlgorithm {
+        match self {
+            IndicatorHash::Md5 => HashAlgorithm::Md5,
+            IndicatorHash::Sha1 => HashAlgorithm::Sha1,
+            IndicatorHash::Sha256 => HashAlgorithm::Sha256,
+            IndicatorHash::Sha512 => HashAlgorithm::Sha512,
+        }
+    }
+}
+
+/// One known-bad hash from the list
+#[derive(Debug, Clone)]
+pub struct Indicator {
+    pub algorithm: IndicatorHash,
+    pub hash: String,
+    /// Remaining columns of the CSV row (threat name, source, ...)
+    pub description: String,
+}
+
+impl Indicator {
+    /// Algorithm and hash, followed by the description when there is one
+    pub fn describe(&self) -> String {
+        if self.description.is_empty() {
+            format!("{} {}", self.algorithm.label(), self.hash)
+        } else {
+            format!("{} {} ({})", self.algorithm.label(), self.hash, self.description)
+        }
+    }
+}
+
+/// Known-bad hashes loaded from a local CSV, for air-gapped checks.
+///
+/// Any column holding a 32/40/64/128-character hex string is taken as an
+/// MD5/SHA-1/SHA-256/SHA-512 hash; the other non-empty columns of the row
+/// become its description. Rows without a hash (headers, comments) are skipped.
+/// Known-file lists (NSRL RDS subsets) are read the same way.
+pub struct IndicatorList {
+    indicators: HashMap<(IndicatorHash, String), Indicator>,
+    /// Digest kinds the list contains
+    kinds: Vec<IndicatorHash>,
+}
+
+impl IndicatorList {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read(path)
+            .with_context(|| format!("Failed to read indicator list: {}", path.display()))?;
+        let content = String::from_utf8_lossy(&content);
+
+        let mut indicators = HashMap::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = split_csv_line(line);
+            let Some(hash_index) = fields.iter().position(|field| {
+                IndicatorHash::from_hex_len(field.len()).is_some()
+                    && field.chars().all(|c| c.is_ascii_hexdigit())
+            }) else {
+                continue;
+            };
+
+            let hash = fields[hash_index].to_lowercase();
+            let algorithm = IndicatorHash::from_hex_len(hash.len()).expect("length checked above");
+            let description = fields
+                .iter()
+                .enumerate()
+                .filter(|(index, field)| *index != hash_index && !field.is_empty())
+                .map(|(_, field)| field.as_str())
+                .collect::<Vec<_>>()
+                .join(" / ");
+            indicators.insert(
+                (algorithm, hash.clone()),
+                Indicator {
+                    algorithm,
+                    hash,
+                    description,
+                },
+            );
+        }
+
+        let kinds = [IndicatorHash::Md5, IndicatorHash::Sha1, IndicatorHash::Sha256, IndicatorHash::Sha512]
+            .into_iter()
+            .filter(|&kind| indicators.keys().any(|(k, _)| *k == kind))
+            .collect();
+        Ok(Self { indicators, kinds })
+    }
+
+    pub fn len(&self) -> usize {
+        self.indicators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indicators.is_empty()
+    }
+
+    /// Algorithms of the digests the list contains, so they can be taken
+    /// in the same pass as the file hashes
+    pub fn algorithms(&self) -> Vec<HashAlgorithm> {
+        self.kinds.iter().map(|kind| kind.algorithm()).collect()
+    }
+
+    /// Check a file against the list, reusing the `known` digests the caller
+    /// already has. Only digests the list contains and `known` lacks are
+    /// computed.
+    pub fn lookup_file(&self, path: &Path, known: Option<&FileDigests>) -> Result<Option<&Indicator>> {
+        let mut missing = Vec::new();
+        for &kind in &self.kinds {
+            match known.and_then(|digests| digests.get(kind.algorithm())) {
+                Some(hash) => {
+                    if let Some(indicator) = self.get(kind, hash) {
+                        return Ok(Some(indicator));
+                    }
+                }
+                None => missing.push(kind),
+            }
+        }
+        if missing.is_empty() {
+            return Ok(None);
+        }
+
+        let algorithms: Vec<HashAlgorithm> = missing.iter().map(|kind| kind.algorithm()).collect();
+        let hashes = digest_file(path, &algorithms)
+            .with_context(|| format!("Failed to hash file for indicator check: {}", path.display()))?;
+        Ok(missing
+            .into_iter()
+            .zip(hashes)
+            .find_map(|(kind, hash)| self.get(kind, &hash)))
+    }
+
+    fn get(&self, algorithm: IndicatorHash, hash: &str) -> Option<&Indicator> {
+        self.indicators.get(&(algorithm, hash.to_lowercase()))
+    }
+}
+
+/// Split one CSV line, honouring double quotes (`""` inside quotes is a quote)
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' | ';' | '\t' if !in_quotes => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
@@ -0,0 +1,66 @@
+// File conversion pipeline: decompression, scanning, conversion, hashing,
+// LLM export and report generation.
+//
+// This crate has no Tauri dependency. Frontends observe a run by handing the
+// pipeline an `EventSink` (see `events`).
+
+pub mod archive_names;
+pub mod checksums;
+pub mod chunking;
+pub mod conversion_cache;
+pub mod conversion_engine;
+pub mod corpus_stats;
+pub mod csv_normalizer;
+pub mod decompression_engine;
+pub mod docx_markdown;
+pub mod duplicates;
+pub mod email_message;
+pub mod email_threads;
+pub mod engagement;
+pub mod engineering_formats;
+pub mod excel_controls;
+pub mod excel_hidden;
+pub mod ept_logger;
+pub mod events;
+pub mod extraction_limits;
+pub mod file_ownership;
+pub mod file_scanner;
+pub mod file_signatures;
+pub mod folder_naming;
+pub mod hashing_service;
+pub mod html_markdown;
+pub mod html_report;
+pub mod image_conversion;
+pub mod image_metadata;
+pub mod indicators;
+pub mod llm_export_engine;
+pub mod long_paths;
+pub mod manifest;
+pub mod office_encryption;
+pub mod office_properties;
+pub mod output_checks;
+pub mod path_filter;
+pub mod pdf_attachments;
+pub mod pdf_document;
+pub mod pdf_text;
+pub mod pptx_markdown;
+pub mod process_controller;
+pub mod remediation;
+pub mod report_model;
+pub mod report_signing;
+pub mod report_writer;
+pub mod retention;
+pub mod run_metadata;
+pub mod run_state;
+pub mod run_summary;
+pub mod scan_limits;
+pub mod search_index;
+pub mod secure_delete;
+pub mod settings;
+pub mod summarization;
+pub mod system_files;
+pub mod tagging;
+pub mod tar_reader;
+pub mod vba_macros;
+pub mod verification;
+pub mod workspace;
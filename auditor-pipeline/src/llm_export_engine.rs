@@ -0,0 +1,816 @@
+use crate::chunking::{self, ChunkSettings};
+use crate::corpus_stats;
+use crate::ept_logger::EPTLogger;
+use crate::hashing_service::{HashAlgorithm, HashingService};
+use crate::report_model::{FailureStage, FileStatus, ReportModel};
+use crate::run_summary::ExportStats;
+use crate::tagging::TaggingEngine;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Export group of files that sit directly in the input folder
+pub const ROOT_EXPORT_GROUP: &str = "_root_files";
+
+/// Folder of the export the combined corpus files are written to
+pub const CORPUS_FOLDER_NAME: &str = "_corpus";
+
+/// How an exported file is renamed when its name is already taken in the
+/// export folder
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportNaming {
+    /// `name_1.ext`, `name_2.ext`, … in the order files are exported
+    #[default]
+    Counter,
+    /// `name_<first 8 hex digits of the hash>.ext`, so the same input gets
+    /// the same names in every run
+    HashSuffix,
+}
+
+/// Hex digits of the file hash appended under `ExportNaming::HashSuffix`
+const HASH_SUFFIX_LEN: usize = 8;
+
+/// Exported formats given YAML front matter; other formats would no longer
+/// parse with it
+const FRONT_MATTER_EXTENSIONS: &[&str] = &["md", "txt"];
+
+/// Folder of the export that files beyond the export size cap are moved to
+pub const OVERFLOW_FOLDER_NAME: &str = "_overflow";
+
+/// Order in which files claim room under the export size cap
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportPriority {
+    /// In the order the files were scanned
+    #[default]
+    InputOrder,
+    /// Most recently modified first
+    NewestFirst,
+    OldestFirst,
+    /// Smallest first, so the most files fit
+    SmallestFirst,
+}
+
+/// Limit on the total size of the export, for destinations that cap
+/// uploads. Files that don't fit go to `_overflow` instead.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportSizeCap {
+    /// Most bytes kept in the export; 0 disables the cap
+    pub max_total_bytes: u64,
+    pub priority: ExportPriority,
+    /// Original file types kept ahead of all others, in this order, e.g.
+    /// `["xlsx", "pdf"]`; `priority` orders files within each type
+    pub preferred_extensions: Vec<String>,
+}
+
+/// File the JSONL export is written to in the export folder
+pub const JSONL_FILE_NAME: &str = "documents.jsonl";
+
+/// One line of the JSONL export: an exported document with its provenance
+#[derive(Debug, Serialize)]
+struct JsonlDocument<'a> {
+    /// Original path relative to the input folder
+    path: &'a str,
+    /// Hash of the original file, under the run's hash algorithm
+    hash: Option<&'a str>,
+    hash_algorithm: &'static str,
+    /// Media type of the exported text's format
+    mime: &'static str,
+    exported_file: &'a str,
+    /// Position among the parts of a split document, counting from 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    part: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parts: Option<usize>,
+    text: String,
+}
+
+/// Every exported text and markdown document concatenated into one or a
+/// few corpus files, for LLM tools that take a single input file. The loose
+/// exported files are kept.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CombinedCorpusSettings {
+    pub enabled: bool,
+    /// Start a new corpus file before one grows beyond this many bytes; a
+    /// document larger than that gets a file of its own. 0 writes a single
+    /// file.
+    pub max_file_bytes: u64,
+}
+
+pub struct LLMExportEngine {
+    logger: EPTLogger,
+    hashing_service: HashingService,
+    naming: ExportNaming,
+}
+
+impl LLMExportEngine {
+    pub fn new(logger: EPTLogger, hash_algorithm: HashAlgorithm) -> Self {
+        let hashing_service = HashingService::with_algorithm(hash_algorithm);
+        Self {
+            logger,
+            hashing_service,
+            naming: ExportNaming::default(),
+        }
+    }
+
+    /// Rename files whose name is taken in the export by `naming`
+    pub fn with_naming(mut self, naming: ExportNaming) -> Self {
+        self.naming = naming;
+        self
+    }
+
+    /// Copy every exportable entry into `output_path`, skipping exact
+    /// duplicates. `on_progress` gets (entries done, exportable entries,
+    /// counts so far) before each entry and once at the end.
+    pub fn copy_llm_readable_files<F>(
+        &self,
+        files: &mut [ReportModel],
+        root_path: &Path,
+        output_path: &Path,
+        mut on_progress: F,
+    ) -> Result<ExportStats>
+    where
+        F: FnMut(usize, usize, &ExportStats),
+    {
+        self.logger.debug(&format!(
+            "Starting LLM export to: {}",
+            output_path.display()
+        ));
+
+        // Create output directory
+        fs::create_dir_all(output_path)
+            .with_context(|| format!("Failed to create output directory: {}", output_path.display()))?;
+
+        // Canonicalize root path for security validation
+        let root_path_canonical = root_path.canonicalize()
+            .context("Failed to canonicalize root path")?;
+        
+        // Track seen hashes for deduplication
+        let mut seen_hashes: HashMap<String, PathBuf> = HashMap::new();
+        let mut stats = ExportStats::default();
+
+        // Skip files that weren't processed or were skipped
+        let total = files.iter().filter(|entry| entry.status.is_exportable()).count();
+        let exportable = files.iter_mut().filter(|entry| entry.status.is_exportable());
+        for (done, file_entry) in exportable.enumerate() {
+            on_progress(done, total, &stats);
+
+            // SECURITY: Safely resolve relative paths and validate they stay within root directory
+            let source_path = match self.safe_resolve_path(root_path, &root_path_canonical, &file_entry.relative_path) {
+                Some(path) => path,
+                None => {
+                    self.logger.warning(&format!(
+                        "SECURITY: Skipping file with invalid path: {}",
+                        file_entry.relative_path
+                    ));
+                    continue;
+                }
+            };
+
+            if !source_path.exists() {
+                self.logger.warning(&format!(
+                    "Source file does not exist: {}",
+                    source_path.display()
+                ));
+                continue;
+            }
+
+            // Check if file is LLM-readable or was converted
+            if !self.is_llm_readable(&source_path, file_entry) {
+                continue;
+            }
+
+            // Get hash for deduplication (of the artifact actually exported)
+            let known_hash = if file_entry.is_converted() {
+                &file_entry.converted_hash
+            } else {
+                &file_entry.original_hash
+            };
+            let hash = if let Some(hash) = known_hash {
+                hash.clone()
+            } else {
+                // Hash the file if not already hashed
+                match self.hashing_service.hash_file(&source_path) {
+                    Ok(h) => h,
+                    Err(e) => {
+                        self.logger.warning(&format!(
+                            "Failed to hash file {}: {}",
+                            source_path.display(),
+                            e
+                        ));
+                        continue;
+                    }
+                }
+            };
+
+            // Check for duplicates
+            if let Some(existing_path) = seen_hashes.get(&hash) {
+                self.logger.info(&format!(
+                    "Skipping duplicate (hash {}): {} (already copied as {})",
+                    &hash[..16],
+                    source_path.display(),
+                    existing_path.display()
+                ));
+                file_entry.status = FileStatus::SkippedDuplicate;
+                file_entry.skip_reason = Some(format!(
+                    "Duplicate of exported file {}",
+                    existing_path.file_name().and_then(|n| n.to_str()).unwrap_or("unknown")
+                ));
+                stats.duplicates_skipped += 1;
+                continue;
+            }
+
+            // Determine output filename
+            // For converted files, use the converted filename
+            // For others, use original filename
+            let output_filename = if file_entry.is_converted() {
+                file_entry.file_name.clone()
+            } else {
+                // Ensure unique filename in flat structure
+                let base_name = source_path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("unknown");
+                
+                let stem = source_path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("file");
+                let ext = source_path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .unwrap_or("");
+                let with_suffix = |suffix: &str| {
+                    if ext.is_empty() {
+                        format!("{}_{}", stem, suffix)
+                    } else {
+                        format!("{}_{}.{}", stem, suffix, ext)
+                    }
+                };
+
+                // If filename already exists, add the hash prefix or a counter
+                let mut final_name = base_name.to_string();
+                if self.naming == ExportNaming::HashSuffix && output_path.join(&final_name).exists() {
+                    final_name = with_suffix(&hash[..HASH_SUFFIX_LEN.min(hash.len())].to_lowercase());
+                }
+                let mut counter = 1;
+                while output_path.join(&final_name).exists() {
+                    final_name = with_suffix(&counter.to_string());
+                    counter += 1;
+                }
+                final_name
+            };
+
+            let dest_path = output_path.join(&output_filename);
+
+            // Copy the file
+            match fs::copy(&source_path, &dest_path) {
+                Ok(bytes) => {
+                    stats.bytes_written += bytes;
+                    // Show relative paths in log
+                    let source_relative = source_path.strip_prefix(root_path)
+                        .unwrap_or(&source_path)
+                        .display();
+                    let dest_relative = dest_path.strip_prefix(output_path)
+                        .unwrap_or(&dest_path)
+                        .display();
+                    self.logger.debug(&format!(
+                        "Copied: {} -> {}",
+                        source_relative,
+                        dest_relative
+                    ));
+                    seen_hashes.insert(hash, dest_path.clone());
+                    file_entry.exported_file_name = Some(output_filename.clone());
+                    stats.copied_count += 1;
+                }
+                Err(e) => {
+                    self.logger.error(&format!(
+                        "Failed to copy {}: {}",
+                        source_path.display(),
+                        e
+                    ));
+                    file_entry.status = FileStatus::Failed { stage: FailureStage::Export };
+                    file_entry.skip_reason = Some(format!("Export failed: {}", e));
+                    stats.failed_count += 1;
+                }
+            }
+        }
+        on_progress(total, total, &stats);
+
+        self.logger.info(&format!(
+            "LLM export complete: {} files copied, {} duplicates skipped, {} failed",
+            stats.copied_count,
+            stats.duplicates_skipped,
+            stats.failed_count
+        ));
+
+        Ok(stats)
+    }
+
+    /// Export into one subfolder per top-level input folder (typically one
+    /// per custodian), so each can be handed to a different reviewer.
+    ///
+    /// Deduplication runs per group: a document held by two custodians is
+    /// exported for both. `exported_file_name` becomes `<group>/<name>`.
+    pub fn copy_by_top_level_folder<F>(
+        &self,
+        files: &mut [ReportModel],
+        root_path: &Path,
+        output_path: &Path,
+        mut on_progress: F,
+    ) -> Result<ExportStats>
+    where
+        F: FnMut(usize, usize, &ExportStats),
+    {
+        let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+        for (index, entry) in files.iter_mut().enumerate() {
+            let group = Self::top_level_folder(&entry.original_relative_path);
+            entry.export_group = Some(group.clone());
+            match groups.iter_mut().find(|(name, _)| *name == group) {
+                Some((_, members)) => members.push(index),
+                None => groups.push((group, vec![index])),
+            }
+        }
+
+        // Progress is reported across all groups
+        let total = files.iter().filter(|entry| entry.status.is_exportable()).count();
+        let mut done_before = 0;
+        let mut totals = ExportStats::default();
+        for (group, members) in groups {
+            self.logger.info(&format!("Exporting {} file(s) for {}", members.len(), group));
+            let mut group_entries: Vec<ReportModel> = members.iter().map(|&i| files[i].clone()).collect();
+            let group_total = group_entries.iter().filter(|entry| entry.status.is_exportable()).count();
+            let stats = self.copy_llm_readable_files(
+                &mut group_entries,
+                root_path,
+                &output_path.join(&group),
+                |done, _, stats| {
+                    let mut running = totals.clone();
+                    running.add(stats);
+                    on_progress(done_before + done, total, &running);
+                },
+            )?;
+            done_before += group_total;
+            for (&index, mut entry) in members.iter().zip(group_entries) {
+                entry.exported_file_name = entry
+                    .exported_file_name
+                    .map(|name| format!("{}/{}", group, name));
+                files[index] = entry;
+            }
+            totals.add(&stats);
+        }
+        Ok(totals)
+    }
+
+    /// Split exported text documents estimated above `settings.max_tokens`
+    /// into numbered parts (`<name>_part001.md`, …) beside them, replacing
+    /// the document, and list the parts on its entry. CSV and TSV parts
+    /// each start with the header row. Returns the number of documents split.
+    pub fn chunk_exports(
+        &self,
+        files: &mut [ReportModel],
+        output_path: &Path,
+        settings: &ChunkSettings,
+    ) -> Result<usize> {
+        let mut chunked = 0;
+        for entry in files.iter_mut() {
+            let Some(exported_file_name) = entry.exported_file_name.clone() else {
+                continue;
+            };
+            let path = output_path.join(&exported_file_name);
+            if !chunking::is_chunkable(&path) {
+                continue;
+            }
+            let text = match fs::read(&path).map(String::from_utf8) {
+                Ok(Ok(text)) => text,
+                // Not UTF-8: splitting could cut a character in half
+                Ok(Err(_)) => continue,
+                Err(e) => {
+                    self.logger.warning(&format!("Failed to read {} for splitting: {}", path.display(), e));
+                    continue;
+                }
+            };
+            if corpus_stats::estimate_tokens(&text) <= settings.max_tokens {
+                continue;
+            }
+
+            let header = chunking::repeats_header(&path)
+                .then(|| text.split_inclusive('\n').next())
+                .flatten();
+            let parts = chunking::split_text(&text, settings);
+            let stem = Path::new(&exported_file_name)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("file");
+            let extension = Path::new(&exported_file_name)
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| format!(".{}", e))
+                .unwrap_or_default();
+            // Parts stay in the document's export group folder
+            let folder = exported_file_name.rsplit_once('/').map(|(folder, _)| format!("{}/", folder));
+            let part_names: Vec<String> = (1..=parts.len())
+                .map(|number| format!("{}{}_part{:03}{}", folder.as_deref().unwrap_or(""), stem, number, extension))
+                .collect();
+            if let Some(taken) = part_names.iter().find(|name| output_path.join(name).exists()) {
+                self.logger.warning(&format!(
+                    "Not splitting {}: {} already exists in the export",
+                    exported_file_name, taken
+                ));
+                continue;
+            }
+
+            for (index, (part, name)) in parts.iter().zip(&part_names).enumerate() {
+                let part_path = output_path.join(name);
+                let content = match header {
+                    Some(header) if index > 0 && !part.starts_with(header) => format!("{}{}", header, part),
+                    _ => part.to_string(),
+                };
+                fs::write(&part_path, content)
+                    .with_context(|| format!("Failed to write document part: {}", part_path.display()))?;
+            }
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to replace split document: {}", path.display()))?;
+            self.logger.info(&format!("Split {} into {} parts", exported_file_name, part_names.len()));
+            entry.export_parts = part_names;
+            chunked += 1;
+        }
+        Ok(chunked)
+    }
+
+    /// Prepend YAML front matter with the provenance of the original
+    /// evidence (path, file name, hash, modified date, archive and parent
+    /// document) to every exported markdown and text file of `files`, so it
+    /// travels with the document into the LLM. Each part of a split document
+    /// gets it too. Returns the number of files changed.
+    pub fn add_front_matter(&self, files: &[ReportModel], output_path: &Path) -> Result<usize> {
+        let hash_key = self.hashing_service.algorithm().label().to_lowercase().replace('-', "");
+        let mut changed = 0;
+        for entry in files {
+            let parts = entry.exported_files();
+            for (index, name) in parts.iter().enumerate() {
+                let path = output_path.join(name);
+                let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+                if !FRONT_MATTER_EXTENSIONS.contains(&extension.as_str()) {
+                    continue;
+                }
+
+                // JSON strings are valid double-quoted YAML scalars
+                let quoted = |value: &str| serde_json::Value::from(value).to_string();
+                let mut fields = vec![
+                    ("original_path", quoted(&entry.original_relative_path)),
+                    ("original_file_name", quoted(&entry.original_file_name)),
+                ];
+                if let Some(hash) = &entry.original_hash {
+                    fields.push((hash_key.as_str(), quoted(hash)));
+                }
+                fields.push(("modified", quoted(&entry.last_modified)));
+                if let Some(archive) = &entry.source_archive {
+                    fields.push(("source_archive", quoted(archive)));
+                }
+                if let Some(parent) = &entry.parent_document {
+                    fields.push(("parent_document", quoted(parent)));
+                }
+                if parts.len() > 1 {
+                    fields.push(("part", format!("{}", index + 1)));
+                    fields.push(("parts", format!("{}", parts.len())));
+                }
+                let mut content = String::from("---\n");
+                for (key, value) in fields {
+                    content.push_str(&format!("{}: {}\n", key, value));
+                }
+                content.push_str("---\n\n");
+
+                let text = fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+                let mut bytes = content.into_bytes();
+                bytes.extend_from_slice(&text);
+                fs::write(&path, bytes)
+                    .with_context(|| format!("Failed to add front matter to {}", path.display()))?;
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Keep the exported files of `files` within `cap.max_total_bytes`,
+    /// taking them in priority order. Once a file doesn't fit, it and every
+    /// file after it are moved to `_overflow` in `output_path`, keeping
+    /// their names, and flagged on their entries. Returns the number of
+    /// entries moved.
+    pub fn apply_size_cap(&self, files: &mut [ReportModel], output_path: &Path, cap: &ExportSizeCap) -> Result<usize> {
+        let preferred: Vec<String> = cap
+            .preferred_extensions
+            .iter()
+            .map(|extension| extension.trim_start_matches('.').to_lowercase())
+            .collect();
+        let type_rank = |entry: &ReportModel| {
+            preferred
+                .iter()
+                .position(|extension| *extension == entry.file_type.to_lowercase())
+                .unwrap_or(preferred.len())
+        };
+        let size_of = |entry: &ReportModel| -> u64 {
+            entry
+                .exported_files()
+                .iter()
+                .map(|name| fs::metadata(output_path.join(name)).map(|m| m.len()).unwrap_or(0))
+                .sum()
+        };
+
+        let mut ranked: Vec<(usize, u64)> = files
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.exported_file_name.is_some())
+            .map(|(index, entry)| (index, size_of(entry)))
+            .collect();
+        // Stable, so files of equal priority stay in input order
+        ranked.sort_by(|&(a, a_size), &(b, b_size)| {
+            let (a_entry, b_entry) = (&files[a], &files[b]);
+            type_rank(a_entry).cmp(&type_rank(b_entry)).then_with(|| match cap.priority {
+                ExportPriority::InputOrder => std::cmp::Ordering::Equal,
+                ExportPriority::NewestFirst => b_entry.last_modified.cmp(&a_entry.last_modified),
+                ExportPriority::OldestFirst => a_entry.last_modified.cmp(&b_entry.last_modified),
+                ExportPriority::SmallestFirst => a_size.cmp(&b_size),
+            })
+        });
+
+        let mut kept_bytes = 0u64;
+        let mut full = false;
+        let mut overflowed = 0;
+        for (index, size) in ranked {
+            if !full && kept_bytes + size <= cap.max_total_bytes {
+                kept_bytes += size;
+                continue;
+            }
+            full = true;
+
+            let entry = &mut files[index];
+            let moved = |name: &String| format!("{}/{}", OVERFLOW_FOLDER_NAME, name);
+            for name in entry.exported_files() {
+                let from = output_path.join(name);
+                let to = output_path.join(OVERFLOW_FOLDER_NAME).join(name);
+                if let Some(parent) = to.parent() {
+                    fs::create_dir_all(parent)
+                        .with_context(|| format!("Failed to create overflow folder: {}", parent.display()))?;
+                }
+                fs::rename(&from, &to)
+                    .with_context(|| format!("Failed to move {} beyond the export size cap", from.display()))?;
+            }
+            entry.exported_file_name = entry.exported_file_name.as_ref().map(moved);
+            entry.export_parts = entry.export_parts.iter().map(moved).collect();
+            entry.over_export_cap = true;
+            overflowed += 1;
+        }
+
+        if overflowed > 0 {
+            self.logger.warning(&format!(
+                "{} file(s) beyond the export size cap of {} moved to {}",
+                overflowed,
+                crate::report_model::format_file_size(cap.max_total_bytes),
+                OVERFLOW_FOLDER_NAME
+            ));
+        }
+        Ok(overflowed)
+    }
+
+    /// Concatenate the exported text documents of `files` into
+    /// `_corpus/corpus_001.txt`, `corpus_002.txt`, … in `output_path`,
+    /// leaving out files beyond the export size cap. Each
+    /// document is framed by delimiter lines giving its original path and
+    /// hash; split documents are added part by part. Returns the corpus
+    /// files written, relative to `output_path`.
+    pub fn write_combined_corpus(
+        &self,
+        files: &[ReportModel],
+        output_path: &Path,
+        settings: &CombinedCorpusSettings,
+    ) -> Result<Vec<String>> {
+        let corpus_dir = output_path.join(CORPUS_FOLDER_NAME);
+        fs::create_dir_all(&corpus_dir)
+            .with_context(|| format!("Failed to create corpus folder: {}", corpus_dir.display()))?;
+        let hash_label = self.hashing_service.algorithm().column_label();
+
+        let mut corpus_files = Vec::new();
+        let mut current = String::new();
+        let mut document_number = 0;
+        let mut not_text = 0;
+        for entry in files.iter().filter(|entry| !entry.over_export_cap) {
+            let parts = entry.exported_files();
+            for (index, name) in parts.iter().enumerate() {
+                let path = output_path.join(name);
+                if !TaggingEngine::is_text_file(&path) {
+                    not_text += 1;
+                    continue;
+                }
+                let text = match fs::read(&path) {
+                    Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+                    Err(e) => {
+                        self.logger.warning(&format!("Failed to read {} for the corpus: {}", path.display(), e));
+                        continue;
+                    }
+                };
+
+                document_number += 1;
+                let mut document = format!("===== BEGIN DOCUMENT {} =====\n", document_number);
+                document.push_str(&format!("Original path: {}\n", entry.original_relative_path));
+                document.push_str(&format!(
+                    "Original {}: {}\n",
+                    hash_label,
+                    entry.original_hash.as_deref().unwrap_or("(not hashed)")
+                ));
+                document.push_str(&format!("Exported as: {}\n", name));
+                if parts.len() > 1 {
+                    document.push_str(&format!("Part: {} of {}\n", index + 1, parts.len()));
+                }
+                document.push_str("=====\n");
+                document.push_str(&text);
+                if !text.ends_with('\n') {
+                    document.push('\n');
+                }
+                document.push_str(&format!("===== END DOCUMENT {} =====\n\n", document_number));
+
+                let cap = settings.max_file_bytes as usize;
+                if cap > 0 && !current.is_empty() && current.len() + document.len() > cap {
+                    corpus_files.push(self.write_corpus_file(&corpus_dir, corpus_files.len() + 1, &current)?);
+                    current.clear();
+                }
+                current.push_str(&document);
+            }
+        }
+        if !current.is_empty() {
+            corpus_files.push(self.write_corpus_file(&corpus_dir, corpus_files.len() + 1, &current)?);
+        }
+
+        self.logger.info(&format!(
+            "Combined corpus written: {} document(s) in {} file(s); {} non-text export(s) left out",
+            document_number,
+            corpus_files.len(),
+            not_text
+        ));
+        Ok(corpus_files)
+    }
+
+    /// Write every exported document of `files` as one JSON object per
+    /// line to `documents.jsonl` in `output_path`, the input most ingestion
+    /// pipelines and vector-store loaders take. PDFs are written as their
+    /// extracted text; documents without readable text and files beyond the
+    /// export size cap are left out.
+    pub fn write_jsonl(&self, files: &[ReportModel], output_path: &Path) -> Result<PathBuf> {
+        let hash_algorithm = self.hashing_service.algorithm().label();
+        let mut lines = String::new();
+        let mut written = 0;
+        let mut without_text = 0;
+        for entry in files.iter().filter(|entry| !entry.over_export_cap) {
+            let parts = entry.exported_files();
+            for (index, name) in parts.iter().enumerate() {
+                let Some(text) = corpus_stats::read_files_text(output_path, &[*name]) else {
+                    without_text += 1;
+                    continue;
+                };
+                let document = JsonlDocument {
+                    path: &entry.original_relative_path,
+                    hash: entry.original_hash.as_deref(),
+                    hash_algorithm,
+                    mime: text_mime_type(Path::new(name)),
+                    exported_file: name,
+                    part: (parts.len() > 1).then_some(index + 1),
+                    parts: (parts.len() > 1).then_some(parts.len()),
+                    text,
+                };
+                lines.push_str(&serde_json::to_string(&document).context("Failed to serialize JSONL document")?);
+                lines.push('\n');
+                written += 1;
+            }
+        }
+
+        let path = output_path.join(JSONL_FILE_NAME);
+        fs::write(&path, lines).with_context(|| format!("Failed to write JSONL export: {}", path.display()))?;
+        self.logger.info(&format!(
+            "JSONL export written: {} document(s); {} without readable text left out",
+            written, without_text
+        ));
+        Ok(path)
+    }
+
+    /// Write corpus file number `number`, returning its path relative to
+    /// the export folder
+    fn write_corpus_file(&self, corpus_dir: &Path, number: usize, content: &str) -> Result<String> {
+        let file_name = format!("corpus_{:03}.txt", number);
+        let path = corpus_dir.join(&file_name);
+        fs::write(&path, content).with_context(|| format!("Failed to write corpus file: {}", path.display()))?;
+        Ok(format!("{}/{}", CORPUS_FOLDER_NAME, file_name))
+    }
+
+    /// First folder of a staging-relative path; files at the top level
+    /// share `ROOT_EXPORT_GROUP`
+    fn top_level_folder(relative_path: &str) -> String {
+        let mut components = Path::new(relative_path).components().filter_map(|c| match c {
+            std::path::Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+            _ => None,
+        });
+        match (components.next(), components.next()) {
+            (Some(folder), Some(_)) => folder,
+            _ => ROOT_EXPORT_GROUP.to_string(),
+        }
+    }
+
+    fn is_llm_readable(&self, file_path: &Path, file_entry: &ReportModel) -> bool {
+        // Check if file was converted (converted files are always LLM-readable)
+        if file_entry.is_converted() {
+            return true;
+        }
+
+        ReportModel::is_llm_readable(file_path)
+    }
+    
+    /// SECURITY: Safely resolve a relative path and ensure it stays within the root directory
+    /// Returns None if path traversal is detected
+    fn safe_resolve_path(
+        &self,
+        root_path: &Path,
+        root_path_canonical: &Path,
+        relative_path: &str,
+    ) -> Option<PathBuf> {
+        // Remove leading slashes/backslashes
+        let sanitized = relative_path.trim_start_matches('/').trim_start_matches('\\');
+        
+        // Join with root path
+        let joined_path = root_path.join(sanitized);
+        
+        // Try to canonicalize to resolve any symlinks and normalize the path
+        match joined_path.canonicalize() {
+            Ok(canonical) => {
+                // Ensure the canonical path is within the root directory
+                if canonical.starts_with(root_path_canonical) {
+                    Some(canonical)
+                } else {
+                    self.logger.warning(&format!(
+                        "SECURITY: Blocked path traversal attempt: {} (resolved to: {})",
+                        relative_path,
+                        canonical.display()
+                    ));
+                    None
+                }
+            }
+            Err(_) => {
+                // If canonicalize fails, check if the path exists and validate manually
+                if joined_path.exists() {
+                    // For existing paths, do a basic check that it's within root_path
+                    // This is less secure than canonicalize but handles edge cases
+                    let mut current = joined_path.as_path();
+                    let mut depth = 0;
+                    const MAX_DEPTH: usize = 100; // Prevent infinite loops
+                    
+                    while let Some(parent) = current.parent() {
+                        if parent == root_path || parent.starts_with(root_path) {
+                            return Some(joined_path);
+                        }
+                        current = parent;
+                        depth += 1;
+                        if depth > MAX_DEPTH {
+                            break;
+                        }
+                    }
+                    
+                    self.logger.warning(&format!(
+                        "SECURITY: Could not validate path safety: {}",
+                        relative_path
+                    ));
+                    None
+                } else {
+                    // Path doesn't exist yet, but we'll allow it if it's clearly within root_path
+                    if sanitized.contains("..") {
+                        self.logger.warning(&format!(
+                            "SECURITY: Blocked path with traversal sequence: {}",
+                            relative_path
+                        ));
+                        None
+                    } else {
+                        Some(joined_path)
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Media type of the text read from an exported file; a PDF's text is
+/// plain text
+fn text_mime_type(path: &Path) -> &'static str {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match extension.as_str() {
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "tsv" => "text/tab-separated-values",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "html" | "htm" => "text/html",
+        "rtf" => "application/rtf",
+        _ => "text/plain",
+    }
+}
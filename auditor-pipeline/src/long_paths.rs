@@ -0,0 +1,208 @@
+use std::path::{Path, PathBuf};
+
+/// Longest path the classic Win32 file APIs accept
+#[cfg_attr(not(windows), allow(dead_code))]
+const MAX_PATH: usize = 260;
+
+/// `path` in the `\\?\` extended-length form on Windows, so that it and
+/// every path joined onto it can exceed `MAX_PATH`: deeply nested client
+/// folders otherwise fail to copy, hash or convert. Relative paths are made
+/// absolute and `.`/`..` resolved first, as the prefix turns off that
+/// normalization. Elsewhere `path` is returned unchanged.
+pub fn extended(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        windows::extended(path)
+    }
+    #[cfg(not(windows))]
+    {
+        path.to_path_buf()
+    }
+}
+
+/// `path` without the `\\?\` prefix when it fits within `MAX_PATH`, for
+/// external tools (LibreOffice, pandoc, 7-Zip) that don't accept
+/// extended-length paths, and for display
+pub fn simplified(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        windows::simplified(path)
+    }
+    #[cfg(not(windows))]
+    {
+        path.to_path_buf()
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::MAX_PATH;
+    use std::path::{Component, Path, PathBuf, Prefix};
+
+    const VERBATIM: &str = r"\\?\";
+    const VERBATIM_UNC: &str = r"\\?\UNC\";
+
+    pub(super) fn extended(path: &Path) -> PathBuf {
+        let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        let mut components = absolute.components();
+        let prefix = match components.next() {
+            Some(Component::Prefix(prefix)) => prefix,
+            _ => return absolute,
+        };
+        let mut extended = match prefix.kind() {
+            Prefix::Verbatim(_) | Prefix::VerbatimUNC(..) | Prefix::VerbatimDisk(_) | Prefix::DeviceNS(_) => {
+                return absolute;
+            }
+            Prefix::Disk(letter) => format!("{}{}:", VERBATIM, letter as char),
+            Prefix::UNC(server, share) => format!(
+                "{}{}\\{}",
+                VERBATIM_UNC,
+                server.to_string_lossy(),
+                share.to_string_lossy()
+            ),
+        };
+
+        // `std::path::absolute` already dropped `.`; `..` is resolved here
+        let mut parts: Vec<String> = Vec::new();
+        for component in components {
+            match component {
+                Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+                Component::ParentDir => {
+                    parts.pop();
+                }
+                _ => {}
+            }
+        }
+        for part in parts {
+            extended.push('\\');
+            extended.push_str(&part);
+        }
+        if extended.ends_with(':') {
+            extended.push('\\');
+        }
+        PathBuf::from(extended)
+    }
+
+    pub(super) fn simplified(path: &Path) -> PathBuf {
+        let text = path.to_string_lossy();
+        let plain = if let Some(rest) = text.strip_prefix(VERBATIM_UNC) {
+            format!(r"\\{}", rest)
+        } else if let Some(rest) = text.strip_prefix(VERBATIM) {
+            rest.to_string()
+        } else {
+            return path.to_path_buf();
+        };
+        if plain.len() < MAX_PATH {
+            PathBuf::from(plain)
+        } else {
+            path.to_path_buf()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(windows))]
+    #[test]
+    fn paths_are_unchanged_off_windows() {
+        let path = Path::new("/evidence/client/../report.docx");
+        assert_eq!(extended(path), path);
+        assert_eq!(simplified(path), path);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn extends_drive_letter_paths() {
+        assert_eq!(extended(Path::new(r"C:\evidence\report.docx")), Path::new(r"\\?\C:\evidence\report.docx"));
+        assert_eq!(extended(Path::new(r"C:\")), Path::new(r"\\?\C:\"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn extends_unc_paths() {
+        assert_eq!(
+            extended(Path::new(r"\\server\share\evidence\report.docx")),
+            Path::new(r"\\?\UNC\server\share\evidence\report.docx")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn leaves_verbatim_paths_alone() {
+        for path in [r"\\?\C:\evidence\..\report.docx", r"\\?\UNC\server\share\report.docx"] {
+            assert_eq!(extended(Path::new(path)), Path::new(path));
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn resolves_parent_and_current_components() {
+        assert_eq!(
+            extended(Path::new(r"C:\evidence\client\..\.\report.docx")),
+            Path::new(r"\\?\C:\evidence\report.docx")
+        );
+        assert_eq!(extended(Path::new(r"C:\..\report.docx")), Path::new(r"\\?\C:\report.docx"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn simplifies_short_paths_only() {
+        assert_eq!(simplified(Path::new(r"\\?\C:\evidence\report.docx")), Path::new(r"C:\evidence\report.docx"));
+        assert_eq!(
+            simplified(Path::new(r"\\?\UNC\server\share\report.docx")),
+            Path::new(r"\\server\share\report.docx")
+        );
+        assert_eq!(simplified(Path::new(r"C:\evidence\report.docx")), Path::new(r"C:\evidence\report.docx"));
+
+        let long = format!(r"\\?\C:\{}\report.docx", "a".repeat(MAX_PATH));
+        assert_eq!(simplified(Path::new(&long)), Path::new(&long));
+    }
+
+    /// A file nested deeper than `MAX_PATH` is staged, hashed and exported
+    #[cfg(windows)]
+    #[test]
+    fn processes_files_beyond_max_path() {
+        use crate::ept_logger::EPTLogger;
+        use crate::events::NoopEventSink;
+        use crate::process_controller::ProcessController;
+        use crate::settings::ProcessingSettings;
+        use std::fs;
+        use std::sync::Arc;
+
+        let root = extended(&std::env::temp_dir().join(format!("auditor-long-paths-{}", std::process::id())));
+        let _ = fs::remove_dir_all(&root);
+        let input = root.join("evidence");
+        let mut folder = input.clone();
+        while folder.as_os_str().len() <= MAX_PATH {
+            folder.push("a-client-folder-nested-well-beyond-max-path");
+        }
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("notes.txt"), "Quarterly reconciliation notes").unwrap();
+
+        let mut controller = ProcessController::new(
+            EPTLogger::new(),
+            Arc::new(NoopEventSink),
+            ProcessingSettings::default(),
+        );
+        let result = controller.start_processing(&input);
+        let outcome = result.as_ref().map(|result| {
+            let entry = result
+                .entries
+                .iter()
+                .find(|entry| entry.original_relative_path.ends_with("notes.txt"))
+                .expect("deeply nested file was not staged")
+                .clone();
+            (extended(Path::new(&result.llm_output_path)), entry)
+        });
+        let checked = outcome.map(|(export_path, entry)| {
+            assert!(entry.original_hash.is_some(), "deeply nested file was not hashed");
+            let exported = entry.exported_file_name.expect("deeply nested file was not exported");
+            fs::read_to_string(export_path.join(exported)).unwrap()
+        });
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(checked.unwrap(), "Quarterly reconciliation notes");
+    }
+}
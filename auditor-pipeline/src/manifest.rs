@@ -0,0 +1,216 @@
+use crate::csv_normalizer;
+use crate::hashing_service::{HashAlgorithm, HashingService};
+use crate::report_model::{FileStatus, ReportModel};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File the manifest is written to in the LLM export folder
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// The manifest's file mapping as CSV, for reviewers working in Excel
+pub const MANIFEST_CSV_FILE_NAME: &str = "manifest.csv";
+
+/// One exported file and the evidence it came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Path inside the LLM export folder
+    pub exported_file_name: String,
+    pub original_relative_path: String,
+    /// Extension of the original file
+    pub file_type: String,
+    /// Extension of the exported file
+    pub exported_format: String,
+    pub size_bytes: u64,
+    /// Hash of the exported file, under the manifest's `hash_algorithm`;
+    /// named `sha512` in manifests of earlier versions, whatever the algorithm
+    #[serde(alias = "sha512")]
+    pub hash: Option<String>,
+    /// Hash of the original evidence file
+    #[serde(default, alias = "original_sha512")]
+    pub original_hash: Option<String>,
+    /// Hash of the converted file, when the original was converted
+    #[serde(default, alias = "converted_sha512")]
+    pub converted_hash: Option<String>,
+    /// Group of files with identical content the original belongs to
+    #[serde(default)]
+    pub duplicate_group: Option<String>,
+    /// Estimated LLM tokens of the exported text, counted once however many
+    /// copies of the file the evidence held
+    #[serde(default)]
+    pub estimated_tokens: u64,
+    /// Other original locations of the same content, left out of the export
+    /// as exact duplicates
+    #[serde(default)]
+    pub duplicate_paths: Vec<String>,
+    /// Numbered parts the document was split into, in order, replacing
+    /// `exported_file_name` in the export; empty when it wasn't split
+    #[serde(default)]
+    pub parts: Vec<String>,
+    /// Summary written by the optional summarization pass
+    #[serde(default)]
+    pub summary: Option<String>,
+}
+
+/// Inventory of what a run exported, kept next to the export so the corpus
+/// can be described without the report or the staging folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub run_id: Option<String>,
+    pub created_at: String,
+    /// Algorithm of the file hashes; manifests written before it was
+    /// configurable used SHA-512
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+    pub files: Vec<ManifestEntry>,
+    /// Sum of the files' token estimates; duplicates don't add to it
+    #[serde(default)]
+    pub estimated_tokens: u64,
+    /// Duplicates collapsed into the exported files
+    #[serde(default)]
+    pub duplicate_count: usize,
+}
+
+impl ExportManifest {
+    /// Manifest of every entry that made it into `export_path`, with the
+    /// locations of exact duplicates listed under the copy that was exported.
+    /// `rehash` hashes the exported files again, for when they were changed
+    /// after export (e.g. given front matter).
+    pub fn from_entries(
+        run_id: Option<String>,
+        hash_algorithm: HashAlgorithm,
+        export_path: &Path,
+        entries: &[ReportModel],
+        rehash: bool,
+    ) -> Self {
+        let hashing_service = HashingService::with_algorithm(hash_algorithm);
+        let mut files: Vec<ManifestEntry> = entries
+            .iter()
+            .filter_map(|entry| {
+                let exported_file_name = entry.exported_file_name.clone()?;
+                let size_bytes = entry
+                    .exported_files()
+                    .iter()
+                    .map(|name| fs::metadata(export_path.join(name)).map(|m| m.len()).unwrap_or(0))
+                    .sum();
+                let exported_format = Path::new(&exported_file_name)
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .map(|e| e.to_lowercase())
+                    .unwrap_or_default();
+                let exported_path = export_path.join(&exported_file_name);
+                let hash = if rehash && exported_path.is_file() {
+                    hashing_service.hash_file(&exported_path).ok()
+                } else {
+                    export_hash(entry).map(str::to_string)
+                };
+                Some(ManifestEntry {
+                    exported_file_name,
+                    original_relative_path: entry.original_relative_path.clone(),
+                    file_type: entry.file_type.to_lowercase(),
+                    exported_format,
+                    size_bytes,
+                    hash,
+                    original_hash: entry.original_hash.clone(),
+                    converted_hash: entry.converted_hash.clone(),
+                    duplicate_group: entry.duplicate_group.clone(),
+                    estimated_tokens: entry.estimated_tokens.unwrap_or(0),
+                    duplicate_paths: Vec::new(),
+                    parts: entry.export_parts.clone(),
+                    summary: entry.summary.clone(),
+                })
+            })
+            .collect();
+
+        // Duplicates were matched on the exported artifact's hash, within their export group
+        let exported: HashMap<(Option<&str>, &str), usize> = entries
+            .iter()
+            .filter(|entry| entry.exported_file_name.is_some())
+            .enumerate()
+            .filter_map(|(index, entry)| Some(((entry.export_group.as_deref(), export_hash(entry)?), index)))
+            .collect();
+        let mut duplicate_count = 0;
+        for entry in entries.iter().filter(|entry| entry.status == FileStatus::SkippedDuplicate) {
+            let Some(hash) = export_hash(entry) else {
+                continue;
+            };
+            if let Some(&index) = exported.get(&(entry.export_group.as_deref(), hash)) {
+                files[index].duplicate_paths.push(entry.original_relative_path.clone());
+                duplicate_count += 1;
+            }
+        }
+
+        Self {
+            run_id,
+            created_at: chrono::Local::now().to_rfc3339(),
+            hash_algorithm,
+            estimated_tokens: files.iter().map(|file| file.estimated_tokens).sum(),
+            duplicate_count,
+            files,
+        }
+    }
+
+    pub fn save(&self, export_path: &Path) -> Result<PathBuf> {
+        let path = export_path.join(MANIFEST_FILE_NAME);
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize manifest")?;
+        fs::write(&path, json).with_context(|| format!("Failed to write manifest: {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Write the mapping of exported names to the original evidence as
+    /// `manifest.csv`, one row per exported file
+    pub fn save_csv(&self, export_path: &Path) -> Result<PathBuf> {
+        let hash_column = self.hash_algorithm.column_label();
+        let headers = [
+            "Exported File".to_string(),
+            "Original Relative Path".to_string(),
+            format!("Original {}", hash_column),
+            format!("Converted {}", hash_column),
+            "Duplicate Group".to_string(),
+            "Duplicate Paths".to_string(),
+            "Parts".to_string(),
+            "Summary".to_string(),
+        ];
+        let text = |value: &Option<String>| value.clone().unwrap_or_default();
+        let rows = self.files.iter().map(|file| {
+            [
+                file.exported_file_name.clone(),
+                file.original_relative_path.clone(),
+                text(&file.original_hash),
+                text(&file.converted_hash),
+                text(&file.duplicate_group),
+                file.duplicate_paths.join("; "),
+                file.parts.join("; "),
+                text(&file.summary),
+            ]
+        });
+
+        // BOM so Excel detects UTF-8, as for the CSV report
+        let mut csv = String::from("\u{FEFF}");
+        for line in std::iter::once(headers).chain(rows) {
+            let fields: Vec<String> = line.iter().map(|field| csv_normalizer::quote_field(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push_str("\r\n");
+        }
+        let path = export_path.join(MANIFEST_CSV_FILE_NAME);
+        fs::write(&path, csv).with_context(|| format!("Failed to write manifest: {}", path.display()))?;
+        Ok(path)
+    }
+
+    pub fn load(export_path: &Path) -> Result<Self> {
+        let path = export_path.join(MANIFEST_FILE_NAME);
+        let bytes = fs::read(&path).with_context(|| format!("No manifest found at {}", path.display()))?;
+        serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+/// Hash of the artifact an entry exports: the converted file if there is one
+fn export_hash(entry: &ReportModel) -> Option<&str> {
+    if entry.is_converted() {
+        entry.converted_hash.as_deref()
+    } else {
+        entry.original_hash.as_deref()
+    }
+}
@@ -0,0 +1,152 @@
+use crate::ept_logger::EPTLogger;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Signature of an OLE compound file; encrypted OOXML is wrapped in one too
+const OLE_MAGIC: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// BIFF record announcing an encrypted workbook
+const BIFF_FILEPASS: u16 = 0x002F;
+const BIFF_EOF: u16 = 0x000A;
+
+/// Whether an Office document is password protected.
+///
+/// Encrypted OOXML files are not zips but OLE containers holding
+/// `EncryptionInfo`/`EncryptedPackage` streams; legacy formats flag it in
+/// the Word FIB, a workbook `FILEPASS` record or PowerPoint's
+/// `EncryptedSummary` stream. Files that can't be inspected count as not
+/// encrypted and are left to the converter.
+pub fn is_encrypted(path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    if !has_ole_magic(path) {
+        return false;
+    }
+    let Ok(mut file) = cfb::open(path) else {
+        return false;
+    };
+
+    match ext.as_str() {
+        "doc" | "dot" => read_stream(&mut file, "/WordDocument")
+            .filter(|fib| fib.len() >= 0x0C)
+            .map(|fib| u16::from_le_bytes([fib[0x0A], fib[0x0B]]) & 0x0100 != 0)
+            .unwrap_or(false),
+        "xls" | "xlt" => read_stream(&mut file, "/Workbook")
+            .or_else(|| read_stream(&mut file, "/Book"))
+            .map(|workbook| has_filepass_record(&workbook))
+            .unwrap_or(false),
+        "ppt" | "pps" | "pot" => file.exists("/EncryptedSummary"),
+        _ => file.exists("/EncryptionInfo") && file.exists("/EncryptedPackage"),
+    }
+}
+
+pub(crate) fn has_ole_magic(path: &Path) -> bool {
+    let mut header = [0u8; 8];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .map(|_| header == OLE_MAGIC)
+        .unwrap_or(false)
+}
+
+pub(crate) fn read_stream<F: std::io::Read + std::io::Seek>(file: &mut cfb::CompoundFile<F>, name: &str) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    file.open_stream(name).ok()?.read_to_end(&mut data).ok()?;
+    Some(data)
+}
+
+/// Walk the records of the workbook globals substream looking for `FILEPASS`
+fn has_filepass_record(workbook: &[u8]) -> bool {
+    let mut offset = 0;
+    while offset + 4 <= workbook.len() {
+        let record = u16::from_le_bytes([workbook[offset], workbook[offset + 1]]);
+        let length = u16::from_le_bytes([workbook[offset + 2], workbook[offset + 3]]) as usize;
+        match record {
+            BIFF_FILEPASS => return true,
+            BIFF_EOF => return false,
+            _ => offset += 4 + length,
+        }
+    }
+    false
+}
+
+/// Unlocks password-protected Office documents with a user-supplied list of
+/// candidate passwords, using `msoffcrypto-tool`.
+///
+/// The decrypted copy is written next to the original as
+/// `<stem>__decrypted.<ext>`; the original is left untouched so its hash
+/// still describes the evidence as received.
+pub struct OfficeDecryptor {
+    logger: EPTLogger,
+    passwords: Vec<String>,
+}
+
+impl OfficeDecryptor {
+    pub fn new(logger: EPTLogger, passwords: &[String]) -> Self {
+        Self {
+            logger,
+            passwords: passwords.iter().filter(|p| !p.is_empty()).cloned().collect(),
+        }
+    }
+
+    /// Try every password in turn, returning the decrypted copy or the
+    /// reason the document stays locked
+    pub fn unlock(&self, path: &Path) -> std::result::Result<PathBuf, String> {
+        if self.passwords.is_empty() {
+            return Err("Password protected; no document passwords supplied".to_string());
+        }
+        let tool = self.find_tool().ok_or_else(|| {
+            "Password protected; decrypting needs msoffcrypto-tool (install it or set EPT_MSOFFCRYPTO_PATH)".to_string()
+        })?;
+
+        let file_stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("document");
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let output_path = path.with_file_name(format!("{}__decrypted.{}", file_stem, ext));
+
+        for (index, password) in self.passwords.iter().enumerate() {
+            let succeeded = Command::new(&tool)
+                .arg("-p")
+                .arg(password)
+                .arg(path)
+                .arg(&output_path)
+                .output()
+                .map(|output| output.status.success())
+                .unwrap_or(false);
+            if succeeded && output_path.exists() {
+                // Never log the password itself
+                self.logger.info(&format!(
+                    "Decrypted {} with supplied password #{}",
+                    path.display(),
+                    index + 1
+                ));
+                return Ok(output_path);
+            }
+        }
+
+        let _ = fs::remove_file(&output_path);
+        Err(format!(
+            "Password protected; none of the {} supplied password(s) worked",
+            self.passwords.len()
+        ))
+    }
+
+    fn find_tool(&self) -> Option<PathBuf> {
+        if let Ok(env_path) = std::env::var("EPT_MSOFFCRYPTO_PATH") {
+            let path = PathBuf::from(&env_path);
+            if path.exists() {
+                return Some(path);
+            }
+            self.logger.warning(&format!("EPT_MSOFFCRYPTO_PATH is set to {}, but file does not exist", env_path));
+        }
+        let candidate = if cfg!(target_os = "windows") {
+            "msoffcrypto-tool.exe"
+        } else {
+            "msoffcrypto-tool"
+        };
+        which::which(candidate).ok()
+    }
+}
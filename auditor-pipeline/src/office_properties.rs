@@ -0,0 +1,183 @@
+use crate::docx_markdown::read_part;
+use crate::office_encryption::{has_ole_magic, read_stream};
+use crate::vba_macros::codepage_encoding;
+use anyhow::{Context, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::fs::File;
+use std::path::Path;
+
+/// Office formats whose document properties are read
+const OFFICE_EXTENSIONS: &[&str] = &[
+    "doc", "dot", "docx", "docm", "dotx", "dotm", "xls", "xlt", "xlsx", "xlsm", "xltx", "xltm", "xlsb", "ppt",
+    "pot", "pps", "pptx", "pptm", "potx", "potm", "ppsx", "ppsm",
+];
+
+/// Property set streams of legacy compound files
+const SUMMARY_INFORMATION_STREAM: &str = "/\u{5}SummaryInformation";
+const DOCUMENT_SUMMARY_INFORMATION_STREAM: &str = "/\u{5}DocumentSummaryInformation";
+
+/// Property ids in the property sets (MS-OLEPS)
+const PID_CODEPAGE: u32 = 0x01;
+const PIDSI_AUTHOR: u32 = 0x04;
+const PIDSI_LASTAUTHOR: u32 = 0x08;
+const PIDSI_APPNAME: u32 = 0x12;
+const PIDDSI_COMPANY: u32 = 0x0F;
+
+/// Property value types
+const VT_I2: u32 = 0x02;
+const VT_LPSTR: u32 = 0x1E;
+const VT_LPWSTR: u32 = 0x1F;
+
+/// Provenance properties stored inside an Office document, as Office wrote
+/// them; any of them may be missing or blank
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DocumentProperties {
+    pub author: Option<String>,
+    pub last_modified_by: Option<String>,
+    pub company: Option<String>,
+    /// Application that created the document, e.g. "Microsoft Excel"
+    pub application: Option<String>,
+}
+
+/// Whether `path` has an extension whose properties `read_properties` reads
+pub fn has_office_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| OFFICE_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Read the document properties of an Office file: `docProps/core.xml` and
+/// `docProps/app.xml` of OOXML packages, the SummaryInformation and
+/// DocumentSummaryInformation streams of legacy compound files
+pub fn read_properties(path: &Path) -> Result<DocumentProperties> {
+    if has_ole_magic(path) {
+        let mut file = cfb::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut properties = DocumentProperties::default();
+        if let Some(data) = read_stream(&mut file, SUMMARY_INFORMATION_STREAM) {
+            let values = read_property_set(&data);
+            properties.author = property(&values, PIDSI_AUTHOR);
+            properties.last_modified_by = property(&values, PIDSI_LASTAUTHOR);
+            properties.application = property(&values, PIDSI_APPNAME);
+        }
+        if let Some(data) = read_stream(&mut file, DOCUMENT_SUMMARY_INFORMATION_STREAM) {
+            properties.company = property(&read_property_set(&data), PIDDSI_COMPANY);
+        }
+        return Ok(properties);
+    }
+
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .with_context(|| format!("Not an Office document: {}", path.display()))?;
+    let mut properties = DocumentProperties::default();
+    if let Some(xml) = read_part(&mut archive, "docProps/core.xml")? {
+        let values = element_texts(&xml, &[b"creator", b"lastModifiedBy"]).context("Malformed docProps/core.xml")?;
+        properties.author = values[0].clone();
+        properties.last_modified_by = values[1].clone();
+    }
+    if let Some(xml) = read_part(&mut archive, "docProps/app.xml")? {
+        let values = element_texts(&xml, &[b"Company", b"Application"]).context("Malformed docProps/app.xml")?;
+        properties.company = values[0].clone();
+        properties.application = values[1].clone();
+    }
+    Ok(properties)
+}
+
+/// Text of the first element with each of `names` (local names), trimmed;
+/// `None` when the element is missing or blank
+fn element_texts(xml: &str, names: &[&[u8]]) -> Result<Vec<Option<String>>> {
+    let mut values = vec![None; names.len()];
+    let mut reader = Reader::from_str(xml);
+    let mut open: Option<usize> = None;
+    let mut text = String::new();
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => {
+                open = names.iter().position(|name| e.local_name().as_ref() == *name);
+                text.clear();
+            }
+            Event::Text(e) if open.is_some() => text.push_str(&e.unescape()?),
+            Event::End(_) => {
+                if let Some(index) = open.take() {
+                    if values[index].is_none() {
+                        values[index] = non_blank(&text);
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(values)
+}
+
+/// String properties of the first section of a property set stream, by
+/// property id. Values that are not strings, or run past the stream, are
+/// left out.
+fn read_property_set(data: &[u8]) -> Vec<(u32, String)> {
+    // Header: byte order, version, system id, CLSID, section count, then
+    // the first section's FMTID and offset
+    let Some(section) = read_u32(data, 44).map(|offset| offset as usize) else {
+        return Vec::new();
+    };
+    let Some(count) = read_u32(data, section + 4) else {
+        return Vec::new();
+    };
+
+    let entries: Vec<(u32, usize)> = (0..count.min(1024) as usize)
+        .map_while(|i| {
+            let id = read_u32(data, section + 8 + i * 8)?;
+            let offset = read_u32(data, section + 12 + i * 8)? as usize;
+            Some((id, section + offset))
+        })
+        .collect();
+    let codepage = entries
+        .iter()
+        .find(|(id, _)| *id == PID_CODEPAGE)
+        .filter(|(_, offset)| read_u32(data, *offset) == Some(VT_I2))
+        .and_then(|(_, offset)| data.get(offset + 4..offset + 6))
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .unwrap_or(1252);
+
+    entries
+        .into_iter()
+        .filter_map(|(id, offset)| {
+            let length = read_u32(data, offset + 4)? as usize;
+            let start = offset + 8;
+            let value = match read_u32(data, offset)? {
+                VT_LPSTR => {
+                    let bytes = data.get(start..start.checked_add(length)?)?;
+                    if codepage == 1200 {
+                        decode_utf16(bytes)
+                    } else {
+                        codepage_encoding(codepage).decode(bytes).0.to_string()
+                    }
+                }
+                VT_LPWSTR => decode_utf16(data.get(start..start.checked_add(length.checked_mul(2)?)?)?),
+                _ => return None,
+            };
+            Some((id, value))
+        })
+        .collect()
+}
+
+fn property(values: &[(u32, String)], id: u32) -> Option<String> {
+    values.iter().find(|(pid, _)| *pid == id).and_then(|(_, value)| non_blank(value))
+}
+
+fn decode_utf16(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]])).collect();
+    String::from_utf16_lossy(&units)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset.checked_add(4)?)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Trimmed text without the terminating NULs of property strings; `None` when blank
+fn non_blank(text: &str) -> Option<String> {
+    let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    (!text.is_empty()).then(|| text.to_string())
+}
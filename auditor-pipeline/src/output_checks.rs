@@ -0,0 +1,117 @@
+use crate::ept_logger::EPTLogger;
+use crate::image_conversion::IMAGE_EXTENSIONS;
+use crate::pdf_text;
+use crate::report_model::{FileStatus, ReportModel};
+use std::fs;
+use std::path::Path;
+
+/// Share of unreadable characters (controls, U+FFFD) above which converted
+/// text is considered garbled
+const MAX_UNREADABLE_RATIO: f64 = 0.10;
+
+/// Post-conversion sanity checks that catch outputs a converter reported as
+/// successful but which are empty or garbage, e.g. a blank PDF from
+/// LibreOffice. Suspicious conversions stay converted but are flagged for
+/// manual review in the report.
+pub struct OutputValidator {
+    logger: EPTLogger,
+}
+
+impl OutputValidator {
+    pub fn new(logger: EPTLogger) -> Self {
+        Self { logger }
+    }
+
+    /// Check every converted entry's output, recording the reason on
+    /// `review_reason`. Returns the number of entries flagged.
+    pub fn check_entries(&self, working_path: &Path, entries: &mut [ReportModel]) -> usize {
+        let mut flagged = 0;
+        for entry in entries.iter_mut() {
+            if entry.status != FileStatus::Converted {
+                continue;
+            }
+            let Some(converted_path) = entry.converted_relative_path.as_ref() else {
+                continue;
+            };
+            // An image wrapped in a PDF has no text layer by design
+            if IMAGE_EXTENSIONS.contains(&entry.file_type.to_lowercase().as_str())
+                && entry.output_format.as_deref() == Some("pdf")
+            {
+                continue;
+            }
+            if let Some(reason) = check_output(&working_path.join(converted_path)) {
+                self.logger.warning(&format!(
+                    "Conversion of {} needs manual review: {}",
+                    entry.original_relative_path, reason
+                ));
+                entry.review_reason = Some(reason);
+                flagged += 1;
+            }
+        }
+
+        if flagged > 0 {
+            self.logger.info(&format!("{} converted file(s) flagged for manual review", flagged));
+        }
+        flagged
+    }
+}
+
+/// Why a converted file looks wrong, or `None` when it passes
+fn check_output(path: &Path) -> Option<String> {
+    let data = match fs::read(path) {
+        Ok(data) => data,
+        Err(e) => return Some(format!("Converted output could not be read: {}", e)),
+    };
+    if data.is_empty() {
+        return Some("Converted output is empty".to_string());
+    }
+
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "pdf" => check_pdf(&data),
+        "md" | "txt" | "csv" => check_text(&String::from_utf8_lossy(&data)),
+        _ => None,
+    }
+}
+
+fn check_pdf(data: &[u8]) -> Option<String> {
+    match pdf_text::extract_page_text(data) {
+        Ok(pages) if pages.iter().all(|page| page.trim().is_empty()) => {
+            Some(format!("Converted PDF has {} page(s) but no extractable text", pages.len()))
+        }
+        Ok(_) => None,
+        Err(e) if e.to_string().contains("no pages") => Some("Converted PDF has no pages".to_string()),
+        // Encrypted or unparseable output is left to the reviewer's judgement
+        Err(_) => None,
+    }
+}
+
+fn check_text(text: &str) -> Option<String> {
+    // Skip the title and "Converted on" lines every markdown converter writes
+    let body: Vec<&str> = text
+        .lines()
+        .skip_while(|line| line.trim().is_empty() || line.starts_with("# ") || line.starts_with("Converted on:"))
+        .collect();
+    let body = body.join("\n");
+    if body.trim().is_empty() {
+        return Some("Converted text has no content".to_string());
+    }
+
+    let total = body.chars().count();
+    let unreadable = body
+        .chars()
+        .filter(|c| *c == '\u{FFFD}' || (c.is_control() && !matches!(c, '\n' | '\r' | '\t')))
+        .count();
+    let ratio = unreadable as f64 / total as f64;
+    if ratio > MAX_UNREADABLE_RATIO {
+        return Some(format!(
+            "Converted text looks garbled ({:.0}% unreadable characters)",
+            ratio * 100.0
+        ));
+    }
+    None
+}
@@ -0,0 +1,88 @@
+use crate::report_model::{FileStatus, ReportModel};
+use anyhow::{Context, Result};
+use globset::{Glob, GlobBuilder, GlobMatcher};
+use serde::{Deserialize, Serialize};
+
+/// Start of the skip reason of every file the patterns leave out
+pub const PATTERN_EXCLUSION_REASON: &str = "Excluded by pattern";
+
+/// What happens to files left out by the include/exclude patterns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternExclusion {
+    /// List them in the report as excluded, with the pattern that excluded them
+    #[default]
+    List,
+    /// Leave them out of the inventory altogether
+    Omit,
+}
+
+/// Include/exclude glob patterns over paths relative to the input folder.
+///
+/// Patterns are matched case-insensitively with `/` as the separator. A
+/// pattern without a `/` (`*.tmp`) is matched against the file name, any
+/// other (`**/Backups/**`) against the whole relative path. Exclusion wins
+/// over inclusion; with no include patterns every file is included.
+pub struct PathFilter {
+    include: Vec<(String, GlobMatcher)>,
+    exclude: Vec<(String, GlobMatcher)>,
+}
+
+impl PathFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        Ok(Self {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Why the file at `relative_path` is left out, or `None` when it is kept
+    pub fn exclusion_reason(&self, relative_path: &str) -> Option<String> {
+        let relative_path = relative_path.replace('\\', "/");
+        let file_name = relative_path.rsplit('/').next().unwrap_or(&relative_path);
+        let matches = |(pattern, matcher): &(String, GlobMatcher)| {
+            if pattern.contains('/') {
+                matcher.is_match(&relative_path)
+            } else {
+                matcher.is_match(file_name)
+            }
+        };
+
+        if let Some((pattern, _)) = self.exclude.iter().find(|pattern| matches(pattern)) {
+            return Some(format!("{} {}", PATTERN_EXCLUSION_REASON, pattern));
+        }
+        if !self.include.is_empty() && !self.include.iter().any(matches) {
+            return Some(format!("{}: matches no include pattern", PATTERN_EXCLUSION_REASON));
+        }
+        None
+    }
+}
+
+/// Whether the scanner listed `entry` as left out by the patterns
+pub fn is_pattern_excluded(entry: &ReportModel) -> bool {
+    entry.status == FileStatus::Excluded
+        && entry
+            .skip_reason
+            .as_deref()
+            .is_some_and(|reason| reason.starts_with(PATTERN_EXCLUSION_REASON))
+}
+
+fn compile(patterns: &[String]) -> Result<Vec<(String, GlobMatcher)>> {
+    patterns
+        .iter()
+        .map(|pattern| pattern.trim())
+        .filter(|pattern| !pattern.is_empty())
+        .map(|pattern| {
+            let glob: Glob = GlobBuilder::new(pattern)
+                .case_insensitive(true)
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("Invalid glob pattern: {}", pattern))?;
+            Ok((pattern.to_string(), glob.compile_matcher()))
+        })
+        .collect()
+}
@@ -0,0 +1,74 @@
+use crate::pdf_document::{decode_text_string, find, PdfDocument, PdfValue};
+use std::collections::HashMap;
+
+/// A file embedded in a PDF (attachment or portfolio member)
+pub struct EmbeddedFile {
+    /// Name from the file specification, when one references the stream
+    pub name: Option<String>,
+    pub object_number: u32,
+}
+
+/// The files embedded in a PDF, decoded one at a time on request so a PDF
+/// full of large (or bomb) streams never holds them all in memory
+pub struct EmbeddedFiles<'a> {
+    document: PdfDocument<'a>,
+    pub files: Vec<EmbeddedFile>,
+}
+
+impl EmbeddedFiles<'_> {
+    /// Decoded content of `file`, cut off after `max_bytes`, or why it
+    /// could not be decoded
+    pub fn decode(&self, file: &EmbeddedFile, max_bytes: u64) -> Result<Vec<u8>, String> {
+        self.document.stream_data_capped(file.object_number, max_bytes)
+    }
+}
+
+/// Cheap check before parsing: embedded file streams always carry this type
+pub fn has_embedded_files(data: &[u8]) -> bool {
+    find(data, b"/EmbeddedFile", 0).is_some()
+}
+
+/// Find every `/Type /EmbeddedFile` stream in a PDF.
+///
+/// Names come from the file specifications that reference the streams,
+/// including those packed into compressed object streams.
+pub fn extract_embedded_files(data: &[u8]) -> EmbeddedFiles<'_> {
+    let document = PdfDocument::parse(data);
+
+    let mut embedded_streams: Vec<u32> = Vec::new();
+    let mut names: HashMap<u32, String> = HashMap::new();
+    for number in document.object_numbers() {
+        let Some(dict) = document.dictionary(number) else {
+            continue;
+        };
+        if dict.is_type("EmbeddedFile") {
+            embedded_streams.push(number);
+        } else if let Some((stream_number, name)) = file_spec_name(&document, &dict) {
+            names.entry(stream_number).or_insert(name);
+        }
+    }
+
+    let files = embedded_streams
+        .into_iter()
+        .map(|number| EmbeddedFile {
+            name: names.get(&number).cloned(),
+            object_number: number,
+        })
+        .collect();
+    EmbeddedFiles { document, files }
+}
+
+/// For a file specification dictionary, the embedded stream it points to and
+/// the file name it gives (`/UF` preferred over `/F`)
+fn file_spec_name(document: &PdfDocument, dict: &PdfValue) -> Option<(u32, String)> {
+    let ef = document.resolve(dict.get("EF")?);
+    let stream_number = ["UF", "F"]
+        .iter()
+        .find_map(|key| ef.get(key).and_then(PdfValue::as_ref_number))?;
+
+    let name = ["UF", "F"].iter().find_map(|key| match dict.get(key).map(|v| document.resolve(v)) {
+        Some(PdfValue::String(bytes)) => Some(decode_text_string(&bytes)).filter(|text| !text.trim().is_empty()),
+        _ => None,
+    })?;
+    Some((stream_number, name))
+}
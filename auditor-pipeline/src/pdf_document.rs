@@ -0,0 +1,596 @@
+use flate2::read::ZlibDecoder;
+use regex::bytes::Regex;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::OnceLock;
+
+/// Most bytes `PdfDocument::stream_data` decodes a stream to; the rest is
+/// cut off, so a flate bomb can't exhaust memory
+const MAX_STREAM_BYTES: u64 = 256 * 1024 * 1024;
+
+/// A parsed PDF value. Streams are represented by their dictionary; their
+/// data is fetched through `PdfDocument::stream_data`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PdfValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Name(String),
+    String(Vec<u8>),
+    Array(Vec<PdfValue>),
+    Dict(Vec<(String, PdfValue)>),
+    Ref(u32),
+    /// Bare keyword, e.g. a content stream operator
+    Keyword(String),
+}
+
+impl PdfValue {
+    /// Entry of a dictionary value
+    pub fn get(&self, key: &str) -> Option<&PdfValue> {
+        match self {
+            PdfValue::Dict(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_name(&self) -> Option<&str> {
+        match self {
+            PdfValue::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            PdfValue::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[PdfValue]> {
+        match self {
+            PdfValue::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_ref_number(&self) -> Option<u32> {
+        match self {
+            PdfValue::Ref(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    /// Whether this dictionary has `/Type /<type_name>`
+    pub fn is_type(&self, type_name: &str) -> bool {
+        self.get("Type").and_then(PdfValue::as_name) == Some(type_name)
+    }
+}
+
+/// The objects of a PDF, located by a tolerant scan of the raw bytes.
+///
+/// Objects are found by their `n g obj` headers rather than through the
+/// cross-reference table, so damaged files still yield what they contain.
+/// Later definitions win (as with incremental updates), and objects packed
+/// into object streams are included. Only the Flate, ASCIIHex and ASCII85
+/// filters are decoded.
+pub struct PdfDocument<'a> {
+    objects: HashMap<u32, Cow<'a, [u8]>>,
+}
+
+impl<'a> PdfDocument<'a> {
+    pub fn parse(data: &'a [u8]) -> Self {
+        static OBJECT: OnceLock<Regex> = OnceLock::new();
+        let object_regex = OBJECT.get_or_init(|| Regex::new(r"(?-u)(\d+)\s+\d+\s+obj\b").unwrap());
+
+        let mut objects: HashMap<u32, Cow<'a, [u8]>> = HashMap::new();
+        for captures in object_regex.captures_iter(data) {
+            let (Some(number), Some(whole)) = (parse_u32(&captures[1]), captures.get(0)) else {
+                continue;
+            };
+            let start = whole.end();
+            let end = find(data, b"endobj", start).unwrap_or(data.len());
+            objects.insert(number, Cow::Borrowed(&data[start..end]));
+        }
+
+        let mut document = Self { objects };
+        document.unpack_object_streams();
+        document
+    }
+
+    fn unpack_object_streams(&mut self) {
+        let object_streams: Vec<u32> = self
+            .objects
+            .keys()
+            .copied()
+            .filter(|&number| self.dictionary(number).is_some_and(|dict| dict.is_type("ObjStm")))
+            .collect();
+
+        let mut packed: Vec<(u32, Vec<u8>)> = Vec::new();
+        for number in object_streams {
+            let Some(first) = self
+                .dictionary(number)
+                .and_then(|dict| dict.get("First").and_then(PdfValue::as_number))
+                .map(|first| first as usize)
+            else {
+                continue;
+            };
+            let Ok(decoded) = self.stream_data(number) else {
+                continue;
+            };
+            let Some(header) = decoded.get(..first) else {
+                continue;
+            };
+
+            // Header is pairs of "object-number offset"
+            let numbers: Vec<usize> = String::from_utf8_lossy(header)
+                .split_whitespace()
+                .filter_map(|n| n.parse().ok())
+                .collect();
+            let entries: Vec<(u32, usize)> = numbers
+                .chunks(2)
+                .filter(|pair| pair.len() == 2)
+                .map(|pair| (pair[0] as u32, first + pair[1]))
+                .collect();
+            for (index, &(object_number, start)) in entries.iter().enumerate() {
+                let end = entries.get(index + 1).map(|&(_, next)| next).unwrap_or(decoded.len());
+                if let Some(body) = decoded.get(start..end.max(start)) {
+                    packed.push((object_number, body.to_vec()));
+                }
+            }
+        }
+
+        for (number, body) in packed {
+            self.objects.entry(number).or_insert(Cow::Owned(body));
+        }
+    }
+
+    /// Object numbers in ascending order
+    pub fn object_numbers(&self) -> Vec<u32> {
+        let mut numbers: Vec<u32> = self.objects.keys().copied().collect();
+        numbers.sort_unstable();
+        numbers
+    }
+
+    /// The value of an object (for a stream, its dictionary)
+    pub fn object(&self, number: u32) -> Option<PdfValue> {
+        let body = self.objects.get(&number)?;
+        Lexer::new(body).parse_value()
+    }
+
+    /// The dictionary of an object, if it is a dictionary or a stream
+    pub fn dictionary(&self, number: u32) -> Option<PdfValue> {
+        self.object(number).filter(|value| matches!(value, PdfValue::Dict(_)))
+    }
+
+    /// Follow a reference; other values are returned as they are
+    pub fn resolve(&self, value: &PdfValue) -> PdfValue {
+        let mut current = value.clone();
+        // Bounded in case of reference cycles
+        for _ in 0..8 {
+            match current {
+                PdfValue::Ref(number) => current = self.object(number).unwrap_or(PdfValue::Null),
+                other => return other,
+            }
+        }
+        PdfValue::Null
+    }
+
+    /// Decoded data of a stream object, cut off at `MAX_STREAM_BYTES`
+    pub fn stream_data(&self, number: u32) -> Result<Vec<u8>, String> {
+        self.stream_data_capped(number, MAX_STREAM_BYTES)
+    }
+
+    /// Decoded data of a stream object, cut off after `max_bytes`
+    pub fn stream_data_capped(&self, number: u32, max_bytes: u64) -> Result<Vec<u8>, String> {
+        let body = self.objects.get(&number).ok_or("object not found")?;
+        let mut lexer = Lexer::new(body);
+        let dict = lexer.parse_value().ok_or("object has no dictionary")?;
+        if lexer.next_token() != Some(Token::Keyword("stream".to_string())) {
+            return Err("object has no stream".to_string());
+        }
+        let mut start = lexer.pos;
+        if body.get(start) == Some(&b'\r') {
+            start += 1;
+        }
+        if body.get(start) == Some(&b'\n') {
+            start += 1;
+        }
+
+        let length = dict
+            .get("Length")
+            .map(|length| self.resolve(length))
+            .and_then(|length| length.as_number())
+            .map(|length| length as usize);
+        let raw = match length.and_then(|length| body.get(start..start + length)) {
+            Some(raw) => raw,
+            None => {
+                // Wrong or missing /Length: fall back to the endstream keyword
+                let end = find(body, b"endstream", start).ok_or("stream data not found")?;
+                let mut slice = &body[start..end];
+                while let Some((last, rest)) = slice.split_last() {
+                    if *last == b'\n' || *last == b'\r' {
+                        slice = rest;
+                    } else {
+                        break;
+                    }
+                }
+                slice
+            }
+        };
+
+        let filters: Vec<String> = match dict.get("Filter").map(|filter| self.resolve(filter)) {
+            Some(PdfValue::Name(name)) => vec![name],
+            Some(PdfValue::Array(items)) => items.iter().filter_map(|item| item.as_name().map(str::to_string)).collect(),
+            _ => Vec::new(),
+        };
+        let mut data = raw.to_vec();
+        for filter in filters {
+            data = match filter.as_str() {
+                "FlateDecode" | "Fl" => inflate(&data, max_bytes)?,
+                "ASCIIHexDecode" | "AHx" => decode_ascii_hex(&data),
+                "ASCII85Decode" | "A85" => decode_ascii85(&data)?,
+                other => return Err(format!("unsupported filter {}", other)),
+            };
+        }
+        Ok(data)
+    }
+}
+
+/// Inflate `data`, stopping after `max_bytes`
+fn inflate(data: &[u8], max_bytes: u64) -> Result<Vec<u8>, String> {
+    let mut decoded = Vec::new();
+    match ZlibDecoder::new(data).take(max_bytes).read_to_end(&mut decoded) {
+        Ok(_) => Ok(decoded),
+        // Truncated streams are common; keep what was recovered
+        Err(_) if !decoded.is_empty() => Ok(decoded),
+        Err(e) => Err(format!("failed to inflate stream: {}", e)),
+    }
+}
+
+fn decode_ascii_hex(data: &[u8]) -> Vec<u8> {
+    let end = data.iter().position(|&b| b == b'>').unwrap_or(data.len());
+    let mut bracketed = vec![b'<'];
+    bracketed.extend_from_slice(&data[..end]);
+    parse_hex_string(&bracketed)
+}
+
+fn decode_ascii85(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let mut group: Vec<u32> = Vec::with_capacity(5);
+    let data = data.strip_prefix(b"<~").unwrap_or(data);
+    for &byte in data {
+        match byte {
+            b'~' => break,
+            b'z' if group.is_empty() => out.extend_from_slice(&[0, 0, 0, 0]),
+            b'!'..=b'u' => {
+                group.push((byte - b'!') as u32);
+                if group.len() == 5 {
+                    let value = group.iter().fold(0u32, |acc, &d| acc.wrapping_mul(85).wrapping_add(d));
+                    out.extend_from_slice(&value.to_be_bytes());
+                    group.clear();
+                }
+            }
+            b if b.is_ascii_whitespace() => {}
+            other => return Err(format!("invalid ASCII85 byte 0x{:02x}", other)),
+        }
+    }
+    if group.len() > 1 {
+        let kept = group.len() - 1;
+        group.resize(5, 84);
+        let value = group.iter().fold(0u32, |acc, &d| acc.wrapping_mul(85).wrapping_add(d));
+        out.extend_from_slice(&value.to_be_bytes()[..kept]);
+    }
+    Ok(out)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    DictStart,
+    DictEnd,
+    ArrayStart,
+    ArrayEnd,
+    Name(String),
+    Number(f64),
+    String(Vec<u8>),
+    Keyword(String),
+}
+
+/// Tokenizer for PDF object syntax and content streams
+pub struct Lexer<'a> {
+    data: &'a [u8],
+    pub pos: usize,
+}
+
+fn is_delimiter(b: u8) -> bool {
+    matches!(b, b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%')
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\r' | b'\n' | b'\x0c' | b'\0')
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(&b) = self.data.get(self.pos) {
+            if is_whitespace(b) {
+                self.pos += 1;
+            } else if b == b'%' {
+                while self.data.get(self.pos).is_some_and(|&b| b != b'\n' && b != b'\r') {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub fn next_token(&mut self) -> Option<Token> {
+        self.skip_whitespace();
+        let &b = self.data.get(self.pos)?;
+        match b {
+            b'<' if self.data.get(self.pos + 1) == Some(&b'<') => {
+                self.pos += 2;
+                Some(Token::DictStart)
+            }
+            b'>' if self.data.get(self.pos + 1) == Some(&b'>') => {
+                self.pos += 2;
+                Some(Token::DictEnd)
+            }
+            b'[' | b'{' => {
+                self.pos += 1;
+                Some(Token::ArrayStart)
+            }
+            b']' | b'}' => {
+                self.pos += 1;
+                Some(Token::ArrayEnd)
+            }
+            b'(' => {
+                let (bytes, consumed) = parse_literal_string_with_len(&self.data[self.pos..]);
+                self.pos += consumed;
+                Some(Token::String(bytes))
+            }
+            b'<' => {
+                let end = self.data[self.pos..]
+                    .iter()
+                    .position(|&b| b == b'>')
+                    .map(|i| self.pos + i + 1)
+                    .unwrap_or(self.data.len());
+                let bytes = parse_hex_string(&self.data[self.pos..end]);
+                self.pos = end;
+                Some(Token::String(bytes))
+            }
+            b'/' => {
+                self.pos += 1;
+                let start = self.pos;
+                while self.data.get(self.pos).is_some_and(|&b| !is_whitespace(b) && !is_delimiter(b)) {
+                    self.pos += 1;
+                }
+                Some(Token::Name(decode_name(&self.data[start..self.pos])))
+            }
+            b')' | b'>' => {
+                // Stray delimiter; skip it
+                self.pos += 1;
+                self.next_token()
+            }
+            _ => {
+                let start = self.pos;
+                while self.data.get(self.pos).is_some_and(|&b| !is_whitespace(b) && !is_delimiter(b)) {
+                    self.pos += 1;
+                }
+                let word = String::from_utf8_lossy(&self.data[start..self.pos]).to_string();
+                match word.parse::<f64>() {
+                    Ok(number) if word.chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+')) => {
+                        Some(Token::Number(number))
+                    }
+                    _ => Some(Token::Keyword(word)),
+                }
+            }
+        }
+    }
+
+    /// Parse one value, turning `n g R` into a reference
+    pub fn parse_value(&mut self) -> Option<PdfValue> {
+        let token = self.next_token()?;
+        self.value_from_token(token)
+    }
+
+    /// Like `parse_value`, starting from an already read token
+    pub fn value_from_token(&mut self, token: Token) -> Option<PdfValue> {
+        Some(match token {
+            Token::DictStart => {
+                let mut entries = Vec::new();
+                loop {
+                    match self.next_token()? {
+                        Token::DictEnd => break,
+                        Token::Name(key) => {
+                            let value = self.parse_value()?;
+                            entries.push((key, value));
+                        }
+                        // Malformed entry; skip the token
+                        _ => {}
+                    }
+                }
+                PdfValue::Dict(entries)
+            }
+            Token::ArrayStart => {
+                let mut items = Vec::new();
+                loop {
+                    let token = self.next_token()?;
+                    if token == Token::ArrayEnd {
+                        break;
+                    }
+                    items.push(self.value_from_token(token)?);
+                }
+                PdfValue::Array(items)
+            }
+            Token::Number(number) => {
+                // Look ahead for `generation R`
+                let saved = self.pos;
+                if let (Some(Token::Number(_)), Some(Token::Keyword(keyword))) = (self.next_token(), self.next_token()) {
+                    if keyword == "R" && number >= 0.0 {
+                        return Some(PdfValue::Ref(number as u32));
+                    }
+                }
+                self.pos = saved;
+                PdfValue::Number(number)
+            }
+            Token::Name(name) => PdfValue::Name(name),
+            Token::String(bytes) => PdfValue::String(bytes),
+            Token::Keyword(keyword) => match keyword.as_str() {
+                "true" => PdfValue::Bool(true),
+                "false" => PdfValue::Bool(false),
+                "null" => PdfValue::Null,
+                _ => PdfValue::Keyword(keyword),
+            },
+            Token::DictEnd | Token::ArrayEnd => PdfValue::Null,
+        })
+    }
+
+    /// Skip the binary data of an inline image, up to and including `EI`
+    pub fn skip_inline_image(&mut self) {
+        // One whitespace byte follows ID
+        self.pos += 1;
+        while self.pos + 2 <= self.data.len() {
+            if &self.data[self.pos..self.pos + 2] == b"EI"
+                && self.pos > 0
+                && is_whitespace(self.data[self.pos - 1])
+                && self.data.get(self.pos + 2).is_none_or(|&b| is_whitespace(b))
+            {
+                self.pos += 2;
+                return;
+            }
+            self.pos += 1;
+        }
+        self.pos = self.data.len();
+    }
+}
+
+fn decode_name(raw: &[u8]) -> String {
+    let mut out = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] == b'#' {
+            if let Some(byte) = raw
+                .get(i + 1..i + 3)
+                .and_then(|h| std::str::from_utf8(h).ok())
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+            {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(raw[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Decode a literal string starting at `(`
+pub fn parse_literal_string(input: &[u8]) -> Vec<u8> {
+    parse_literal_string_with_len(input).0
+}
+
+fn parse_literal_string_with_len(input: &[u8]) -> (Vec<u8>, usize) {
+    let mut out = Vec::new();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < input.len() {
+        let b = input[i];
+        match b {
+            b'(' => {
+                if depth > 0 {
+                    out.push(b);
+                }
+                depth += 1;
+            }
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return (out, i + 1);
+                }
+                out.push(b);
+            }
+            b'\\' => {
+                i += 1;
+                match input.get(i) {
+                    Some(b'n') => out.push(b'\n'),
+                    Some(b'r') => out.push(b'\r'),
+                    Some(b't') => out.push(b'\t'),
+                    Some(b'b') => out.push(0x08),
+                    Some(b'f') => out.push(0x0c),
+                    Some(d) if (b'0'..=b'7').contains(d) => {
+                        let mut value: u32 = 0;
+                        let mut digits = 0;
+                        while digits < 3 && input.get(i).is_some_and(|d| (b'0'..=b'7').contains(d)) {
+                            value = value * 8 + (input[i] - b'0') as u32;
+                            i += 1;
+                            digits += 1;
+                        }
+                        out.push(value as u8);
+                        continue;
+                    }
+                    // Line continuation
+                    Some(b'\r') | Some(b'\n') => {}
+                    Some(&other) => out.push(other),
+                    None => break,
+                }
+            }
+            _ => out.push(b),
+        }
+        i += 1;
+    }
+    (out, input.len())
+}
+
+/// Decode a hex string starting at `<`
+pub fn parse_hex_string(input: &[u8]) -> Vec<u8> {
+    let end = input.iter().position(|&b| b == b'>').unwrap_or(input.len());
+    let digits: Vec<u8> = input[1.min(end)..end]
+        .iter()
+        .filter(|b| b.is_ascii_hexdigit())
+        .copied()
+        .collect();
+    digits
+        .chunks(2)
+        .filter_map(|pair| {
+            let text = std::str::from_utf8(pair).ok()?;
+            // An odd final digit is padded with 0
+            u8::from_str_radix(&format!("{:0<2}", text), 16).ok()
+        })
+        .collect()
+}
+
+/// PDF text strings are UTF-16BE with a BOM, or PDFDocEncoding (treated as Latin-1)
+pub fn decode_text_string(bytes: &[u8]) -> String {
+    if bytes.starts_with(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = bytes[2..]
+            .chunks(2)
+            .filter(|c| c.len() == 2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else if let Ok(text) = std::str::from_utf8(bytes) {
+        text.to_string()
+    } else {
+        bytes.iter().map(|&b| b as char).collect()
+    }
+}
+
+pub fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| pos + from)
+}
+
+fn parse_u32(bytes: &[u8]) -> Option<u32> {
+    std::str::from_utf8(bytes).ok()?.parse().ok()
+}
@@ -0,0 +1,447 @@
+use crate::pdf_document::{find, Lexer, PdfDocument, PdfValue, Token};
+use anyhow::{bail, Result};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// How deeply Form XObjects may nest inside page content
+const MAX_FORM_DEPTH: usize = 3;
+
+/// TJ adjustments (thousandths of an em) wider than this read as a word gap
+const WORD_GAP: f64 = 200.0;
+
+/// Extract the text of every page of a PDF, in page order.
+///
+/// Text is decoded through the fonts' ToUnicode maps where present (falling
+/// back to WinAnsi for simple fonts) and laid out as lines from the text
+/// positioning operators; no attempt is made to reconstruct columns or
+/// tables. Pages without text (e.g. scans) yield an empty string.
+pub fn extract_page_text(data: &[u8]) -> Result<Vec<String>> {
+    if find(data, b"/Encrypt", 0).is_some() {
+        bail!("PDF is encrypted");
+    }
+
+    let document = PdfDocument::parse(data);
+    let pages = page_objects(&document);
+    if pages.is_empty() {
+        bail!("no pages found");
+    }
+
+    let mut extractor = TextExtractor {
+        document: &document,
+        fonts: HashMap::new(),
+    };
+    Ok(pages
+        .into_iter()
+        .map(|(page, resources)| {
+            let content = extractor.page_content(&page);
+            let mut writer = TextWriter::default();
+            extractor.run(&content, &resources, &mut writer, 0);
+            writer.finish()
+        })
+        .collect())
+}
+
+/// Page dictionaries with their (possibly inherited) resources, in page order
+fn page_objects(document: &PdfDocument) -> Vec<(PdfValue, PdfValue)> {
+    let mut pages = Vec::new();
+    let root = document
+        .object_numbers()
+        .into_iter()
+        .rev()
+        .filter_map(|number| document.dictionary(number))
+        .find(|dict| dict.is_type("Catalog"))
+        .and_then(|catalog| catalog.get("Pages").and_then(PdfValue::as_ref_number));
+    if let Some(root) = root {
+        collect_pages(document, root, PdfValue::Null, &mut pages, &mut HashSet::new());
+    }
+
+    // Broken page tree: fall back to every page object in file order
+    if pages.is_empty() {
+        for number in document.object_numbers() {
+            if let Some(page) = document.dictionary(number).filter(|dict| dict.is_type("Page")) {
+                let resources = inherited_resources(document, &page);
+                pages.push((page, resources));
+            }
+        }
+    }
+    pages
+}
+
+fn collect_pages(
+    document: &PdfDocument,
+    number: u32,
+    resources: PdfValue,
+    pages: &mut Vec<(PdfValue, PdfValue)>,
+    visited: &mut HashSet<u32>,
+) {
+    if !visited.insert(number) {
+        return;
+    }
+    let Some(node) = document.dictionary(number) else {
+        return;
+    };
+    let resources = match node.get("Resources") {
+        Some(own) => document.resolve(own),
+        None => resources,
+    };
+
+    match node.get("Kids").map(|kids| document.resolve(kids)) {
+        Some(PdfValue::Array(kids)) if !node.is_type("Page") => {
+            for kid in kids.iter().filter_map(PdfValue::as_ref_number) {
+                collect_pages(document, kid, resources.clone(), pages, visited);
+            }
+        }
+        _ => pages.push((node, resources)),
+    }
+}
+
+fn inherited_resources(document: &PdfDocument, page: &PdfValue) -> PdfValue {
+    let mut node = page.clone();
+    for _ in 0..32 {
+        if let Some(resources) = node.get("Resources") {
+            return document.resolve(resources);
+        }
+        match node.get("Parent").and_then(PdfValue::as_ref_number).and_then(|n| document.dictionary(n)) {
+            Some(parent) => node = parent,
+            None => break,
+        }
+    }
+    PdfValue::Null
+}
+
+/// Maps character codes of one font to text
+struct FontDecoder {
+    code_bytes: usize,
+    to_unicode: HashMap<u32, String>,
+}
+
+impl FontDecoder {
+    /// Single-byte WinAnsi decoding, also used for text shown without a usable font
+    fn simple() -> Self {
+        Self {
+            code_bytes: 1,
+            to_unicode: HashMap::new(),
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> String {
+        let mut text = String::new();
+        for chunk in bytes.chunks(self.code_bytes) {
+            let code = chunk.iter().fold(0u32, |acc, &b| (acc << 8) | b as u32);
+            if let Some(mapped) = self.to_unicode.get(&code) {
+                text.push_str(mapped);
+            } else if self.code_bytes == 1 {
+                let (decoded, _, _) = encoding_rs::WINDOWS_1252.decode(chunk);
+                text.push_str(&decoded);
+            }
+            // Unmapped multi-byte codes are glyph ids with no known text
+        }
+        text
+    }
+}
+
+struct TextExtractor<'d, 'a> {
+    document: &'d PdfDocument<'a>,
+    fonts: HashMap<u32, Rc<FontDecoder>>,
+}
+
+impl TextExtractor<'_, '_> {
+    /// Concatenated content streams of a page
+    fn page_content(&self, page: &PdfValue) -> Vec<u8> {
+        let streams: Vec<u32> = match page.get("Contents") {
+            Some(PdfValue::Ref(number)) => match self.document.object(*number) {
+                // An indirect array of streams
+                Some(PdfValue::Array(items)) => items.iter().filter_map(PdfValue::as_ref_number).collect(),
+                _ => vec![*number],
+            },
+            Some(PdfValue::Array(items)) => items.iter().filter_map(PdfValue::as_ref_number).collect(),
+            _ => Vec::new(),
+        };
+
+        let mut content = Vec::new();
+        for number in streams {
+            if let Ok(data) = self.document.stream_data(number) {
+                content.extend_from_slice(&data);
+                content.push(b'\n');
+            }
+        }
+        content
+    }
+
+    fn font(&mut self, resources: &PdfValue, name: &str) -> Option<Rc<FontDecoder>> {
+        let fonts = self.document.resolve(resources.get("Font")?);
+        let font_ref = fonts.get(name)?;
+        if let Some(number) = font_ref.as_ref_number() {
+            if let Some(font) = self.fonts.get(&number) {
+                return Some(font.clone());
+            }
+            let font = Rc::new(self.load_font(&self.document.resolve(font_ref)));
+            self.fonts.insert(number, font.clone());
+            Some(font)
+        } else {
+            Some(Rc::new(self.load_font(font_ref)))
+        }
+    }
+
+    fn load_font(&self, font: &PdfValue) -> FontDecoder {
+        let composite = font.get("Subtype").and_then(PdfValue::as_name) == Some("Type0");
+        let mut decoder = FontDecoder::simple();
+        if composite {
+            decoder.code_bytes = 2;
+        }
+        if let Some(cmap) = font
+            .get("ToUnicode")
+            .and_then(PdfValue::as_ref_number)
+            .and_then(|number| self.document.stream_data(number).ok())
+        {
+            parse_to_unicode(&cmap, &mut decoder);
+        }
+        decoder
+    }
+
+    /// Interpret a content stream, writing the text it shows
+    fn run(&mut self, content: &[u8], resources: &PdfValue, writer: &mut TextWriter, depth: usize) {
+        let mut lexer = Lexer::new(content);
+        let mut operands: Vec<PdfValue> = Vec::new();
+        let fallback = Rc::new(FontDecoder::simple());
+        let mut font = fallback.clone();
+        // Vertical text position, and where the last text was shown; a change
+        // between the two starts a new line
+        let mut text_y = 0.0;
+        let mut leading = 0.0;
+        let mut shown_y: Option<f64> = None;
+
+        while let Some(token) = lexer.next_token() {
+            let operator = match token {
+                Token::Keyword(keyword) => keyword,
+                other => {
+                    if let Some(value) = lexer.value_from_token(other) {
+                        operands.push(value);
+                    }
+                    continue;
+                }
+            };
+
+            let number = |index: usize| operands.get(index).and_then(PdfValue::as_number).unwrap_or(0.0);
+            match operator.as_str() {
+                "Tf" => {
+                    font = operands
+                        .first()
+                        .and_then(PdfValue::as_name)
+                        .and_then(|name| self.font(resources, name))
+                        .unwrap_or_else(|| fallback.clone());
+                }
+                "BT" => text_y = 0.0,
+                "TL" => leading = number(0),
+                "Td" | "TD" => {
+                    let (tx, ty) = (number(0), number(1));
+                    if operator == "TD" {
+                        leading = -ty;
+                    }
+                    if ty.abs() <= 0.01 && tx > 0.0 {
+                        writer.space();
+                    }
+                    text_y += ty;
+                }
+                "Tm" => {
+                    text_y = number(5);
+                    writer.space();
+                }
+                "T*" | "'" | "\"" => {
+                    if leading == 0.0 {
+                        writer.newline();
+                    }
+                    text_y -= leading;
+                }
+                _ => {}
+            }
+            if matches!(operator.as_str(), "Tj" | "'" | "\"" | "TJ") {
+                if shown_y.is_some_and(|y: f64| (y - text_y).abs() > 1.0) {
+                    writer.newline();
+                }
+                shown_y = Some(text_y);
+            }
+            match operator.as_str() {
+                "Tj" | "'" | "\"" => {
+                    if let Some(PdfValue::String(bytes)) = operands.last() {
+                        writer.push(&font.decode(bytes));
+                    }
+                }
+                "TJ" => {
+                    for item in operands.first().and_then(PdfValue::as_array).unwrap_or(&[]) {
+                        match item {
+                            PdfValue::String(bytes) => writer.push(&font.decode(bytes)),
+                            PdfValue::Number(adjustment) if *adjustment < -WORD_GAP => writer.space(),
+                            _ => {}
+                        }
+                    }
+                }
+                "Do" if depth < MAX_FORM_DEPTH => {
+                    if let Some(name) = operands.first().and_then(PdfValue::as_name) {
+                        self.run_form(resources, name, writer, depth);
+                    }
+                }
+                // Inline image data is binary; skip it
+                "ID" => lexer.skip_inline_image(),
+                _ => {}
+            }
+            operands.clear();
+        }
+    }
+
+    fn run_form(&mut self, resources: &PdfValue, name: &str, writer: &mut TextWriter, depth: usize) {
+        let Some(number) = resources
+            .get("XObject")
+            .map(|xobjects| self.document.resolve(xobjects))
+            .and_then(|xobjects| xobjects.get(name).and_then(PdfValue::as_ref_number))
+        else {
+            return;
+        };
+        let Some(form) = self
+            .document
+            .dictionary(number)
+            .filter(|dict| dict.get("Subtype").and_then(PdfValue::as_name) == Some("Form"))
+        else {
+            return;
+        };
+        let Ok(content) = self.document.stream_data(number) else {
+            return;
+        };
+        let form_resources = match form.get("Resources") {
+            Some(own) => self.document.resolve(own),
+            None => resources.clone(),
+        };
+        writer.newline();
+        self.run(&content, &form_resources, writer, depth + 1);
+        writer.newline();
+    }
+}
+
+/// Read the code-space width and bfchar/bfrange mappings of a ToUnicode CMap
+fn parse_to_unicode(cmap: &[u8], decoder: &mut FontDecoder) {
+    let mut lexer = Lexer::new(cmap);
+    let mut section: Option<String> = None;
+    let mut pending: Vec<PdfValue> = Vec::new();
+    let mut width_set = false;
+
+    while let Some(token) = lexer.next_token() {
+        if let Token::Keyword(keyword) = &token {
+            match keyword.as_str() {
+                "begincodespacerange" | "beginbfchar" | "beginbfrange" => {
+                    section = Some(keyword.clone());
+                    pending.clear();
+                }
+                "endcodespacerange" | "endbfchar" | "endbfrange" => section = None,
+                _ => {}
+            }
+            continue;
+        }
+        let Some(section) = section.as_deref() else {
+            continue;
+        };
+        let Some(value) = lexer.value_from_token(token) else {
+            break;
+        };
+        pending.push(value);
+
+        match (section, pending.as_slice()) {
+            ("begincodespacerange", [PdfValue::String(low), _]) => {
+                if !width_set && !low.is_empty() {
+                    decoder.code_bytes = low.len();
+                    width_set = true;
+                }
+                pending.clear();
+            }
+            ("beginbfchar", [PdfValue::String(source), destination]) => {
+                if let PdfValue::String(target) = destination {
+                    decoder.to_unicode.insert(code_of(source), utf16_text(target));
+                }
+                pending.clear();
+            }
+            ("beginbfrange", [PdfValue::String(low), PdfValue::String(high), destination]) => {
+                let (low, high) = (code_of(low), code_of(high));
+                // Guard against absurd ranges in damaged maps
+                if high >= low && high - low <= 0xFFFF {
+                    match destination {
+                        PdfValue::String(start) => {
+                            for (offset, code) in (low..=high).enumerate() {
+                                decoder.to_unicode.insert(code, utf16_text_offset(start, offset as u32));
+                            }
+                        }
+                        PdfValue::Array(targets) => {
+                            for (code, target) in (low..=high).zip(targets) {
+                                if let PdfValue::String(target) = target {
+                                    decoder.to_unicode.insert(code, utf16_text(target));
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                pending.clear();
+            }
+            (_, values) if values.len() >= 3 => pending.clear(),
+            _ => {}
+        }
+    }
+}
+
+fn code_of(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0u32, |acc, &b| (acc << 8) | b as u32)
+}
+
+fn utf16_text(bytes: &[u8]) -> String {
+    utf16_text_offset(bytes, 0)
+}
+
+/// UTF-16BE destination string, with `offset` added to its last code unit
+fn utf16_text_offset(bytes: &[u8], offset: u32) -> String {
+    let mut units: Vec<u16> = bytes
+        .chunks(2)
+        .map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)]))
+        .collect();
+    if let Some(last) = units.last_mut() {
+        *last = last.wrapping_add(offset as u16);
+    }
+    String::from_utf16_lossy(&units)
+}
+
+/// Accumulates shown text into lines
+#[derive(Default)]
+struct TextWriter {
+    text: String,
+}
+
+impl TextWriter {
+    fn push(&mut self, text: &str) {
+        self.text.extend(text.chars().filter(|c| !c.is_control() || *c == '\n'));
+    }
+
+    fn space(&mut self) {
+        if !self.text.is_empty() && !self.text.ends_with([' ', '\n']) {
+            self.text.push(' ');
+        }
+    }
+
+    fn newline(&mut self) {
+        if !self.text.is_empty() && !self.text.ends_with("\n\n") {
+            self.text.push('\n');
+        }
+    }
+
+    /// Tidy whitespace: single spaces within lines, at most one blank line
+    fn finish(self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        for line in self.text.lines() {
+            let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+            if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+                continue;
+            }
+            lines.push(line);
+        }
+        while lines.last().is_some_and(|last| last.is_empty()) {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
+}
@@ -0,0 +1,310 @@
+use crate::docx_markdown::{attribute, read_part, render_table};
+use anyhow::{Context, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+
+/// Render the slides of a PPTX as markdown without LibreOffice.
+///
+/// Each slide becomes a `## Slide N: <title>` section holding its body text
+/// and tables, followed by the speaker notes when there are any. Images,
+/// charts, SmartArt and hidden slide masters are not rendered.
+pub fn pptx_to_markdown(path: &Path) -> Result<String> {
+    let file = File::open(path).with_context(|| format!("Failed to open PPTX: {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .with_context(|| format!("Not a valid PPTX (zip) file: {}", path.display()))?;
+
+    let presentation = read_part(&mut archive, "ppt/presentation.xml")?
+        .with_context(|| format!("PPTX has no ppt/presentation.xml: {}", path.display()))?;
+    let relationships = match read_part(&mut archive, "ppt/_rels/presentation.xml.rels")? {
+        Some(xml) => relationship_targets(&xml, "ppt"),
+        None => HashMap::new(),
+    };
+
+    let mut sections = Vec::new();
+    for (index, slide_part) in slide_parts(&presentation, &relationships).iter().enumerate() {
+        let Some(slide_xml) = read_part(&mut archive, slide_part)? else {
+            continue;
+        };
+        let slide = parse_slide(&slide_xml).with_context(|| format!("Malformed {}", slide_part))?;
+
+        let notes = match related_part(&mut archive, slide_part, "/notesSlide")? {
+            Some(notes_part) => match read_part(&mut archive, &notes_part)? {
+                Some(notes_xml) => parse_slide(&notes_xml)
+                    .with_context(|| format!("Malformed {}", notes_part))?
+                    .notes_text(),
+                None => String::new(),
+            },
+            None => String::new(),
+        };
+
+        sections.push(render_slide(index + 1, &slide, &notes));
+    }
+
+    if sections.is_empty() {
+        return Ok("*Presentation contains no slides*".to_string());
+    }
+    Ok(sections.join("\n\n"))
+}
+
+/// Relationship id → part name, with targets resolved against `base_dir`
+pub(crate) fn relationship_targets(xml: &str, base_dir: &str) -> HashMap<String, String> {
+    relationships(xml)
+        .into_iter()
+        .map(|(id, _, target)| (id, resolve_target(base_dir, &target)))
+        .collect()
+}
+
+/// `(id, type, target)` of every relationship in a `.rels` part
+fn relationships(xml: &str) -> Vec<(String, String, String)> {
+    let mut found = Vec::new();
+    let mut reader = Reader::from_str(xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Empty(e)) | Ok(Event::Start(e)) if e.local_name().as_ref() == b"Relationship" => {
+                if let (Some(id), Some(target)) = (attribute(&e, b"Id"), attribute(&e, b"Target")) {
+                    found.push((id, attribute(&e, b"Type").unwrap_or_default(), target));
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    found
+}
+
+/// Resolve a relationship target (relative to the folder of the part that
+/// owns the relationships) to a part name inside the package
+fn resolve_target(base_dir: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+    let mut segments: Vec<&str> = base_dir.split('/').filter(|s| !s.is_empty()).collect();
+    for segment in target.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            other => segments.push(other),
+        }
+    }
+    segments.join("/")
+}
+
+/// Slide part names in presentation order
+fn slide_parts(presentation_xml: &str, relationships: &HashMap<String, String>) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut reader = Reader::from_str(presentation_xml);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Empty(e)) | Ok(Event::Start(e)) if e.local_name().as_ref() == b"sldId" => {
+                if let Some(part) = relationship_id(&e).and_then(|id| relationships.get(&id)) {
+                    parts.push(part.clone());
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    parts
+}
+
+/// The `r:id` attribute of a `p:sldId`; its local name clashes with the
+/// numeric `id` attribute, so it is matched on the qualified name
+fn relationship_id(element: &quick_xml::events::BytesStart) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref().ends_with(b":id"))
+        .map(|attr| String::from_utf8_lossy(&attr.value).to_string())
+}
+
+/// Part name of the first part `part` relates to with a relationship type
+/// ending in `kind` (e.g. a slide's `/notesSlide`), if it has one
+pub(crate) fn related_part(archive: &mut zip::ZipArchive<File>, part: &str, kind: &str) -> Result<Option<String>> {
+    let (part_dir, part_file) = part.rsplit_once('/').unwrap_or(("", part));
+    let rels_part = format!("{}/_rels/{}.rels", part_dir, part_file);
+    let Some(rels_xml) = read_part(archive, &rels_part)? else {
+        return Ok(None);
+    };
+    Ok(relationships(&rels_xml)
+        .into_iter()
+        .find(|(_, relationship_kind, _)| relationship_kind.ends_with(kind))
+        .map(|(_, _, target)| resolve_target(part_dir, &target)))
+}
+
+#[derive(Default)]
+struct Shape {
+    /// Placeholder type (`title`, `body`, `sldNum`, ...); `body` when the
+    /// placeholder has no explicit type
+    placeholder: Option<String>,
+    paragraphs: Vec<String>,
+}
+
+#[derive(Default)]
+struct Slide {
+    shapes: Vec<Shape>,
+    tables: Vec<Vec<Vec<String>>>,
+}
+
+impl Slide {
+    fn title(&self) -> Option<String> {
+        self.shapes
+            .iter()
+            .find(|shape| matches!(shape.placeholder.as_deref(), Some("title") | Some("ctrTitle")))
+            .map(|shape| shape.paragraphs.join(" "))
+            .filter(|title| !title.is_empty())
+    }
+
+    /// Text of everything except the title and slide furniture
+    fn body_paragraphs(&self) -> Vec<&str> {
+        self.shapes
+            .iter()
+            .filter(|shape| {
+                !matches!(
+                    shape.placeholder.as_deref(),
+                    Some("title") | Some("ctrTitle") | Some("sldNum") | Some("dt") | Some("ftr") | Some("hdr")
+                )
+            })
+            .flat_map(|shape| shape.paragraphs.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Speaker notes live in the body placeholder of the notes slide
+    fn notes_text(&self) -> String {
+        self.shapes
+            .iter()
+            .filter(|shape| shape.placeholder.as_deref() == Some("body"))
+            .flat_map(|shape| shape.paragraphs.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+fn parse_slide(xml: &str) -> Result<Slide> {
+    let mut reader = Reader::from_str(xml);
+    let mut slide = Slide::default();
+    let mut shapes: Vec<Shape> = Vec::new();
+    let mut tables: Vec<Vec<Vec<String>>> = Vec::new();
+    let mut paragraph: Option<String> = None;
+    let mut paragraph_level = 0usize;
+    let mut in_text = false;
+
+    loop {
+        let event = reader.read_event()?;
+        match event {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"sp" => shapes.push(Shape::default()),
+                b"ph" => set_placeholder(&mut shapes, attribute(&e, b"type")),
+                b"p" => {
+                    paragraph = Some(String::new());
+                    paragraph_level = 0;
+                }
+                b"pPr" => paragraph_level = attribute(&e, b"lvl").and_then(|l| l.parse().ok()).unwrap_or(0),
+                b"t" => in_text = true,
+                b"tbl" => tables.push(Vec::new()),
+                b"tr" => {
+                    if let Some(table) = tables.last_mut() {
+                        table.push(Vec::new());
+                    }
+                }
+                b"tc" => {
+                    if let Some(row) = tables.last_mut().and_then(|table| table.last_mut()) {
+                        row.push(String::new());
+                    }
+                }
+                _ => {}
+            },
+            Event::Empty(e) => match e.local_name().as_ref() {
+                b"ph" => set_placeholder(&mut shapes, attribute(&e, b"type")),
+                b"pPr" => paragraph_level = attribute(&e, b"lvl").and_then(|l| l.parse().ok()).unwrap_or(0),
+                b"br" => {
+                    if let Some(paragraph) = paragraph.as_mut() {
+                        paragraph.push(' ');
+                    }
+                }
+                _ => {}
+            },
+            Event::Text(e) if in_text => {
+                if let Some(paragraph) = paragraph.as_mut() {
+                    paragraph.push_str(&e.unescape()?);
+                }
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"p" => {
+                    let text = paragraph
+                        .take()
+                        .map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+                        .unwrap_or_default();
+                    if text.is_empty() {
+                        continue;
+                    }
+                    let cell = tables
+                        .last_mut()
+                        .and_then(|table| table.last_mut())
+                        .and_then(|row| row.last_mut());
+                    if let Some(cell) = cell {
+                        if !cell.is_empty() {
+                            cell.push_str("<br>");
+                        }
+                        cell.push_str(&text);
+                    } else if let Some(shape) = shapes.last_mut() {
+                        // Indented outline levels become nested list items
+                        shape.paragraphs.push(if paragraph_level > 0 {
+                            format!("{}- {}", "  ".repeat(paragraph_level.min(8) - 1), text)
+                        } else {
+                            text
+                        });
+                    }
+                }
+                b"tbl" => {
+                    if let Some(table) = tables.pop() {
+                        slide.tables.push(table);
+                    }
+                }
+                b"sp" => {
+                    if let Some(shape) = shapes.pop() {
+                        slide.shapes.push(shape);
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(slide)
+}
+
+fn set_placeholder(shapes: &mut [Shape], kind: Option<String>) {
+    if let Some(shape) = shapes.last_mut() {
+        shape.placeholder = Some(kind.unwrap_or_else(|| "body".to_string()));
+    }
+}
+
+fn render_slide(number: usize, slide: &Slide, notes: &str) -> String {
+    let mut blocks = vec![match slide.title() {
+        Some(title) => format!("## Slide {}: {}", number, title),
+        None => format!("## Slide {}", number),
+    }];
+
+    let body = slide.body_paragraphs();
+    if !body.is_empty() {
+        blocks.push(body.join("\n\n"));
+    }
+    for table in &slide.tables {
+        if let Some(table) = render_table(table) {
+            blocks.push(table);
+        }
+    }
+    if !notes.is_empty() {
+        blocks.push(format!("### Speaker Notes\n\n{}", notes));
+    }
+
+    blocks.join("\n\n")
+}
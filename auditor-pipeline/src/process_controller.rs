@@ -0,0 +1,2237 @@
+use crate::checksums;
+use crate::conversion_engine::ConversionEngine;
+use crate::corpus_stats;
+use crate::decompression_engine::DecompressionEngine;
+use crate::duplicates;
+use crate::email_threads::EmailThreadDeduplicator;
+use crate::engineering_formats;
+use crate::ept_logger::EPTLogger;
+use crate::events::{EventSink, ProgressTracker, ProgressUpdate};
+use crate::file_ownership::OwnershipReader;
+use crate::file_scanner::FileScanner;
+use crate::file_signatures;
+use crate::folder_naming::FolderNaming;
+use crate::hashing_service::{FileDigests, HashAlgorithm, HashingService};
+use crate::image_metadata;
+use crate::indicators::IndicatorList;
+use crate::llm_export_engine::LLMExportEngine;
+use crate::long_paths;
+use crate::manifest::ExportManifest;
+use crate::office_encryption::{self, OfficeDecryptor};
+use crate::office_properties;
+use crate::output_checks::OutputValidator;
+use crate::path_filter::{self, PathFilter, PatternExclusion};
+use crate::report_model::{self, ArchiveRecord, ExcludedFile, FailureStage, FileStatus, ReportModel};
+use crate::remediation::{self, Remediation, RemediationSummary};
+use crate::report_signing;
+use crate::report_writer::ReportWriter;
+use crate::run_metadata::RunMetadata;
+use crate::run_state::{ExtractionOutcome, ProgressRecord, RunCheckpoint, RunProgress, RunState, RUN_PROGRESS_FILE_NAME};
+use crate::run_summary::{ExportStats, RunSummary, StageTimer};
+use crate::search_index::SearchIndex;
+use crate::summarization::Summarizer;
+use crate::system_files::SystemFileList;
+use crate::settings::{ProcessingSettings, SettingsSnapshot};
+use crate::tagging::TaggingEngine;
+use crate::vba_macros;
+use crate::workspace::{ExtractedArchivePolicy, StagingCleanup, StagingWorkspace};
+use anyhow::{bail, Context, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessingResult {
+    pub entries: Vec<ReportModel>,
+    pub staging_path: String,
+    pub llm_output_path: String,
+    /// The workbook, or the CSV report when only that is written
+    pub report_path: String,
+    /// Present when `build_search_index` was enabled and the index was written
+    pub search_index_path: Option<String>,
+    /// Combined corpus files, when `combined_corpus` is enabled
+    pub corpus_paths: Vec<String>,
+    /// Present when `write_jsonl_export` was enabled
+    pub jsonl_path: Option<String>,
+    /// Maps every exported file to the evidence it came from, as JSON and CSV
+    pub manifest_path: String,
+    pub manifest_csv_path: String,
+    /// Every archive opened or skipped during decompression
+    pub archives: Vec<ArchiveRecord>,
+    pub summary: RunSummary,
+    /// Effective configuration of the run, also written to `run_settings.json`
+    pub settings_snapshot: SettingsSnapshot,
+    /// Next steps for files that weren't exported, with how many files each covers
+    pub remediations: Vec<RemediationSummary>,
+}
+
+/// Tracks the last emitted progress update so bursts can be coalesced
+#[derive(Default)]
+struct ProgressThrottle {
+    last_emit: Option<Instant>,
+    last_current: usize,
+    last_category: String,
+}
+
+/// One file handed to a conversion worker
+struct FileJob {
+    /// Position of the entry in `report_entries`
+    index: usize,
+    entry: ReportModel,
+    file_path: PathBuf,
+    /// Outcome of the LibreOffice batch pass, if the file was part of it
+    precomputed: Option<std::result::Result<PathBuf, String>>,
+    indicator_match: Option<String>,
+    /// Known-file list entry the file matched
+    known_file: Option<String>,
+    /// Decrypted copy, or why it couldn't be made, for password-protected files
+    unlocked: Option<std::result::Result<PathBuf, String>>,
+    /// Digests taken by the parallel hashing step
+    digests: Option<FileDigests>,
+}
+
+/// Staging subfolder that files matching the indicator list are moved into
+const QUARANTINE_FOLDER_NAME: &str = "_quarantine";
+
+pub struct ProcessController {
+    logger: EPTLogger,
+    decompression_engine: DecompressionEngine,
+    report_entries: Vec<ReportModel>,
+    /// Staging paths of archives moved aside after extraction; not scanned
+    set_aside_archives: Vec<PathBuf>,
+    /// Hidden and system files left out by the staging copy or the scan
+    excluded_files: Vec<ExcludedFile>,
+    /// Who started the run, where and with what, for the report
+    run_metadata: Option<RunMetadata>,
+    events: Arc<dyn EventSink>,
+    settings: ProcessingSettings,
+    progress_throttle: RefCell<ProgressThrottle>,
+    progress: ProgressTracker,
+    naming: FolderNaming,
+    /// Log the file stages record their progress in, for resuming the run
+    run_progress: Option<RunProgress>,
+    /// Digests an interrupted run recorded, by entry position
+    resumed_digests: std::collections::HashMap<usize, FileDigests>,
+}
+
+impl ProcessController {
+    pub fn new(logger: EPTLogger, events: Arc<dyn EventSink>, settings: ProcessingSettings) -> Self {
+        let logger_clone = logger.clone();
+        let naming = FolderNaming::from_settings(&settings);
+        let decompression_engine = DecompressionEngine::new(logger, &settings, naming.clone());
+        Self {
+            logger: logger_clone,
+            decompression_engine,
+            report_entries: Vec::new(),
+            set_aside_archives: Vec::new(),
+            excluded_files: Vec::new(),
+            run_metadata: None,
+            events,
+            settings,
+            progress_throttle: RefCell::new(ProgressThrottle::default()),
+            progress: ProgressTracker::new(),
+            naming,
+            run_progress: None,
+            resumed_digests: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Record progress in `tracker`, e.g. one held in application state so
+    /// the UI can query it after a reload
+    pub fn with_progress_tracker(mut self, tracker: ProgressTracker) -> Self {
+        self.progress = tracker;
+        self
+    }
+    
+    fn emit_progress(&self, current: usize, total: usize, task_category: &str) {
+        self.emit_update(ProgressUpdate {
+            current,
+            total,
+            task_category: task_category.to_string(),
+            export: None,
+            paused: self.progress.is_paused(),
+        });
+    }
+
+    /// Per-file progress of the LLM export, with its running counts
+    fn emit_export_progress(&self, current: usize, total: usize, stats: &ExportStats) {
+        self.emit_update(ProgressUpdate {
+            current,
+            total,
+            task_category: "Exporting files".to_string(),
+            export: Some(stats.clone()),
+            paused: self.progress.is_paused(),
+        });
+    }
+
+    fn emit_update(&self, update: ProgressUpdate) {
+        // The tracker always holds the exact latest state; only events are throttled
+        self.progress.record(&update);
+        if !self.should_emit_progress(update.current, update.total, &update.task_category) {
+            return;
+        }
+        self.events.emit_progress(&update);
+    }
+
+    /// Coalesce progress updates: emit on a category change, on the final
+    /// state, once the configured interval has elapsed, or after N increments.
+    fn should_emit_progress(&self, current: usize, total: usize, task_category: &str) -> bool {
+        let mut throttle = self.progress_throttle.borrow_mut();
+        let now = Instant::now();
+
+        let is_final = current >= total;
+        let category_changed = throttle.last_category != task_category;
+        let interval_elapsed = match throttle.last_emit {
+            Some(last) => now.duration_since(last) >= Duration::from_millis(self.settings.progress_interval_ms),
+            None => true,
+        };
+        let count_reached = self.settings.progress_every_n > 0
+            && current.saturating_sub(throttle.last_current) >= self.settings.progress_every_n;
+
+        if is_final || category_changed || interval_elapsed || count_reached {
+            throttle.last_emit = Some(now);
+            throttle.last_current = current;
+            throttle.last_category = task_category.to_string();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn start_processing(&mut self, input_path: &Path) -> Result<ProcessingResult> {
+        self.progress.start_run();
+        let result = self.run_pipeline(input_path);
+        self.progress.finish_run();
+        result
+    }
+
+    fn run_pipeline(&mut self, input_path: &Path) -> Result<ProcessingResult> {
+        self.logger.info("Starting processing...");
+        // Staging and export paths derive from the input, so all of them
+        // can exceed MAX_PATH on Windows
+        let input_path = &long_paths::extended(input_path);
+        self.settings.validate().context("Invalid processing settings")?;
+        self.report_entries.clear();
+        self.excluded_files.clear();
+        let libreoffice = ConversionEngine::new(self.logger.clone()).find_libreoffice().ok();
+        self.run_metadata = Some(RunMetadata::capture(
+            &self.settings,
+            &long_paths::simplified(input_path),
+            libreoffice.as_deref(),
+        ));
+        let mut timer = StageTimer::start();
+        
+        // 1. Prepare Workspace (Expand ZIP or Copy Folder)
+        self.progress.begin_stage("workspace");
+        let working_path = self.prepare_workspace(input_path)
+            .context("Failed to prepare workspace")?;
+        timer.finish_stage("workspace");
+        
+        // 2. Recursive Decompression
+        self.progress.begin_stage("decompression");
+        self.decompress_archives(&working_path)
+            .context("Failed during recursive decompression")?;
+        self.dispose_extracted_archives(&working_path)
+            .context("Failed to set aside extracted archives")?;
+        timer.finish_stage("decompression");
+        
+        // 3. Scan Files
+        self.progress.begin_stage("scan");
+        self.scan_files(&working_path)
+            .context("Failed to scan files")?;
+        if self.settings.capture_ownership {
+            self.capture_ownership(input_path);
+        }
+        timer.finish_stage("scan");
+
+        // Checkpoint the scan so an interrupted run can be resumed from here.
+        // Nothing is written into an input folder used in place.
+        let in_staging = working_path != input_path;
+        let archives = self.decompression_engine.archives().to_vec();
+        let extraction_outcomes = self.extraction_outcomes(&working_path);
+        if in_staging {
+            self.save_checkpoint(&working_path, &archives, &extraction_outcomes);
+        }
+        self.process_and_finalize(&working_path, in_staging, &archives, &extraction_outcomes, None, timer)
+    }
+
+    /// Continue a run that was interrupted during the file stages, from the
+    /// checkpoint and progress log in its staging folder. Files already
+    /// hashed or processed aren't hashed or processed again; the later
+    /// stages, export and report run as usual.
+    pub fn resume_processing(&mut self, staging_path: &Path) -> Result<ProcessingResult> {
+        self.progress.start_run();
+        let result = self.run_resume(&long_paths::extended(staging_path));
+        self.progress.finish_run();
+        result
+    }
+
+    fn run_resume(&mut self, staging_path: &Path) -> Result<ProcessingResult> {
+        self.settings.validate().context("Invalid processing settings")?;
+        let checkpoint = RunCheckpoint::load(staging_path)?;
+        let (digests, finished) = RunProgress::load(staging_path)?;
+        self.restore_run_settings(&checkpoint.state);
+        self.report_entries = checkpoint.state.entries;
+        let pending: std::collections::HashSet<usize> = (0..self.report_entries.len())
+            .filter(|index| !finished.contains_key(index))
+            .collect();
+        for (index, entry) in finished {
+            if let Some(slot) = self.report_entries.get_mut(index) {
+                *slot = entry;
+            }
+        }
+        self.logger.info(&format!(
+            "Resuming {}: {} of {} files already processed, {} already hashed",
+            staging_path.display(),
+            self.report_entries.len() - pending.len(),
+            self.report_entries.len(),
+            digests.len()
+        ));
+        self.resumed_digests = digests;
+        match RunProgress::open(staging_path) {
+            Ok(log) => self.run_progress = Some(log),
+            Err(e) => self.logger.warning(&format!("{:#}", e)),
+        }
+
+        let timer = StageTimer::start();
+        self.process_and_finalize(
+            staging_path,
+            true,
+            &checkpoint.state.archives,
+            &checkpoint.extraction_outcomes,
+            Some(&pending),
+            timer,
+        )
+    }
+
+    /// Stages 4 to 6 of a run: the file stages (for the entries in `only`,
+    /// when given), export, report and staging cleanup
+    fn process_and_finalize(
+        &mut self,
+        working_path: &Path,
+        in_staging: bool,
+        archives: &[ArchiveRecord],
+        extraction_outcomes: &[ExtractionOutcome],
+        only: Option<&std::collections::HashSet<usize>>,
+        mut timer: StageTimer,
+    ) -> Result<ProcessingResult> {
+        let total_files = self.report_entries.len();
+        self.logger.info(&format!("Found {} files. Starting conversion and hashing...", total_files));
+        
+        // 4. Process Files (Hash, Convert)
+        // Progress updates are handled inside process_file_entries
+        self.progress.begin_stage("processing");
+        self.process_file_entries(working_path, only)
+            .context("Failed during file processing loop")?;
+        self.apply_extraction_outcomes(extraction_outcomes);
+        self.group_duplicates();
+        timer.finish_stage("processing");
+        
+        // 4a. Flag converted outputs that look empty or garbled
+        if self.settings.validate_conversions {
+            self.progress.begin_stage("validation");
+            self.emit_progress(0, 0, "Checking converted files");
+            let validator = OutputValidator::new(self.logger.clone());
+            validator.check_entries(working_path, &mut self.report_entries);
+            timer.finish_stage("validation");
+        }
+        
+        // 4b. Optionally shrink the staging workspace
+        if self.settings.compress_staging_passthrough {
+            self.progress.begin_stage("workspace_compression");
+            let workspace = StagingWorkspace::new(self.logger.clone());
+            let conversion_engine = ConversionEngine::with_settings(self.logger.clone(), &self.settings);
+            workspace.compress_passthrough_files(working_path, &mut self.report_entries, &conversion_engine)
+                .context("Failed to compress staging workspace")?;
+            timer.finish_stage("workspace_compression");
+        }
+        
+        // 4c. Optionally collapse email threads to their most inclusive message
+        if self.settings.dedupe_email_threads {
+            self.progress.begin_stage("email_threads");
+            let deduplicator = EmailThreadDeduplicator::new(self.logger.clone());
+            deduplicator.suppress_quoted_messages(working_path, &mut self.report_entries);
+            timer.finish_stage("email_threads");
+        }
+        
+        // 4d. Tag text content against the user's keyword/regex lists
+        if !self.settings.tag_rules.is_empty() {
+            self.progress.begin_stage("tagging");
+            self.emit_progress(0, 0, "Tagging content");
+            let tagging_engine = TaggingEngine::new(self.logger.clone(), &self.settings.tag_rules)
+                .context("Invalid tag rules")?;
+            tagging_engine.tag_entries(working_path, &mut self.report_entries);
+            timer.finish_stage("tagging");
+        }
+        
+        // 5. Finalize Output (Export, Report). What the stages above produced
+        // is kept in staging so `rerun_finalize` can redo this step alone.
+        self.progress.begin_stage("finalize");
+        if in_staging {
+            self.save_run_state(working_path, total_files, archives);
+        }
+        let staging_kept = !in_staging || self.settings.staging_cleanup == StagingCleanup::Keep;
+        let mut result = self.finalize_output(working_path, total_files, archives, staging_kept, &timer)
+            .context("Failed to finalize output")?;
+        // The run state supersedes the checkpoint now the run has finished
+        self.run_progress = None;
+        if in_staging {
+            RunCheckpoint::remove(working_path);
+        }
+        timer.finish_stage("finalize");
+
+        // 6. Clean up staging; the run already succeeded, so failures only warn.
+        // When the input was used in place there is no staging copy to remove.
+        if self.settings.staging_cleanup != StagingCleanup::Keep && in_staging {
+            self.progress.begin_stage("cleanup");
+            let workspace = StagingWorkspace::new(self.logger.clone());
+            if let Err(e) = workspace.cleanup(working_path, self.settings.staging_cleanup, self.settings.shred_passes) {
+                self.logger.warning(&format!("Staging cleanup incomplete: {}", e));
+            }
+            timer.finish_stage("cleanup");
+        }
+        timer.apply_to(&mut result.summary);
+        
+        self.logger.info(&format!(
+            "Processing complete. {} files processed. Output: {}",
+            self.report_entries.len(),
+            result.llm_output_path
+        ));
+        
+        Ok(result)
+    }
+
+    /// Redo only the export and report of an earlier run, from the state it
+    /// saved in its kept staging folder. Export, report, indexing and
+    /// summarization settings take effect; settings of the per-file stages
+    /// (conversion, tagging, thread deduplication) do not, as those stages
+    /// aren't repeated. The earlier export folder is removed per
+    /// `staging_cleanup`, or renamed aside when staging is kept.
+    pub fn rerun_finalize(&mut self, staging_path: &Path) -> Result<ProcessingResult> {
+        self.progress.start_run();
+        let result = self.run_finalize_only(&long_paths::extended(staging_path));
+        self.progress.finish_run();
+        result
+    }
+
+    fn run_finalize_only(&mut self, staging_path: &Path) -> Result<ProcessingResult> {
+        self.settings.validate().context("Invalid processing settings")?;
+        let state = RunState::load(staging_path)?;
+        self.logger.info(&format!(
+            "Redoing export and report of {} from {} saved entries",
+            staging_path.display(),
+            state.entries.len()
+        ));
+
+        self.restore_run_settings(&state);
+        self.report_entries = state.entries;
+
+        let mut timer = StageTimer::start();
+        self.progress.begin_stage("finalize");
+        let mut result = self.redo_finalize(staging_path, state.total_files, &state.archives, &timer)?;
+        timer.finish_stage("finalize");
+        timer.apply_to(&mut result.summary);
+        Ok(result)
+    }
+
+    /// Process the entries of an earlier run whose original relative paths
+    /// are in `entry_ids` again, e.g. after installing OCR or supplying a
+    /// document password, then redo the export and report. The other entries
+    /// keep their saved outcome; conversion settings apply to the selected
+    /// entries only. Quarantined files stay where they are.
+    pub fn reprocess_entries(&mut self, staging_path: &Path, entry_ids: &[String]) -> Result<ProcessingResult> {
+        self.progress.start_run();
+        let result = self.run_reprocess_entries(&long_paths::extended(staging_path), entry_ids);
+        self.progress.finish_run();
+        result
+    }
+
+    fn run_reprocess_entries(&mut self, staging_path: &Path, entry_ids: &[String]) -> Result<ProcessingResult> {
+        self.settings.validate().context("Invalid processing settings")?;
+        let state = RunState::load(staging_path)?;
+        self.restore_run_settings(&state);
+        self.report_entries = state.entries;
+
+        let mut selected = std::collections::HashSet::new();
+        for entry_id in entry_ids {
+            let Some(index) = self
+                .report_entries
+                .iter()
+                .position(|entry| &entry.original_relative_path == entry_id)
+            else {
+                bail!("Run has no entry {}", entry_id);
+            };
+            if self.report_entries[index].status == FileStatus::Quarantined {
+                self.logger.warning(&format!("Not reprocessing quarantined file {}", entry_id));
+                continue;
+            }
+            // A pass-through file compressed in staging is processed from the original again
+            StagingWorkspace::new(self.logger.clone())
+                .restore_compressed(staging_path, &self.report_entries[index])
+                .with_context(|| format!("Failed to restore {} for reprocessing", entry_id))?;
+            self.report_entries[index].reset_processing();
+            selected.insert(index);
+        }
+        self.logger.info(&format!(
+            "Reprocessing {} of {} entries of {}",
+            selected.len(),
+            self.report_entries.len(),
+            staging_path.display()
+        ));
+
+        let mut timer = StageTimer::start();
+        self.progress.begin_stage("processing");
+        self.process_file_entries(staging_path, Some(&selected))
+            .context("Failed during file processing loop")?;
+        self.group_duplicates();
+        timer.finish_stage("processing");
+
+        // Validation and tagging only look at the reprocessed entries, so
+        // they run over those alone
+        let mut reprocessed: Vec<ReportModel> = selected.iter().map(|&index| self.report_entries[index].clone()).collect();
+        if self.settings.validate_conversions {
+            self.progress.begin_stage("validation");
+            self.emit_progress(0, 0, "Checking converted files");
+            let validator = OutputValidator::new(self.logger.clone());
+            validator.check_entries(staging_path, &mut reprocessed);
+            timer.finish_stage("validation");
+        }
+        if !self.settings.tag_rules.is_empty() {
+            self.progress.begin_stage("tagging");
+            self.emit_progress(0, 0, "Tagging content");
+            let tagging_engine = TaggingEngine::new(self.logger.clone(), &self.settings.tag_rules)
+                .context("Invalid tag rules")?;
+            tagging_engine.tag_entries(staging_path, &mut reprocessed);
+            timer.finish_stage("tagging");
+        }
+        for (&index, entry) in selected.iter().zip(reprocessed) {
+            self.report_entries[index] = entry;
+        }
+
+        // The updated entries replace the saved ones, so a later rerun or
+        // reprocessing starts from them
+        self.progress.begin_stage("finalize");
+        self.save_run_state(staging_path, state.total_files, &state.archives);
+        let mut result = self.redo_finalize(staging_path, state.total_files, &state.archives, &timer)?;
+        timer.finish_stage("finalize");
+        timer.apply_to(&mut result.summary);
+        Ok(result)
+    }
+
+    /// Group files with identical content, now that every file is hashed
+    fn group_duplicates(&mut self) {
+        let groups = duplicates::assign_groups(&mut self.report_entries);
+        if groups > 0 {
+            let (copies, redundant_bytes) = self
+                .report_entries
+                .iter()
+                .filter(|entry| entry.duplicate_of.is_some())
+                .fold((0, 0u64), |(count, bytes), entry| (count + 1, bytes + entry.file_size_bytes));
+            self.logger.info(&format!(
+                "{} redundant copies in {} duplicate group(s), {}",
+                copies,
+                groups,
+                report_model::format_file_size(redundant_bytes)
+            ));
+        }
+    }
+
+    /// Take over the identity and filing location of a saved run, and the
+    /// algorithms its saved hashes were taken with
+    fn restore_run_settings(&mut self, state: &RunState) {
+        self.settings.run_id = state.run_id.clone();
+        self.settings.hash_algorithm = state.hash_algorithm;
+        self.settings.additional_hash_algorithms = state.additional_hash_algorithms.clone();
+        if self.settings.output_root.is_none() {
+            self.settings.output_root = state.output_root.clone();
+        }
+        if self.settings.engagement_code.is_none() {
+            self.settings.engagement_code = state.engagement_code.clone();
+        }
+        self.excluded_files = state.excluded_files.clone();
+        self.run_metadata = state.run_metadata.clone();
+    }
+
+    /// Remove the run's earlier export the way `staging_cleanup` removes
+    /// staging, and export again. With `keep` the earlier export is renamed
+    /// to `<name>_previous` instead, numbered if that exists.
+    fn redo_finalize(
+        &mut self,
+        staging_path: &Path,
+        total_files: usize,
+        archives: &[ArchiveRecord],
+        timer: &StageTimer,
+    ) -> Result<ProcessingResult> {
+        let llm_output_path = self.llm_output_path(staging_path)?;
+        if llm_output_path.exists() {
+            match self.settings.staging_cleanup {
+                StagingCleanup::Keep => {
+                    let previous = Self::previous_export_path(&llm_output_path);
+                    self.logger.info(&format!(
+                        "Renaming the previous export {} to {}",
+                        llm_output_path.display(),
+                        previous.display()
+                    ));
+                    fs::rename(&llm_output_path, &previous).with_context(|| {
+                        format!("Failed to rename the previous export: {}", llm_output_path.display())
+                    })?;
+                }
+                mode => StagingWorkspace::new(self.logger.clone()).remove_folder(
+                    &llm_output_path,
+                    "previous export",
+                    mode,
+                    self.settings.shred_passes,
+                )?,
+            }
+        }
+        self.finalize_output(staging_path, total_files, archives, true, timer)
+            .context("Failed to finalize output")
+    }
+
+    /// `<export>_previous`, or `<export>_previous_2` and so on if taken
+    fn previous_export_path(llm_output_path: &Path) -> PathBuf {
+        let name = llm_output_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let mut candidate = llm_output_path.with_file_name(format!("{}_previous", name));
+        let mut counter = 2;
+        while candidate.exists() {
+            candidate = llm_output_path.with_file_name(format!("{}_previous_{}", name, counter));
+            counter += 1;
+        }
+        candidate
+    }
+
+    /// Save the pre-export entries in staging; failing to only costs the
+    /// option of a rerun, so it warns
+    fn save_run_state(&self, working_path: &Path, total_files: usize, archives: &[ArchiveRecord]) {
+        if let Err(e) = self.current_run_state(total_files, archives).save(working_path) {
+            self.logger.warning(&format!("{:#}", e));
+        }
+    }
+
+    /// Save the scanned entries in staging and start the progress log, so
+    /// the run can be resumed if it is interrupted; failing to only costs
+    /// that option, so it warns
+    fn save_checkpoint(&mut self, working_path: &Path, archives: &[ArchiveRecord], extraction_outcomes: &[ExtractionOutcome]) {
+        let checkpoint = RunCheckpoint {
+            state: self.current_run_state(self.report_entries.len(), archives),
+            extraction_outcomes: extraction_outcomes.to_vec(),
+        };
+        // A progress log left by an earlier attempt doesn't match the new scan
+        let _ = fs::remove_file(working_path.join(RUN_PROGRESS_FILE_NAME));
+        match checkpoint.save(working_path).and_then(|_| RunProgress::open(working_path)) {
+            Ok(log) => self.run_progress = Some(log),
+            Err(e) => self.logger.warning(&format!("Run can't be resumed if interrupted: {:#}", e)),
+        }
+    }
+
+    fn current_run_state(&self, total_files: usize, archives: &[ArchiveRecord]) -> RunState {
+        RunState {
+            run_id: self.settings.run_id.clone(),
+            saved_at: chrono::Local::now().to_rfc3339(),
+            entries: self.report_entries.clone(),
+            archives: archives.to_vec(),
+            total_files,
+            output_root: self.settings.output_root.clone(),
+            engagement_code: self.settings.engagement_code.clone(),
+            hash_algorithm: self.settings.hash_algorithm,
+            additional_hash_algorithms: self.settings.additional_hash_algorithms.clone(),
+            excluded_files: self.excluded_files.clone(),
+            run_metadata: self.run_metadata.clone(),
+            settings: Some(self.settings.without_secrets()),
+        }
+    }
+
+    /// Append to the progress log; a failed write stops the logging, as
+    /// it only costs the option of resuming
+    fn record_progress(&mut self, record: ProgressRecord) {
+        if let Some(log) = &mut self.run_progress {
+            if let Err(e) = log.append(&record) {
+                self.logger.warning(&format!("Run can't be resumed if interrupted: {:#}", e));
+                self.run_progress = None;
+            }
+        }
+    }
+
+    fn prepare_workspace(&mut self, input_path: &Path) -> Result<PathBuf> {
+        if input_path.is_file() {
+            if let Some(ext) = input_path.extension().and_then(|e| e.to_str()) {
+                if ext.to_lowercase() == "zip" {
+                    self.logger.info(&format!("Input is a ZIP file, expanding: {}", input_path.display()));
+                    self.emit_progress(0, 1, "Decompressing zip files");
+                    return self.decompression_engine.expand_input_zip(input_path)
+                        .with_context(|| format!("Failed to expand zip file: {}", input_path.display()));
+                }
+            }
+        } else if input_path.is_dir() {
+            self.logger.info(&format!("Input is a folder, copying to staging folder: {}", input_path.display()));
+            self.emit_progress(0, 1, "Preparing staging folder");
+            
+            let folder_name = input_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("folder");
+            
+            let parent_dir = input_path
+                .parent()
+                .context("Input folder has no parent directory")?;
+            
+            let staging_path = self.naming.unique_path(parent_dir, folder_name, "");
+            
+            self.logger.debug(&format!("Copying folder {} to staging folder {}", 
+                input_path.display(), staging_path.display()));
+            
+            self.copy_directory_recursive(input_path, &staging_path)
+                .with_context(|| format!("Failed to copy directory from {} to {}", input_path.display(), staging_path.display()))?;
+            
+            return Ok(staging_path);
+        }
+        
+        // Fallback if not zip or dir (should be handled by caller usually)
+        Ok(input_path.to_path_buf())
+    }
+
+    fn decompress_archives(&mut self, working_path: &Path) -> Result<()> {
+        self.logger.info("Starting recursive decompression...");
+        self.emit_progress(0, 1, "Decompressing zip files");
+        self.decompression_engine.recursive_decompress(working_path)
+            .context("Failed to recursively decompress archives")
+    }
+
+    fn dispose_extracted_archives(&mut self, working_path: &Path) -> Result<()> {
+        self.set_aside_archives.clear();
+        let policy = self.settings.extracted_archives;
+        if policy == ExtractedArchivePolicy::Keep {
+            return Ok(());
+        }
+        let workspace = StagingWorkspace::new(self.logger.clone());
+        let archives = self.decompression_engine.extracted_archives().to_vec();
+        for (archive, new_path) in workspace.dispose_extracted_archives(working_path, &archives, policy)? {
+            let display_path = archive
+                .strip_prefix(working_path)
+                .unwrap_or(&archive)
+                .to_string_lossy()
+                .to_string();
+            let note = match new_path {
+                Some(new_path) => {
+                    let note = format!(
+                        "Moved to {} after extraction",
+                        new_path.strip_prefix(working_path).unwrap_or(&new_path).display()
+                    );
+                    self.set_aside_archives.push(new_path);
+                    note
+                }
+                None => "Deleted from staging after extraction".to_string(),
+            };
+            self.decompression_engine.annotate_archive(&display_path, &note);
+        }
+        Ok(())
+    }
+
+    fn scan_files(&mut self, working_path: &Path) -> Result<()> {
+        self.logger.info("Scanning and cataloging files...");
+        self.emit_progress(0, 0, "Scanning files");
+        let filter = PathFilter::new(&self.settings.include_patterns, &self.settings.exclude_patterns)?;
+        let scanner = FileScanner::with_logger(self.logger.clone())
+            .with_system_files(self.system_file_list()?)
+            .with_filter(filter, self.settings.pattern_excluded_files)
+            .with_limits(self.settings.scan_limits.clone());
+        let outcome = scanner.scan_with_logging(working_path)
+            .context("File scanner failed")?;
+        self.report_entries = outcome.entries;
+        self.excluded_files.extend(outcome.excluded);
+        if !self.excluded_files.is_empty() {
+            self.logger.info(&format!(
+                "{} hidden/system file(s) left out; see the Exclusions sheet",
+                self.excluded_files.len()
+            ));
+        }
+        if let Some(reason) = outcome.limit_hit {
+            let inventory_path = self.write_partial_inventory(working_path)?;
+            bail!(
+                "Scan stopped early because the {}. A partial inventory of the {} files found so far was written to {}. \
+                 Select a narrower input or raise the scan limits in the settings.",
+                reason,
+                self.report_entries.len(),
+                inventory_path.display()
+            );
+        }
+
+        // Archives already listed on the Archives sheet are not counted again
+        if !self.set_aside_archives.is_empty() {
+            let set_aside: std::collections::HashSet<PathBuf> = self.set_aside_archives.iter().cloned().collect();
+            self.report_entries
+                .retain(|entry| !set_aside.contains(&working_path.join(&entry.original_relative_path)));
+        }
+
+        // Keep the undecoded ZIP entry name of files whose name had to be decoded
+        for (extracted_path, raw_name) in self.decompression_engine.raw_entry_names() {
+            let relative_path = match extracted_path.strip_prefix(working_path) {
+                Ok(relative) => relative.to_string_lossy().to_string(),
+                Err(_) => continue,
+            };
+            if let Some(entry) = self
+                .report_entries
+                .iter_mut()
+                .find(|entry| entry.original_relative_path == relative_path)
+            {
+                entry.raw_archive_entry_name = Some(raw_name.clone());
+            }
+        }
+
+        // Link email and PDF attachments to the document they were extracted from
+        let relative = |path: &Path| path.strip_prefix(working_path).ok().map(|p| p.to_string_lossy().to_string());
+        let parents: std::collections::HashMap<String, String> = self
+            .decompression_engine
+            .attachment_parents()
+            .iter()
+            .filter_map(|(attachment, parent)| Some((relative(attachment)?, relative(parent)?)))
+            .collect();
+        if !parents.is_empty() {
+            for entry in &mut self.report_entries {
+                if let Some(parent) = parents.get(&entry.original_relative_path) {
+                    entry.parent_document = Some(parent.clone());
+                }
+            }
+        }
+
+        // Record the innermost archive each extracted file came out of
+        let outputs: Vec<(PathBuf, String)> = self
+            .decompression_engine
+            .extraction_outputs()
+            .iter()
+            .filter_map(|(output, archive)| Some((output.clone(), relative(archive)?)))
+            .collect();
+        if !outputs.is_empty() {
+            for entry in &mut self.report_entries {
+                let path = working_path.join(&entry.original_relative_path);
+                entry.source_archive = outputs
+                    .iter()
+                    .filter(|(output, _)| path.starts_with(output))
+                    .max_by_key(|(output, _)| output.components().count())
+                    .map(|(_, archive)| archive.clone());
+            }
+        }
+
+        self.classify_extensionless_files(working_path);
+        self.sniff_content_types(working_path);
+        if self.settings.capture_exif {
+            self.capture_exif(working_path);
+        }
+        Ok(())
+    }
+
+    /// Patterns of the hidden and system files to leave out; `None` when
+    /// they are included
+    fn system_file_list(&self) -> Result<Option<SystemFileList>> {
+        if self.settings.include_hidden_files {
+            return Ok(None);
+        }
+        SystemFileList::new(&self.settings.system_file_patterns).map(Some)
+    }
+
+    /// Write the report of a scan stopped by a scan limit, listing the files
+    /// found before it stopped, none of them processed
+    fn write_partial_inventory(&self, working_path: &Path) -> Result<PathBuf> {
+        let input_name = working_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("output");
+        let llm_output_path = self.llm_output_path(working_path)?;
+        fs::create_dir_all(&llm_output_path)
+            .with_context(|| format!("Failed to create output folder: {}", llm_output_path.display()))?;
+        let inventory_path = llm_output_path.join(format!("{}_LLM_partial-inventory.xlsx", input_name));
+        ReportWriter::new(self.logger.clone())
+            .with_run_metadata(self.run_metadata.clone())
+            .generate_report(
+                &self.report_entries,
+                self.decompression_engine.archives(),
+                &self.excluded_files,
+                &RunSummary::from_entries(&self.report_entries, &ExportStats::default()),
+                &SettingsSnapshot::capture(&self.settings),
+                &inventory_path,
+            )
+            .context("Failed to write partial inventory")?;
+        Ok(inventory_path)
+    }
+
+    /// Record the MIME type each file's content shows, and flag files whose
+    /// extension claims another format, e.g. a `.pdf` that is a zip
+    fn sniff_content_types(&mut self, working_path: &Path) {
+        self.emit_progress(0, 0, "Checking file types");
+        self.report_entries.par_iter_mut().for_each(|entry| {
+            let file_path = working_path.join(&entry.relative_path);
+            let Some(detected) = file_signatures::detect(&file_path) else {
+                return;
+            };
+            entry.detected_mime = Some(detected.mime.to_string());
+            if let Some(extension) = file_path.extension().and_then(|e| e.to_str()) {
+                if detected.contradicts_extension(extension) {
+                    entry.extension_mismatch =
+                        Some(format!("Named .{} but the content is a {}", extension.to_lowercase(), detected.description));
+                }
+            }
+        });
+        let mismatches = self.report_entries.iter().filter(|entry| entry.extension_mismatch.is_some()).count();
+        if mismatches > 0 {
+            self.logger.warning(&format!("{} file(s) have an extension that doesn't match their content", mismatches));
+        }
+    }
+
+    /// Record who owns each file and its permissions, read from the input
+    /// folder as the staging copy belongs to whoever ran the tool. Zip
+    /// inputs and files extracted from archives carry no ownership.
+    fn capture_ownership(&mut self, input_path: &Path) {
+        if !input_path.is_dir() {
+            self.logger.info("File ownership is only recorded for folder inputs");
+            return;
+        }
+        self.emit_progress(0, 0, "Reading file ownership");
+        let reader = OwnershipReader::new();
+        self.report_entries.par_iter_mut().for_each(|entry| {
+            let source_path = input_path.join(&entry.original_relative_path);
+            if !source_path.is_file() {
+                return;
+            }
+            if let Ok(ownership) = reader.read(&source_path) {
+                entry.owner = ownership.owner;
+                entry.owner_group = ownership.group;
+                entry.permissions = ownership.permissions;
+            }
+        });
+    }
+
+    /// Record when, where and with what camera each image was taken, from
+    /// its EXIF data
+    fn capture_exif(&mut self, working_path: &Path) {
+        self.emit_progress(0, 0, "Reading image metadata");
+        self.report_entries.par_iter_mut().for_each(|entry| {
+            let file_path = working_path.join(&entry.relative_path);
+            if !image_metadata::has_exif_extension(&file_path) {
+                return;
+            }
+            if let Ok(metadata) = image_metadata::read_exif(&file_path) {
+                entry.exif_taken_at = metadata.taken_at;
+                entry.exif_gps = metadata.gps_coordinates;
+                entry.exif_camera = metadata.camera_model;
+            }
+        });
+        let located = self.report_entries.iter().filter(|entry| entry.exif_gps.is_some()).count();
+        if located > 0 {
+            self.logger.info(&format!("{} image(s) carry GPS coordinates", located));
+        }
+    }
+
+    /// Give extension-less files the extension their content shows, so they
+    /// are converted and exported like any other file of that type. The
+    /// staging copy is renamed; the original name stays on the entry.
+    fn classify_extensionless_files(&mut self, working_path: &Path) {
+        let mut classified = 0;
+        for entry in &mut self.report_entries {
+            let file_path = working_path.join(&entry.relative_path);
+            if file_path.extension().is_some() || !file_path.is_file() {
+                continue;
+            }
+            let Some(detected) = file_signatures::detect(&file_path) else {
+                self.logger.debug(&format!("Could not recognise extension-less file {}", entry.relative_path));
+                continue;
+            };
+
+            let file_name = file_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let typed_path = std::iter::once(format!("{}.{}", file_name, detected.extension))
+                .chain((2..).map(|n| format!("{}_{}.{}", file_name, n, detected.extension)))
+                .map(|name| file_path.with_file_name(name))
+                .find(|path| !path.exists())
+                .expect("unbounded counter always finds a free name");
+            if let Err(e) = fs::rename(&file_path, &typed_path) {
+                self.logger.warning(&format!("Failed to rename {}: {}", file_path.display(), e));
+                continue;
+            }
+
+            entry.file_name = typed_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| entry.file_name.clone());
+            entry.relative_path = typed_path
+                .strip_prefix(working_path)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|_| typed_path.display().to_string());
+            entry.staged_relative_path = Some(entry.relative_path.clone());
+            entry.file_type = detected.extension.to_string();
+            entry.detected_type = Some(format!("{} (detected from content)", detected.description));
+            classified += 1;
+        }
+        if classified > 0 {
+            self.logger.info(&format!("Recognised the type of {} extension-less file(s) from their content", classified));
+        }
+    }
+
+    /// Hash, check and convert the scanned files; with `only`, just the
+    /// entries at those positions
+    fn process_file_entries(
+        &mut self,
+        working_path: &Path,
+        only: Option<&std::collections::HashSet<usize>>,
+    ) -> Result<()> {
+        let hashing_service = HashingService::with_algorithm(self.settings.hash_algorithm)
+            .with_additional(&self.settings.additional_hash_algorithms);
+        let conversion_engine = ConversionEngine::with_settings(self.logger.clone(), &self.settings);
+
+        // Canonicalize working path for security validation
+        let working_path_canonical = working_path.canonicalize()
+            .context("Failed to canonicalize working path")?;
+        
+        // Collect file paths with their original indices to avoid borrowing issues
+        // SECURITY: Filter out entries with path traversal attempts, but track original indices
+        let file_paths_with_indices: Vec<_> = self.report_entries
+            .iter()
+            .enumerate()
+            .filter(|(orig_idx, _)| only.map_or(true, |only| only.contains(orig_idx)))
+            .filter(|(_, entry)| !path_filter::is_pattern_excluded(entry))
+            .filter_map(|(orig_idx, entry)| {
+                // SECURITY: Safely resolve relative paths and validate they stay within working directory
+                self.safe_resolve_path(working_path, &working_path_canonical, &entry.relative_path)
+                    .map(|path| (orig_idx, path))
+            })
+            .collect();
+        
+        // Mark entries that were filtered out due to security issues
+        let valid_indices: std::collections::HashSet<usize> = file_paths_with_indices
+            .iter()
+            .map(|(idx, _)| *idx)
+            .collect();
+        for (idx, entry) in self.report_entries.iter_mut().enumerate() {
+            if only.map_or(true, |only| only.contains(&idx))
+                && !valid_indices.contains(&idx)
+                && !path_filter::is_pattern_excluded(entry)
+            {
+                entry.status = FileStatus::Failed { stage: FailureStage::PathValidation };
+                entry.skip_reason = Some("Path validation failed - potential path traversal".to_string());
+            }
+        }
+        
+        let file_paths: Vec<_> = file_paths_with_indices.iter().map(|(_, path)| path).collect();
+        
+        // Count files that will actually be converted/processed
+        // (convertible files OR LLM-readable files)
+        let conversion_count = file_paths
+            .iter()
+            .filter(|file_path| {
+                file_path.exists()
+                    && (conversion_engine.is_convertible_file(file_path)
+                        || ReportModel::is_llm_readable(file_path))
+            })
+            .count();
+        
+        self.logger.info(&format!(
+            "Found {} files to convert/process out of {} total files",
+            conversion_count,
+            self.report_entries.len()
+        ));
+        
+        // Hash everything up front in parallel; the indicator check, the
+        // conversion cache and the workers all reuse the digests. The pass
+        // also takes whatever digests the indicator and known-file lists
+        // hold. Digests an interrupted run recorded aren't taken again
+        // unless a list needs one they lack.
+        let indicator_list = self.load_hash_list(self.settings.indicator_list_path.as_deref(), "indicator")?;
+        let known_file_list = self.load_hash_list(self.settings.known_files_list_path.as_deref(), "known-file")?;
+        let list_algorithms: Vec<HashAlgorithm> = indicator_list
+            .iter()
+            .chain(known_file_list.iter())
+            .flat_map(|list| list.algorithms())
+            .collect();
+        let list_hashing_service = HashingService::with_algorithm(self.settings.hash_algorithm)
+            .with_additional(&self.settings.additional_hash_algorithms)
+            .with_additional(&list_algorithms);
+        let resumed_digests = std::mem::take(&mut self.resumed_digests);
+        let unhashed: Vec<&PathBuf> = file_paths_with_indices
+            .iter()
+            .filter(|(orig_idx, _)| {
+                resumed_digests.get(orig_idx).map_or(true, |digests| {
+                    list_algorithms.iter().any(|&algorithm| digests.get(algorithm).is_none())
+                })
+            })
+            .map(|(_, path)| path)
+            .collect();
+        let mut list_digests = self.hash_files(&unhashed, &list_hashing_service);
+        let mut known_hashes = std::collections::HashMap::new();
+        for (orig_idx, file_path) in &file_paths_with_indices {
+            if let Some(digests) = resumed_digests.get(orig_idx) {
+                known_hashes.insert(file_path.clone(), digests.clone());
+                list_digests.entry(file_path.clone()).or_insert_with(|| digests.clone());
+            } else if let Some(digests) = list_digests.get(file_path) {
+                // Digests taken only for the lists stay out of the report
+                let digests = digests.keeping(&self.settings.additional_hash_algorithms);
+                let record = ProgressRecord::Hashed { index: *orig_idx, digests: digests.clone() };
+                self.record_progress(record);
+                known_hashes.insert(file_path.clone(), digests);
+            }
+        }
+
+        // Known-bad files are caught before any converter opens them
+        let indicator_matches = self.check_indicator_list(&file_paths, indicator_list.as_ref(), &list_digests);
+        
+        // OS and application files are excluded before anything converts them
+        let known_files =
+            self.check_known_files(&file_paths, known_file_list.as_ref(), &list_digests, &indicator_matches);
+        let not_converted: std::collections::HashSet<PathBuf> =
+            indicator_matches.keys().chain(known_files.keys()).cloned().collect();
+        
+        // Password-protected Office documents are converted from a decrypted
+        // copy when a supplied password works, and skipped otherwise
+        let unlocked = self.unlock_encrypted_documents(&file_paths, &not_converted, &conversion_engine);
+        
+        if let Some(cache_dir) = &self.settings.conversion_cache_dir {
+            self.logger.info(&format!(
+                "{} conversions cached in {}",
+                if self.settings.force_reconversion { "Refreshing" } else { "Reusing" },
+                cache_dir.display()
+            ));
+        }
+        
+        // Pre-convert LibreOffice-bound documents in batches so a single
+        // soffice process handles many files. Documents whose output is
+        // cached are left out of the batches.
+        let mut batch_results = if self.settings.libreoffice_batch_size > 1 {
+            let check_cache = self.settings.conversion_cache_dir.is_some() && !self.settings.force_reconversion;
+            let libreoffice_files: Vec<PathBuf> = file_paths
+                .iter()
+                .filter(|file_path| !not_converted.contains(file_path.as_path()))
+                .filter(|file_path| file_path.exists() && conversion_engine.requires_libreoffice(file_path))
+                .filter_map(|file_path| {
+                    let source = match unlocked.get(file_path.as_path()) {
+                        Some(Ok(decrypted)) => decrypted.clone(),
+                        Some(Err(_)) => return None,
+                        None => file_path.to_path_buf(),
+                    };
+                    if check_cache {
+                        if let Some(digests) = known_hashes.get(file_path.as_path()) {
+                            if conversion_engine.has_cached_conversion(&source, &digests.primary) {
+                                return None;
+                            }
+                        }
+                    }
+                    Some(source)
+                })
+                .collect();
+            if !libreoffice_files.is_empty() {
+                self.logger.info(&format!(
+                    "Converting {} documents with LibreOffice in batches of up to {}",
+                    libreoffice_files.len(),
+                    self.settings.libreoffice_batch_size
+                ));
+                self.emit_progress(0, libreoffice_files.len(), "Batch converting documents");
+            }
+            conversion_engine.convert_batches(
+                &libreoffice_files,
+                working_path,
+                self.settings.libreoffice_batch_size,
+                |done, total| self.emit_progress(done, total, "Batch converting documents"),
+            )
+        } else {
+            std::collections::HashMap::new()
+        };
+        
+        if conversion_count > 0 {
+            self.emit_progress(0, conversion_count, "Converting Documents");
+        }
+        
+        // Everything a worker needs to process one file, taken out of the
+        // shared state up front so workers never touch `self`
+        let jobs: Vec<FileJob> = file_paths_with_indices
+            .iter()
+            .map(|(orig_idx, file_path)| {
+                let unlocked = unlocked.get(file_path.as_path()).cloned();
+                let conversion_source = match &unlocked {
+                    Some(Ok(decrypted)) => decrypted.clone(),
+                    _ => file_path.clone(),
+                };
+                FileJob {
+                    index: *orig_idx,
+                    entry: self.report_entries[*orig_idx].clone(),
+                    precomputed: batch_results.remove(conversion_source.as_path()),
+                    indicator_match: indicator_matches.get(file_path.as_path()).cloned(),
+                    known_file: known_files.get(file_path.as_path()).cloned(),
+                    unlocked,
+                    digests: known_hashes.remove(file_path.as_path()),
+                    file_path: file_path.clone(),
+                }
+            })
+            .collect();
+        
+        let workers = self.settings.conversion_workers.clamp(1, jobs.len().max(1));
+        if workers > 1 {
+            self.logger.info(&format!("Processing files with {} workers", workers));
+        }
+        
+        // Workers pull jobs from a shared queue and send finished entries
+        // back; only this thread writes report entries and emits progress
+        let queue = Mutex::new(jobs.into_iter());
+        let (sender, receiver) = mpsc::channel();
+        let logger = self.logger.clone();
+        let vba_naming = self.settings.extract_vba_macros.then(|| self.naming.clone());
+        let progress = self.progress.clone();
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                let sender = sender.clone();
+                let (queue, logger, conversion_engine, hashing_service, vba_naming, progress) =
+                    (&queue, &logger, &conversion_engine, &hashing_service, vba_naming.as_ref(), &progress);
+                scope.spawn(move || loop {
+                    // A pause takes effect between files
+                    progress.wait_while_paused();
+                    let Some(job) = queue.lock().ok().and_then(|mut jobs| jobs.next()) else {
+                        break;
+                    };
+                    let finished = Self::process_file_job(
+                        logger,
+                        job,
+                        working_path,
+                        conversion_engine,
+                        hashing_service,
+                        vba_naming,
+                    );
+                    if sender.send(finished).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(sender);
+            
+            let mut processed_count = 0;
+            for (index, entry, needs_processing) in receiver {
+                self.record_progress(ProgressRecord::Processed { index, entry: entry.clone() });
+                self.report_entries[index] = entry;
+                // Only increment progress counter for files that were actually processed
+                if needs_processing {
+                    processed_count += 1;
+                    self.emit_progress(processed_count, conversion_count, "Converting Documents");
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Hash, check and convert one file. Returns the entry's index, the
+    /// updated entry and whether the file counted towards conversion progress.
+    fn process_file_job(
+        logger: &EPTLogger,
+        job: FileJob,
+        working_path: &Path,
+        conversion_engine: &ConversionEngine,
+        hashing_service: &HashingService,
+        vba_naming: Option<&FolderNaming>,
+    ) -> (usize, ReportModel, bool) {
+        let FileJob { index, mut entry, file_path, precomputed, indicator_match, known_file, unlocked, digests } = job;
+        
+        if !file_path.exists() {
+            entry.status = FileStatus::Failed { stage: FailureStage::Missing };
+            entry.skip_reason = Some("File not found".to_string());
+            logger.debug(&format!("Skipping non-existent file: {}", file_path.display()));
+            return (index, entry, false);
+        }
+        
+        // Check if this file needs conversion/processing
+        let is_convertible = conversion_engine.is_convertible_file(&file_path);
+        let is_llm_readable = ReportModel::is_llm_readable(&file_path);
+        let needs_processing = is_convertible || is_llm_readable;
+        
+        // Log file being processed
+        if needs_processing {
+            logger.info(&format!(
+                "Processing file: {}",
+                file_path.file_name().and_then(|n| n.to_str()).unwrap_or("unknown")
+            ));
+        } else {
+            logger.debug(&format!("Skipping non-convertible file: {}", file_path.display()));
+        }
+        
+        // Hash the file, unless the hashing step already did
+        match digests.map(Ok).unwrap_or_else(|| hashing_service.hash_file_all(&file_path)) {
+            Ok(FileDigests { primary: hash, additional, .. }) => {
+                // Get hash prefix for logging before moving
+                let hash_prefix = hash[..16.min(hash.len())].to_string();
+                entry.original_hash = Some(hash);
+                entry.additional_hashes = additional
+                    .into_iter()
+                    .map(|(algorithm, digest)| (algorithm.column_label().to_string(), digest))
+                    .collect();
+                logger.debug(&format!("Hashed file: {} ({}: {}...)", 
+                    file_path.display(), 
+                    hashing_service.algorithm().label(),
+                    hash_prefix));
+            }
+            Err(e) => {
+                logger.warning(&format!(
+                    "Failed to hash {}: {}",
+                    file_path.display(),
+                    e
+                ));
+                entry.status = FileStatus::Failed { stage: FailureStage::Hashing };
+                entry.skip_reason = Some(format!("Hash failed: {}", e));
+                return (index, entry, needs_processing);
+            }
+        }
+        
+        if let Some(indicator_match) = indicator_match {
+            Self::quarantine_entry(logger, &mut entry, &file_path, working_path, &indicator_match);
+            return (index, entry, needs_processing);
+        }
+        
+        if let Some(known_file) = known_file {
+            entry.status = FileStatus::KnownFile;
+            entry.skip_reason = Some(format!("Known system file – excluded: {}", known_file));
+            return (index, entry, needs_processing);
+        }
+        
+        let conversion_source = match unlocked {
+            Some(Ok(decrypted)) => decrypted,
+            Some(Err(reason)) => {
+                entry.status = FileStatus::SkippedEncrypted;
+                entry.skip_reason = Some(reason);
+                return (index, entry, needs_processing);
+            }
+            None => file_path.clone(),
+        };
+        
+        if office_properties::has_office_extension(&file_path) {
+            match office_properties::read_properties(&conversion_source) {
+                Ok(properties) => {
+                    entry.document_author = properties.author;
+                    entry.document_last_modified_by = properties.last_modified_by;
+                    entry.document_company = properties.company;
+                    entry.document_application = properties.application;
+                }
+                Err(e) => logger.debug(&format!(
+                    "No document properties read from {}: {:#}",
+                    entry.original_relative_path, e
+                )),
+            }
+        }
+        
+        if vba_macros::contains_macros(&conversion_source) {
+            entry.contains_macros = true;
+            logger.info(&format!("Macros found in {}", entry.original_relative_path));
+            if let Some(naming) = vba_naming {
+                Self::extract_vba_source(logger, &mut entry, &conversion_source, &file_path, working_path, naming);
+            }
+        }
+        
+        // Check conversion, using the batch conversion outcome when there is one
+        let precomputed = precomputed.map(|outcome| outcome.map(Some).map_err(|e| anyhow::anyhow!(e)));
+        Self::process_single_file_conversion(
+            logger,
+            &mut entry, 
+            &conversion_source, 
+            working_path, 
+            conversion_engine, 
+            hashing_service,
+            precomputed
+        );
+        (index, entry, needs_processing)
+    }
+
+    /// Hash every file on a bounded thread pool, emitting aggregated
+    /// progress from this thread. Files that fail to hash are left out; their
+    /// worker hashes them again and reports the failure.
+    fn hash_files(
+        &self,
+        file_paths: &[&PathBuf],
+        hashing_service: &HashingService,
+    ) -> std::collections::HashMap<PathBuf, FileDigests> {
+        let threads = match self.settings.hashing_threads {
+            0 => std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            n => n,
+        };
+        let pool = match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+            Ok(pool) => pool,
+            Err(e) => {
+                self.logger.warning(&format!("Could not start hashing threads, hashing during conversion: {}", e));
+                return std::collections::HashMap::new();
+            }
+        };
+
+        self.emit_progress(0, file_paths.len(), "Hashing files");
+        let progress = self.progress.clone();
+        let (sender, receiver) = mpsc::channel();
+        let digests = std::thread::scope(|scope| {
+            let hashing = scope.spawn(|| {
+                // Results come back in `file_paths` order
+                pool.install(|| {
+                    file_paths
+                        .par_iter()
+                        .map_with(sender, |sender, file_path| {
+                            // A pause takes effect between files
+                            progress.wait_while_paused();
+                            let digests = file_path
+                                .exists()
+                                .then(|| hashing_service.hash_file_all(file_path).ok())
+                                .flatten();
+                            let _ = sender.send(());
+                            digests
+                        })
+                        .collect::<Vec<_>>()
+                })
+            });
+            let mut hashed = 0;
+            for () in receiver {
+                hashed += 1;
+                self.emit_progress(hashed, file_paths.len(), "Hashing files");
+            }
+            hashing.join().unwrap_or_default()
+        });
+
+        file_paths
+            .iter()
+            .zip(digests)
+            .filter_map(|(file_path, digests)| Some((file_path.to_path_buf(), digests?)))
+            .collect()
+    }
+
+    /// Load the indicator or known-file list at `list_path`, when one is
+    /// configured
+    fn load_hash_list(&self, list_path: Option<&Path>, kind: &str) -> Result<Option<IndicatorList>> {
+        let Some(list_path) = list_path else {
+            return Ok(None);
+        };
+        let list = IndicatorList::load(list_path).with_context(|| format!("Failed to load {} list", kind))?;
+        Ok(Some(list))
+    }
+
+    /// Match every file against the configured indicator list, returning
+    /// the matches keyed by path. Without a list this is a no-op.
+    fn check_indicator_list(
+        &self,
+        file_paths: &[&PathBuf],
+        list: Option<&IndicatorList>,
+        known_hashes: &std::collections::HashMap<PathBuf, FileDigests>,
+    ) -> std::collections::HashMap<PathBuf, String> {
+        let mut matches = std::collections::HashMap::new();
+        let (Some(list), Some(list_path)) = (list, &self.settings.indicator_list_path) else {
+            return matches;
+        };
+
+        self.logger.info(&format!(
+            "Checking {} files against {} known-bad hashes from {}",
+            file_paths.len(),
+            list.len(),
+            list_path.display()
+        ));
+        if list.is_empty() {
+            self.logger.warning("Indicator list contains no recognisable hashes");
+            return matches;
+        }
+
+        self.emit_progress(0, file_paths.len(), "Checking indicator list");
+        for (index, file_path) in file_paths.iter().enumerate() {
+            if file_path.exists() {
+                match list.lookup_file(file_path, known_hashes.get(file_path.as_path())) {
+                    Ok(Some(indicator)) => {
+                        matches.insert(file_path.to_path_buf(), indicator.describe());
+                    }
+                    Ok(None) => {}
+                    Err(e) => self.logger.warning(&format!("Indicator check failed: {}", e)),
+                }
+            }
+            self.emit_progress(index + 1, file_paths.len(), "Checking indicator list");
+        }
+
+        if !matches.is_empty() {
+            self.logger.error(&format!("{} file(s) match the known-bad indicator list", matches.len()));
+        }
+        matches
+    }
+
+    /// Match the files against the known-file list, leaving out indicator
+    /// matches, and return the matches keyed by path. Without a list this
+    /// is a no-op.
+    fn check_known_files(
+        &self,
+        file_paths: &[&PathBuf],
+        list: Option<&IndicatorList>,
+        known_hashes: &std::collections::HashMap<PathBuf, FileDigests>,
+        indicator_matches: &std::collections::HashMap<PathBuf, String>,
+    ) -> std::collections::HashMap<PathBuf, String> {
+        let mut matches = std::collections::HashMap::new();
+        let (Some(list), Some(list_path)) = (list, &self.settings.known_files_list_path) else {
+            return matches;
+        };
+
+        self.logger.info(&format!(
+            "Checking {} files against {} known-file hashes from {}",
+            file_paths.len(),
+            list.len(),
+            list_path.display()
+        ));
+        if list.is_empty() {
+            self.logger.warning("Known-file list contains no recognisable hashes");
+            return matches;
+        }
+
+        self.emit_progress(0, file_paths.len(), "Checking known-file list");
+        for (index, file_path) in file_paths.iter().enumerate() {
+            if file_path.exists() && !indicator_matches.contains_key(file_path.as_path()) {
+                match list.lookup_file(file_path, known_hashes.get(file_path.as_path())) {
+                    Ok(Some(known_file)) => {
+                        matches.insert(file_path.to_path_buf(), known_file.describe());
+                    }
+                    Ok(None) => {}
+                    Err(e) => self.logger.warning(&format!("Known-file check failed: {}", e)),
+                }
+            }
+            self.emit_progress(index + 1, file_paths.len(), "Checking known-file list");
+        }
+
+        self.logger.info(&format!("{} known system file(s) excluded", matches.len()));
+        matches
+    }
+
+    /// Detect password-protected Office documents among the convertible
+    /// files and try the supplied passwords on each. Returns the decrypted
+    /// copy, or the reason it stays locked, keyed by original path.
+    fn unlock_encrypted_documents(
+        &self,
+        file_paths: &[&PathBuf],
+        not_converted: &std::collections::HashSet<PathBuf>,
+        conversion_engine: &ConversionEngine,
+    ) -> std::collections::HashMap<PathBuf, std::result::Result<PathBuf, String>> {
+        let decryptor = OfficeDecryptor::new(self.logger.clone(), &self.settings.document_passwords);
+        let mut unlocked = std::collections::HashMap::new();
+        for file_path in file_paths {
+            if not_converted.contains(file_path.as_path())
+                || !file_path.exists()
+                || !conversion_engine.is_convertible_file(file_path)
+                || !office_encryption::is_encrypted(file_path)
+            {
+                continue;
+            }
+            let outcome = decryptor.unlock(file_path);
+            if let Err(reason) = &outcome {
+                self.logger.warning(&format!("{}: {}", file_path.display(), reason));
+            }
+            unlocked.insert(file_path.to_path_buf(), outcome);
+        }
+        unlocked
+    }
+
+    /// Write the VBA modules of a macro-enabled document into a folder next
+    /// to it in staging, recording that folder on the entry
+    fn extract_vba_source(
+        logger: &EPTLogger,
+        entry: &mut ReportModel,
+        source: &Path,
+        file_path: &Path,
+        working_path: &Path,
+        naming: &FolderNaming,
+    ) {
+        let Some(parent) = file_path.parent() else {
+            return;
+        };
+        let file_stem = file_path.file_stem().and_then(|s| s.to_str()).unwrap_or("document");
+        let output_dir = naming.unique_path(parent, &format!("{}__vba", file_stem), "");
+        let written = vba_macros::extract_modules(source)
+            .and_then(|modules| vba_macros::write_modules(&modules, &output_dir));
+        match written {
+            Ok(files) => {
+                let relative_dir = output_dir
+                    .strip_prefix(working_path)
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_else(|_| output_dir.display().to_string());
+                logger.info(&format!(
+                    "Extracted {} VBA module(s) from {} to {}",
+                    files.len(),
+                    entry.original_relative_path,
+                    relative_dir
+                ));
+                entry.vba_source_path = Some(relative_dir);
+            }
+            Err(e) => logger.warning(&format!(
+                "Could not extract VBA source from {}: {:#}",
+                entry.original_relative_path, e
+            )),
+        }
+    }
+
+    /// Move a file that matched the indicator list into `_quarantine/` in
+    /// staging so nothing downstream opens it, keeping its original identity
+    fn quarantine_entry(
+        logger: &EPTLogger,
+        entry: &mut ReportModel,
+        file_path: &Path,
+        working_path: &Path,
+        indicator_match: &str,
+    ) {
+        logger.error(&format!(
+            "INDICATOR MATCH: {} matches {}",
+            entry.original_relative_path, indicator_match
+        ));
+        entry.status = FileStatus::Quarantined;
+        entry.skip_reason = Some(format!("Matches known-bad hash: {}", indicator_match));
+        entry.indicator_match = Some(indicator_match.to_string());
+
+        let quarantine_path = working_path.join(QUARANTINE_FOLDER_NAME).join(&entry.original_relative_path);
+        let moved = quarantine_path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::rename(file_path, &quarantine_path));
+        match moved {
+            Ok(()) => {
+                entry.relative_path = Path::new(QUARANTINE_FOLDER_NAME)
+                    .join(&entry.original_relative_path)
+                    .to_string_lossy()
+                    .to_string();
+            }
+            Err(e) => logger.warning(&format!(
+                "Failed to move {} into quarantine: {}",
+                file_path.display(),
+                e
+            )),
+        }
+    }
+
+    fn process_single_file_conversion(
+        logger: &EPTLogger,
+        entry: &mut ReportModel,
+        file_path: &Path,
+        working_path: &Path,
+        conversion_engine: &ConversionEngine,
+        hashing_service: &HashingService,
+        precomputed: Option<Result<Option<PathBuf>>>
+    ) {
+        let is_convertible = conversion_engine.is_convertible_file(file_path);
+        
+        if is_convertible {
+            // Use the batch conversion outcome when there is one, then the
+            // output of an earlier run of the same source
+            let source_hash = entry.original_hash.clone();
+            let cached = match (&precomputed, source_hash.as_deref()) {
+                (None, Some(hash)) => conversion_engine.restore_cached_conversion(file_path, hash),
+                _ => None,
+            };
+            let conversion = match (precomputed, &cached) {
+                (Some(outcome), _) => outcome,
+                (None, Some(cached_path)) => Ok(Some(cached_path.clone())),
+                (None, None) => conversion_engine.convert_file(file_path, working_path),
+            };
+            // Documents LibreOffice fails on get another try with pandoc or natively
+            let mut fallback = None;
+            let conversion = match conversion {
+                Err(e) => match conversion_engine.convert_with_fallback(file_path, &format!("{:#}", e)) {
+                    Some((converted_path, backend)) => {
+                        fallback = Some(backend);
+                        Ok(Some(converted_path))
+                    }
+                    None => Err(e),
+                },
+                outcome => outcome,
+            };
+            match conversion {
+                Ok(Some(converted_path)) => {
+                    let relative_file_path = file_path
+                        .strip_prefix(working_path)
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_else(|_| file_path.display().to_string());
+                    let relative_converted_path = converted_path
+                        .strip_prefix(working_path)
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_else(|_| converted_path.display().to_string());
+                    logger.debug(&format!(
+                        "Converted {} to {}",
+                        relative_file_path,
+                        relative_converted_path
+                    ));
+                    // Extract converted file name
+                    let converted_file_name = converted_path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .map(|s| s.to_string());
+                    
+                    // Update working identity to point to converted file;
+                    // original_file_name / original_relative_path / original_hash
+                    // keep describing the source document
+                    entry.file_name = converted_file_name
+                        .clone()
+                        .unwrap_or_else(|| entry.file_name.clone());
+                    entry.relative_path = relative_converted_path.clone();
+                    
+                    // Converted artifact info for the report
+                    entry.converted_file_name = converted_file_name;
+                    entry.converted_relative_path = Some(relative_converted_path);
+                    entry.conversion_method = match fallback {
+                        Some(backend) => Some(format!("{} to Markdown (LibreOffice fallback)", backend.label())),
+                        None => conversion_engine
+                            .conversion_method(file_path)
+                            .map(|m| if cached.is_some() { format!("{} (cached)", m) } else { m.to_string() }),
+                    };
+                    entry.conversion_backend = fallback
+                        .or_else(|| conversion_engine.backend(file_path))
+                        .map(|backend| backend.label().to_string());
+                    // Fallback outputs aren't in the configured format, so they aren't cached
+                    if let (None, None, Some(hash)) = (&cached, fallback, &source_hash) {
+                        conversion_engine.cache_conversion(file_path, hash, &converted_path);
+                    }
+                    entry.output_format = converted_path
+                        .extension()
+                        .and_then(|e| e.to_str())
+                        .map(|e| e.to_lowercase());
+                    entry.source_encoding = conversion_engine.source_encoding(file_path);
+                    
+                    // Hash converted file
+                    match hashing_service.hash_file(&converted_path) {
+                        Ok(hash) => {
+                            entry.converted_hash = Some(hash);
+                        }
+                        Err(e) => {
+                            logger.warning(&format!(
+                                "Failed to hash converted file {}: {}",
+                                converted_path.display(),
+                                e
+                            ));
+                            entry.converted_hash = None;
+                        }
+                    }
+                    entry.status = FileStatus::Converted;
+                }
+                Ok(None) => {
+                    // No conversion needed, mark as processed if LLM-readable
+                    entry.status = FileStatus::CopiedAsIs;
+                }
+                Err(e) => {
+                    logger.error(&format!(
+                        "Conversion failed for {}: {}",
+                        file_path.display(),
+                        e
+                    ));
+                    entry.status = FileStatus::Failed { stage: FailureStage::Conversion };
+                    entry.skip_reason = Some(conversion_engine.failure_reason(file_path, &e.to_string()));
+                }
+            }
+        } else {
+            // Check if file is already LLM-readable
+            if ReportModel::is_llm_readable(file_path) {
+                entry.status = FileStatus::CopiedAsIs;
+            } else if let Some(info) = engineering_formats::inspect(file_path) {
+                entry.status = FileStatus::InventoryOnly;
+                entry.skip_reason = Some("Inventory only — specialist review required".to_string());
+                entry.format_details = Some(info.describe());
+            } else {
+                entry.status = FileStatus::Excluded;
+                entry.skip_reason = Some("Not LLM-readable and not convertible".to_string());
+            }
+        }
+    }
+
+    /// Status of the entries of archives that failed to extract or were
+    /// nested too deeply
+    fn extraction_outcomes(&self, working_path: &Path) -> Vec<ExtractionOutcome> {
+        let relative = |path: &Path| path.strip_prefix(working_path).ok().map(|p| p.to_string_lossy().to_string());
+        let mut outcomes: Vec<ExtractionOutcome> = self
+            .decompression_engine
+            .extraction_failures()
+            .iter()
+            .filter_map(|(archive_path, reason)| {
+                Some(ExtractionOutcome {
+                    relative_path: relative(archive_path)?,
+                    status: FileStatus::Failed { stage: FailureStage::Extraction },
+                    skip_reason: format!("Archive not extracted: {}", reason),
+                })
+            })
+            .collect();
+
+        let max_depth = self.decompression_engine.max_nesting_depth();
+        outcomes.extend(self.decompression_engine.depth_limited_archives().iter().filter_map(|archive_path| {
+            Some(ExtractionOutcome {
+                relative_path: relative(archive_path)?,
+                status: FileStatus::Excluded,
+                skip_reason: format!(
+                    "Nested archive not extracted: deeper than the {}-level nesting limit",
+                    max_depth
+                ),
+            })
+        }));
+        outcomes
+    }
+
+    /// Give archives that could not (or were not allowed to) be extracted a
+    /// clear skip reason in the report
+    fn apply_extraction_outcomes(&mut self, outcomes: &[ExtractionOutcome]) {
+        for outcome in outcomes {
+            if let Some(entry) = self
+                .report_entries
+                .iter_mut()
+                .find(|entry| entry.original_relative_path == outcome.relative_path)
+            {
+                entry.status = outcome.status.clone();
+                entry.skip_reason = Some(outcome.skip_reason.clone());
+            }
+        }
+    }
+
+    /// Export folder of a run: `<staging name>_LLM`, next to staging or under `output_root`
+    fn llm_output_path(&self, working_path: &Path) -> Result<PathBuf> {
+        let input_name = working_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("output");
+        let llm_folder_name = format!("{}_LLM", input_name);
+        
+        let parent_dir = match &self.settings.output_root {
+            Some(output_root) => long_paths::extended(output_root),
+            None => working_path
+                .parent()
+                .context("Working path has no parent directory")?
+                .to_path_buf(),
+        };
+        Ok(parent_dir.join(&llm_folder_name))
+    }
+
+    fn finalize_output(
+        &mut self,
+        working_path: &Path,
+        total_files: usize,
+        archives: &[ArchiveRecord],
+        staging_kept: bool,
+        timer: &StageTimer,
+    ) -> Result<ProcessingResult> {
+        let input_name = working_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("output");
+        let llm_output_path = self.llm_output_path(working_path)?;
+        
+        // Export LLM-readable files
+        self.logger.info("Exporting LLM-readable files...");
+        let llm_export_engine = LLMExportEngine::new(self.logger.clone(), self.settings.hash_algorithm)
+            .with_naming(self.settings.export_naming);
+        // Taken out of `self` for the export so progress can be emitted meanwhile
+        let mut entries = std::mem::take(&mut self.report_entries);
+        // Called before each entry, so a pause takes effect between files here too
+        let on_progress = |done, total, stats: &ExportStats| {
+            self.emit_export_progress(done, total, stats);
+            self.progress.wait_while_paused();
+        };
+        let export_result = if self.settings.split_export_by_top_level_folder {
+            llm_export_engine.copy_by_top_level_folder(&mut entries, working_path, &llm_output_path, on_progress)
+        } else {
+            llm_export_engine.copy_llm_readable_files(&mut entries, working_path, &llm_output_path, on_progress)
+        };
+        self.report_entries = entries;
+        let export_stats = export_result.context("Failed to export LLM-readable files")?;
+        if self.settings.chunking.is_enabled() {
+            self.emit_progress(total_files, total_files, "Splitting long documents");
+            let chunked = llm_export_engine
+                .chunk_exports(&mut self.report_entries, &llm_output_path, &self.settings.chunking)
+                .context("Failed to split long documents")?;
+            if chunked > 0 {
+                self.logger.info(&format!("{} long document(s) split into parts", chunked));
+            }
+        }
+        if self.settings.export_front_matter {
+            llm_export_engine
+                .add_front_matter(&self.report_entries, &llm_output_path)
+                .context("Failed to add front matter")?;
+        }
+        if self.settings.export_size_cap.max_total_bytes > 0 {
+            llm_export_engine
+                .apply_size_cap(&mut self.report_entries, &llm_output_path, &self.settings.export_size_cap)
+                .context("Failed to apply the export size cap")?;
+        }
+        self.emit_progress(total_files, total_files, "Estimating tokens");
+        for entry in &mut self.report_entries {
+            entry.estimated_tokens = corpus_stats::estimate_entry_tokens(&llm_output_path, entry);
+        }
+        self.emit_progress(total_files, total_files, "Finishing up");
+
+        // Index the exported text; a failure here shouldn't cost the run its report
+        let search_index_path = if self.settings.build_search_index {
+            self.emit_progress(total_files, total_files, "Building search index");
+            match SearchIndex::build(&self.logger, &llm_output_path).map(|_| SearchIndex::path(&llm_output_path)) {
+                Ok(path) => Some(path.to_string_lossy().to_string()),
+                Err(e) => {
+                    self.logger.warning(&format!("Failed to build search index: {}", e));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Optional per-document summaries, written to the entries and INDEX.md
+        if self.settings.summarization.enabled {
+            self.emit_progress(0, 0, "Summarizing documents");
+            match Summarizer::new(self.logger.clone(), self.settings.summarization.clone()) {
+                Ok(summarizer) => {
+                    // Taken out of self so progress can be emitted while summarizing
+                    let mut entries = std::mem::take(&mut self.report_entries);
+                    summarizer.summarize_entries(&llm_output_path, &mut entries, |done, total| {
+                        self.emit_progress(done, total, "Summarizing documents");
+                        self.progress.wait_while_paused();
+                    });
+                    self.report_entries = entries;
+                    if let Err(e) = summarizer.write_index(&llm_output_path, &self.report_entries) {
+                        self.logger.warning(&format!("Failed to write document index: {}", e));
+                    }
+                }
+                Err(e) => self.logger.warning(&format!("Summarization skipped: {}", e)),
+            }
+        }
+        
+        // After indexing, so the index doesn't hold every document twice
+        let corpus_paths = if self.settings.combined_corpus.enabled {
+            self.emit_progress(total_files, total_files, "Writing combined corpus");
+            llm_export_engine
+                .write_combined_corpus(&self.report_entries, &llm_output_path, &self.settings.combined_corpus)
+                .context("Failed to write combined corpus")?
+                .iter()
+                .map(|name| llm_output_path.join(name).to_string_lossy().to_string())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let jsonl_path = if self.settings.write_jsonl_export {
+            self.emit_progress(total_files, total_files, "Writing JSONL export");
+            let path = llm_export_engine
+                .write_jsonl(&self.report_entries, &llm_output_path)
+                .context("Failed to write JSONL export")?;
+            Some(path.to_string_lossy().to_string())
+        } else {
+            None
+        };
+
+        for entry in &mut self.report_entries {
+            entry.remediation = Remediation::for_entry(entry);
+        }
+        
+        // Record how the run was configured and what it exported alongside
+        // the export; written after indexing so neither is indexed
+        let settings_snapshot = SettingsSnapshot::capture(&self.settings);
+        settings_snapshot
+            .save(&llm_output_path)
+            .context("Failed to write settings snapshot")?;
+        let manifest = ExportManifest::from_entries(
+            self.settings.run_id.clone(),
+            self.settings.hash_algorithm,
+            &llm_output_path,
+            &self.report_entries,
+            self.settings.export_front_matter,
+        );
+        let manifest_path = manifest
+            .save(&llm_output_path)
+            .context("Failed to write export manifest")?;
+        let manifest_csv_path = manifest
+            .save_csv(&llm_output_path)
+            .context("Failed to write export manifest")?;
+
+        // Stage durations so far go on the Summary sheet; the caller updates
+        // them in the result once the run has finished
+        let mut summary = RunSummary::from_entries(&self.report_entries, &export_stats);
+        timer.apply_to(&mut summary);
+
+        // Generate report
+        let report_format = self.settings.report_format;
+        let xlsx_report_path = llm_output_path.join(format!("{}_LLM_file-report.xlsx", input_name));
+        let csv_report_path = llm_output_path.join(format!("{}_LLM_file-report.csv", input_name));
+        
+        self.logger.info("Generating report...");
+        // Rows link to the exported copies, and to the staged files unless
+        // staging is cleaned up once the run finishes
+        let report_writer = ReportWriter::new(self.logger.clone())
+            .with_file_links(staging_kept.then_some(working_path))
+            .with_run_metadata(self.run_metadata.clone());
+        let mut report_paths = Vec::new();
+        if report_format.writes_xlsx() {
+            report_writer
+                .generate_report(
+                    &self.report_entries,
+                    archives,
+                    &self.excluded_files,
+                    &summary,
+                    &settings_snapshot,
+                    &xlsx_report_path,
+                )
+                .context("Failed to generate Excel report")?;
+            report_paths.push(xlsx_report_path);
+        }
+        if report_format.writes_csv() {
+            report_writer
+                .generate_csv_report(&self.report_entries, &settings_snapshot, &csv_report_path)
+                .context("Failed to generate CSV report")?;
+            report_paths.push(csv_report_path);
+        }
+        if self.settings.write_html_report {
+            let html_report_path = llm_output_path.join(format!("{}_LLM_file-report.html", input_name));
+            report_writer
+                .generate_html_report(
+                    &self.report_entries,
+                    &self.excluded_files,
+                    &summary,
+                    &settings_snapshot,
+                    &html_report_path,
+                )
+                .context("Failed to generate HTML report")?;
+            report_paths.push(html_report_path);
+        }
+        // The workbook is the run's report unless only the CSV is written
+        let report_path = report_paths[0].clone();
+
+        let result = ProcessingResult {
+            entries: self.report_entries.clone(),
+            staging_path: long_paths::simplified(working_path).to_string_lossy().to_string(),
+            llm_output_path: long_paths::simplified(&llm_output_path).to_string_lossy().to_string(),
+            report_path: long_paths::simplified(&report_path).to_string_lossy().to_string(),
+            search_index_path,
+            corpus_paths,
+            jsonl_path,
+            manifest_path: long_paths::simplified(&manifest_path).to_string_lossy().to_string(),
+            manifest_csv_path: long_paths::simplified(&manifest_csv_path).to_string_lossy().to_string(),
+            archives: archives.to_vec(),
+            summary,
+            settings_snapshot,
+            remediations: remediation::summarize(&self.report_entries),
+        };
+
+        // The result as JSON, for engagement automation that shouldn't parse Excel
+        if self.settings.write_json_report {
+            let json_report_path = llm_output_path.join(format!("{}_LLM_file-report.json", input_name));
+            let json = serde_json::to_string_pretty(&result).context("Failed to serialize JSON report")?;
+            fs::write(&json_report_path, json)
+                .with_context(|| format!("Failed to write JSON report: {}", json_report_path.display()))?;
+            self.logger.info(&format!("JSON report written to {}", json_report_path.display()));
+            report_paths.push(json_report_path);
+        }
+
+        // Detached signatures prove the report and manifest weren't edited later
+        if let Some(key) = report_signing::signing_key(self.settings.report_signing_key.as_deref()) {
+            for path in report_paths.iter().cloned().chain([manifest_path, manifest_csv_path]) {
+                let sig_path = report_signing::sign_file(&path, &key)
+                    .with_context(|| format!("Failed to sign {}", path.display()))?;
+                self.logger.info(&format!("Signature written to {}", sig_path.display()));
+            }
+        }
+
+        // Checksums go last so they cover the report and manifest as well
+        self.emit_progress(total_files, total_files, "Writing checksums");
+        let hashing_service = HashingService::with_algorithm(self.settings.hash_algorithm);
+        let checksum_path = checksums::write_checksum_file(&llm_output_path, &hashing_service)
+            .context("Failed to write checksum file")?;
+        self.logger.info(&format!("Checksums of the export written to {}", checksum_path.display()));
+
+        // Emit final progress
+        self.emit_progress(total_files, total_files, "Complete");
+        
+        Ok(result)
+    }
+
+    /// SECURITY: Safely resolve a relative path and ensure it stays within the working directory
+    /// Returns None if path traversal is detected
+    fn safe_resolve_path(
+        &self,
+        working_path: &Path,
+        working_path_canonical: &Path,
+        relative_path: &str,
+    ) -> Option<PathBuf> {
+        // Remove leading slashes/backslashes
+        let sanitized = relative_path.trim_start_matches('/').trim_start_matches('\\');
+        
+        // Join with working path
+        let joined_path = working_path.join(sanitized);
+        
+        // Try to canonicalize to resolve any symlinks and normalize the path
+        match joined_path.canonicalize() {
+            Ok(canonical) => {
+                // Ensure the canonical path is within the working directory
+                if canonical.starts_with(working_path_canonical) {
+                    Some(canonical)
+                } else {
+                    self.logger.warning(&format!(
+                        "SECURITY: Blocked path traversal attempt: {} (resolved to: {})",
+                        relative_path,
+                        canonical.display()
+                    ));
+                    None
+                }
+            }
+            Err(_) => {
+                // If canonicalize fails, check if the path exists and validate manually
+                if joined_path.exists() {
+                    // For existing paths, do a basic check that it's within working_path
+                    // This is less secure than canonicalize but handles edge cases
+                    let mut current = joined_path.as_path();
+                    let mut depth = 0;
+                    const MAX_DEPTH: usize = 100; // Prevent infinite loops
+                    
+                    while let Some(parent) = current.parent() {
+                        if parent == working_path || parent.starts_with(working_path) {
+                            return Some(joined_path);
+                        }
+                        current = parent;
+                        depth += 1;
+                        if depth > MAX_DEPTH {
+                            break;
+                        }
+                    }
+                    
+                    self.logger.warning(&format!(
+                        "SECURITY: Could not validate path safety: {}",
+                        relative_path
+                    ));
+                    None
+                } else {
+                    // Path doesn't exist yet, but we'll allow it if it's clearly within working_path
+                    if sanitized.contains("..") {
+                        self.logger.warning(&format!(
+                            "SECURITY: Blocked path with traversal sequence: {}",
+                            relative_path
+                        ));
+                        None
+                    } else {
+                        Some(joined_path)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Copy the input folder into staging. The scan limits are applied while
+    /// copying, counting files as the scan will, so an input selected by
+    /// mistake (e.g. a whole mounted drive) stops the run before most of it
+    /// is copied; the partial copy is removed.
+    fn copy_directory_recursive(&mut self, src: &Path, dst: &Path) -> Result<()> {
+        let system_files = self.system_file_list()?;
+        let filter = PathFilter::new(&self.settings.include_patterns, &self.settings.exclude_patterns)?;
+        let omit_filtered = self.settings.pattern_excluded_files == PatternExclusion::Omit;
+        let limits = self.settings.scan_limits.clone();
+        let mut walker = WalkDir::new(src);
+        if let Some(depth) = limits.walk_depth() {
+            walker = walker.max_depth(depth);
+        }
+        let (mut files, mut total_bytes) = (0usize, 0u64);
+        let mut limit_hit = None;
+
+        // Create destination directory
+        fs::create_dir_all(dst)
+            .with_context(|| format!("Failed to create destination directory: {}", dst.display()))?;
+        
+        // Walk through all files and directories in source
+        for entry in walker.into_iter().filter_map(|e| e.ok()) {
+            limit_hit = limits.check_depth(entry.depth());
+            if limit_hit.is_some() {
+                break;
+            }
+            let src_path = entry.path();
+            let relative_path = src_path
+                .strip_prefix(src)
+                .with_context(|| format!("Failed to get relative path for {}", src_path.display()))?;
+            let dst_path = dst.join(relative_path);
+            
+            if src_path.is_dir() {
+                // Create directory in destination
+                fs::create_dir_all(&dst_path)
+                    .with_context(|| format!("Failed to create directory: {}", dst_path.display()))?;
+            } else if src_path.is_file() {
+                // Leave out hidden and system files, listing them on the Exclusions sheet
+                let reason = system_files.as_ref().and_then(|system_files| {
+                    let file_name = src_path.file_name()?.to_str()?;
+                    system_files.exclusion_reason(file_name)
+                });
+                if let Some(reason) = reason {
+                    self.excluded_files.push(ExcludedFile {
+                        relative_path: relative_path.to_string_lossy().to_string(),
+                        reason,
+                    });
+                    continue;
+                }
+
+                if !(omit_filtered && filter.exclusion_reason(&relative_path.to_string_lossy()).is_some()) {
+                    files += 1;
+                    total_bytes += entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+                    limit_hit = limits.check_totals(files, total_bytes);
+                    if limit_hit.is_some() {
+                        break;
+                    }
+                }
+                
+                // Copy file to destination
+                if let Some(parent) = dst_path.parent() {
+                    fs::create_dir_all(parent)
+                        .with_context(|| format!("Failed to create parent directory: {}", parent.display()))?;
+                }
+                fs::copy(src_path, &dst_path)
+                    .with_context(|| format!("Failed to copy file from {} to {}", 
+                        src_path.display(), dst_path.display()))?;
+            }
+        }
+
+        if let Some(reason) = limit_hit {
+            if let Err(e) = fs::remove_dir_all(dst) {
+                self.logger.warning(&format!("Failed to remove partial staging copy {}: {}", dst.display(), e));
+            }
+            bail!(
+                "Input not copied to staging because the {}. Select a narrower input or raise the scan limits in the settings.",
+                reason
+            );
+        }
+        
+        self.logger.debug(&format!("Successfully copied directory from {} to {}", 
+            src.display(), dst.display()));
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::NoopEventSink;
+
+    fn controller() -> ProcessController {
+        ProcessController::new(EPTLogger::new(), Arc::new(NoopEventSink), ProcessingSettings::default())
+    }
+
+    /// Reprocessing a converted file hashes its staged original again, not
+    /// the converted output its working path pointed at
+    #[test]
+    fn reprocessing_a_converted_file_keeps_its_original_hash() {
+        let root = std::env::temp_dir().join(format!("auditor-reprocess-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let input = root.join("evidence");
+        fs::create_dir_all(&input).unwrap();
+        fs::write(
+            input.join("message.eml"),
+            "From: auditor@example.com\r\nSubject: Ledger\r\n\r\nThe reconciled ledger is attached.\r\n",
+        )
+        .unwrap();
+
+        let outcome = (|| -> Result<(ReportModel, ReportModel)> {
+            let first = controller().start_processing(&input)?;
+            let converted = first
+                .entries
+                .iter()
+                .find(|entry| entry.original_file_name == "message.eml")
+                .context("message was not scanned")?
+                .clone();
+            let again = controller().reprocess_entries(
+                Path::new(&first.staging_path),
+                &[converted.original_relative_path.clone()],
+            )?;
+            let reprocessed = again
+                .entries
+                .into_iter()
+                .find(|entry| entry.original_file_name == "message.eml")
+                .context("message is missing after reprocessing")?;
+            Ok((converted, reprocessed))
+        })();
+        let _ = fs::remove_dir_all(&root);
+
+        let (converted, reprocessed) = outcome.unwrap();
+        assert_eq!(converted.status, FileStatus::Converted);
+        assert!(converted.original_hash.is_some());
+        assert_eq!(reprocessed.original_hash, converted.original_hash);
+        assert_eq!(reprocessed.status, FileStatus::Converted);
+        assert_eq!(reprocessed.relative_path, converted.relative_path);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::image_conversion::IMAGE_EXTENSIONS;
+use crate::path_filter;
+use crate::report_model::{FailureStage, FileStatus, ReportModel};
+use serde::{Deserialize, Serialize};
+
+/// What to do about a file that didn't make it into the export, so the
+/// person running the tool can act on a skip without escalating it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Remediation {
+    InstallLibreOffice,
+    InstallTesseract,
+    InstallImageMagick,
+    InstallMsoffcrypto,
+    ProvideDocumentPassword,
+    ProvideArchivePassword,
+    RaiseExtractionLimits,
+    RequestUncorruptedCopy,
+    EnableImageConversion,
+    ConvertManually,
+    SpecialistReview,
+    EscalateToSecurity,
+    CheckFileAccess,
+}
+
+impl Remediation {
+    /// Stable machine-readable key
+    pub fn key(&self) -> &'static str {
+        match self {
+            Remediation::InstallLibreOffice => "install_libreoffice",
+            Remediation::InstallTesseract => "install_tesseract",
+            Remediation::InstallImageMagick => "install_imagemagick",
+            Remediation::InstallMsoffcrypto => "install_msoffcrypto",
+            Remediation::ProvideDocumentPassword => "provide_document_password",
+            Remediation::ProvideArchivePassword => "provide_archive_password",
+            Remediation::RaiseExtractionLimits => "raise_extraction_limits",
+            Remediation::RequestUncorruptedCopy => "request_uncorrupted_copy",
+            Remediation::EnableImageConversion => "enable_image_conversion",
+            Remediation::ConvertManually => "convert_manually",
+            Remediation::SpecialistReview => "specialist_review",
+            Remediation::EscalateToSecurity => "escalate_to_security",
+            Remediation::CheckFileAccess => "check_file_access",
+        }
+    }
+
+    /// Next step as shown in the report
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Remediation::InstallLibreOffice => "Install LibreOffice (or set EPT_LIBREOFFICE_PATH) and rerun",
+            Remediation::InstallTesseract => "Install Tesseract OCR (or set EPT_TESSERACT_PATH) and rerun",
+            Remediation::InstallImageMagick => "Install ImageMagick (or set EPT_MAGICK_PATH) and rerun",
+            Remediation::InstallMsoffcrypto => "Install msoffcrypto-tool (or set EPT_MSOFFCRYPTO_PATH) and rerun",
+            Remediation::ProvideDocumentPassword => {
+                "Ask the client for the document password, add it to the document passwords and rerun"
+            }
+            Remediation::ProvideArchivePassword => {
+                "Ask the client for the archive password, extract the archive manually and rerun on its contents"
+            }
+            Remediation::RaiseExtractionLimits => {
+                "Check the archive is genuine, then raise the extraction limits or nesting depth and rerun"
+            }
+            Remediation::RequestUncorruptedCopy => {
+                "Request an uncorrupted copy, or one saved in a current file format, from the client"
+            }
+            Remediation::EnableImageConversion => "Turn on image OCR or image-to-PDF conversion and rerun",
+            Remediation::ConvertManually => {
+                "Open the file in its native application and save it as PDF or text if it is needed as evidence"
+            }
+            Remediation::SpecialistReview => "Pass the file to a specialist with the right software for review",
+            Remediation::EscalateToSecurity => "Do not open the file; escalate it to IT security",
+            Remediation::CheckFileAccess => {
+                "Check the file is readable and the disk has free space, then rerun"
+            }
+        }
+    }
+
+    /// Remediation for a file's outcome, read from its status and skip
+    /// reason; `None` when nothing needs doing (exported, duplicates,
+    /// suppressed thread messages, known system files, files left out by
+    /// the include/exclude patterns)
+    pub fn for_entry(entry: &ReportModel) -> Option<Self> {
+        let reason = entry.skip_reason.as_deref().unwrap_or("").to_lowercase();
+        match &entry.status {
+            FileStatus::Pending
+            | FileStatus::Converted
+            | FileStatus::CopiedAsIs
+            | FileStatus::SkippedDuplicate
+            | FileStatus::SuppressedInThread
+            | FileStatus::KnownFile => None,
+            FileStatus::SkippedEncrypted if reason.contains("msoffcrypto") => Some(Remediation::InstallMsoffcrypto),
+            FileStatus::SkippedEncrypted => Some(Remediation::ProvideDocumentPassword),
+            FileStatus::Quarantined => Some(Remediation::EscalateToSecurity),
+            FileStatus::InventoryOnly => Some(Remediation::SpecialistReview),
+            FileStatus::Excluded if reason.contains("nesting limit") => Some(Remediation::RaiseExtractionLimits),
+            FileStatus::Excluded if path_filter::is_pattern_excluded(entry) => None,
+            FileStatus::Excluded if IMAGE_EXTENSIONS.contains(&entry.file_type.to_lowercase().as_str()) => {
+                Some(Remediation::EnableImageConversion)
+            }
+            FileStatus::Excluded => Some(Remediation::ConvertManually),
+            FileStatus::Failed { stage } => Some(match stage {
+                FailureStage::PathValidation => Remediation::EscalateToSecurity,
+                FailureStage::Missing | FailureStage::Hashing | FailureStage::Export => Remediation::CheckFileAccess,
+                FailureStage::Extraction if reason.contains("password") || reason.contains("encrypt") => {
+                    Remediation::ProvideArchivePassword
+                }
+                FailureStage::Extraction if reason.contains("extraction limit") => Remediation::RaiseExtractionLimits,
+                FailureStage::Extraction => Remediation::RequestUncorruptedCopy,
+                FailureStage::Conversion if reason.contains("libreoffice not found") => {
+                    Remediation::InstallLibreOffice
+                }
+                FailureStage::Conversion if reason.contains("needs tesseract") => Remediation::InstallTesseract,
+                FailureStage::Conversion if reason.contains("needs imagemagick") => Remediation::InstallImageMagick,
+                FailureStage::Conversion => Remediation::RequestUncorruptedCopy,
+            }),
+        }
+    }
+}
+
+/// How many files call for one remediation, for the run result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemediationSummary {
+    pub remediation: Remediation,
+    pub hint: String,
+    pub file_count: usize,
+}
+
+/// One summary per remediation needed by any entry, most files first
+pub fn summarize(entries: &[ReportModel]) -> Vec<RemediationSummary> {
+    let mut counts = std::collections::BTreeMap::new();
+    for remediation in entries.iter().filter_map(|entry| entry.remediation) {
+        *counts.entry(remediation).or_insert(0usize) += 1;
+    }
+    let mut summaries: Vec<RemediationSummary> = counts
+        .into_iter()
+        .map(|(remediation, file_count)| RemediationSummary {
+            remediation,
+            hint: remediation.hint().to_string(),
+            file_count,
+        })
+        .collect();
+    summaries.sort_by(|a, b| b.file_count.cmp(&a.file_count));
+    summaries
+}
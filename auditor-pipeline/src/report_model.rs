@@ -0,0 +1,432 @@
+use crate::remediation::Remediation;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Single-file compression formats, named after the file they wrap
+/// (`ledger.csv.gz`)
+pub const STREAM_COMPRESSION_EXTENSIONS: &[&str] = &["gz", "bz2", "zst", "xz"];
+
+/// Lowercased extension of the file inside a single-file compression
+/// wrapper: `csv` for `ledger.csv.gz`, `tar` for `backup.tar.bz2`. `None`
+/// when the file isn't compressed or its name doesn't say what it wraps.
+pub fn wrapped_extension(path: &Path) -> Option<String> {
+    let outer = path.extension()?.to_str()?.to_lowercase();
+    if !STREAM_COMPRESSION_EXTENSIONS.contains(&outer.as_str()) {
+        return None;
+    }
+    let inner = Path::new(path.file_stem()?).extension()?.to_str()?.to_lowercase();
+    // Version numbers and dates (`v1.2.gz`) aren't extensions
+    let looks_like_extension = (1..=5).contains(&inner.len())
+        && inner.chars().all(|c| c.is_ascii_alphanumeric())
+        && inner.chars().any(|c| c.is_ascii_alphabetic());
+    looks_like_extension.then_some(inner)
+}
+
+/// Extension as recorded in the report, keeping the wrapped type of a
+/// compressed file: `csv.gz` for `ledger.csv.gz`, `pdf` for `memo.pdf`
+pub fn compound_extension(path: &Path) -> Option<String> {
+    let outer = path.extension()?.to_str()?;
+    Some(match wrapped_extension(path) {
+        Some(inner) => format!("{}.{}", inner, outer.to_lowercase()),
+        None => outer.to_string(),
+    })
+}
+
+/// Pipeline stage at which a file failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureStage {
+    Extraction,
+    PathValidation,
+    Missing,
+    Hashing,
+    Conversion,
+    Export,
+}
+
+impl FailureStage {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FailureStage::Extraction => "extraction",
+            FailureStage::PathValidation => "path validation",
+            FailureStage::Missing => "file missing",
+            FailureStage::Hashing => "hashing",
+            FailureStage::Conversion => "conversion",
+            FailureStage::Export => "export",
+        }
+    }
+}
+
+/// Outcome of processing a single file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FileStatus {
+    /// Not processed yet
+    Pending,
+    /// Converted to an LLM-readable format
+    Converted,
+    /// Already LLM-readable, exported unchanged
+    CopiedAsIs,
+    /// Content identical to a file that was already exported
+    SkippedDuplicate,
+    /// Password-protected or encrypted, could not be read
+    SkippedEncrypted,
+    /// Email whose content is quoted by a later message in the same thread
+    SuppressedInThread,
+    /// Matches the known-file hash list (OS or application file)
+    KnownFile,
+    Failed { stage: FailureStage },
+    /// Deliberately left out (not LLM-readable and not convertible)
+    Excluded,
+    /// Moved aside as suspicious or unsafe
+    Quarantined,
+    /// Engineering/CAD file that can't be converted; listed for specialist review
+    InventoryOnly,
+}
+
+impl FileStatus {
+    /// Whether the file ends up in the LLM export
+    pub fn is_exportable(&self) -> bool {
+        matches!(self, FileStatus::Converted | FileStatus::CopiedAsIs)
+    }
+
+    /// Stable machine-readable key, ignoring the failure stage
+    pub fn key(&self) -> &'static str {
+        match self {
+            FileStatus::Pending => "pending",
+            FileStatus::Converted => "converted",
+            FileStatus::CopiedAsIs => "copied_as_is",
+            FileStatus::SkippedDuplicate => "skipped_duplicate",
+            FileStatus::SkippedEncrypted => "skipped_encrypted",
+            FileStatus::SuppressedInThread => "suppressed_in_thread",
+            FileStatus::KnownFile => "known_file",
+            FileStatus::Failed { .. } => "failed",
+            FileStatus::Excluded => "excluded",
+            FileStatus::Quarantined => "quarantined",
+            FileStatus::InventoryOnly => "inventory_only",
+        }
+    }
+
+    /// Readable form used in reports
+    pub fn label(&self) -> String {
+        match self {
+            FileStatus::Pending => "Pending".to_string(),
+            FileStatus::Converted => "Converted".to_string(),
+            FileStatus::CopiedAsIs => "Copied as-is".to_string(),
+            FileStatus::SkippedDuplicate => "Skipped (duplicate)".to_string(),
+            FileStatus::SkippedEncrypted => "Skipped (encrypted)".to_string(),
+            FileStatus::SuppressedInThread => "Suppressed (email thread)".to_string(),
+            FileStatus::KnownFile => "Excluded (known system file)".to_string(),
+            FileStatus::Failed { stage } => format!("Failed ({})", stage.label()),
+            FileStatus::Excluded => "Excluded".to_string(),
+            FileStatus::Quarantined => "Quarantined".to_string(),
+            FileStatus::InventoryOnly => "Inventory only".to_string(),
+        }
+    }
+}
+
+/// What happened to an archive during decompression
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveOutcome {
+    Extracted,
+    Failed,
+    /// Nested deeper than the configured limit
+    SkippedNestingDepth,
+    /// Same file reached twice (e.g. through a link)
+    SkippedAlreadyProcessed,
+}
+
+impl ArchiveOutcome {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ArchiveOutcome::Extracted => "Extracted",
+            ArchiveOutcome::Failed => "Failed",
+            ArchiveOutcome::SkippedNestingDepth => "Skipped (nesting depth)",
+            ArchiveOutcome::SkippedAlreadyProcessed => "Skipped (already processed)",
+        }
+    }
+}
+
+/// One archive the decompression stage opened or considered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveRecord {
+    // Relative to the staging folder; the input path for an input archive
+    pub path: String,
+    pub format: String,
+    #[serde(alias = "sha512")]
+    pub hash: Option<String>, // under the run's hash algorithm
+    pub nesting_depth: usize,
+    pub entry_count: usize,
+    pub bytes_extracted: u64,
+    pub outcome: ArchiveOutcome,
+    pub detail: Option<String>,
+}
+
+/// A file left out of the run before it was listed, e.g. a thumbnail cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExcludedFile {
+    // Relative to the input folder
+    pub relative_path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportModel {
+    // Original identity (from initial scan, post-decompression, pre-conversion)
+    pub original_file_name: String,
+    pub original_relative_path: String,
+    // Undecoded ZIP entry name, when it was not plain ASCII
+    pub raw_archive_entry_name: Option<String>,
+    // Email or PDF the file was attached to, relative to the staging folder
+    pub parent_document: Option<String>,
+    // Innermost archive the file was extracted from, relative to the staging folder
+    #[serde(default)]
+    pub source_archive: Option<String>,
+    // Known-bad hash list entry this file matched
+    pub indicator_match: Option<String>,
+
+    // Working identity (may be updated during processing/conversion)
+    pub file_name: String,
+    pub relative_path: String,
+    // Staged original, which `relative_path` leaves for a converted output or
+    // a compressed copy; restored when the entry is processed again
+    #[serde(default)]
+    pub staged_relative_path: Option<String>,
+
+    // Processing metadata
+    // Earlier versions named these `*sha512` whatever the algorithm
+    #[serde(alias = "sha512", alias = "original_sha512")]
+    pub original_hash: Option<String>, // Hash of the original file, kept when it is converted; None in Phase 1
+    pub additional_hashes: BTreeMap<String, String>, // report column (e.g. "MD5") → further digests of the original
+    pub status: FileStatus,
+    pub skip_reason: Option<String>,
+    pub file_type: String,
+    pub file_size_bytes: u64,
+    pub file_size_human: String,
+    pub last_modified: String,
+    pub created_time: String,
+
+    // Converted artifact info (if any)
+    pub converted_file_name: Option<String>,
+    pub converted_relative_path: Option<String>,
+    #[serde(alias = "converted_sha512")]
+    pub converted_hash: Option<String>,
+    pub conversion_method: Option<String>, // e.g. "LibreOffice"
+    pub conversion_backend: Option<String>, // converter that produced the output, e.g. "pandoc" after LibreOffice failed
+    pub output_format: Option<String>,     // extension of the converted artifact
+    pub format_details: Option<String>,    // header metadata of inventory-only formats
+    pub review_reason: Option<String>,     // set when the converted output looks suspicious
+    pub source_encoding: Option<String>,   // detected text encoding of a normalized CSV/TSV
+    pub contains_macros: bool,             // Office document carrying a VBA project
+    pub vba_source_path: Option<String>,   // folder the VBA modules were extracted to, relative to staging
+    pub detected_type: Option<String>,     // type recognised from the content of an extension-less file
+    pub detected_mime: Option<String>,     // MIME type recognised from the file's leading bytes
+    pub extension_mismatch: Option<String>, // set when the content contradicts the extension
+    pub document_author: Option<String>,   // Office document properties, for provenance
+    pub document_last_modified_by: Option<String>,
+    pub document_company: Option<String>,
+    pub document_application: Option<String>, // application that created the document
+    pub exif_taken_at: Option<String>,     // EXIF capture time of an image, when enabled
+    pub exif_gps: Option<String>,          // "lat, lon" in decimal degrees
+    pub exif_camera: Option<String>,
+    pub owner: Option<String>,             // owner of the input file, when enabled
+    pub owner_group: Option<String>,
+    pub permissions: Option<String>,       // NTFS ACL (SDDL) or POSIX mode bits
+    pub duplicate_group: Option<String>,   // shared by files with identical content
+    pub duplicate_of: Option<String>,      // first file of the group, on every later copy
+    pub remediation: Option<Remediation>,  // next step for a file that wasn't exported
+
+    // Tag name → number of matches in the file's text
+    pub tags: BTreeMap<String, usize>,
+
+    // Export results
+    pub exported_file_name: Option<String>, // name inside the LLM export folder
+    #[serde(default)]
+    pub export_parts: Vec<String>,          // numbered parts replacing a long document in the export
+    pub export_group: Option<String>,       // top-level input folder, when the export is split
+    #[serde(default)]
+    pub estimated_tokens: Option<u64>,      // of the exported text, about four characters per token
+    #[serde(default)]
+    pub over_export_cap: bool,              // moved to `_overflow` as beyond the export size cap
+    pub summary: Option<String>,            // LLM-generated, when enabled
+}
+
+impl ReportModel {
+    pub fn new(
+        file_name: String,
+        relative_path: String,
+        file_type: String,
+        file_size_bytes: u64,
+        last_modified: String,
+        created_time: String,
+    ) -> Self {
+        let file_size_human = format_file_size(file_size_bytes);
+        Self {
+            // Original identity
+            original_file_name: file_name.clone(),
+            original_relative_path: relative_path.clone(),
+            raw_archive_entry_name: None,
+            parent_document: None,
+            source_archive: None,
+            indicator_match: None,
+
+            // Working identity (initially same as original)
+            file_name,
+            staged_relative_path: Some(relative_path.clone()),
+            relative_path,
+
+            // Processing metadata
+            original_hash: None,
+            additional_hashes: BTreeMap::new(),
+            status: FileStatus::Pending,
+            skip_reason: None,
+            file_type,
+            file_size_bytes,
+            file_size_human,
+            last_modified,
+            created_time,
+            converted_file_name: None,
+            converted_relative_path: None,
+            converted_hash: None,
+            conversion_method: None,
+            conversion_backend: None,
+            output_format: None,
+            format_details: None,
+            review_reason: None,
+            source_encoding: None,
+            contains_macros: false,
+            vba_source_path: None,
+            detected_type: None,
+            detected_mime: None,
+            extension_mismatch: None,
+            document_author: None,
+            document_last_modified_by: None,
+            document_company: None,
+            document_application: None,
+            exif_taken_at: None,
+            exif_gps: None,
+            exif_camera: None,
+            owner: None,
+            owner_group: None,
+            permissions: None,
+            duplicate_group: None,
+            duplicate_of: None,
+            remediation: None,
+            tags: BTreeMap::new(),
+            exported_file_name: None,
+            export_parts: Vec::new(),
+            export_group: None,
+            estimated_tokens: None,
+            over_export_cap: false,
+            summary: None,
+        }
+    }
+
+    /// Tags as a single cell value, e.g. "journal entry (3); vendor (1)"
+    pub fn tags_summary(&self) -> String {
+        self.tags
+            .iter()
+            .map(|(tag, count)| format!("{} ({})", tag, count))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    pub fn is_converted(&self) -> bool {
+        self.converted_file_name.is_some()
+    }
+
+    /// Staged original relative to the staging folder. Runs saved before it
+    /// was recorded fall back to the original identity.
+    pub fn staged_path(&self) -> &str {
+        self.staged_relative_path.as_deref().unwrap_or(&self.original_relative_path)
+    }
+
+    /// Clear what hashing, conversion, tagging and export recorded, keeping
+    /// the file's identity and scan metadata, so the entry can be processed
+    /// again. The working identity goes back to the staged original.
+    pub fn reset_processing(&mut self) {
+        let staged = self.staged_path().to_string();
+        self.file_name = Path::new(&staged)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.original_file_name.clone());
+        self.relative_path = staged;
+        self.indicator_match = None;
+        self.original_hash = None;
+        self.additional_hashes.clear();
+        self.status = FileStatus::Pending;
+        self.skip_reason = None;
+        self.converted_file_name = None;
+        self.converted_relative_path = None;
+        self.converted_hash = None;
+        self.conversion_method = None;
+        self.conversion_backend = None;
+        self.output_format = None;
+        self.format_details = None;
+        self.review_reason = None;
+        self.source_encoding = None;
+        self.contains_macros = false;
+        self.vba_source_path = None;
+        self.document_author = None;
+        self.document_last_modified_by = None;
+        self.document_company = None;
+        self.document_application = None;
+        self.duplicate_group = None;
+        self.duplicate_of = None;
+        self.remediation = None;
+        self.tags.clear();
+        self.exported_file_name = None;
+        self.export_parts.clear();
+        self.export_group = None;
+        self.estimated_tokens = None;
+        self.over_export_cap = false;
+        self.summary = None;
+    }
+
+    /// Files of the entry in the LLM export: its parts when it was split,
+    /// otherwise the exported file, if any
+    pub fn exported_files(&self) -> Vec<&str> {
+        if self.export_parts.is_empty() {
+            self.exported_file_name.iter().map(String::as_str).collect()
+        } else {
+            self.export_parts.iter().map(String::as_str).collect()
+        }
+    }
+
+    pub fn is_llm_readable(file_path: &Path) -> bool {
+        // A compressed `notes.txt.gz` is exported through its decompressed copy
+        if wrapped_extension(file_path).is_some() {
+            return false;
+        }
+        if let Some(ext) = file_path.extension().and_then(|e| e.to_str()) {
+            let ext_lower = ext.to_lowercase();
+            matches!(
+                ext_lower.as_str(),
+                "txt" | "md" | "pdf" | "csv" | "json" | "xml" | "html" | "htm" | "log" | "rtf"
+            )
+        } else {
+            false
+        }
+    }
+}
+
+pub(crate) fn format_file_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit_index = 0;
+
+    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit_index += 1;
+    }
+
+    if unit_index == 0 {
+        format!("{} {}", bytes, UNITS[unit_index])
+    } else {
+        format!("{:.2} {}", size, UNITS[unit_index])
+    }
+}
+
@@ -0,0 +1,69 @@
+use anyhow::{Context, Result};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Environment variable the signing key is read from when the settings
+/// don't hold one
+pub const SIGNING_KEY_ENV: &str = "EPT_REPORT_SIGNING_KEY";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Signing key from the settings, or else from `EPT_REPORT_SIGNING_KEY`;
+/// `None` when neither holds a non-empty key
+pub fn signing_key(configured: Option<&str>) -> Option<String> {
+    configured
+        .map(str::to_string)
+        .or_else(|| std::env::var(SIGNING_KEY_ENV).ok())
+        .filter(|key| !key.is_empty())
+}
+
+/// Detached signature path of `path`: `report.xlsx.sig` for `report.xlsx`
+pub fn signature_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".sig");
+    path.with_file_name(name)
+}
+
+/// Write an HMAC-SHA256 of `path` under `key` to a detached `.sig` file,
+/// in the `HMAC-SHA256(<file name>)= <hex>` form `openssl dgst -sha256
+/// -hmac <key>` prints, so it can be checked without this tool
+pub fn sign_file(path: &Path, key: &str) -> Result<PathBuf> {
+    let digest = hmac_file(path, key)?;
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let sig_path = signature_path(path);
+    std::fs::write(&sig_path, format!("HMAC-SHA256({})= {}\n", name, digest))
+        .with_context(|| format!("Failed to write signature: {}", sig_path.display()))?;
+    Ok(sig_path)
+}
+
+/// Whether `path` still matches its detached signature under `key`
+pub fn verify_file(path: &Path, key: &str) -> Result<bool> {
+    let sig_path = signature_path(path);
+    let signature = std::fs::read_to_string(&sig_path)
+        .with_context(|| format!("Failed to read signature: {}", sig_path.display()))?;
+    let expected = signature
+        .trim()
+        .rsplit_once("= ")
+        .map(|(_, digest)| digest.to_lowercase())
+        .with_context(|| format!("Malformed signature file: {}", sig_path.display()))?;
+    Ok(hmac_file(path, key)? == expected)
+}
+
+fn hmac_file(path: &Path, key: &str) -> Result<String> {
+    let mut mac = HmacSha256::new_from_slice(key.as_bytes()).context("Invalid signing key")?;
+    let mut file = File::open(path).with_context(|| format!("Failed to open file for signing: {}", path.display()))?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let bytes_read = file
+            .read(&mut buffer)
+            .with_context(|| format!("Failed to read file for signing: {}", path.display()))?;
+        if bytes_read == 0 {
+            break;
+        }
+        mac.update(&buffer[..bytes_read]);
+    }
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
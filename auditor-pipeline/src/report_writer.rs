@@ -0,0 +1,1384 @@
+use crate::csv_normalizer;
+use crate::ept_logger::EPTLogger;
+use crate::html_report::{self, HtmlCell, HtmlChart, HtmlReport};
+use crate::long_paths;
+use crate::report_model::{self, ArchiveRecord, ExcludedFile, FileStatus, ReportModel};
+use crate::run_metadata::RunMetadata;
+use crate::run_summary::RunSummary;
+use crate::settings::{ProcessingSettings, SettingsSnapshot};
+use anyhow::{bail, Context, Result};
+use rust_xlsxwriter::utility::{column_number_to_name, row_col_to_cell};
+use rust_xlsxwriter::{Chart, ChartType, Color, ConditionalFormatFormula, Format, Url, Workbook, Worksheet};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Most characters an xlsx cell can hold
+const MAX_CELL_CHARS: usize = 32_767;
+
+/// Appended to a value cut short to fit in a cell
+const TRUNCATION_MARKER: &str = " … [truncated, full value on the Overflow sheet]";
+
+/// Main sheet column holding the file's status label
+const STATUS_COL: usize = 3;
+
+/// Main sheet column holding the file size as a number
+const FILE_SIZE_BYTES_COL: usize = 7;
+
+/// Main sheet column naming the format the content shows, when it
+/// contradicts the extension
+const EXTENSION_MISMATCH_COL: usize = 28;
+
+/// Main sheet column holding the estimated tokens of the exported text
+const ESTIMATED_TOKENS_COL: usize = 34;
+
+/// Categories charted per breakdown; the smallest ones beyond it are
+/// summed into "Other"
+const MAX_CHART_CATEGORIES: usize = 15;
+
+/// Most hyperlinks Excel keeps on one worksheet; rows beyond it show the
+/// path as text
+const MAX_LINKS_PER_SHEET: usize = 65_530;
+
+/// Longest hyperlink target Excel accepts
+const MAX_URL_CHARS: usize = 2_079;
+
+/// Which files the report is written as
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    /// Excel workbook with the main sheet and the supporting sheets
+    #[default]
+    Xlsx,
+    /// UTF-8 CSV of the main sheet, for IDEA, ACL/Galvanize and the like
+    Csv,
+    /// Both the workbook and the CSV
+    Both,
+}
+
+impl ReportFormat {
+    pub fn writes_xlsx(self) -> bool {
+        self != ReportFormat::Csv
+    }
+
+    pub fn writes_csv(self) -> bool {
+        self != ReportFormat::Xlsx
+    }
+}
+
+/// Headers of the link columns the workbook and HTML report add after the
+/// main sheet's columns
+const STAGED_FILE_HEADER: &str = "Staged File";
+const EXPORTED_COPY_HEADER: &str = "Exported Copy";
+
+/// A main sheet column to show, picked by its default header
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportColumn {
+    /// Default header of the column, e.g. `Relative Path`, matched ignoring
+    /// case. Hash columns are named after their algorithm, e.g.
+    /// `Original SHA-256`.
+    pub column: String,
+    /// Header shown instead of the default one
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// Columns of the main sheet, shared by the workbook, CSV and HTML reports.
+/// Every column is built; `report_columns` picks which are shown, in what
+/// order and under what header.
+struct MainSheetColumns {
+    /// Headers of the shown columns
+    headers: Vec<String>,
+    /// Position among every column of each shown column
+    sources: Vec<usize>,
+    /// Link columns shown, with their headers; every one when `None`
+    link_headers: Option<Vec<(String, String)>>,
+    additional_hash_columns: Vec<&'static str>,
+    first_additional_hash_col: usize,
+    first_exif_col: Option<usize>,
+    first_ownership_col: Option<usize>,
+}
+
+impl MainSheetColumns {
+    fn new(settings: &ProcessingSettings) -> Result<Self> {
+        let hash_column = settings.hash_algorithm.column_label();
+        let mut headers: Vec<String> = vec![
+            "File Name".to_string(),
+            "Converted File Name".to_string(),
+            format!("Original {}", hash_column),
+            "Status".to_string(),
+            "Skip Reason".to_string(),
+            "Relative Path".to_string(),
+            "File Type".to_string(),
+            "File Size (Bytes)".to_string(),
+            "File Size (Human)".to_string(),
+            "Last Modified".to_string(),
+            "Created Time".to_string(),
+            "Converted Relative Path".to_string(),
+            format!("Converted {}", hash_column),
+        ];
+        headers.extend(
+            [
+                "Conversion Method",
+                "Output Format",
+                "Tags",
+                "Summary",
+                "Raw Archive Entry Name",
+                "Parent Document",
+                "Format Details",
+                "Needs Review",
+                "Export Group",
+                "Source Encoding",
+                "Contains Macros",
+                "VBA Source",
+                "Detected Type",
+                "Conversion Backend",
+                "Detected MIME Type",
+                "Extension Mismatch",
+                "Author",
+                "Last Modified By",
+                "Company",
+                "Creating Application",
+                "Duplicate Of / Group",
+                "Estimated Tokens",
+                "Beyond Export Cap",
+            ]
+            .map(String::from),
+        );
+
+        // One more column per additional digest of the originals
+        let mut additional_hash_columns: Vec<&'static str> = Vec::new();
+        for algorithm in &settings.additional_hash_algorithms {
+            let column = algorithm.column_label();
+            if *algorithm != settings.hash_algorithm && !additional_hash_columns.contains(&column) {
+                additional_hash_columns.push(column);
+            }
+        }
+        let first_additional_hash_col = headers.len();
+        headers.extend(additional_hash_columns.iter().map(|column| column.to_string()));
+
+        // Image metadata columns, when EXIF was captured
+        let first_exif_col = settings.capture_exif.then_some(headers.len());
+        if first_exif_col.is_some() {
+            headers.extend(["EXIF Taken At", "GPS Coordinates", "Camera Model"].map(String::from));
+        }
+        // Ownership columns, when ownership was captured
+        let first_ownership_col = settings.capture_ownership.then_some(headers.len());
+        if first_ownership_col.is_some() {
+            headers.extend(["Owner", "Group", "Permissions"].map(String::from));
+        }
+
+        let spec = &settings.report_columns;
+        let mut shown = Vec::new();
+        let mut link_headers = Vec::new();
+        for wanted in spec {
+            let name = wanted.column.trim();
+            let label = |default: &str| wanted.label.clone().unwrap_or_else(|| default.to_string());
+            if let Some(source) = headers.iter().position(|header| header.eq_ignore_ascii_case(name)) {
+                shown.push((label(&headers[source]), source));
+            } else if let Some(link) = [STAGED_FILE_HEADER, EXPORTED_COPY_HEADER]
+                .into_iter()
+                .find(|link| link.eq_ignore_ascii_case(name))
+            {
+                link_headers.push((link.to_string(), label(link)));
+            } else {
+                bail!("Unknown report column: {} (columns: {})", name, headers.join(", "));
+            }
+        }
+        if spec.is_empty() {
+            shown = headers.iter().cloned().zip(0..).collect();
+        }
+        let (headers, sources) = shown.into_iter().unzip();
+
+        Ok(Self {
+            headers,
+            sources,
+            link_headers: (!spec.is_empty()).then_some(link_headers),
+            additional_hash_columns,
+            first_additional_hash_col,
+            first_exif_col,
+            first_ownership_col,
+        })
+    }
+
+    /// Where the column at `source` among every column is shown; `None`
+    /// when it isn't. Picked more than once, the first place counts.
+    fn position(&self, source: usize) -> Option<u16> {
+        self.sources.iter().position(|&shown| shown == source).map(|col| col as u16)
+    }
+
+    /// Header of the link column with default header `default`; `None`
+    /// when `report_columns` leaves it out
+    fn link_header(&self, default: &str) -> Option<String> {
+        match &self.link_headers {
+            None => Some(default.to_string()),
+            Some(links) => links
+                .iter()
+                .find(|(link, _)| link == default)
+                .map(|(_, header)| header.clone()),
+        }
+    }
+
+    /// Cell values of `entry`, one per shown header
+    fn row(&self, entry: &ReportModel) -> Vec<String> {
+        let row = self.full_row(entry);
+        self.sources.iter().map(|&source| row[source].clone()).collect()
+    }
+
+    /// Cell values of `entry` in every column
+    fn full_row(&self, entry: &ReportModel) -> Vec<String> {
+        let text = |value: &Option<String>| value.clone().unwrap_or_default();
+        let duplicate = match (&entry.duplicate_group, &entry.duplicate_of) {
+            (Some(group), Some(first)) => format!("{}: duplicate of {}", group, first),
+            (Some(group), None) => format!("{}: first copy", group),
+            _ => String::new(),
+        };
+        let mut row = vec![
+            entry.original_file_name.clone(),
+            text(&entry.converted_file_name),
+            text(&entry.original_hash),
+            entry.status.label(),
+            text(&entry.skip_reason),
+            entry.original_relative_path.clone(),
+            entry.file_type.clone(),
+            entry.file_size_bytes.to_string(),
+            entry.file_size_human.clone(),
+            entry.last_modified.clone(),
+            entry.created_time.clone(),
+            text(&entry.converted_relative_path),
+            text(&entry.converted_hash),
+            text(&entry.conversion_method),
+            text(&entry.output_format),
+            entry.tags_summary(),
+            text(&entry.summary),
+            text(&entry.raw_archive_entry_name),
+            text(&entry.parent_document),
+            text(&entry.format_details),
+            text(&entry.review_reason),
+            text(&entry.export_group),
+            text(&entry.source_encoding),
+            if entry.contains_macros { "Yes" } else { "" }.to_string(),
+            text(&entry.vba_source_path),
+            text(&entry.detected_type),
+            text(&entry.conversion_backend),
+            text(&entry.detected_mime),
+            text(&entry.extension_mismatch),
+            text(&entry.document_author),
+            text(&entry.document_last_modified_by),
+            text(&entry.document_company),
+            text(&entry.document_application),
+            duplicate,
+            entry.estimated_tokens.map(|tokens| tokens.to_string()).unwrap_or_default(),
+            if entry.over_export_cap { "Yes" } else { "" }.to_string(),
+        ];
+        for column in &self.additional_hash_columns {
+            row.push(entry.additional_hashes.get(*column).cloned().unwrap_or_default());
+        }
+        if self.first_exif_col.is_some() {
+            row.extend([&entry.exif_taken_at, &entry.exif_gps, &entry.exif_camera].map(text));
+        }
+        if self.first_ownership_col.is_some() {
+            row.extend([&entry.owner, &entry.owner_group, &entry.permissions].map(text));
+        }
+        row
+    }
+}
+
+/// A value too long for its cell, kept in full for the Overflow sheet
+struct OverflowCell {
+    sheet: String,
+    cell: String,
+    value: String,
+}
+
+/// Where the files the main sheet links to are
+struct FileLinks {
+    /// Folder the entries' relative paths resolve against; `None` when it
+    /// is removed after the run
+    staging_root: Option<PathBuf>,
+}
+
+pub struct ReportWriter {
+    logger: EPTLogger,
+    overflow: RefCell<Vec<OverflowCell>>,
+    links: Option<FileLinks>,
+    run_metadata: Option<RunMetadata>,
+}
+
+impl ReportWriter {
+    pub fn new(logger: EPTLogger) -> Self {
+        Self {
+            logger,
+            overflow: RefCell::new(Vec::new()),
+            links: None,
+            run_metadata: None,
+        }
+    }
+
+    /// Add columns to the workbook's main sheet linking each entry to its
+    /// exported copy and, when `staging_root` is given, to the file in
+    /// staging. Export links are relative, so the workbook must be written
+    /// to the export folder and keep working when that folder is moved.
+    pub fn with_file_links(mut self, staging_root: Option<&Path>) -> Self {
+        self.links = Some(FileLinks {
+            staging_root: staging_root.map(Path::to_path_buf),
+        });
+        self
+    }
+
+    /// Add a Run Metadata sheet recording who ran the run, where and with
+    /// what; left out when `run_metadata` is `None`
+    pub fn with_run_metadata(mut self, run_metadata: Option<RunMetadata>) -> Self {
+        self.run_metadata = run_metadata;
+        self
+    }
+
+    /// Write a text cell, truncating values beyond the xlsx cell limit and
+    /// keeping the full value for the Overflow sheet
+    fn write_text(&self, worksheet: &mut Worksheet, row: u32, col: u16, value: &str) -> Result<()> {
+        let length = value.chars().count();
+        if length <= MAX_CELL_CHARS {
+            worksheet.write_string(row, col, value)?;
+            return Ok(());
+        }
+
+        let keep = MAX_CELL_CHARS - TRUNCATION_MARKER.chars().count();
+        let truncated: String = value.chars().take(keep).chain(TRUNCATION_MARKER.chars()).collect();
+        worksheet.write_string(row, col, truncated)?;
+
+        let cell = row_col_to_cell(row, col);
+        self.logger.warning(&format!(
+            "Report cell {}!{} truncated ({} characters)",
+            worksheet.name(),
+            cell,
+            length
+        ));
+        self.overflow.borrow_mut().push(OverflowCell {
+            sheet: worksheet.name(),
+            cell,
+            value: value.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Link columns after the main sheet's last column: the file in staging,
+    /// when it is kept, and the exported copy
+    fn write_file_links(
+        &self,
+        worksheet: &mut Worksheet,
+        entries: &[ReportModel],
+        links: &FileLinks,
+        columns: &MainSheetColumns,
+    ) -> Result<()> {
+        let staged_header = links
+            .staging_root
+            .as_ref()
+            .and_then(|_| columns.link_header(STAGED_FILE_HEADER));
+        let exported_header = columns.link_header(EXPORTED_COPY_HEADER);
+        let first_col = columns.headers.len() as u16;
+        let staged_col = staged_header.as_ref().map(|_| first_col);
+        let exported_col = exported_header
+            .as_ref()
+            .map(|_| first_col + staged_col.is_some() as u16);
+        for (col, header) in [(staged_col, staged_header), (exported_col, exported_header)] {
+            let (Some(col), Some(header)) = (col, header) else {
+                continue;
+            };
+            worksheet
+                .write_string(0, col, &header)
+                .with_context(|| format!("Failed to write header: {}", header))?;
+            worksheet.set_column_width(col, 15.0)?;
+        }
+
+        let mut link_count = 0;
+        for (row, entry) in entries.iter().enumerate() {
+            let row_num = (row + 1) as u32;
+            if let (Some(col), Some(staging_root)) = (staged_col, &links.staging_root) {
+                let path = long_paths::simplified(&staging_root.join(&entry.relative_path));
+                let path = path.to_string_lossy();
+                self.write_link(worksheet, row_num, col, &path, &mut link_count)
+                    .with_context(|| "Failed to write staged file link")?;
+            }
+            // A document split into parts links to its first part
+            if let (Some(col), Some(exported_file_name)) = (exported_col, entry.exported_files().first()) {
+                self.write_link(worksheet, row_num, col, exported_file_name, &mut link_count)
+                    .with_context(|| "Failed to write exported copy link")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write an "Open" hyperlink to the file at `path` (absolute, or
+    /// relative to the workbook), or the path as text
+    /// once the sheet is out of hyperlinks or it is too long to link
+    fn write_link(
+        &self,
+        worksheet: &mut Worksheet,
+        row: u32,
+        col: u16,
+        path: &str,
+        link_count: &mut usize,
+    ) -> Result<()> {
+        let target = format!("file:///{}", path.trim_start_matches('/'));
+        if *link_count >= MAX_LINKS_PER_SHEET || target.chars().count() > MAX_URL_CHARS {
+            return self.write_text(worksheet, row, col, path);
+        }
+        worksheet.write_url_with_text(row, col, Url::new(target), "Open")?;
+        *link_count += 1;
+        Ok(())
+    }
+
+    /// Write the main sheet's columns as UTF-8 CSV, with a BOM so Excel
+    /// detects the encoding too. Values are never truncated.
+    pub fn generate_csv_report(
+        &self,
+        entries: &[ReportModel],
+        settings: &SettingsSnapshot,
+        output_path: &Path,
+    ) -> Result<()> {
+        let columns = MainSheetColumns::new(&settings.settings)?;
+        let mut csv = String::from("\u{FEFF}");
+        let lines = std::iter::once(columns.headers.clone()).chain(entries.iter().map(|entry| columns.row(entry)));
+        for line in lines {
+            let fields: Vec<String> = line.iter().map(|field| csv_normalizer::quote_field(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push_str("\r\n");
+        }
+        std::fs::write(output_path, csv)
+            .with_context(|| format!("Failed to write CSV report: {}", output_path.display()))?;
+        self.logger.info(&format!(
+            "CSV report generated successfully: {} ({} entries)",
+            output_path.display(),
+            entries.len()
+        ));
+        Ok(())
+    }
+
+    /// Write the main sheet's columns as a self-contained HTML page with
+    /// the run's totals and charts, for machines without Excel. Rows link
+    /// to the files as in the workbook.
+    pub fn generate_html_report(
+        &self,
+        entries: &[ReportModel],
+        excluded: &[ExcludedFile],
+        summary: &RunSummary,
+        settings: &SettingsSnapshot,
+        output_path: &Path,
+    ) -> Result<()> {
+        let columns = MainSheetColumns::new(&settings.settings)?;
+        let mut headers = columns.headers.clone();
+        let staging_root = self.links.as_ref().and_then(|links| links.staging_root.as_ref());
+        let staged_header = staging_root.and_then(|_| columns.link_header(STAGED_FILE_HEADER));
+        let exported_header = self
+            .links
+            .as_ref()
+            .and_then(|_| columns.link_header(EXPORTED_COPY_HEADER));
+        let link_staged = staged_header.is_some();
+        let link_exported = exported_header.is_some();
+        headers.extend(staged_header);
+        headers.extend(exported_header);
+
+        let rows = entries
+            .iter()
+            .map(|entry| {
+                let mut row: Vec<HtmlCell> = columns.row(entry).into_iter().map(HtmlCell::Text).collect();
+                if let (true, Some(staging_root)) = (link_staged, staging_root) {
+                    let path = long_paths::simplified(&staging_root.join(&entry.relative_path));
+                    row.push(HtmlCell::Link(Some(html_report::file_href(&path.to_string_lossy(), true))));
+                }
+                if link_exported {
+                    let exported = entry.exported_files().first().copied();
+                    row.push(HtmlCell::Link(exported.map(|name| html_report::file_href(name, false))));
+                }
+                row
+            })
+            .collect();
+
+        let charts = vec![
+            HtmlChart {
+                title: "Files by status".to_string(),
+                bars: count_by_status(entries)
+                    .into_iter()
+                    .map(|(label, count)| (label, count as u64))
+                    .collect(),
+                as_bytes: false,
+            },
+            HtmlChart {
+                title: "Files by extension".to_string(),
+                bars: summary
+                    .file_type_counts
+                    .iter()
+                    .map(|(extension, count)| (extension_label(extension), *count as u64))
+                    .collect(),
+                as_bytes: false,
+            },
+            HtmlChart {
+                title: "Bytes by extension".to_string(),
+                bars: bytes_by_extension(entries)
+                    .into_iter()
+                    .map(|(extension, bytes)| (extension_label(&extension), bytes))
+                    .collect(),
+                as_bytes: true,
+            },
+        ];
+
+        let title = output_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "File report".to_string());
+        let html = html_report::render(&HtmlReport {
+            title,
+            totals: summary_totals(summary, excluded),
+            charts,
+            headers,
+            rows,
+        });
+        std::fs::write(output_path, html)
+            .with_context(|| format!("Failed to write HTML report: {}", output_path.display()))?;
+        self.logger.info(&format!(
+            "HTML report generated successfully: {} ({} entries)",
+            output_path.display(),
+            entries.len()
+        ));
+        Ok(())
+    }
+
+    pub fn generate_report(
+        &self,
+        entries: &[ReportModel],
+        archives: &[ArchiveRecord],
+        excluded: &[ExcludedFile],
+        summary: &RunSummary,
+        settings: &SettingsSnapshot,
+        output_path: &Path,
+    ) -> Result<()> {
+        self.logger.debug(&format!(
+            "Generating report: {}",
+            output_path.display()
+        ));
+
+        // Create parent directory if it doesn't exist
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create report directory: {}", parent.display()))?;
+        }
+
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet();
+
+        // Write headers; the hash columns are named after the run's algorithm
+        let hash_column = settings.settings.hash_algorithm.column_label();
+        let columns = MainSheetColumns::new(&settings.settings)?;
+        let headers = &columns.headers;
+        for (col, header) in headers.iter().enumerate() {
+            worksheet
+                .write_string(0, col as u16, header.to_string())
+                .with_context(|| format!("Failed to write header: {}", header))?;
+        }
+
+        // Write data rows
+        for (row, entry) in entries.iter().enumerate() {
+            let row_num = (row + 1) as u32;
+            for (col, value) in columns.row(entry).iter().enumerate() {
+                if columns.sources[col] == FILE_SIZE_BYTES_COL {
+                    worksheet
+                        .write_number(row_num, col as u16, entry.file_size_bytes as f64)
+                        .with_context(|| "Failed to write file_size_bytes")?;
+                } else if let (ESTIMATED_TOKENS_COL, Some(tokens)) = (columns.sources[col], entry.estimated_tokens) {
+                    worksheet
+                        .write_number(row_num, col as u16, tokens as f64)
+                        .with_context(|| "Failed to write estimated_tokens")?;
+                } else {
+                    self.write_text(worksheet, row_num, col as u16, value)
+                        .with_context(|| format!("Failed to write {}", headers[col]))?;
+                }
+            }
+        }
+
+        // Auto-fit columns (approximate)
+        for col in 0..headers.len() {
+            worksheet.set_column_width(col as u16, 15.0)?;
+        }
+
+        if let Some(links) = &self.links {
+            self.write_file_links(worksheet, entries, links, &columns)?;
+        }
+
+        if !entries.is_empty() {
+            highlight_main_sheet(worksheet, entries.len() as u32, &columns)?;
+        }
+
+        // Set specific column widths, by position among every column
+        let mut widths: Vec<(usize, f64)> = vec![
+            (0, 30.0),  // File Name
+            (1, 30.0),  // Converted File Name
+            (2, 64.0),  // Hash
+            (5, 40.0),  // Relative Path
+            (11, 40.0), // Converted Relative Path
+            (12, 64.0), // Converted hash
+            (15, 40.0), // Tags
+            (16, 60.0), // Summary
+            (17, 30.0), // Raw Archive Entry Name
+            (18, 40.0), // Parent Document
+            (19, 50.0), // Format Details
+            (20, 50.0), // Needs Review
+            (21, 25.0), // Export Group
+            (22, 18.0), // Source Encoding
+            (24, 40.0), // VBA Source
+            (25, 30.0), // Detected Type
+            (26, 20.0), // Conversion Backend
+            (27, 30.0), // Detected MIME Type
+            (28, 40.0), // Extension Mismatch
+            (29, 25.0), // Author
+            (30, 25.0), // Last Modified By
+            (31, 25.0), // Company
+            (32, 30.0), // Creating Application
+            (33, 50.0), // Duplicate Of / Group
+        ];
+        for offset in 0..columns.additional_hash_columns.len() {
+            widths.push((columns.first_additional_hash_col + offset, 64.0));
+        }
+        if let Some(first_exif_col) = columns.first_exif_col {
+            widths.push((first_exif_col, 20.0)); // EXIF Taken At
+            widths.push((first_exif_col + 1, 25.0)); // GPS Coordinates
+            widths.push((first_exif_col + 2, 25.0)); // Camera Model
+        }
+        if let Some(first_ownership_col) = columns.first_ownership_col {
+            widths.push((first_ownership_col, 30.0)); // Owner
+            widths.push((first_ownership_col + 1, 30.0)); // Group
+            widths.push((first_ownership_col + 2, 50.0)); // Permissions
+        }
+        for (source, width) in widths {
+            if let Some(col) = columns.position(source) {
+                worksheet.set_column_width(col, width)?;
+            }
+        }
+
+        self.write_summary_sheet(&mut workbook, entries, excluded, summary)?;
+
+        if !entries.is_empty() {
+            self.write_charts_sheet(&mut workbook, entries)?;
+        }
+
+        if entries.iter().any(is_exception) {
+            self.write_exceptions_sheet(&mut workbook, entries)?;
+        }
+
+        if entries.iter().any(|entry| !entry.tags.is_empty()) {
+            self.write_tags_sheet(&mut workbook, entries)?;
+        }
+
+        if !archives.is_empty() {
+            self.write_archives_sheet(&mut workbook, archives, hash_column)?;
+        }
+
+        if entries.iter().any(|entry| entry.export_group.is_some()) {
+            self.write_export_groups_sheet(&mut workbook, entries)?;
+        }
+
+        if entries.iter().any(|entry| entry.remediation.is_some()) {
+            self.write_skipped_sheet(&mut workbook, entries)?;
+        }
+
+        if !excluded.is_empty() {
+            self.write_exclusions_sheet(&mut workbook, excluded)?;
+        }
+
+        if entries.iter().any(|entry| entry.indicator_match.is_some()) {
+            self.write_indicator_matches_sheet(&mut workbook, entries, hash_column)?;
+        }
+
+        if let Some(run_metadata) = &self.run_metadata {
+            self.write_run_metadata_sheet(&mut workbook, run_metadata)?;
+        }
+
+        self.write_settings_sheet(&mut workbook, settings)?;
+
+        let overflow = std::mem::take(&mut *self.overflow.borrow_mut());
+        if !overflow.is_empty() {
+            self.write_overflow_sheet(&mut workbook, &overflow)?;
+        }
+
+        // Save the workbook
+        workbook
+            .save(output_path)
+            .with_context(|| format!("Failed to save workbook to: {}", output_path.display()))?;
+
+        self.logger.info(&format!(
+            "Report generated successfully: {} ({} entries)",
+            output_path.display(),
+            entries.len()
+        ));
+
+        Ok(())
+    }
+
+    /// One row per (file, tag) pair, for filtering in Excel
+    fn write_tags_sheet(&self, workbook: &mut Workbook, entries: &[ReportModel]) -> Result<()> {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Tags")?;
+
+        let headers = ["File Name", "Relative Path", "Tag", "Matches"];
+        for (col, header) in headers.iter().enumerate() {
+            worksheet
+                .write_string(0, col as u16, header.to_string())
+                .with_context(|| format!("Failed to write header: {}", header))?;
+        }
+
+        let mut row_num: u32 = 1;
+        for entry in entries {
+            for (tag, count) in &entry.tags {
+                self.write_text(worksheet, row_num, 0, &entry.original_file_name)
+                    .with_context(|| "Failed to write original_file_name")?;
+                self.write_text(worksheet, row_num, 1, &entry.original_relative_path)
+                    .with_context(|| "Failed to write relative_path")?;
+                self.write_text(worksheet, row_num, 2, tag)
+                    .with_context(|| "Failed to write tag")?;
+                worksheet
+                    .write_number(row_num, 3, *count as f64)
+                    .with_context(|| "Failed to write tag count")?;
+                row_num += 1;
+            }
+        }
+
+        worksheet.set_column_width(0, 30.0)?;
+        worksheet.set_column_width(1, 40.0)?;
+        worksheet.set_column_width(2, 25.0)?;
+
+        Ok(())
+    }
+
+    /// One row per archive the decompression stage opened or skipped
+    fn write_archives_sheet(&self, workbook: &mut Workbook, archives: &[ArchiveRecord], hash_column: &str) -> Result<()> {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Archives")?;
+
+        let headers = [
+            "Archive Path",
+            "Format",
+            hash_column,
+            "Nesting Depth",
+            "Entries",
+            "Bytes Extracted",
+            "Outcome",
+            "Detail",
+        ];
+        for (col, header) in headers.iter().enumerate() {
+            worksheet
+                .write_string(0, col as u16, header.to_string())
+                .with_context(|| format!("Failed to write header: {}", header))?;
+        }
+
+        for (row, archive) in archives.iter().enumerate() {
+            let row_num = (row + 1) as u32;
+
+            self.write_text(worksheet, row_num, 0, &archive.path)
+                .with_context(|| "Failed to write archive path")?;
+            self.write_text(worksheet, row_num, 1, &archive.format)
+                .with_context(|| "Failed to write archive format")?;
+            self.write_text(worksheet, row_num, 2, archive.hash.as_deref().unwrap_or(""))
+                .with_context(|| "Failed to write archive hash")?;
+            worksheet
+                .write_number(row_num, 3, archive.nesting_depth as f64)
+                .with_context(|| "Failed to write nesting depth")?;
+            worksheet
+                .write_number(row_num, 4, archive.entry_count as f64)
+                .with_context(|| "Failed to write entry count")?;
+            worksheet
+                .write_number(row_num, 5, archive.bytes_extracted as f64)
+                .with_context(|| "Failed to write bytes extracted")?;
+            self.write_text(worksheet, row_num, 6, archive.outcome.label())
+                .with_context(|| "Failed to write archive outcome")?;
+            self.write_text(worksheet, row_num, 7, archive.detail.as_deref().unwrap_or(""))
+                .with_context(|| "Failed to write archive detail")?;
+        }
+
+        worksheet.set_column_width(0, 50.0)?;
+        worksheet.set_column_width(2, 64.0)?;
+        worksheet.set_column_width(3, 14.0)?;
+        worksheet.set_column_width(5, 16.0)?;
+        worksheet.set_column_width(6, 26.0)?;
+        worksheet.set_column_width(7, 50.0)?;
+
+        Ok(())
+    }
+
+    /// One row per export group of a split export, with its file counts
+    fn write_export_groups_sheet(&self, workbook: &mut Workbook, entries: &[ReportModel]) -> Result<()> {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Export Groups")?;
+
+        let headers = ["Export Group", "Files", "Exported", "Duplicates", "Not Exported"];
+        for (col, header) in headers.iter().enumerate() {
+            worksheet
+                .write_string(0, col as u16, header.to_string())
+                .with_context(|| format!("Failed to write header: {}", header))?;
+        }
+
+        // (files, exported, duplicates) per group, in first-seen order
+        let mut groups: Vec<(&str, [usize; 3])> = Vec::new();
+        for entry in entries {
+            let Some(group) = entry.export_group.as_deref() else {
+                continue;
+            };
+            let counts = match groups.iter().position(|(name, _)| *name == group) {
+                Some(position) => &mut groups[position].1,
+                None => {
+                    groups.push((group, [0; 3]));
+                    &mut groups.last_mut().expect("just pushed").1
+                }
+            };
+            counts[0] += 1;
+            if entry.exported_file_name.is_some() {
+                counts[1] += 1;
+            }
+            if entry.status == FileStatus::SkippedDuplicate {
+                counts[2] += 1;
+            }
+        }
+
+        for (row, (group, [files, exported, duplicates])) in groups.iter().enumerate() {
+            let row_num = (row + 1) as u32;
+            self.write_text(worksheet, row_num, 0, group)
+                .with_context(|| "Failed to write export group")?;
+            worksheet.write_number(row_num, 1, *files as f64)?;
+            worksheet.write_number(row_num, 2, *exported as f64)?;
+            worksheet.write_number(row_num, 3, *duplicates as f64)?;
+            worksheet.write_number(row_num, 4, (files - exported - duplicates) as f64)?;
+        }
+
+        worksheet.set_column_width(0, 30.0)?;
+        for col in 1..headers.len() {
+            worksheet.set_column_width(col as u16, 14.0)?;
+        }
+
+        Ok(())
+    }
+
+    /// Files and bytes per file type and per outcome, as tables with a bar
+    /// chart of each column beside them
+    fn write_charts_sheet(&self, workbook: &mut Workbook, entries: &[ReportModel]) -> Result<()> {
+        const SHEET: &str = "Charts";
+        const CHART_COL: u16 = 8;
+        const CHART_ROWS: u32 = 16;
+
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(SHEET)?;
+        let title_format = Format::new().set_bold();
+        let megabytes_format = Format::new().set_num_format("#,##0.00");
+
+        let breakdowns = [
+            (
+                "File Type",
+                "file type",
+                chart_breakdown(entries, |entry| extension_label(&entry.file_type.to_lowercase())),
+            ),
+            ("Outcome", "outcome", chart_breakdown(entries, |entry| entry.status.label())),
+        ];
+
+        let mut chart_row = 0u32;
+        for (index, (category, name, rows)) in breakdowns.iter().enumerate() {
+            let first_col = (index * 4) as u16;
+            for (offset, header) in [*category, "Files", "Size (MB)"].iter().enumerate() {
+                worksheet
+                    .write_string_with_format(0, first_col + offset as u16, *header, &title_format)
+                    .with_context(|| format!("Failed to write header: {}", header))?;
+            }
+            for (row, (label, files, bytes)) in rows.iter().enumerate() {
+                let row_num = (row + 1) as u32;
+                self.write_text(worksheet, row_num, first_col, label)
+                    .with_context(|| "Failed to write chart category")?;
+                worksheet
+                    .write_number(row_num, first_col + 1, *files as f64)
+                    .with_context(|| "Failed to write chart file count")?;
+                worksheet
+                    .write_number_with_format(row_num, first_col + 2, *bytes as f64 / 1_048_576.0, &megabytes_format)
+                    .with_context(|| "Failed to write chart size")?;
+            }
+            worksheet.set_column_width(first_col, 28.0)?;
+
+            let last_row = rows.len() as u32;
+            for (value_col, title) in [(1, format!("Files per {}", name)), (2, format!("Size per {} (MB)", name))] {
+                let mut chart = Chart::new(ChartType::Bar);
+                chart.title().set_name(&title);
+                chart.legend().set_hidden();
+                chart
+                    .add_series()
+                    .set_categories((SHEET, 1, first_col, last_row, first_col))
+                    .set_values((SHEET, 1, first_col + value_col, last_row, first_col + value_col));
+                worksheet
+                    .insert_chart(chart_row, CHART_COL, &chart)
+                    .with_context(|| format!("Failed to insert chart: {}", title))?;
+                chart_row += CHART_ROWS;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Files that didn't make it into the export, grouped by why, the
+    /// largest group first; each group opens with a row giving its count
+    fn write_exceptions_sheet(&self, workbook: &mut Workbook, entries: &[ReportModel]) -> Result<()> {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Exceptions")?;
+        let group_format = Format::new().set_bold();
+
+        let headers = ["File Name", "Relative Path", "Status", "Skip Reason"];
+        for (col, header) in headers.iter().enumerate() {
+            worksheet
+                .write_string(0, col as u16, header.to_string())
+                .with_context(|| format!("Failed to write header: {}", header))?;
+        }
+
+        // Entries without a skip reason are grouped under their status
+        let mut groups: BTreeMap<String, Vec<&ReportModel>> = BTreeMap::new();
+        for entry in entries.iter().filter(|entry| is_exception(entry)) {
+            let reason = entry.skip_reason.clone().unwrap_or_else(|| entry.status.label());
+            groups.entry(reason).or_default().push(entry);
+        }
+        let mut groups: Vec<(String, Vec<&ReportModel>)> = groups.into_iter().collect();
+        groups.sort_by(|(_, a), (_, b)| b.len().cmp(&a.len()));
+
+        let mut row_num = 1u32;
+        for (reason, members) in groups {
+            let title = format!("{} ({} file{})", reason, members.len(), if members.len() == 1 { "" } else { "s" });
+            worksheet
+                .write_string_with_format(row_num, 0, title, &group_format)
+                .with_context(|| "Failed to write exception group")?;
+            row_num += 1;
+            for entry in members {
+                self.write_text(worksheet, row_num, 0, &entry.original_file_name)
+                    .with_context(|| "Failed to write exception file name")?;
+                self.write_text(worksheet, row_num, 1, &entry.original_relative_path)
+                    .with_context(|| "Failed to write exception relative path")?;
+                self.write_text(worksheet, row_num, 2, &entry.status.label())
+                    .with_context(|| "Failed to write exception status")?;
+                self.write_text(worksheet, row_num, 3, entry.skip_reason.as_deref().unwrap_or(""))
+                    .with_context(|| "Failed to write exception skip reason")?;
+                row_num += 1;
+            }
+        }
+
+        worksheet.set_column_width(0, 40.0)?;
+        worksheet.set_column_width(1, 50.0)?;
+        worksheet.set_column_width(2, 25.0)?;
+        worksheet.set_column_width(3, 60.0)?;
+
+        Ok(())
+    }
+
+    /// Files left out of the export that need someone to act, with what to do
+    fn write_skipped_sheet(&self, workbook: &mut Workbook, entries: &[ReportModel]) -> Result<()> {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Skipped")?;
+
+        let headers = ["File Name", "Relative Path", "Status", "Skip Reason", "Next Step", "Remediation Key"];
+        for (col, header) in headers.iter().enumerate() {
+            worksheet
+                .write_string(0, col as u16, header.to_string())
+                .with_context(|| format!("Failed to write header: {}", header))?;
+        }
+
+        let skipped = entries
+            .iter()
+            .filter_map(|entry| Some((entry, entry.remediation?)));
+        for (row, (entry, remediation)) in skipped.enumerate() {
+            let row_num = (row + 1) as u32;
+            self.write_text(worksheet, row_num, 0, &entry.original_file_name)
+                .with_context(|| "Failed to write skipped file name")?;
+            self.write_text(worksheet, row_num, 1, &entry.original_relative_path)
+                .with_context(|| "Failed to write skipped relative path")?;
+            self.write_text(worksheet, row_num, 2, &entry.status.label())
+                .with_context(|| "Failed to write skipped status")?;
+            self.write_text(worksheet, row_num, 3, entry.skip_reason.as_deref().unwrap_or(""))
+                .with_context(|| "Failed to write skip reason")?;
+            self.write_text(worksheet, row_num, 4, remediation.hint())
+                .with_context(|| "Failed to write remediation hint")?;
+            self.write_text(worksheet, row_num, 5, remediation.key())
+                .with_context(|| "Failed to write remediation key")?;
+        }
+
+        worksheet.set_column_width(0, 30.0)?;
+        worksheet.set_column_width(1, 40.0)?;
+        worksheet.set_column_width(2, 20.0)?;
+        worksheet.set_column_width(3, 60.0)?;
+        worksheet.set_column_width(4, 70.0)?;
+        worksheet.set_column_width(5, 26.0)?;
+
+        Ok(())
+    }
+
+    /// Run statistics: totals, files by outcome, files by extension and
+    /// the time each stage took
+    fn write_summary_sheet(
+        &self,
+        workbook: &mut Workbook,
+        entries: &[ReportModel],
+        excluded: &[ExcludedFile],
+        summary: &RunSummary,
+    ) -> Result<()> {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Summary")?;
+        let section_format = Format::new().set_bold();
+
+        let totals = summary_totals(summary, excluded);
+        let by_status = count_by_status(entries);
+        let by_extension: Vec<(String, String)> = summary
+            .file_type_counts
+            .iter()
+            .map(|(extension, count)| (extension_label(extension), count.to_string()))
+            .collect();
+        let mut durations: Vec<(String, String)> = summary
+            .stage_durations_ms
+            .iter()
+            .map(|(stage, ms)| (stage.clone(), format_seconds(*ms)))
+            .collect();
+        durations.push(("Total".to_string(), format_seconds(summary.total_duration_ms)));
+
+        let sections: Vec<(&str, Vec<(String, String)>)> = vec![
+            ("Totals", totals),
+            ("Files by status", by_status.into_iter().map(|(label, count)| (label, count.to_string())).collect()),
+            ("Files by extension", by_extension),
+            ("Stage durations (seconds)", durations),
+        ];
+
+        let mut row_num = 0u32;
+        for (title, rows) in sections {
+            worksheet
+                .write_string_with_format(row_num, 0, title, &section_format)
+                .with_context(|| format!("Failed to write summary section: {}", title))?;
+            row_num += 1;
+            for (name, value) in rows {
+                self.write_text(worksheet, row_num, 0, &name)
+                    .with_context(|| "Failed to write summary label")?;
+                self.write_text(worksheet, row_num, 1, &value)
+                    .with_context(|| "Failed to write summary value")?;
+                row_num += 1;
+            }
+            row_num += 1;
+        }
+
+        worksheet.set_column_width(0, 45.0)?;
+        worksheet.set_column_width(1, 20.0)?;
+
+        Ok(())
+    }
+
+    /// Hidden and system files left out of the run before they were listed
+    fn write_exclusions_sheet(&self, workbook: &mut Workbook, excluded: &[ExcludedFile]) -> Result<()> {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Exclusions")?;
+
+        let headers = ["File Name", "Relative Path", "Reason"];
+        for (col, header) in headers.iter().enumerate() {
+            worksheet
+                .write_string(0, col as u16, header.to_string())
+                .with_context(|| format!("Failed to write header: {}", header))?;
+        }
+
+        for (row, file) in excluded.iter().enumerate() {
+            let row_num = (row + 1) as u32;
+            let file_name = Path::new(&file.relative_path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            self.write_text(worksheet, row_num, 0, &file_name)
+                .with_context(|| "Failed to write excluded file name")?;
+            self.write_text(worksheet, row_num, 1, &file.relative_path)
+                .with_context(|| "Failed to write excluded relative path")?;
+            self.write_text(worksheet, row_num, 2, &file.reason)
+                .with_context(|| "Failed to write exclusion reason")?;
+        }
+
+        worksheet.set_column_width(0, 30.0)?;
+        worksheet.set_column_width(1, 50.0)?;
+        worksheet.set_column_width(2, 45.0)?;
+
+        Ok(())
+    }
+
+    /// Files matching the known-bad hash list; the workbook opens on this
+    /// sheet so the matches can't be overlooked
+    fn write_indicator_matches_sheet(
+        &self,
+        workbook: &mut Workbook,
+        entries: &[ReportModel],
+        hash_column: &str,
+    ) -> Result<()> {
+        let warning_format = Format::new().set_bold().set_font_color(Color::Red);
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Indicator Matches")?;
+        worksheet.set_tab_color(Color::Red);
+        worksheet.set_active(true);
+
+        let matches: Vec<&ReportModel> = entries.iter().filter(|entry| entry.indicator_match.is_some()).collect();
+        worksheet
+            .write_string_with_format(
+                0,
+                0,
+                format!("WARNING: {} file(s) match the known-bad hash list and were quarantined", matches.len()),
+                &warning_format,
+            )
+            .with_context(|| "Failed to write indicator warning")?;
+
+        let headers = ["File Name", "Relative Path", hash_column, "Matched Indicator", "Quarantined At"];
+        for (col, header) in headers.iter().enumerate() {
+            worksheet
+                .write_string_with_format(2, col as u16, header.to_string(), &warning_format)
+                .with_context(|| format!("Failed to write header: {}", header))?;
+        }
+
+        for (row, entry) in matches.iter().enumerate() {
+            let row_num = (row + 3) as u32;
+            self.write_text(worksheet, row_num, 0, &entry.original_file_name)
+                .with_context(|| "Failed to write matched file name")?;
+            self.write_text(worksheet, row_num, 1, &entry.original_relative_path)
+                .with_context(|| "Failed to write matched relative path")?;
+            self.write_text(worksheet, row_num, 2, entry.original_hash.as_deref().unwrap_or(""))
+                .with_context(|| "Failed to write matched hash")?;
+            self.write_text(worksheet, row_num, 3, entry.indicator_match.as_deref().unwrap_or(""))
+                .with_context(|| "Failed to write matched indicator")?;
+            self.write_text(worksheet, row_num, 4, &entry.relative_path)
+                .with_context(|| "Failed to write quarantine path")?;
+        }
+
+        worksheet.set_column_width(0, 30.0)?;
+        worksheet.set_column_width(1, 40.0)?;
+        worksheet.set_column_width(2, 64.0)?;
+        worksheet.set_column_width(3, 60.0)?;
+        worksheet.set_column_width(4, 40.0)?;
+
+        Ok(())
+    }
+
+    /// The effective configuration of the run, one setting per row
+    fn write_run_metadata_sheet(&self, workbook: &mut Workbook, run_metadata: &RunMetadata) -> Result<()> {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Run Metadata")?;
+
+        let headers = ["Field", "Value"];
+        for (col, header) in headers.iter().enumerate() {
+            worksheet
+                .write_string(0, col as u16, header.to_string())
+                .with_context(|| format!("Failed to write header: {}", header))?;
+        }
+
+        for (row, (field, value)) in run_metadata.entries().iter().enumerate() {
+            let row_num = (row + 1) as u32;
+            self.write_text(worksheet, row_num, 0, field)
+                .with_context(|| "Failed to write run metadata field")?;
+            self.write_text(worksheet, row_num, 1, value)
+                .with_context(|| "Failed to write run metadata value")?;
+        }
+
+        worksheet.set_column_width(0, 25.0)?;
+        worksheet.set_column_width(1, 80.0)?;
+
+        Ok(())
+    }
+
+    fn write_settings_sheet(&self, workbook: &mut Workbook, settings: &SettingsSnapshot) -> Result<()> {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Run Settings")?;
+
+        let headers = ["Setting", "Value"];
+        for (col, header) in headers.iter().enumerate() {
+            worksheet
+                .write_string(0, col as u16, header.to_string())
+                .with_context(|| format!("Failed to write header: {}", header))?;
+        }
+
+        for (row, (setting, value)) in settings.entries().iter().enumerate() {
+            let row_num = (row + 1) as u32;
+            self.write_text(worksheet, row_num, 0, setting)
+                .with_context(|| "Failed to write setting name")?;
+            self.write_text(worksheet, row_num, 1, value)
+                .with_context(|| "Failed to write setting value")?;
+        }
+
+        worksheet.set_column_width(0, 40.0)?;
+        worksheet.set_column_width(1, 80.0)?;
+
+        Ok(())
+    }
+
+    /// Full text of every truncated cell, split over as many columns as it
+    /// takes to stay within the cell limit
+    fn write_overflow_sheet(&self, workbook: &mut Workbook, overflow: &[OverflowCell]) -> Result<()> {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("Overflow")?;
+
+        let headers = ["Sheet", "Cell", "Length", "Full Value"];
+        for (col, header) in headers.iter().enumerate() {
+            worksheet
+                .write_string(0, col as u16, header.to_string())
+                .with_context(|| format!("Failed to write header: {}", header))?;
+        }
+
+        for (row, item) in overflow.iter().enumerate() {
+            let row_num = (row + 1) as u32;
+            worksheet
+                .write_string(row_num, 0, &item.sheet)
+                .with_context(|| "Failed to write overflow sheet name")?;
+            worksheet
+                .write_string(row_num, 1, &item.cell)
+                .with_context(|| "Failed to write overflow cell")?;
+            let chars: Vec<char> = item.value.chars().collect();
+            worksheet
+                .write_number(row_num, 2, chars.len() as f64)
+                .with_context(|| "Failed to write overflow length")?;
+            for (part, chunk) in chars.chunks(MAX_CELL_CHARS).enumerate() {
+                worksheet
+                    .write_string(row_num, 3 + part as u16, chunk.iter().collect::<String>())
+                    .with_context(|| "Failed to write overflow value")?;
+            }
+        }
+
+        worksheet.set_column_width(0, 20.0)?;
+        worksheet.set_column_width(3, 80.0)?;
+
+        Ok(())
+    }
+}
+
+/// Colour rows of failed files red and of skipped files yellow, and flag
+/// empty files and extension mismatches, as conditional formats so the
+/// colours follow the rows when the sheet is sorted or filtered
+fn highlight_main_sheet(worksheet: &mut Worksheet, rows: u32, columns: &MainSheetColumns) -> Result<()> {
+    let cols = columns.headers.len() as u16;
+    let cell = |col: u16| format!("${}2", column_number_to_name(col));
+
+    let failed_format = Format::new()
+        .set_background_color(Color::RGB(0xFFC7CE))
+        .set_font_color(Color::RGB(0x9C0006));
+    let skipped_format = Format::new()
+        .set_background_color(Color::RGB(0xFFEB9C))
+        .set_font_color(Color::RGB(0x9C5700));
+    let flag_format = Format::new().set_bold().set_background_color(Color::RGB(0xF8CBAD));
+
+    // Each rule needs the column it tests to be shown. Flags are added
+    // first so they take priority over the row colour.
+    if let Some(size_col) = columns.position(FILE_SIZE_BYTES_COL) {
+        let empty_file = ConditionalFormatFormula::new()
+            .set_rule(format!("={}=0", cell(size_col)).as_str())
+            .set_format(&flag_format);
+        worksheet.add_conditional_format(1, size_col, rows, size_col, &empty_file)?;
+    }
+    if let Some(mismatch_col) = columns.position(EXTENSION_MISMATCH_COL) {
+        let extension_mismatch = ConditionalFormatFormula::new()
+            .set_rule(format!("={}<>\"\"", cell(mismatch_col)).as_str())
+            .set_format(&flag_format);
+        worksheet.add_conditional_format(1, mismatch_col, rows, mismatch_col, &extension_mismatch)?;
+    }
+    if let Some(status_col) = columns.position(STATUS_COL) {
+        // Status labels as `FileStatus::label` writes them
+        let status = cell(status_col);
+        let failed = ConditionalFormatFormula::new()
+            .set_rule(format!("=LEFT({},6)=\"Failed\"", status).as_str())
+            .set_format(failed_format);
+        let skipped = ConditionalFormatFormula::new()
+            .set_rule(
+                format!(
+                    "=OR({0}=\"Skipped (encrypted)\",{0}=\"Excluded\",{0}=\"Inventory only\")",
+                    status
+                )
+                .as_str(),
+            )
+            .set_format(skipped_format);
+        worksheet.add_conditional_format(1, 0, rows, cols - 1, &failed)?;
+        worksheet.add_conditional_format(1, 0, rows, cols - 1, &skipped)?;
+    }
+    Ok(())
+}
+
+/// Check the `report_columns` of `settings` name existing columns
+pub(crate) fn validate_report_columns(settings: &ProcessingSettings) -> Result<()> {
+    MainSheetColumns::new(settings).map(|_| ())
+}
+
+/// Headline figures of a run, for the Summary sheet and the HTML report
+fn summary_totals(summary: &RunSummary, excluded: &[ExcludedFile]) -> Vec<(String, String)> {
+    let converted = summary.status_counts.get("converted").copied().unwrap_or(0);
+    let totals = [
+        ("Files found", summary.total_files.to_string()),
+        ("Files exported", summary.exported_files.to_string()),
+        ("Files converted", converted.to_string()),
+        ("Duplicates collapsed in the export", summary.duplicates_collapsed.to_string()),
+        ("Duplicate groups", summary.duplicate_groups.to_string()),
+        ("Redundant bytes", report_model::format_file_size(summary.redundant_bytes)),
+        ("Total bytes in", report_model::format_file_size(summary.total_bytes_in)),
+        ("Total bytes exported", report_model::format_file_size(summary.total_bytes_out)),
+        ("Estimated tokens exported", summary.estimated_tokens.to_string()),
+        ("Files beyond the export size cap", summary.over_export_cap.to_string()),
+        ("Indicator matches", summary.indicator_matches.to_string()),
+        ("Known files excluded", summary.known_files_excluded.to_string()),
+        ("Needs review", summary.needs_review.to_string()),
+        ("Password protected", summary.password_protected.to_string()),
+        ("Documents with macros", summary.macro_documents.to_string()),
+        ("Hidden, system and pattern-excluded files", excluded.len().to_string()),
+    ];
+    totals.into_iter().map(|(name, value)| (name.to_string(), value)).collect()
+}
+
+/// Entries per status label, so failures show their stage and the skip
+/// reasons can be told apart
+fn count_by_status(entries: &[ReportModel]) -> BTreeMap<String, usize> {
+    let mut by_status = BTreeMap::new();
+    for entry in entries {
+        *by_status.entry(entry.status.label()).or_insert(0) += 1;
+    }
+    by_status
+}
+
+/// Bytes of the input per lowercased extension, as `RunSummary` counts files
+fn bytes_by_extension(entries: &[ReportModel]) -> BTreeMap<String, u64> {
+    let mut bytes = BTreeMap::new();
+    for entry in entries {
+        *bytes.entry(entry.file_type.to_lowercase()).or_insert(0) += entry.file_size_bytes;
+    }
+    bytes
+}
+
+/// Files and bytes per category of `entries`, largest count first, with
+/// the categories beyond the chartable number summed into "Other"
+fn chart_breakdown(entries: &[ReportModel], category: impl Fn(&ReportModel) -> String) -> Vec<(String, usize, u64)> {
+    let mut totals: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+    for entry in entries {
+        let total = totals.entry(category(entry)).or_insert((0, 0));
+        total.0 += 1;
+        total.1 += entry.file_size_bytes;
+    }
+    let mut rows: Vec<(String, usize, u64)> = totals
+        .into_iter()
+        .map(|(label, (files, bytes))| (label, files, bytes))
+        .collect();
+    rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    if rows.len() > MAX_CHART_CATEGORIES {
+        let (files, bytes) = rows
+            .drain(MAX_CHART_CATEGORIES - 1..)
+            .fold((0, 0), |(files, bytes), row| (files + row.1, bytes + row.2));
+        rows.push(("Other".to_string(), files, bytes));
+    }
+    rows
+}
+
+/// `.pdf` for `pdf`, `(none)` for files without an extension
+fn extension_label(extension: &str) -> String {
+    if extension.is_empty() {
+        "(none)".to_string()
+    } else {
+        format!(".{}", extension)
+    }
+}
+
+/// Whether `entry` was processed but left out of the export; files not
+/// processed yet (a partial inventory) aren't exceptions
+fn is_exception(entry: &ReportModel) -> bool {
+    !entry.status.is_exportable() && entry.status != FileStatus::Pending
+}
+
+/// Milliseconds as seconds with one decimal, e.g. `12.3`
+fn format_seconds(ms: u64) -> String {
+    format!("{:.1}", ms as f64 / 1000.0)
+}
@@ -0,0 +1,207 @@
+use crate::engagement::{Engagement, EngagementRun, EngagementStore};
+use crate::ept_logger::EPTLogger;
+use crate::secure_delete::{secure_delete_path, DeletionStats};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A run whose retention date has passed and whose data still exists
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpiredRun {
+    pub engagement_id: String,
+    pub engagement_name: String,
+    pub run_id: String,
+    pub delete_after: NaiveDate,
+}
+
+/// Result of purging one run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgedRun {
+    pub engagement_id: String,
+    pub run_id: String,
+    pub certificate_path: String,
+    pub stats: DeletionStats,
+}
+
+/// The date a run's data becomes due for deletion: the date recorded with
+/// the run, or the engagement's current retention date
+fn due_date(engagement: &Engagement, run: &EngagementRun) -> Option<NaiveDate> {
+    run.delete_after.or(engagement.retention_date)
+}
+
+/// Runs due for deletion on or before `today` that have not been purged yet
+pub fn expired_runs(store: &EngagementStore, today: NaiveDate) -> Result<Vec<ExpiredRun>> {
+    let mut expired = Vec::new();
+    for engagement in store.list()? {
+        for run in &engagement.runs {
+            if run.purged_at.is_some() {
+                continue;
+            }
+            if let Some(delete_after) = due_date(&engagement, run).filter(|date| *date <= today) {
+                expired.push(ExpiredRun {
+                    engagement_id: engagement.id.clone(),
+                    engagement_name: engagement.name.clone(),
+                    run_id: run.run_id.clone(),
+                    delete_after,
+                });
+            }
+        }
+    }
+    Ok(expired)
+}
+
+/// Securely delete the staging and export folders of every expired run and
+/// write a deletion certificate for each into the engagement folder.
+///
+/// The original input is never touched.
+pub fn purge_expired(store: &EngagementStore, logger: &EPTLogger, today: NaiveDate) -> Result<Vec<PurgedRun>> {
+    let mut purged = Vec::new();
+
+    for engagement in store.list()? {
+        let mut engagement = engagement;
+        let mut changed = false;
+
+        for index in 0..engagement.runs.len() {
+            let run = &engagement.runs[index];
+            if run.purged_at.is_some() {
+                continue;
+            }
+            let Some(delete_after) = due_date(&engagement, run).filter(|date| *date <= today) else {
+                continue;
+            };
+
+            logger.info(&format!(
+                "Purging run {} of engagement {} (retention date {})",
+                run.run_id, engagement.id, delete_after
+            ));
+
+            let mut targets: Vec<(&str, PathBuf)> = Vec::new();
+            if let Some(staging_path) = &run.staging_path {
+                let staging_path = PathBuf::from(staging_path);
+                // Defensive: staging is a copy, never the evidence itself
+                if staging_path != Path::new(&run.input_path) {
+                    targets.push(("Staging", staging_path));
+                }
+            }
+            targets.push(("Export and report", store.run_dir(&engagement.id, &run.run_id)));
+            if let Some(llm_output_path) = &run.llm_output_path {
+                let llm_output_path = PathBuf::from(llm_output_path);
+                if !llm_output_path.starts_with(store.run_dir(&engagement.id, &run.run_id)) {
+                    targets.push(("Export", llm_output_path));
+                }
+            }
+
+            let mut stats = DeletionStats::default();
+            let mut deleted_paths = Vec::new();
+            for (label, target) in &targets {
+                let existed = target.exists();
+                let target_stats = secure_delete_path(target, 1, logger)
+                    .with_context(|| format!("Failed to delete {}", target.display()))?;
+                deleted_paths.push(format!(
+                    "{}: {} ({})",
+                    label,
+                    target.display(),
+                    if existed {
+                        format!("{} files, {} bytes", target_stats.files_deleted, target_stats.bytes_overwritten)
+                    } else {
+                        "already absent".to_string()
+                    }
+                ));
+                stats.files_deleted += target_stats.files_deleted;
+                stats.bytes_overwritten += target_stats.bytes_overwritten;
+                stats.failures.extend(target_stats.failures);
+            }
+
+            let purged_at = chrono::Local::now();
+            let certificate_path = write_certificate(
+                store,
+                &engagement,
+                run,
+                delete_after,
+                &purged_at.to_rfc3339(),
+                &deleted_paths,
+                &stats,
+            )?;
+
+            if stats.failures.is_empty() {
+                engagement.runs[index].purged_at = Some(purged_at.to_rfc3339());
+                changed = true;
+            } else {
+                logger.warning(&format!(
+                    "Run {} was only partly purged: {} items could not be deleted",
+                    engagement.runs[index].run_id,
+                    stats.failures.len()
+                ));
+            }
+
+            purged.push(PurgedRun {
+                engagement_id: engagement.id.clone(),
+                run_id: engagement.runs[index].run_id.clone(),
+                certificate_path: certificate_path.to_string_lossy().to_string(),
+                stats,
+            });
+        }
+
+        if changed {
+            store.save(&engagement)?;
+        }
+    }
+
+    logger.info(&format!("Retention purge complete: {} runs purged", purged.len()));
+    Ok(purged)
+}
+
+fn write_certificate(
+    store: &EngagementStore,
+    engagement: &Engagement,
+    run: &EngagementRun,
+    delete_after: NaiveDate,
+    purged_at: &str,
+    deleted_paths: &[String],
+    stats: &DeletionStats,
+) -> Result<PathBuf> {
+    let operator = std::env::var("USERNAME")
+        .or_else(|_| std::env::var("USER"))
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    let mut certificate = String::new();
+    certificate.push_str("DATA DELETION CERTIFICATE\n\n");
+    certificate.push_str(&format!("Engagement:      {} ({})\n", engagement.name, engagement.id));
+    certificate.push_str(&format!("Client code:     {}\n", engagement.client_code));
+    certificate.push_str(&format!("Run:             {} (started {})\n", run.run_id, run.started_at));
+    certificate.push_str(&format!("Original input:  {} (not deleted)\n", run.input_path));
+    certificate.push_str(&format!("Retention date:  {}\n", delete_after));
+    certificate.push_str(&format!("Deleted at:      {}\n", purged_at));
+    certificate.push_str(&format!("Deleted by:      {}\n", operator));
+    certificate.push_str("Method:          file contents overwritten with zeros, flushed to disk, then removed\n\n");
+    certificate.push_str("Deleted locations:\n");
+    for path in deleted_paths {
+        certificate.push_str(&format!("  - {}\n", path));
+    }
+    certificate.push_str(&format!(
+        "\nTotal: {} files, {} bytes overwritten\n",
+        stats.files_deleted, stats.bytes_overwritten
+    ));
+    if stats.failures.is_empty() {
+        certificate.push_str("Failures: none\n");
+    } else {
+        certificate.push_str("Failures (not deleted):\n");
+        for (path, error) in &stats.failures {
+            certificate.push_str(&format!("  - {}: {}\n", path, error));
+        }
+    }
+
+    let dir = store.certificates_dir(&engagement.id);
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create certificates folder: {}", dir.display()))?;
+    let path = dir.join(format!(
+        "{}_deletion_certificate_{}.txt",
+        run.run_id,
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    ));
+    fs::write(&path, certificate)
+        .with_context(|| format!("Failed to write deletion certificate: {}", path.display()))?;
+    Ok(path)
+}
@@ -0,0 +1,101 @@
+use crate::settings::ProcessingSettings;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+
+/// Who ran the tool, where and with what, for documenting how the evidence
+/// was transformed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunMetadata {
+    pub tool_version: String,
+    pub run_started_at: String,
+    /// OS account the run was started under
+    pub operator: String,
+    pub machine_name: String,
+    /// e.g. `windows x86_64`
+    pub operating_system: String,
+    pub input_path: String,
+    /// Settings profile the run was configured from, if any
+    pub profile: Option<String>,
+    pub hash_algorithm: String,
+    /// Version of the LibreOffice installation conversions use; `None`
+    /// when it isn't installed
+    pub libreoffice_version: Option<String>,
+}
+
+impl RunMetadata {
+    /// Record the run about to start on `input_path`, with the LibreOffice
+    /// executable conversions will use
+    pub fn capture(settings: &ProcessingSettings, input_path: &Path, libreoffice: Option<&Path>) -> Self {
+        Self {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            run_started_at: chrono::Local::now().to_rfc3339(),
+            operator: std::env::var("USERNAME")
+                .or_else(|_| std::env::var("USER"))
+                .unwrap_or_else(|_| "unknown".to_string()),
+            machine_name: machine_name(),
+            operating_system: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            input_path: input_path.to_string_lossy().to_string(),
+            profile: settings.profile.clone(),
+            hash_algorithm: settings.hash_algorithm.label().to_string(),
+            libreoffice_version: libreoffice.and_then(libreoffice_version),
+        }
+    }
+
+    /// `(field, value)` pairs for the report's Run Metadata sheet
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        let or_none = |value: &Option<String>| value.clone().unwrap_or_else(|| "(none)".to_string());
+        vec![
+            ("Tool version", self.tool_version.clone()),
+            ("Run started", self.run_started_at.clone()),
+            ("Operator", self.operator.clone()),
+            ("Machine name", self.machine_name.clone()),
+            ("Operating system", self.operating_system.clone()),
+            ("Input path", self.input_path.clone()),
+            ("Settings profile", or_none(&self.profile)),
+            ("Hash algorithm", self.hash_algorithm.clone()),
+            ("LibreOffice version", or_none(&self.libreoffice_version)),
+        ]
+    }
+}
+
+fn machine_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .or_else(|| {
+            std::fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|name| name.trim().to_string())
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Version of the LibreOffice installation `soffice` belongs to, e.g.
+/// `7.6.4.1`. Read from `version.ini` (`versionrc` on Linux and macOS)
+/// beside it where there is one, as `soffice.exe --version` prints nothing
+/// on Windows.
+fn libreoffice_version(soffice: &Path) -> Option<String> {
+    // `/usr/bin/soffice` is usually a link into the installation
+    let installed = soffice.canonicalize().unwrap_or_else(|_| soffice.to_path_buf());
+    let program_dir = installed.parent()?;
+    for (file_name, key) in [("version.ini", "MsiProductVersion"), ("versionrc", "ProductVersion")] {
+        let Ok(text) = std::fs::read_to_string(program_dir.join(file_name)) else {
+            continue;
+        };
+        let version = text.lines().find_map(|line| {
+            let (name, value) = line.split_once('=')?;
+            (name.trim() == key).then(|| value.trim().to_string())
+        });
+        if let Some(version) = version.filter(|version| !version.is_empty()) {
+            return Some(version);
+        }
+    }
+
+    // `LibreOffice 7.6.4.1 60(Build:1) …`
+    let output = Command::new(soffice).arg("--version").output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let version = text.split_whitespace().nth(1)?;
+    Some(version.to_string())
+}
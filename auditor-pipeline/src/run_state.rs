@@ -0,0 +1,182 @@
+use crate::hashing_service::{FileDigests, HashAlgorithm};
+use crate::report_model::{ArchiveRecord, ExcludedFile, FileStatus, ReportModel};
+use crate::run_metadata::RunMetadata;
+use crate::settings::ProcessingSettings;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// File the run state is written to in the staging folder
+pub const RUN_STATE_FILE_NAME: &str = ".run_state.json";
+
+/// File the scanned entries of a run in progress are written to
+pub const RUN_CHECKPOINT_FILE_NAME: &str = ".run_checkpoint.json";
+
+/// File per-entry progress of a run is appended to, one JSON record per line
+pub const RUN_PROGRESS_FILE_NAME: &str = ".run_progress.jsonl";
+
+/// What the per-file stages of a run produced, saved in its staging folder
+/// just before export so the export and report can be redone later without
+/// reprocessing the evidence.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunState {
+    pub run_id: Option<String>,
+    pub saved_at: String,
+    /// Entries as they were before export
+    pub entries: Vec<ReportModel>,
+    pub archives: Vec<ArchiveRecord>,
+    /// Files found by the scan, for progress reporting
+    pub total_files: usize,
+    /// Where the run filed its export, when not next to the staging folder
+    pub output_root: Option<PathBuf>,
+    pub engagement_code: Option<String>,
+    /// Algorithm the entries' hashes were taken with
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+    #[serde(default)]
+    pub additional_hash_algorithms: Vec<HashAlgorithm>,
+    /// Hidden and system files left out of the run
+    #[serde(default)]
+    pub excluded_files: Vec<ExcludedFile>,
+    /// Who ran the run, where and with what; absent in states saved before
+    /// it was recorded
+    #[serde(default)]
+    pub run_metadata: Option<RunMetadata>,
+    /// Settings the run was made with, without secrets, so a rerun starts
+    /// from them; absent in states saved before they were recorded
+    #[serde(default)]
+    pub settings: Option<ProcessingSettings>,
+}
+
+impl RunState {
+    pub fn save(&self, staging_path: &Path) -> Result<PathBuf> {
+        let path = staging_path.join(RUN_STATE_FILE_NAME);
+        let json = serde_json::to_string(self).context("Failed to serialize run state")?;
+        std::fs::write(&path, json).with_context(|| format!("Failed to write run state: {}", path.display()))?;
+        Ok(path)
+    }
+
+    pub fn load(staging_path: &Path) -> Result<Self> {
+        let path = staging_path.join(RUN_STATE_FILE_NAME);
+        let bytes = std::fs::read(&path).with_context(|| {
+            format!(
+                "No saved run state at {}; the staging folder must be kept to redo a run's export",
+                path.display()
+            )
+        })?;
+        serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+/// Settings saved with the run in `staging_path`, from its run state or,
+/// for a run that didn't get that far, its checkpoint
+pub fn saved_settings(staging_path: &Path) -> Option<ProcessingSettings> {
+    RunState::load(staging_path)
+        .map(|state| state.settings)
+        .or_else(|_| RunCheckpoint::load(staging_path).map(|checkpoint| checkpoint.state.settings))
+        .ok()
+        .flatten()
+}
+
+/// Status an archive's entry gets from how its extraction went, applied once
+/// the file stages have run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractionOutcome {
+    pub relative_path: String,
+    pub status: FileStatus,
+    pub skip_reason: String,
+}
+
+/// What a run had found when it started on the files, saved in staging so
+/// an interrupted run can be resumed without decompressing and scanning
+/// again. The entries are as scanned; `RunProgress` records what became of
+/// them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunCheckpoint {
+    pub state: RunState,
+    pub extraction_outcomes: Vec<ExtractionOutcome>,
+}
+
+impl RunCheckpoint {
+    pub fn save(&self, staging_path: &Path) -> Result<PathBuf> {
+        let path = staging_path.join(RUN_CHECKPOINT_FILE_NAME);
+        let json = serde_json::to_string(self).context("Failed to serialize run checkpoint")?;
+        std::fs::write(&path, json).with_context(|| format!("Failed to write run checkpoint: {}", path.display()))?;
+        Ok(path)
+    }
+
+    pub fn load(staging_path: &Path) -> Result<Self> {
+        let path = staging_path.join(RUN_CHECKPOINT_FILE_NAME);
+        let bytes = std::fs::read(&path).with_context(|| {
+            format!("No run checkpoint at {}; only interrupted runs can be resumed", path.display())
+        })?;
+        serde_json::from_slice(&bytes).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Remove the checkpoint and progress log once the run has finished
+    pub fn remove(staging_path: &Path) {
+        let _ = std::fs::remove_file(staging_path.join(RUN_CHECKPOINT_FILE_NAME));
+        let _ = std::fs::remove_file(staging_path.join(RUN_PROGRESS_FILE_NAME));
+    }
+}
+
+/// One line of the progress log; entries are identified by their position
+/// in the checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProgressRecord {
+    Hashed { index: usize, digests: FileDigests },
+    Processed { index: usize, entry: ReportModel },
+}
+
+/// Append-only log of finished hashing and file processing, written as the
+/// run goes so a crash loses at most the files in flight
+pub struct RunProgress {
+    file: File,
+}
+
+impl RunProgress {
+    /// Open the log of `staging_path` for appending, creating it if needed
+    pub fn open(staging_path: &Path) -> Result<Self> {
+        let path = staging_path.join(RUN_PROGRESS_FILE_NAME);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open run progress log: {}", path.display()))?;
+        Ok(Self { file })
+    }
+
+    pub fn append(&mut self, record: &ProgressRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record).context("Failed to serialize progress record")?;
+        line.push(b'\n');
+        self.file.write_all(&line).context("Failed to append to run progress log")
+    }
+
+    /// Digests and finished entries recorded so far, by entry position. A
+    /// line cut short by the crash is ignored.
+    pub fn load(staging_path: &Path) -> Result<(HashMap<usize, FileDigests>, HashMap<usize, ReportModel>)> {
+        let path = staging_path.join(RUN_PROGRESS_FILE_NAME);
+        let mut digests = HashMap::new();
+        let mut entries = HashMap::new();
+        if !path.exists() {
+            return Ok((digests, entries));
+        }
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read run progress log: {}", path.display()))?;
+        for record in text.lines().filter_map(|line| serde_json::from_str::<ProgressRecord>(line).ok()) {
+            match record {
+                ProgressRecord::Hashed { index, digests: file_digests } => {
+                    digests.insert(index, file_digests);
+                }
+                ProgressRecord::Processed { index, entry } => {
+                    entries.insert(index, entry);
+                }
+            }
+        }
+        Ok((digests, entries))
+    }
+}
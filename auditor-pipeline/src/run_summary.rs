@@ -0,0 +1,153 @@
+use crate::report_model::{FileStatus, ReportModel};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// Outcome of the LLM export stage
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportStats {
+    pub copied_count: usize,
+    pub duplicates_skipped: usize,
+    /// Files that could not be copied into the export
+    #[serde(default)]
+    pub failed_count: usize,
+    pub bytes_written: u64,
+}
+
+impl ExportStats {
+    pub fn add(&mut self, other: &ExportStats) {
+        self.copied_count += other.copied_count;
+        self.duplicates_skipped += other.duplicates_skipped;
+        self.failed_count += other.failed_count;
+        self.bytes_written += other.bytes_written;
+    }
+}
+
+/// Aggregates over a whole run, so consumers don't have to recompute them
+/// from thousands of entries.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub total_files: usize,
+    /// Keyed by `FileStatus::key`
+    pub status_counts: BTreeMap<String, usize>,
+    /// Keyed by lowercased file extension
+    pub file_type_counts: BTreeMap<String, usize>,
+    pub total_bytes_in: u64,
+    pub total_bytes_out: u64,
+    pub exported_files: usize,
+    /// Estimated LLM tokens of the export, for planning context windows and
+    /// budget before uploading; duplicates left out don't add to it
+    #[serde(default)]
+    pub estimated_tokens: u64,
+    /// Exported files moved to `_overflow` as beyond the export size cap;
+    /// their tokens aren't counted in `estimated_tokens`
+    #[serde(default)]
+    pub over_export_cap: usize,
+    pub duplicates_collapsed: usize,
+    /// Sets of files with identical content
+    #[serde(default)]
+    pub duplicate_groups: usize,
+    /// Bytes taken by the copies after the first of each duplicate group
+    #[serde(default)]
+    pub redundant_bytes: u64,
+    /// Files quarantined because they matched the known-bad hash list
+    pub indicator_matches: usize,
+    /// Files left out because they matched the known-file hash list
+    #[serde(default)]
+    pub known_files_excluded: usize,
+    /// Converted files whose output failed a quality check
+    pub needs_review: usize,
+    /// Office documents skipped because no supplied password opened them
+    pub password_protected: usize,
+    /// Office documents carrying a VBA project
+    pub macro_documents: usize,
+    /// Wall-clock time per pipeline stage, in milliseconds
+    pub stage_durations_ms: BTreeMap<String, u64>,
+    pub total_duration_ms: u64,
+}
+
+impl RunSummary {
+    pub fn from_entries(entries: &[ReportModel], export_stats: &ExportStats) -> Self {
+        let mut summary = Self {
+            total_files: entries.len(),
+            total_bytes_out: export_stats.bytes_written,
+            exported_files: export_stats.copied_count,
+            duplicates_collapsed: export_stats.duplicates_skipped,
+            ..Self::default()
+        };
+
+        for entry in entries {
+            *summary
+                .status_counts
+                .entry(entry.status.key().to_string())
+                .or_insert(0) += 1;
+            *summary
+                .file_type_counts
+                .entry(entry.file_type.to_lowercase())
+                .or_insert(0) += 1;
+            summary.total_bytes_in += entry.file_size_bytes;
+            if entry.over_export_cap {
+                summary.over_export_cap += 1;
+            } else {
+                summary.estimated_tokens += entry.estimated_tokens.unwrap_or(0);
+            }
+            if entry.indicator_match.is_some() {
+                summary.indicator_matches += 1;
+            }
+            if entry.status == FileStatus::KnownFile {
+                summary.known_files_excluded += 1;
+            }
+            if entry.review_reason.is_some() {
+                summary.needs_review += 1;
+            }
+            if entry.status == FileStatus::SkippedEncrypted {
+                summary.password_protected += 1;
+            }
+            if entry.contains_macros {
+                summary.macro_documents += 1;
+            }
+            if entry.duplicate_of.is_some() {
+                summary.redundant_bytes += entry.file_size_bytes;
+            }
+        }
+        summary.duplicate_groups = entries
+            .iter()
+            .filter_map(|entry| entry.duplicate_group.as_deref())
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+
+        summary
+    }
+}
+
+/// Measures consecutive pipeline stages
+pub struct StageTimer {
+    run_started: Instant,
+    stage_started: Instant,
+    durations: BTreeMap<String, u64>,
+}
+
+impl StageTimer {
+    pub fn start() -> Self {
+        let now = Instant::now();
+        Self {
+            run_started: now,
+            stage_started: now,
+            durations: BTreeMap::new(),
+        }
+    }
+
+    /// Record the time since the previous stage ended under `name`
+    pub fn finish_stage(&mut self, name: &str) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.stage_started).as_millis() as u64;
+        *self.durations.entry(name.to_string()).or_insert(0) += elapsed;
+        self.stage_started = now;
+    }
+
+    /// Copy the recorded durations into `summary`
+    pub fn apply_to(&self, summary: &mut RunSummary) {
+        summary.stage_durations_ms = self.durations.clone();
+        summary.total_duration_ms = self.run_started.elapsed().as_millis() as u64;
+    }
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// Guards that stop a scan of a runaway input, e.g. a whole mounted drive
+/// selected by mistake, before it runs for hours.
+///
+/// A value of 0 disables the corresponding limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanLimits {
+    /// Maximum folder depth of a file; a file directly in the input is at depth 1
+    pub max_depth: usize,
+    /// Maximum number of files listed
+    pub max_files: usize,
+    /// Maximum total size of the files listed, in bytes
+    pub max_total_bytes: u64,
+}
+
+impl Default for ScanLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 64,
+            max_files: 500_000,
+            max_total_bytes: 2 * 1024 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+impl ScanLimits {
+    /// Walk depth to descend to: one level past `max_depth`, so content
+    /// nested too deep is noticed without walking all of it
+    pub fn walk_depth(&self) -> Option<usize> {
+        (self.max_depth > 0).then(|| self.max_depth + 1)
+    }
+
+    /// Reason the scan must stop at a walk entry at `depth`
+    pub fn check_depth(&self, depth: usize) -> Option<String> {
+        (self.max_depth > 0 && depth > self.max_depth)
+            .then(|| format!("folders are nested deeper than {} levels", self.max_depth))
+    }
+
+    /// Reason the scan must stop after listing `files` files of `total_bytes`
+    pub fn check_totals(&self, files: usize, total_bytes: u64) -> Option<String> {
+        if self.max_files > 0 && files > self.max_files {
+            return Some(format!("input holds more than {} files", self.max_files));
+        }
+        if self.max_total_bytes > 0 && total_bytes > self.max_total_bytes {
+            return Some(format!("input holds more than {} bytes", self.max_total_bytes));
+        }
+        None
+    }
+}
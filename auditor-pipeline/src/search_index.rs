@@ -0,0 +1,211 @@
+use crate::ept_logger::EPTLogger;
+use crate::llm_export_engine::OVERFLOW_FOLDER_NAME;
+use crate::tagging::TaggingEngine;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tantivy::collector::TopDocs;
+use tantivy::query::QueryParser;
+use tantivy::schema::{Field, Schema, Value, STORED, STRING, TEXT};
+use tantivy::{doc, Index, IndexWriter, TantivyDocument};
+use walkdir::WalkDir;
+
+/// Folder holding the index inside the LLM export folder
+pub const SEARCH_INDEX_DIR_NAME: &str = ".search_index";
+
+/// Characters of context shown on each side of the first match
+const SNIPPET_RADIUS: usize = 80;
+
+/// Memory budget of the tantivy writer while indexing
+const WRITER_HEAP_BYTES: usize = 50_000_000;
+
+/// One matching document returned by [`SearchIndex::search`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub file_name: String,
+    pub path: String,
+    pub score: f64,
+    pub snippet: String,
+}
+
+/// Tantivy index over the text files of an LLM export folder.
+///
+/// Built once at the end of a run and saved next to the exported files, so
+/// an evidence set can be searched without re-reading every document.
+pub struct SearchIndex {
+    index: Index,
+    file_name: Field,
+    body: Field,
+}
+
+impl SearchIndex {
+    fn schema() -> (Schema, Field, Field) {
+        let mut builder = Schema::builder();
+        let file_name = builder.add_text_field("file_name", STRING | STORED);
+        let body = builder.add_text_field("body", TEXT);
+        (builder.build(), file_name, body)
+    }
+
+    /// Index every readable text file in `export_path`, including the
+    /// per-group subfolders of a split export. Files moved to the overflow
+    /// folder by the size cap are left out, as they are not part of the
+    /// export.
+    pub fn build(logger: &EPTLogger, export_path: &Path) -> Result<Self> {
+        if !export_path.is_dir() {
+            anyhow::bail!("Failed to read export folder: {}", export_path.display());
+        }
+
+        let index_dir = export_path.join(SEARCH_INDEX_DIR_NAME);
+        if index_dir.exists() {
+            fs::remove_dir_all(&index_dir)
+                .with_context(|| format!("Failed to remove old search index: {}", index_dir.display()))?;
+        }
+        fs::create_dir_all(&index_dir)
+            .with_context(|| format!("Failed to create search index folder: {}", index_dir.display()))?;
+
+        let (schema, file_name_field, body_field) = Self::schema();
+        let index = Index::create_in_dir(&index_dir, schema).context("Failed to create search index")?;
+        let mut writer: IndexWriter = index
+            .writer(WRITER_HEAP_BYTES)
+            .context("Failed to open search index writer")?;
+
+        let mut paths: Vec<PathBuf> = WalkDir::new(export_path)
+            .into_iter()
+            .filter_entry(|e| {
+                e.depth() != 1
+                    || (e.file_name() != OVERFLOW_FOLDER_NAME && e.file_name() != SEARCH_INDEX_DIR_NAME)
+            })
+            .filter_map(|e| e.ok())
+            .map(|e| e.into_path())
+            .filter(|p| p.is_file() && TaggingEngine::is_text_file(p))
+            .collect();
+        paths.sort();
+
+        let mut document_count = 0;
+        for path in paths {
+            let bytes = match fs::read(&path) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    logger.warning(&format!("Failed to read {} for indexing: {}", path.display(), e));
+                    continue;
+                }
+            };
+            // Relative to the export folder so split exports stay unambiguous
+            let file_name = path
+                .strip_prefix(export_path)
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+                .unwrap_or_else(|_| "unknown".to_string());
+            writer
+                .add_document(doc!(
+                    file_name_field => file_name,
+                    body_field => String::from_utf8_lossy(&bytes).into_owned(),
+                ))
+                .context("Failed to add document to search index")?;
+            document_count += 1;
+        }
+
+        writer.commit().context("Failed to commit search index")?;
+        logger.info(&format!("Search index built: {} documents", document_count));
+        Ok(Self {
+            index,
+            file_name: file_name_field,
+            body: body_field,
+        })
+    }
+
+    /// Folder the index was written to
+    pub fn path(export_path: &Path) -> PathBuf {
+        export_path.join(SEARCH_INDEX_DIR_NAME)
+    }
+
+    pub fn load(export_path: &Path) -> Result<Self> {
+        let index_dir = Self::path(export_path);
+        let index = Index::open_in_dir(&index_dir)
+            .with_context(|| format!("No search index found at {}", index_dir.display()))?;
+        let schema = index.schema();
+        let file_name = schema.get_field("file_name").context("Search index has no file_name field")?;
+        let body = schema.get_field("body").context("Search index has no body field")?;
+        Ok(Self { index, file_name, body })
+    }
+
+    /// Documents containing every term of `query`, best match first (BM25).
+    /// Query syntax errors are tolerated; the parsable part is searched.
+    pub fn search(&self, export_path: &Path, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        let terms: Vec<String> = tokenize(query).collect();
+        if terms.is_empty() || limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let reader = self.index.reader().context("Failed to open search index")?;
+        let searcher = reader.searcher();
+        let mut parser = QueryParser::for_index(&self.index, vec![self.body]);
+        parser.set_conjunction_by_default();
+        let (parsed, _errors) = parser.parse_query_lenient(query);
+        let top_docs = searcher
+            .search(&parsed, &TopDocs::with_limit(limit))
+            .context("Failed to search index")?;
+
+        let mut hits = Vec::with_capacity(top_docs.len());
+        for (score, address) in top_docs {
+            let document: TantivyDocument = searcher.doc(address).context("Failed to read search hit")?;
+            let file_name = document
+                .get_first(self.file_name)
+                .and_then(|value| value.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let path = export_path.join(&file_name);
+            let snippet = fs::read(&path)
+                .map(|bytes| make_snippet(&String::from_utf8_lossy(&bytes), &terms[0]))
+                .unwrap_or_default();
+            hits.push(SearchHit {
+                file_name,
+                path: path.to_string_lossy().to_string(),
+                score: score as f64,
+                snippet,
+            });
+        }
+        Ok(hits)
+    }
+}
+
+/// Lowercased alphanumeric words, used to pick the snippet anchor
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 1)
+        .map(|word| word.to_lowercase())
+}
+
+/// Text around the first whole-word occurrence of `term`, on one line
+fn make_snippet(text: &str, term: &str) -> String {
+    let lower = text.to_lowercase();
+    // Lowercasing can change byte lengths for some scripts; fall back to the
+    // start of the document rather than slicing at a bad offset
+    let position = if lower.len() == text.len() {
+        lower
+            .match_indices(term)
+            .map(|(i, _)| i)
+            .find(|&i| {
+                let before = lower[..i].chars().next_back();
+                let after = lower[i + term.len()..].chars().next();
+                !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+            })
+            .unwrap_or(0)
+    } else {
+        0
+    };
+
+    let mut start = position.saturating_sub(SNIPPET_RADIUS);
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (position + term.len() + SNIPPET_RADIUS).min(text.len());
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+
+    let snippet = text[start..end].split_whitespace().collect::<Vec<_>>().join(" ");
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if end < text.len() { "…" } else { "" };
+    format!("{}{}{}", prefix, snippet, suffix)
+}
@@ -0,0 +1,89 @@
+use crate::ept_logger::EPTLogger;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+use walkdir::WalkDir;
+
+const OVERWRITE_CHUNK: usize = 1024 * 1024;
+
+/// Outcome of deleting one folder tree
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeletionStats {
+    pub files_deleted: usize,
+    pub bytes_overwritten: u64,
+    /// (path, error) for anything that could not be removed
+    pub failures: Vec<(String, String)>,
+}
+
+/// Overwrite every file under `path`, flush it to disk and remove it, then
+/// remove the folders. Missing paths are not an error.
+///
+/// Each of the `passes` (at least one) alternates between zeros and ones,
+/// starting with zeros. Individual failures are collected and logged rather
+/// than aborting, so one locked file doesn't leave the rest of the tree behind.
+pub fn secure_delete_path(path: &Path, passes: u32, logger: &EPTLogger) -> Result<DeletionStats> {
+    let mut stats = DeletionStats::default();
+    if !path.exists() {
+        return Ok(stats);
+    }
+
+    for entry in WalkDir::new(path).contents_first(true) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                stats.failures.push((path.display().to_string(), e.to_string()));
+                continue;
+            }
+        };
+        let entry_path = entry.path();
+
+        let result = if entry.file_type().is_dir() {
+            fs::remove_dir(entry_path).context("Failed to remove folder")
+        } else {
+            overwrite_and_remove(entry_path, passes.max(1)).map(|bytes| {
+                logger.debug(&format!("Shredded {} ({} bytes)", entry_path.display(), bytes));
+                stats.files_deleted += 1;
+                stats.bytes_overwritten += bytes;
+            })
+        };
+        if let Err(e) = result {
+            logger.warning(&format!("Failed to shred {}: {}", entry_path.display(), e));
+            stats.failures.push((entry_path.display().to_string(), e.to_string()));
+        }
+    }
+
+    Ok(stats)
+}
+
+fn overwrite_and_remove(path: &Path, passes: u32) -> Result<u64> {
+    let metadata = fs::symlink_metadata(path).context("Failed to read file metadata")?;
+
+    // Never follow links out of the tree being deleted
+    if !metadata.file_type().is_file() {
+        fs::remove_file(path).context("Failed to remove link")?;
+        return Ok(0);
+    }
+
+    let len = metadata.len();
+    {
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .open(path)
+            .context("Failed to open file for overwriting")?;
+        for pass in 0..passes {
+            let pattern = vec![if pass % 2 == 0 { 0x00 } else { 0xFF }; OVERWRITE_CHUNK];
+            file.seek(SeekFrom::Start(0)).context("Failed to rewind file")?;
+            let mut remaining = len;
+            while remaining > 0 {
+                let chunk = remaining.min(OVERWRITE_CHUNK as u64) as usize;
+                file.write_all(&pattern[..chunk]).context("Failed to overwrite file")?;
+                remaining -= chunk as u64;
+            }
+            file.sync_all().context("Failed to flush overwritten file")?;
+        }
+    }
+    fs::remove_file(path).context("Failed to remove file")?;
+    Ok(len)
+}
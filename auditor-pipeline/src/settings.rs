@@ -0,0 +1,449 @@
+use crate::chunking::ChunkSettings;
+use crate::conversion_engine::{self, ConversionTarget, DocxConverter, FormulaOutput, ImageOutput, PresentationOutput};
+use crate::extraction_limits::ExtractionLimits;
+use crate::folder_naming::{self, DEFAULT_FOLDER_NAME_TEMPLATE};
+use crate::hashing_service::{self, HashAlgorithm};
+use crate::llm_export_engine::{CombinedCorpusSettings, ExportNaming, ExportSizeCap};
+use crate::path_filter::{PathFilter, PatternExclusion};
+use crate::report_writer::{self, ReportColumn, ReportFormat};
+use crate::scan_limits::ScanLimits;
+use crate::summarization::SummarizationSettings;
+use crate::system_files::{self, SystemFileList};
+use crate::tagging::TagRule;
+use crate::workspace::{ExtractedArchivePolicy, StagingCleanup};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// File the settings snapshot is written to in the LLM export folder
+pub const SETTINGS_SNAPSHOT_FILE_NAME: &str = "run_settings.json";
+
+/// User-tunable options for a processing run.
+///
+/// Every field has a default so the frontend can send a partial object
+/// (or nothing at all) and get the standard behaviour.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessingSettings {
+    /// Minimum time between two `progress-update` events, in milliseconds.
+    pub progress_interval_ms: u64,
+    /// Emit a `progress-update` after this many increments even if the
+    /// interval has not elapsed yet (0 disables the count trigger).
+    pub progress_every_n: usize,
+    /// Gzip files in the staging workspace that are neither convertible nor
+    /// LLM-readable, trading CPU for disk space.
+    pub compress_staging_passthrough: bool,
+    /// Maximum number of documents passed to a single LibreOffice
+    /// `--convert-to` invocation (1 converts each document separately).
+    pub libreoffice_batch_size: usize,
+    /// Files hashed and converted concurrently; LibreOffice conversions
+    /// outside of batches still run one at a time.
+    pub conversion_workers: usize,
+    /// Digest recorded for every original and converted file and used to
+    /// find duplicates: SHA-256, SHA-512 or BLAKE3
+    pub hash_algorithm: HashAlgorithm,
+    /// Digests taken of each original file besides `hash_algorithm`, in the
+    /// same read, each in its own report column (e.g. `md5` for matching
+    /// against old load files)
+    pub additional_hash_algorithms: Vec<HashAlgorithm>,
+    /// Files hashed at the same time before conversion starts (0 uses one
+    /// thread per CPU core)
+    pub hashing_threads: usize,
+    /// Folder converted outputs are kept in across runs, keyed by the
+    /// source's digest, so reprocessing only converts changed files; unset
+    /// disables the cache
+    pub conversion_cache_dir: Option<PathBuf>,
+    /// Convert every file even when the cache holds its output; the fresh
+    /// outputs replace the cached ones
+    pub force_reconversion: bool,
+    /// Export only the most inclusive message of each email thread; messages
+    /// quoted by a later reply are listed in the report as suppressed.
+    pub dedupe_email_threads: bool,
+    /// Keyword/regex tags applied to the text of exported files
+    pub tag_rules: Vec<TagRule>,
+    /// Record the owner, group and permissions (NTFS access control list on
+    /// Windows, mode bits elsewhere) of each input file, as report columns.
+    /// Read from the input folder, so files inside archives get none.
+    pub capture_ownership: bool,
+    /// Read the EXIF timestamp, GPS position and camera model of images
+    /// while scanning, and add them as report columns
+    pub capture_exif: bool,
+    /// Write the report as an Excel workbook, a UTF-8 CSV of the main
+    /// sheet, or both
+    pub report_format: ReportFormat,
+    /// Main sheet columns to show, in order, optionally renamed; empty shows
+    /// every column. Applies to the workbook, CSV and HTML reports. The
+    /// `Staged File` and `Exported Copy` link columns can be picked too and
+    /// always come last.
+    pub report_columns: Vec<ReportColumn>,
+    /// Also write the run's result (entries, archives, summary, settings) as
+    /// pretty-printed JSON next to the report. Stage timings aren't in it,
+    /// as the run is still finishing when it is written.
+    pub write_json_report: bool,
+    /// Also write the report as a single HTML page (sortable file table,
+    /// summary charts, links to the files) that opens without Excel
+    pub write_html_report: bool,
+    /// Split exported text documents too long for an LLM into numbered,
+    /// overlapping parts; off unless a token limit is set
+    pub chunking: ChunkSettings,
+    /// Build a local full-text index over the LLM export for `search_corpus`
+    pub build_search_index: bool,
+    /// How exported files are renamed when their name is already taken;
+    /// `hash_suffix` gives the same names in every run
+    pub export_naming: ExportNaming,
+    /// Prepend YAML front matter with the original path, hash, modified
+    /// date and archive to exported markdown and text files
+    pub export_front_matter: bool,
+    /// Limit on the total size of the export; files that don't fit, by the
+    /// configured priority, go to `_overflow`
+    pub export_size_cap: ExportSizeCap,
+    /// Also concatenate the exported text into a few delimited corpus files
+    pub combined_corpus: CombinedCorpusSettings,
+    /// Also write the exported documents as `documents.jsonl`, one JSON
+    /// object with path, hash, media type and text per line
+    pub write_jsonl_export: bool,
+    /// Optional per-document LLM summaries after export
+    pub summarization: SummarizationSettings,
+    /// Zip-bomb guards applied while expanding archives
+    pub extraction_limits: ExtractionLimits,
+    /// Guards that stop the scan of a runaway input early, with a partial
+    /// inventory, instead of scanning for hours
+    pub scan_limits: ScanLimits,
+    /// Folder the LLM export and report are written under; by default they
+    /// go next to the staging folder. Set per run when filing under an engagement.
+    pub output_root: Option<PathBuf>,
+    /// Encoding label (e.g. `windows-1252`, `shift_jis`) for ZIP entry names
+    /// that are not UTF-8; CP437 when unset
+    pub zip_name_encoding: Option<String>,
+    /// What to do with the staging workspace after the report is written
+    pub staging_cleanup: StagingCleanup,
+    /// Overwrite passes per file when `staging_cleanup` is `secure_shred`
+    pub shred_passes: u32,
+    /// Carve attachments and portfolio members out of PDFs into the staging
+    /// tree so they are scanned and reported like other evidence
+    pub extract_pdf_attachments: bool,
+    /// Extract `.eml`/`.msg` attachments into the staging tree so they are
+    /// processed as files of their own, linked to their message
+    pub extract_email_attachments: bool,
+    /// What to do with archives in staging after their contents are extracted
+    pub extracted_archives: ExtractedArchivePolicy,
+    /// CSV of known-bad MD5/SHA-1/SHA-256/SHA-512 hashes; matching files are
+    /// quarantined instead of converted or exported
+    pub indicator_list_path: Option<PathBuf>,
+    /// Known-file hash list (an NSRL RDS subset exported as CSV, or a
+    /// firm-provided list in the indicator list format); matching OS and
+    /// application files are excluded before conversion
+    pub known_files_list_path: Option<PathBuf>,
+    /// Glob patterns of files to scan, e.g. `**/Finance/**` or `*.xlsx`;
+    /// empty scans everything
+    pub include_patterns: Vec<String>,
+    /// Glob patterns of files to leave out, e.g. `**/Backups/**`; these win
+    /// over the include patterns
+    pub exclude_patterns: Vec<String>,
+    /// Whether files left out by the patterns are listed in the report.
+    /// Patterns apply after decompression, so archives are still extracted
+    pub pattern_excluded_files: PatternExclusion,
+    /// Keep hidden and system files (Office lock files, Finder metadata,
+    /// thumbnail caches) instead of leaving them out
+    pub include_hidden_files: bool,
+    /// File name patterns of the hidden and system files left out, e.g.
+    /// `thumbs.db` or `~$*`; listed on the report's Exclusions sheet
+    pub system_file_patterns: Vec<String>,
+    /// Export PDFs as `__converted.md` text with page markers instead of the
+    /// original PDF
+    pub pdf_text_extraction: bool,
+    /// Which converter handles `.docx` files; the native one needs no LibreOffice
+    pub docx_converter: DocxConverter,
+    /// Convert `.pptx` files to PDF, or extract their slide text to markdown
+    pub pptx_output: PresentationOutput,
+    /// Sanity-check converted outputs and flag suspicious ones for manual review
+    pub validate_conversions: bool,
+    /// Convert `.html`/`.htm` files to cleaned-up `__converted.md` instead of
+    /// exporting the raw markup
+    pub html_to_markdown: bool,
+    /// Rewrite CSV/TSV files as UTF-8, comma-separated `__converted.csv`,
+    /// detecting their encoding and delimiter
+    pub normalize_csv: bool,
+    /// Convert JPEG/PNG/TIFF/HEIC images to OCR markdown (Tesseract) or wrap
+    /// them in a PDF; off leaves them out of the export
+    pub image_output: ImageOutput,
+    /// Tesseract language code(s) for image OCR, e.g. `eng` or `eng+deu`
+    pub ocr_language: String,
+    /// Include cell formulas in Excel-to-markdown conversions, inline or as a
+    /// per-sheet listing
+    pub excel_formulas: FormulaOutput,
+    /// Append each sheet's defined names, data validation rules and
+    /// protection status to Excel-to-markdown conversions
+    pub excel_controls_appendix: bool,
+    /// Emit hidden sheets under a "Hidden Sheet" heading and mark hidden rows
+    /// and columns in Excel-to-markdown conversions
+    pub excel_hidden_content: bool,
+    /// Append cell comments/notes after each sheet in Excel-to-markdown
+    /// conversions
+    pub excel_comments: bool,
+    /// When LibreOffice fails on a document, retry it with pandoc and then
+    /// the native converter, writing markdown
+    pub conversion_fallback: bool,
+    /// Sheets left out of Excel-to-markdown conversions, matched by name
+    /// ignoring case; `*` matches any characters (e.g. `Cover*`)
+    pub skip_sheets: Vec<String>,
+    /// Most rows converted per sheet in Excel-to-markdown conversions,
+    /// below the header row; longer sheets are cut off with a notice.
+    /// 0 converts every row.
+    pub excel_max_rows_per_sheet: usize,
+    /// Write the VBA source of macro-enabled documents into the staging
+    /// tree (`<name>__vba/<module>.bas`) for review
+    pub extract_vba_macros: bool,
+    /// Output format per input extension (e.g. `docx = "md"`), overriding
+    /// the built-in conversions
+    pub conversion_targets: BTreeMap<String, ConversionTarget>,
+    /// Name of the staging folder and of every folder an archive or document
+    /// is unpacked into. Tokens: `{name}`, `{date}`, `{time}`, `{timestamp}`,
+    /// `{run_id}`, `{engagement}`. The LLM export folder is the staging
+    /// folder's name plus `_LLM`.
+    pub folder_name_template: String,
+    /// Run identifier for the `{run_id}` token; set per run by the frontend
+    pub run_id: Option<String>,
+    /// Engagement client code for the `{engagement}` token; set per run when
+    /// filing under an engagement
+    pub engagement_code: Option<String>,
+    /// Export into one subfolder of the LLM folder per top-level input
+    /// folder (e.g. per custodian), deduplicating within each
+    pub split_export_by_top_level_folder: bool,
+    /// Passwords tried, in order, on password-protected Office documents.
+    /// Redacted from the settings snapshot.
+    pub document_passwords: Vec<String>,
+    /// Secret for HMAC-SHA256 signatures of the report and manifest, written
+    /// as detached `.sig` files; the `EPT_REPORT_SIGNING_KEY` environment
+    /// variable is used when unset. Redacted from the settings snapshot.
+    pub report_signing_key: Option<String>,
+    /// Settings profile these settings were loaded from, for the report's
+    /// Run Metadata sheet; set by `load_profile`, not saved in profiles
+    pub profile: Option<String>,
+}
+
+impl Default for ProcessingSettings {
+    fn default() -> Self {
+        Self {
+            progress_interval_ms: 100,
+            progress_every_n: 250,
+            compress_staging_passthrough: false,
+            libreoffice_batch_size: 25,
+            conversion_workers: std::thread::available_parallelism()
+                .map(|n| n.get().min(8))
+                .unwrap_or(1),
+            hash_algorithm: HashAlgorithm::Sha512,
+            additional_hash_algorithms: Vec::new(),
+            hashing_threads: 0,
+            conversion_cache_dir: None,
+            force_reconversion: false,
+            dedupe_email_threads: false,
+            tag_rules: Vec::new(),
+            capture_ownership: false,
+            capture_exif: false,
+            report_format: ReportFormat::Xlsx,
+            report_columns: Vec::new(),
+            write_json_report: false,
+            write_html_report: false,
+            chunking: ChunkSettings::default(),
+            build_search_index: false,
+            export_naming: ExportNaming::default(),
+            export_front_matter: false,
+            export_size_cap: ExportSizeCap::default(),
+            combined_corpus: CombinedCorpusSettings::default(),
+            write_jsonl_export: false,
+            summarization: SummarizationSettings::default(),
+            extraction_limits: ExtractionLimits::default(),
+            scan_limits: ScanLimits::default(),
+            output_root: None,
+            zip_name_encoding: None,
+            staging_cleanup: StagingCleanup::Keep,
+            shred_passes: 1,
+            extract_pdf_attachments: true,
+            extract_email_attachments: true,
+            extracted_archives: ExtractedArchivePolicy::Keep,
+            indicator_list_path: None,
+            known_files_list_path: None,
+            include_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+            pattern_excluded_files: PatternExclusion::List,
+            include_hidden_files: false,
+            system_file_patterns: system_files::DEFAULT_SYSTEM_FILE_PATTERNS
+                .iter()
+                .map(|pattern| pattern.to_string())
+                .collect(),
+            pdf_text_extraction: false,
+            docx_converter: DocxConverter::Auto,
+            pptx_output: PresentationOutput::Pdf,
+            validate_conversions: true,
+            html_to_markdown: false,
+            normalize_csv: true,
+            image_output: ImageOutput::Off,
+            ocr_language: "eng".to_string(),
+            excel_formulas: FormulaOutput::Off,
+            excel_controls_appendix: false,
+            excel_hidden_content: true,
+            excel_comments: true,
+            conversion_fallback: true,
+            skip_sheets: vec!["Conversion Notice".to_string()],
+            excel_max_rows_per_sheet: 0,
+            extract_vba_macros: false,
+            conversion_targets: BTreeMap::new(),
+            folder_name_template: DEFAULT_FOLDER_NAME_TEMPLATE.to_string(),
+            run_id: None,
+            engagement_code: None,
+            split_export_by_top_level_folder: false,
+            document_passwords: Vec::new(),
+            report_signing_key: None,
+            profile: None,
+        }
+    }
+}
+
+impl ProcessingSettings {
+    /// Load settings from a TOML profile; keys left out keep their defaults
+    pub fn load_profile(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read settings profile: {}", path.display()))?;
+        let mut settings: Self = toml::from_str(&text)
+            .with_context(|| format!("Invalid settings profile: {}", path.display()))?;
+        settings.validate()?;
+        settings.profile = Some(path.to_string_lossy().to_string());
+        Ok(settings)
+    }
+
+    /// Save settings as a TOML profile. Secrets are left out, as a profile
+    /// is meant to be shared and kept with engagement files.
+    pub fn save_profile(&self, path: &Path) -> Result<()> {
+        self.validate()?;
+        let profile = Self {
+            profile: None,
+            ..self.without_secrets()
+        };
+        let text = toml::to_string_pretty(&profile).context("Failed to serialize settings profile")?;
+        std::fs::write(path, text)
+            .with_context(|| format!("Failed to write settings profile: {}", path.display()))
+    }
+
+    /// These settings with the API key, document passwords and report
+    /// signing key left out, as with the settings snapshot. They are left
+    /// empty rather than redacted so settings loaded back don't carry a
+    /// placeholder as the real value.
+    pub fn without_secrets(&self) -> Self {
+        let mut settings = self.clone();
+        settings.summarization.api_key = None;
+        settings.document_passwords.clear();
+        settings.report_signing_key = None;
+        settings
+    }
+
+    /// These settings with the keys present in `overrides`, a JSON object
+    /// in the shape of the settings, applied on top. Nested objects are
+    /// merged key by key, so e.g. `{"summarization": {"model": "llama3"}}`
+    /// leaves the other summarization settings as they were.
+    pub fn with_overrides(&self, overrides: &serde_json::Value) -> Result<Self> {
+        fn merge(base: &mut serde_json::Value, overrides: &serde_json::Value) {
+            match (base, overrides) {
+                (serde_json::Value::Object(base), serde_json::Value::Object(overrides)) => {
+                    for (key, value) in overrides {
+                        match base.get_mut(key) {
+                            Some(existing) => merge(existing, value),
+                            None => {
+                                base.insert(key.clone(), value.clone());
+                            }
+                        }
+                    }
+                }
+                (base, value) => *base = value.clone(),
+            }
+        }
+
+        let mut merged = serde_json::to_value(self).context("Failed to serialize settings")?;
+        merge(&mut merged, overrides);
+        serde_json::from_value(merged).context("Invalid settings")
+    }
+
+    /// Reject combinations the pipeline can't carry out
+    pub fn validate(&self) -> Result<()> {
+        conversion_engine::validate_conversion_targets(&self.conversion_targets)?;
+        hashing_service::validate_primary_algorithm(self.hash_algorithm)?;
+        PathFilter::new(&self.include_patterns, &self.exclude_patterns)?;
+        SystemFileList::new(&self.system_file_patterns)?;
+        report_writer::validate_report_columns(self)?;
+        folder_naming::validate_template(&self.folder_name_template)
+    }
+}
+
+/// The effective configuration of a run, embedded in its report and written
+/// next to the export so it can be reproduced later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsSnapshot {
+    pub tool_version: String,
+    pub captured_at: String,
+    pub hash_algorithm: String,
+    /// Settings as used by the run, with secrets redacted
+    pub settings: ProcessingSettings,
+}
+
+impl SettingsSnapshot {
+    pub fn capture(settings: &ProcessingSettings) -> Self {
+        let mut settings = settings.clone();
+        if settings.summarization.api_key.is_some() {
+            settings.summarization.api_key = Some("(redacted)".to_string());
+        }
+        for password in settings.document_passwords.iter_mut() {
+            *password = "(redacted)".to_string();
+        }
+        if settings.report_signing_key.is_some() {
+            settings.report_signing_key = Some("(redacted)".to_string());
+        }
+        Self {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            captured_at: chrono::Local::now().to_rfc3339(),
+            hash_algorithm: settings.hash_algorithm.label().to_string(),
+            settings,
+        }
+    }
+
+    /// Flattened `(setting, value)` pairs, e.g. `("summarization.model", "llama3")`
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries = vec![
+            ("tool_version".to_string(), self.tool_version.clone()),
+            ("captured_at".to_string(), self.captured_at.clone()),
+            ("hash_algorithm".to_string(), self.hash_algorithm.clone()),
+        ];
+        if let Ok(value) = serde_json::to_value(&self.settings) {
+            flatten_value("", &value, &mut entries);
+        }
+        entries
+    }
+
+    /// Write the snapshot as JSON into `output_dir`
+    pub fn save(&self, output_dir: &Path) -> Result<PathBuf> {
+        let path = output_dir.join(SETTINGS_SNAPSHOT_FILE_NAME);
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize settings snapshot")?;
+        std::fs::write(&path, json)
+            .with_context(|| format!("Failed to write settings snapshot: {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+fn flatten_value(prefix: &str, value: &serde_json::Value, entries: &mut Vec<(String, String)>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten_value(&key, child, entries);
+            }
+        }
+        serde_json::Value::Null => entries.push((prefix.to_string(), String::new())),
+        serde_json::Value::String(s) => entries.push((prefix.to_string(), s.clone())),
+        // Arrays (e.g. tag rules) stay as compact JSON in a single cell
+        other => entries.push((prefix.to_string(), other.to_string())),
+    }
+}
@@ -0,0 +1,231 @@
+use crate::ept_logger::EPTLogger;
+use crate::report_model::ReportModel;
+use crate::tagging::TaggingEngine;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// File name of the document index written to the LLM export folder
+pub const INDEX_FILE_NAME: &str = "INDEX.md";
+
+/// Give up on the stage after this many failed requests in a row, so an
+/// unreachable endpoint doesn't cost one timeout per document
+const MAX_CONSECUTIVE_FAILURES: usize = 3;
+
+/// Endpoint and prompt for the optional per-document summary pass.
+///
+/// The endpoint must speak the OpenAI-compatible chat completions API, which
+/// local servers (Ollama, LM Studio, llama.cpp, vLLM) all provide.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SummarizationSettings {
+    pub enabled: bool,
+    /// Full URL of the chat completions endpoint, over HTTP or HTTPS
+    pub endpoint: String,
+    pub model: String,
+    /// Sent as a bearer token when set
+    pub api_key: Option<String>,
+    /// Instruction sent ahead of each document's text
+    pub prompt: String,
+    /// Document text beyond this many characters is not sent
+    pub max_input_chars: usize,
+    pub max_summary_tokens: u32,
+    pub timeout_secs: u64,
+}
+
+impl Default for SummarizationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "http://localhost:11434/v1/chat/completions".to_string(),
+            model: String::new(),
+            api_key: None,
+            prompt: "Summarize the following document in 2-3 sentences for an auditor. \
+                     Mention the document type, the parties involved and any amounts or dates."
+                .to_string(),
+            max_input_chars: 12_000,
+            max_summary_tokens: 200,
+            timeout_secs: 120,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatMessage<'a>>,
+    max_tokens: u32,
+    temperature: f32,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatResponseMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatResponseMessage {
+    content: String,
+}
+
+/// Generates a short summary for each exported text document and writes
+/// them to the entries and to `INDEX.md`.
+pub struct Summarizer {
+    logger: EPTLogger,
+    settings: SummarizationSettings,
+    client: reqwest::blocking::Client,
+}
+
+impl Summarizer {
+    pub fn new(logger: EPTLogger, settings: SummarizationSettings) -> Result<Self> {
+        if settings.model.trim().is_empty() {
+            return Err(anyhow::anyhow!("Summarization is enabled but no model is configured"));
+        }
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(settings.timeout_secs))
+            .build()
+            .context("Failed to create HTTP client")?;
+        Ok(Self {
+            logger,
+            settings,
+            client,
+        })
+    }
+
+    /// Summarize every exported text file; `on_progress(done, total)` is
+    /// called after each document. Returns the number of summaries written.
+    pub fn summarize_entries(
+        &self,
+        export_path: &Path,
+        entries: &mut [ReportModel],
+        mut on_progress: impl FnMut(usize, usize),
+    ) -> usize {
+        let targets: Vec<usize> = entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| {
+                entry
+                    .exported_file_name
+                    .as_ref()
+                    .is_some_and(|name| TaggingEngine::is_text_file(Path::new(name)))
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        let total = targets.len();
+        let mut summarized = 0;
+        let mut consecutive_failures = 0;
+
+        for (done, index) in targets.into_iter().enumerate() {
+            let entry = &mut entries[index];
+            // A document split into parts is summarized from its first part
+            let exported_name = entry.exported_files().first().map(|name| name.to_string()).unwrap_or_default();
+            let file_path = export_path.join(&exported_name);
+
+            match self.summarize_file(&file_path) {
+                Ok(summary) => {
+                    entry.summary = Some(summary);
+                    summarized += 1;
+                    consecutive_failures = 0;
+                }
+                Err(e) => {
+                    self.logger.warning(&format!("Failed to summarize {}: {}", exported_name, e));
+                    consecutive_failures += 1;
+                    if consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+                        self.logger.error(&format!(
+                            "Stopping summarization after {} consecutive failures",
+                            consecutive_failures
+                        ));
+                        break;
+                    }
+                }
+            }
+            on_progress(done + 1, total);
+        }
+
+        self.logger.info(&format!("Summarization complete: {} of {} documents summarized", summarized, total));
+        summarized
+    }
+
+    fn summarize_file(&self, file_path: &Path) -> Result<String> {
+        let bytes = fs::read(file_path)
+            .with_context(|| format!("Failed to read {}", file_path.display()))?;
+        let text = String::from_utf8_lossy(&bytes);
+        let text = match text.char_indices().nth(self.settings.max_input_chars) {
+            Some((cut, _)) => &text[..cut],
+            None => &text[..],
+        };
+        if text.trim().is_empty() {
+            return Err(anyhow::anyhow!("Document has no text"));
+        }
+
+        let request = ChatRequest {
+            model: &self.settings.model,
+            messages: vec![
+                ChatMessage { role: "system", content: &self.settings.prompt },
+                ChatMessage { role: "user", content: text },
+            ],
+            max_tokens: self.settings.max_summary_tokens,
+            temperature: 0.2,
+        };
+
+        let mut builder = self.client.post(&self.settings.endpoint).json(&request);
+        if let Some(api_key) = self.settings.api_key.as_deref().filter(|k| !k.is_empty()) {
+            builder = builder.bearer_auth(api_key);
+        }
+
+        let response = builder
+            .send()
+            .with_context(|| format!("Request to {} failed", self.settings.endpoint))?
+            .error_for_status()
+            .context("Summarization endpoint returned an error")?;
+        let body: ChatResponse = response.json().context("Unexpected response from summarization endpoint")?;
+
+        let summary = body
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|summary| !summary.is_empty())
+            .context("Summarization endpoint returned no summary")?;
+        Ok(summary)
+    }
+
+    /// Write `INDEX.md` listing every exported document with its summary
+    pub fn write_index(&self, export_path: &Path, entries: &[ReportModel]) -> Result<()> {
+        let mut index = String::from("# Document Index\n\n");
+        for entry in entries {
+            let Some(exported_name) = &entry.exported_file_name else {
+                continue;
+            };
+            index.push_str(&format!("## {}\n\n", exported_name));
+            index.push_str(&format!("- Original: {}\n", entry.original_relative_path));
+            if !entry.export_parts.is_empty() {
+                index.push_str(&format!("- Parts: {}\n", entry.export_parts.join(", ")));
+            }
+            if let Some(summary) = &entry.summary {
+                index.push_str(&format!("\n{}\n", summary));
+            }
+            index.push('\n');
+        }
+
+        let index_path = export_path.join(INDEX_FILE_NAME);
+        fs::write(&index_path, index)
+            .with_context(|| format!("Failed to write {}", index_path.display()))?;
+        self.logger.info(&format!("Document index written: {}", index_path.display()));
+        Ok(())
+    }
+}
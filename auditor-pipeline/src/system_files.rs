@@ -0,0 +1,58 @@
+use anyhow::{Context, Result};
+use globset::{Glob, GlobBuilder, GlobMatcher};
+
+/// File name patterns of operating-system and editor artifacts left out of
+/// a run unless hidden and system files are included
+pub const DEFAULT_SYSTEM_FILE_PATTERNS: &[&str] = &["~$*", "._*", ".DS*", "desktop.ini", "thumbs.db"];
+
+/// What the default patterns match, for the Exclusions sheet
+const KNOWN_ARTIFACTS: &[(&str, &str)] = &[
+    ("~$*", "Office lock/owner file"),
+    ("._*", "macOS resource fork"),
+    (".DS*", "macOS Finder metadata"),
+    ("desktop.ini", "Windows folder settings"),
+    ("thumbs.db", "Windows thumbnail cache"),
+];
+
+/// File name patterns of hidden and system files to leave out, matched
+/// case-insensitively against the file name only
+pub struct SystemFileList {
+    patterns: Vec<(String, GlobMatcher)>,
+}
+
+impl SystemFileList {
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| pattern.trim())
+            .filter(|pattern| !pattern.is_empty())
+            .map(|pattern| {
+                let glob: Glob = GlobBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .with_context(|| format!("Invalid system file pattern: {}", pattern))?;
+                Ok((pattern.to_string(), glob.compile_matcher()))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Why a file named `file_name` is left out, e.g. "Hidden/system file
+    /// (Windows thumbnail cache)"; `None` when it is kept
+    pub fn exclusion_reason(&self, file_name: &str) -> Option<String> {
+        let (pattern, _) = self.patterns.iter().find(|(_, matcher)| matcher.is_match(file_name))?;
+        let kind = KNOWN_ARTIFACTS
+            .iter()
+            .find(|(known, _)| known.eq_ignore_ascii_case(pattern))
+            .map(|(_, kind)| kind.to_string())
+            .unwrap_or_else(|| format!("matches {}", pattern));
+        Some(format!("Hidden/system file ({})", kind))
+    }
+}
+
+impl Default for SystemFileList {
+    fn default() -> Self {
+        let patterns: Vec<String> = DEFAULT_SYSTEM_FILE_PATTERNS.iter().map(|p| p.to_string()).collect();
+        Self::new(&patterns).expect("default system file patterns are valid globs")
+    }
+}
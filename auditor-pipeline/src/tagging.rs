@@ -0,0 +1,130 @@
+use crate::ept_logger::EPTLogger;
+use crate::report_model::ReportModel;
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// A user-defined tag: a file gets the tag when any keyword or pattern matches
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TagRule {
+    pub name: String,
+    /// Literal phrases, e.g. "journal entry" or a vendor name
+    pub keywords: Vec<String>,
+    /// Regular expressions, e.g. `\b\d{4}-\d{4}\b` for account numbers
+    pub patterns: Vec<String>,
+    pub case_sensitive: bool,
+}
+
+struct CompiledRule {
+    name: String,
+    regexes: Vec<Regex>,
+}
+
+/// Scans converted/extracted text against tag rules and records per-tag
+/// match counts on each entry.
+pub struct TaggingEngine {
+    logger: EPTLogger,
+    rules: Vec<CompiledRule>,
+}
+
+impl TaggingEngine {
+    pub fn new(logger: EPTLogger, rules: &[TagRule]) -> Result<Self> {
+        let mut compiled = Vec::new();
+        for rule in rules {
+            let mut regexes = Vec::new();
+            let sources = rule
+                .keywords
+                .iter()
+                .filter(|k| !k.trim().is_empty())
+                .map(|k| regex::escape(k.trim()))
+                .chain(rule.patterns.iter().cloned());
+            for source in sources {
+                let regex = RegexBuilder::new(&source)
+                    .case_insensitive(!rule.case_sensitive)
+                    .build()
+                    .with_context(|| format!("Invalid pattern for tag '{}': {}", rule.name, source))?;
+                regexes.push(regex);
+            }
+            if !regexes.is_empty() {
+                compiled.push(CompiledRule {
+                    name: rule.name.clone(),
+                    regexes,
+                });
+            }
+        }
+        Ok(Self {
+            logger,
+            rules: compiled,
+        })
+    }
+
+    /// Text formats the engine can read directly
+    pub fn is_text_file(path: &Path) -> bool {
+        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+            let ext_lower = ext.to_lowercase();
+            matches!(
+                ext_lower.as_str(),
+                "txt" | "md" | "csv" | "json" | "xml" | "html" | "htm" | "log" | "rtf"
+            )
+        } else {
+            false
+        }
+    }
+
+    /// Count matches of every rule in `text`, omitting tags without matches
+    pub fn tag_text(&self, text: &str) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for rule in &self.rules {
+            let count: usize = rule.regexes.iter().map(|r| r.find_iter(text).count()).sum();
+            if count > 0 {
+                *counts.entry(rule.name.clone()).or_insert(0) += count;
+            }
+        }
+        counts
+    }
+
+    /// Tag every exportable entry whose working file is readable text.
+    ///
+    /// Returns the number of entries that received at least one tag.
+    pub fn tag_entries(&self, working_path: &Path, entries: &mut [ReportModel]) -> usize {
+        if self.rules.is_empty() {
+            return 0;
+        }
+
+        let mut tagged_count = 0;
+        for entry in entries.iter_mut() {
+            if !entry.status.is_exportable() {
+                continue;
+            }
+            let file_path = working_path.join(&entry.relative_path);
+            if !Self::is_text_file(&file_path) {
+                continue;
+            }
+
+            let bytes = match fs::read(&file_path) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    self.logger.warning(&format!(
+                        "Failed to read {} for tagging: {}",
+                        file_path.display(),
+                        e
+                    ));
+                    continue;
+                }
+            };
+
+            let counts = self.tag_text(&String::from_utf8_lossy(&bytes));
+            if !counts.is_empty() {
+                tagged_count += 1;
+                entry.tags = counts;
+            }
+        }
+
+        self.logger.info(&format!("Tagging complete: {} files matched at least one tag", tagged_count));
+        tagged_count
+    }
+}
@@ -0,0 +1,175 @@
+use anyhow::{Context, Result};
+use std::io::{self, Read, Write};
+
+const BLOCK_SIZE: u64 = 512;
+
+/// Kind of a tar member that the pipeline cares about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TarEntryKind {
+    File,
+    Directory,
+    /// Links, devices, FIFOs: never extracted
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct TarEntry {
+    pub path: String,
+    pub kind: TarEntryKind,
+    pub size: u64,
+}
+
+/// Minimal streaming reader for ustar, GNU and pax tar archives.
+///
+/// Only what extraction needs is supported: regular files, directories,
+/// GNU long names (`L`) and pax `path` records. Everything else is skipped.
+pub struct TarReader<R: Read> {
+    inner: R,
+    /// Bytes of the current entry's data (plus padding) not consumed yet
+    pending: u64,
+}
+
+impl<R: Read> TarReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner, pending: 0 }
+    }
+
+    /// Advance to the next file or directory entry, skipping any unread data
+    pub fn next_entry(&mut self) -> Result<Option<TarEntry>> {
+        self.skip_pending()?;
+
+        let mut override_path: Option<String> = None;
+        loop {
+            let mut header = [0u8; BLOCK_SIZE as usize];
+            if !self.read_block(&mut header)? || header.iter().all(|&b| b == 0) {
+                return Ok(None);
+            }
+
+            let size = Self::parse_size(&header[124..136])?;
+            let padded = size.div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+            let typeflag = header[156];
+
+            match typeflag {
+                // GNU long name: the data is the name of the next entry
+                b'L' => {
+                    let data = self.read_data(size, padded)?;
+                    override_path = Some(Self::c_string(&data));
+                }
+                // pax extended header: look for a `path` record
+                b'x' => {
+                    let data = self.read_data(size, padded)?;
+                    if let Some(path) = Self::pax_path(&data) {
+                        override_path = Some(path);
+                    }
+                }
+                _ => {
+                    let path = override_path.take().unwrap_or_else(|| Self::header_path(&header));
+                    let kind = match typeflag {
+                        b'0' | b'\0' | b'7' => TarEntryKind::File,
+                        b'5' => TarEntryKind::Directory,
+                        _ => TarEntryKind::Other,
+                    };
+                    let kind = if kind == TarEntryKind::File && path.ends_with('/') {
+                        TarEntryKind::Directory
+                    } else {
+                        kind
+                    };
+                    self.pending = padded;
+                    let size = if kind == TarEntryKind::File { size } else { 0 };
+                    if kind != TarEntryKind::File {
+                        self.skip_pending()?;
+                    }
+                    return Ok(Some(TarEntry { path, kind, size }));
+                }
+            }
+        }
+    }
+
+    /// Copy the data of the current file entry into `writer`
+    pub fn copy_data<W: Write>(&mut self, entry: &TarEntry, writer: &mut W) -> Result<u64> {
+        let mut limited = (&mut self.inner).take(entry.size);
+        let copied = io::copy(&mut limited, writer).context("Failed to read tar entry data")?;
+        if copied < entry.size {
+            return Err(anyhow::anyhow!("Unexpected end of tar archive"));
+        }
+        self.pending = self.pending.saturating_sub(copied);
+        Ok(copied)
+    }
+
+    fn skip_pending(&mut self) -> Result<()> {
+        if self.pending > 0 {
+            let mut limited = (&mut self.inner).take(self.pending);
+            io::copy(&mut limited, &mut io::sink()).context("Failed to skip tar entry data")?;
+            self.pending = 0;
+        }
+        Ok(())
+    }
+
+    /// Read one header block; `false` on a clean end of stream
+    fn read_block(&mut self, block: &mut [u8]) -> Result<bool> {
+        let mut filled = 0;
+        while filled < block.len() {
+            let n = self.inner.read(&mut block[filled..]).context("Failed to read tar header")?;
+            if n == 0 {
+                if filled == 0 {
+                    return Ok(false);
+                }
+                return Err(anyhow::anyhow!("Truncated tar header"));
+            }
+            filled += n;
+        }
+        Ok(true)
+    }
+
+    fn read_data(&mut self, size: u64, padded: u64) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        (&mut self.inner)
+            .take(padded)
+            .read_to_end(&mut data)
+            .context("Failed to read tar extended header")?;
+        data.truncate(size as usize);
+        Ok(data)
+    }
+
+    /// Octal ASCII, or GNU base-256 when the high bit is set
+    fn parse_size(field: &[u8]) -> Result<u64> {
+        if field[0] & 0x80 != 0 {
+            let mut value: u64 = (field[0] & 0x7f) as u64;
+            for &b in &field[1..] {
+                value = (value << 8) | b as u64;
+            }
+            return Ok(value);
+        }
+        let text = Self::c_string(field);
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(0);
+        }
+        u64::from_str_radix(text, 8).with_context(|| format!("Invalid tar size field: {}", text))
+    }
+
+    fn header_path(header: &[u8]) -> String {
+        let name = Self::c_string(&header[0..100]);
+        let is_ustar = &header[257..262] == b"ustar";
+        let prefix = if is_ustar { Self::c_string(&header[345..500]) } else { String::new() };
+        if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        }
+    }
+
+    fn c_string(bytes: &[u8]) -> String {
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..end]).to_string()
+    }
+
+    /// Records look like `<len> path=<value>\n`
+    fn pax_path(data: &[u8]) -> Option<String> {
+        let text = String::from_utf8_lossy(data);
+        text.lines().find_map(|line| {
+            let (_, record) = line.split_once(' ')?;
+            record.strip_prefix("path=").map(|p| p.to_string())
+        })
+    }
+}
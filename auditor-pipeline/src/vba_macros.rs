@@ -0,0 +1,258 @@
+use crate::office_encryption::has_ole_magic;
+use anyhow::{bail, Context, Result};
+use encoding_rs::Encoding;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+
+/// Office formats that can carry a VBA project. Macro-free OOXML types are
+/// included because a renamed `.xlsm` still runs its macros.
+const MACRO_CAPABLE_EXTENSIONS: &[&str] = &[
+    "doc", "dot", "docm", "dotm", "docx", "xls", "xlt", "xla", "xlsm", "xltm", "xlam", "xlsb", "xlsx", "pptm",
+    "potm", "ppsm", "ppam", "pptx",
+];
+
+/// Part holding the VBA project inside an OOXML package
+const VBA_PROJECT_PART: &str = "vbaProject.bin";
+
+/// Signature of a zip (OOXML) package
+const ZIP_MAGIC: [u8; 4] = [0x50, 0x4B, 0x03, 0x04];
+
+/// Records of the `dir` stream the module list is read from
+const DIR_PROJECT_CODEPAGE: u16 = 0x0003;
+const DIR_PROJECT_VERSION: u16 = 0x0009;
+const DIR_MODULE_NAME: u16 = 0x0019;
+const DIR_MODULE_STREAM_NAME: u16 = 0x001A;
+const DIR_MODULE_OFFSET: u16 = 0x0031;
+const DIR_MODULE_TERMINATOR: u16 = 0x002B;
+
+/// One VBA module and its source code
+#[derive(Debug, Clone)]
+pub struct VbaModule {
+    pub name: String,
+    pub source: String,
+}
+
+/// Whether an Office document contains a VBA project: a `vbaProject.bin`
+/// part in OOXML packages, a `VBA/dir` stream in legacy compound files.
+/// Files that can't be read count as macro-free.
+pub fn contains_macros(path: &Path) -> bool {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    if !MACRO_CAPABLE_EXTENSIONS.contains(&ext.as_str()) {
+        return false;
+    }
+    if has_ole_magic(path) {
+        return cfb::open(path).map(|file| find_dir_stream(&file).is_some()).unwrap_or(false);
+    }
+    if has_zip_magic(path) {
+        return File::open(path)
+            .ok()
+            .and_then(|file| zip::ZipArchive::new(file).ok())
+            .map(|archive| find_vba_project_part(&archive).is_some())
+            .unwrap_or(false);
+    }
+    false
+}
+
+/// Decompress the source of every module of a document's VBA project
+pub fn extract_modules(path: &Path) -> Result<Vec<VbaModule>> {
+    if has_ole_magic(path) {
+        let mut file = cfb::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        return read_vba_project(&mut file);
+    }
+
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .with_context(|| format!("Not an Office document: {}", path.display()))?;
+    let part = find_vba_project_part(&archive).context("Document has no VBA project")?;
+    let mut data = Vec::new();
+    archive
+        .by_name(&part)
+        .with_context(|| format!("Failed to open {}", part))?
+        .read_to_end(&mut data)
+        .with_context(|| format!("Failed to read {}", part))?;
+    let mut project = cfb::CompoundFile::open(Cursor::new(data)).with_context(|| format!("Malformed {}", part))?;
+    read_vba_project(&mut project)
+}
+
+/// Write each module to `output_dir` as `<module>.bas`, returning the files written
+pub fn write_modules(modules: &[VbaModule], output_dir: &Path) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create {}", output_dir.display()))?;
+    let mut written = Vec::new();
+    for module in modules {
+        let file_name: String = module
+            .name
+            .chars()
+            .map(|c| if c.is_alphanumeric() || matches!(c, '_' | '-') { c } else { '_' })
+            .collect();
+        let path = output_dir.join(format!("{}.bas", file_name));
+        std::fs::write(&path, &module.source).with_context(|| format!("Failed to write {}", path.display()))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+fn has_zip_magic(path: &Path) -> bool {
+    let mut header = [0u8; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .map(|_| header == ZIP_MAGIC)
+        .unwrap_or(false)
+}
+
+fn find_vba_project_part(archive: &zip::ZipArchive<File>) -> Option<String> {
+    archive
+        .file_names()
+        .find(|name| name.rsplit('/').next().is_some_and(|file| file.eq_ignore_ascii_case(VBA_PROJECT_PART)))
+        .map(str::to_string)
+}
+
+/// Path of the `dir` stream of the VBA storage (`/Macros/VBA/dir` in Word,
+/// `/_VBA_PROJECT_CUR/VBA/dir` in Excel, `/VBA/dir` in `vbaProject.bin`)
+fn find_dir_stream<F>(file: &cfb::CompoundFile<F>) -> Option<PathBuf> {
+    file.walk()
+        .filter(|entry| entry.is_stream() && entry.name().eq_ignore_ascii_case("dir"))
+        .map(|entry| entry.path().to_path_buf())
+        .find(|path| {
+            path.parent()
+                .and_then(|parent| parent.file_name())
+                .is_some_and(|parent| parent.eq_ignore_ascii_case("VBA"))
+        })
+}
+
+fn read_stream<F: Read + Seek>(file: &mut cfb::CompoundFile<F>, path: &Path) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    file.open_stream(path)
+        .with_context(|| format!("Missing stream {}", path.display()))?
+        .read_to_end(&mut data)
+        .with_context(|| format!("Failed to read stream {}", path.display()))?;
+    Ok(data)
+}
+
+/// Read the module list from the `dir` stream, then decompress each
+/// module's source from its stream, starting at the recorded offset
+fn read_vba_project<F: Read + Seek>(file: &mut cfb::CompoundFile<F>) -> Result<Vec<VbaModule>> {
+    let dir_path = find_dir_stream(file).context("Document has no VBA project")?;
+    let vba_storage = dir_path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let dir = decompress(&read_stream(file, &dir_path)?).context("Malformed VBA dir stream")?;
+
+    let mut codepage = 1252;
+    let mut entries = Vec::new();
+    let (mut name, mut stream_name, mut offset) = (None, None, 0usize);
+    let mut position = 0;
+    while position + 6 <= dir.len() {
+        let id = u16::from_le_bytes([dir[position], dir[position + 1]]);
+        let size = u32::from_le_bytes(dir[position + 2..position + 6].try_into().unwrap()) as usize;
+        // PROJECTVERSION declares 4 bytes but carries 6
+        let size = if id == DIR_PROJECT_VERSION { 6 } else { size };
+        let Some(data) = dir.get(position + 6..position + 6 + size) else {
+            break;
+        };
+        match id {
+            DIR_PROJECT_CODEPAGE if size >= 2 => codepage = u16::from_le_bytes([data[0], data[1]]),
+            DIR_MODULE_NAME => name = Some(data.to_vec()),
+            DIR_MODULE_STREAM_NAME => stream_name = Some(data.to_vec()),
+            DIR_MODULE_OFFSET if size >= 4 => offset = u32::from_le_bytes(data[..4].try_into().unwrap()) as usize,
+            DIR_MODULE_TERMINATOR => {
+                if let Some(stream_name) = stream_name.take() {
+                    entries.push((name.take().unwrap_or_else(|| stream_name.clone()), stream_name, offset));
+                }
+                offset = 0;
+            }
+            _ => {}
+        }
+        position += 6 + size;
+    }
+
+    let encoding = codepage_encoding(codepage);
+    let mut modules = Vec::new();
+    for (name, stream_name, offset) in entries {
+        let stream_name = encoding.decode(&stream_name).0.into_owned();
+        let stream = read_stream(file, &vba_storage.join(&stream_name))?;
+        let Some(compressed) = stream.get(offset..) else {
+            bail!("Module {} has an offset past the end of its stream", stream_name);
+        };
+        let source = decompress(compressed).with_context(|| format!("Malformed VBA module {}", stream_name))?;
+        modules.push(VbaModule {
+            name: encoding.decode(&name).0.into_owned(),
+            source: encoding.decode(&source).0.replace("\r\n", "\n"),
+        });
+    }
+    Ok(modules)
+}
+
+/// Windows code page of the VBA project → text encoding
+pub(crate) fn codepage_encoding(codepage: u16) -> &'static Encoding {
+    let label = match codepage {
+        65001 => "utf-8".to_string(),
+        932 => "shift_jis".to_string(),
+        936 => "gbk".to_string(),
+        949 => "euc-kr".to_string(),
+        950 => "big5".to_string(),
+        874 | 1250..=1258 => format!("windows-{}", codepage),
+        _ => "windows-1252".to_string(),
+    };
+    Encoding::for_label(label.as_bytes()).unwrap_or(encoding_rs::WINDOWS_1252)
+}
+
+/// MS-OVBA run-length decompression of a `CompressedContainer`
+fn decompress(data: &[u8]) -> Option<Vec<u8>> {
+    if data.first() != Some(&0x01) {
+        return None;
+    }
+    let mut output: Vec<u8> = Vec::with_capacity(data.len() * 2);
+    let mut position = 1;
+
+    while position + 2 <= data.len() {
+        let header = u16::from_le_bytes([data[position], data[position + 1]]);
+        let chunk_end = (position + (header & 0x0FFF) as usize + 3).min(data.len());
+        position += 2;
+        let chunk_start = output.len();
+
+        if header & 0x8000 == 0 {
+            // Stored chunk: 4096 literal bytes
+            let end = (position + 4096).min(data.len());
+            output.extend_from_slice(&data[position..end]);
+            position = end;
+            continue;
+        }
+
+        while position < chunk_end {
+            let flags = data[position];
+            position += 1;
+            for bit in 0..8 {
+                if position >= chunk_end {
+                    break;
+                }
+                if flags & (1 << bit) == 0 {
+                    output.push(data[position]);
+                    position += 1;
+                    continue;
+                }
+
+                let token = u16::from_le_bytes([data[position], *data.get(position + 1)?]);
+                position += 2;
+                let decompressed = output.len() - chunk_start;
+                let mut bit_count = 4;
+                while (1usize << bit_count) < decompressed {
+                    bit_count += 1;
+                }
+                let length_mask = 0xFFFFu16 >> bit_count;
+                let length = (token & length_mask) as usize + 3;
+                let offset = (token >> (16 - bit_count)) as usize + 1;
+                if offset > output.len() - chunk_start {
+                    return None;
+                }
+                for _ in 0..length {
+                    output.push(output[output.len() - offset]);
+                }
+            }
+        }
+    }
+    Some(output)
+}
@@ -0,0 +1,181 @@
+use crate::checksums;
+use crate::hashing_service::{HashAlgorithm, HashingService};
+use crate::manifest::{ExportManifest, MANIFEST_FILE_NAME};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A file whose content no longer matches the recorded hash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifiedFile {
+    pub path: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Outcome of checking an output folder against the manifest of the run
+/// that produced it, e.g. after evidence was handed to another team
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub run_id: Option<String>,
+    pub verified_at: String,
+    pub reference_path: String,
+    pub output_path: String,
+    pub hash_algorithm: HashAlgorithm,
+    /// True when nothing is missing, modified or extra
+    pub passed: bool,
+    pub files_checked: usize,
+    pub files_matched: usize,
+    pub missing: Vec<String>,
+    pub modified: Vec<ModifiedFile>,
+    /// Files in the output folder the run didn't record
+    pub extra: Vec<String>,
+    /// Recorded files without a hash to compare; only their presence is checked
+    pub unhashed: Vec<String>,
+}
+
+impl VerificationReport {
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize verification report")?;
+        fs::write(path, json).with_context(|| format!("Failed to write verification report: {}", path.display()))
+    }
+}
+
+/// Re-hash every file in `output_path` and compare it with what the manifest
+/// at `reference_path` recorded. The checksum file written next to that
+/// manifest, when present, also covers the report and the run's other
+/// artifacts, so they are checked too rather than reported as extra.
+///
+/// `reference_path` may also be the run's JSON report, whose
+/// `manifest_path` leads to the manifest.
+pub fn verify_run(reference_path: &Path, output_path: &Path) -> Result<VerificationReport> {
+    let (manifest, manifest_path) = load_reference(reference_path)?;
+    if !output_path.is_dir() {
+        anyhow::bail!("Output folder not found: {}", output_path.display());
+    }
+
+    let algorithm = manifest.hash_algorithm;
+    let checksum_file_name = checksums::checksum_file_name(algorithm);
+    let mut expected: BTreeMap<String, Option<String>> = manifest
+        .files
+        .iter()
+        .flat_map(|file| {
+            // A split document is in the folder as its parts, hashed in the checksum file
+            if file.parts.is_empty() {
+                vec![(normalize(&file.exported_file_name), file.hash.clone())]
+            } else {
+                file.parts.iter().map(|part| (normalize(part), None)).collect()
+            }
+        })
+        .collect();
+    let sums_path = manifest_path
+        .parent()
+        .map(|dir| dir.join(&checksum_file_name))
+        .filter(|path| path.is_file());
+    if let Some(sums_path) = sums_path {
+        for (digest, relative) in checksums::read_checksum_file(&sums_path)? {
+            expected.insert(normalize(&relative), Some(digest));
+        }
+    }
+
+    let hashing_service = HashingService::with_algorithm(algorithm);
+    let mut report = VerificationReport {
+        run_id: manifest.run_id.clone(),
+        verified_at: chrono::Local::now().to_rfc3339(),
+        reference_path: reference_path.to_string_lossy().to_string(),
+        output_path: output_path.to_string_lossy().to_string(),
+        hash_algorithm: algorithm,
+        passed: false,
+        files_checked: expected.len(),
+        files_matched: 0,
+        missing: Vec::new(),
+        modified: Vec::new(),
+        extra: Vec::new(),
+        unhashed: Vec::new(),
+    };
+
+    for (relative, digest) in &expected {
+        let path = output_path.join(relative);
+        if !path.is_file() {
+            report.missing.push(relative.clone());
+            continue;
+        }
+        let Some(digest) = digest else {
+            report.unhashed.push(relative.clone());
+            continue;
+        };
+        let actual = hashing_service.hash_file(&path)?;
+        if actual.eq_ignore_ascii_case(digest) {
+            report.files_matched += 1;
+        } else {
+            report.modified.push(ModifiedFile {
+                path: relative.clone(),
+                expected: digest.clone(),
+                actual,
+            });
+        }
+    }
+
+    for file in WalkDir::new(output_path).sort_by_file_name().into_iter().filter_map(|e| e.ok()) {
+        if !file.file_type().is_file() {
+            continue;
+        }
+        let relative = normalize(&file.path().strip_prefix(output_path).unwrap_or(file.path()).to_string_lossy());
+        if relative != checksum_file_name && !expected.contains_key(&relative) {
+            report.extra.push(relative);
+        }
+    }
+
+    report.passed = report.missing.is_empty() && report.modified.is_empty() && report.extra.is_empty();
+    Ok(report)
+}
+
+/// The manifest at `reference_path`, or the one a JSON report points to,
+/// with the path it was read from
+fn load_reference(reference_path: &Path) -> Result<(ExportManifest, PathBuf)> {
+    let bytes = fs::read(reference_path)
+        .with_context(|| format!("Failed to read reference manifest: {}", reference_path.display()))?;
+    let value: serde_json::Value = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Failed to parse {}", reference_path.display()))?;
+    if value.get("files").is_some() {
+        let manifest = serde_json::from_value(value)
+            .with_context(|| format!("Failed to parse {}", reference_path.display()))?;
+        return Ok((manifest, reference_path.to_path_buf()));
+    }
+
+    let Some(recorded) = value.get("manifest_path").and_then(|path| path.as_str()) else {
+        anyhow::bail!(
+            "{} is neither an export manifest nor a JSON report of a run",
+            reference_path.display()
+        );
+    };
+    // The report is written next to the manifest, so a moved export folder
+    // still has it alongside
+    let manifest_path = [
+        Some(PathBuf::from(recorded)),
+        reference_path.parent().map(|dir| dir.join(MANIFEST_FILE_NAME)),
+    ]
+    .into_iter()
+    .flatten()
+    .find(|path| path.is_file())
+    .with_context(|| {
+        format!(
+            "Manifest of the JSON report {} not found at {}",
+            reference_path.display(),
+            recorded
+        )
+    })?;
+    let bytes = fs::read(&manifest_path)
+        .with_context(|| format!("Failed to read reference manifest: {}", manifest_path.display()))?;
+    let manifest = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Failed to parse {}", manifest_path.display()))?;
+    Ok((manifest, manifest_path))
+}
+
+/// Relative path with forward slashes, as checksum files record it
+fn normalize(relative: &str) -> String {
+    relative.replace('\\', "/")
+}
@@ -0,0 +1,285 @@
+use crate::conversion_engine::ConversionEngine;
+use crate::ept_logger::EPTLogger;
+use crate::report_model::ReportModel;
+use crate::secure_delete::secure_delete_path;
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What happens to the staging workspace once the export and report are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StagingCleanup {
+    /// Leave the workspace in place for inspection
+    #[default]
+    Keep,
+    /// Remove it with a plain recursive delete
+    Delete,
+    /// Overwrite every file before removing it
+    SecureShred,
+    /// Send it to the OS recycle bin / trash, so it can still be restored
+    RecycleBin,
+}
+
+/// Staging subfolder that extracted archives are moved into
+pub const ARCHIVES_FOLDER_NAME: &str = "_archives";
+
+/// What happens to an archive in staging once its contents have been extracted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractedArchivePolicy {
+    /// Leave it next to its contents, where it is hashed and reported too
+    #[default]
+    Keep,
+    /// Move it under `_archives/`, keeping its relative path
+    MoveToArchivesFolder,
+    /// Remove it from staging (the original input is untouched)
+    Delete,
+}
+
+/// Manages the on-disk staging workspace of a run.
+pub struct StagingWorkspace {
+    logger: EPTLogger,
+}
+
+impl StagingWorkspace {
+    pub fn new(logger: EPTLogger) -> Self {
+        Self { logger }
+    }
+
+    /// Gzip pass-through files (neither convertible nor LLM-readable) in place.
+    ///
+    /// These files are hashed and reported but never exported, so on
+    /// disk-constrained machines they only cost space. Each compressed file is
+    /// replaced by `<name>.gz` and the entry's working path is updated; the
+    /// original identity and hash are left untouched.
+    ///
+    /// Returns the number of bytes saved.
+    pub fn compress_passthrough_files(
+        &self,
+        working_path: &Path,
+        entries: &mut [ReportModel],
+        conversion_engine: &ConversionEngine,
+    ) -> Result<u64> {
+        self.logger.info("Compressing pass-through files in staging workspace...");
+
+        let mut compressed_count = 0;
+        let mut bytes_saved: u64 = 0;
+
+        for entry in entries.iter_mut() {
+            let file_path = working_path.join(&entry.relative_path);
+            if !file_path.is_file()
+                || conversion_engine.is_convertible_file(&file_path)
+                || ReportModel::is_llm_readable(&file_path)
+                || Self::is_already_compressed(&file_path)
+            {
+                continue;
+            }
+
+            match self.gzip_in_place(&file_path) {
+                Ok((compressed_path, saved)) => {
+                    if let Ok(relative) = compressed_path.strip_prefix(working_path) {
+                        entry.relative_path = relative.to_string_lossy().to_string();
+                    }
+                    compressed_count += 1;
+                    bytes_saved += saved;
+                }
+                Err(e) => {
+                    self.logger.warning(&format!(
+                        "Failed to compress staged file {}: {}",
+                        file_path.display(),
+                        e
+                    ));
+                }
+            }
+        }
+
+        self.logger.info(&format!(
+            "Compressed {} pass-through files, saved {} bytes",
+            compressed_count,
+            bytes_saved
+        ));
+
+        Ok(bytes_saved)
+    }
+
+    fn gzip_in_place(&self, file_path: &Path) -> Result<(PathBuf, u64)> {
+        let original_size = fs::metadata(file_path)
+            .with_context(|| format!("Failed to read metadata: {}", file_path.display()))?
+            .len();
+
+        let mut compressed_name = file_path.as_os_str().to_os_string();
+        compressed_name.push(".gz");
+        let compressed_path = PathBuf::from(compressed_name);
+
+        {
+            let mut input = fs::File::open(file_path)
+                .with_context(|| format!("Failed to open file: {}", file_path.display()))?;
+            let output = fs::File::create(&compressed_path)
+                .with_context(|| format!("Failed to create file: {}", compressed_path.display()))?;
+            let mut encoder = GzEncoder::new(output, Compression::default());
+            std::io::copy(&mut input, &mut encoder)
+                .with_context(|| format!("Failed to compress file: {}", file_path.display()))?;
+            encoder.finish()
+                .with_context(|| format!("Failed to finish compressed file: {}", compressed_path.display()))?;
+        }
+
+        let compressed_size = fs::metadata(&compressed_path)
+            .with_context(|| format!("Failed to read metadata: {}", compressed_path.display()))?
+            .len();
+
+        fs::remove_file(file_path)
+            .with_context(|| format!("Failed to remove original file: {}", file_path.display()))?;
+
+        self.logger.debug(&format!(
+            "Compressed staged file {} ({} -> {} bytes)",
+            file_path.display(),
+            original_size,
+            compressed_size
+        ));
+
+        Ok((compressed_path, original_size.saturating_sub(compressed_size)))
+    }
+
+    /// Undo `compress_passthrough_files` for one entry: gunzip its `.gz` copy
+    /// back to the staged original, so the file can be processed again. A
+    /// no-op for entries that weren't compressed.
+    pub fn restore_compressed(&self, working_path: &Path, entry: &ReportModel) -> Result<()> {
+        let staged_path = working_path.join(entry.staged_path());
+        let compressed_path = working_path.join(&entry.relative_path);
+        if compressed_path == staged_path
+            || !entry.relative_path.ends_with(".gz")
+            || !compressed_path.is_file()
+        {
+            return Ok(());
+        }
+
+        {
+            let input = fs::File::open(&compressed_path)
+                .with_context(|| format!("Failed to open file: {}", compressed_path.display()))?;
+            let mut output = fs::File::create(&staged_path)
+                .with_context(|| format!("Failed to create file: {}", staged_path.display()))?;
+            std::io::copy(&mut GzDecoder::new(input), &mut output)
+                .with_context(|| format!("Failed to decompress file: {}", compressed_path.display()))?;
+        }
+        fs::remove_file(&compressed_path)
+            .with_context(|| format!("Failed to remove compressed file: {}", compressed_path.display()))?;
+
+        self.logger.debug(&format!("Restored staged file {}", staged_path.display()));
+        Ok(())
+    }
+
+    /// Formats that are already compressed gain nothing from another gzip pass
+    fn is_already_compressed(path: &Path) -> bool {
+        if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+            let ext_lower = ext.to_lowercase();
+            matches!(
+                ext_lower.as_str(),
+                "zip" | "gz" | "7z" | "rar" | "jpg" | "jpeg" | "png" | "gif" | "mp3" | "mp4" | "mov" | "heic"
+            )
+        } else {
+            false
+        }
+    }
+
+    /// Remove the staging workspace according to `mode`.
+    pub fn cleanup(&self, working_path: &Path, mode: StagingCleanup, shred_passes: u32) -> Result<()> {
+        self.remove_folder(working_path, "staging workspace", mode, shred_passes)
+    }
+
+    /// Remove `folder` according to `mode`; `what` names it in the log and
+    /// errors, e.g. "staging workspace"
+    pub fn remove_folder(&self, folder: &Path, what: &str, mode: StagingCleanup, shred_passes: u32) -> Result<()> {
+        match mode {
+            StagingCleanup::Keep => Ok(()),
+            StagingCleanup::Delete => {
+                self.logger.info(&format!("Deleting {}: {}", what, folder.display()));
+                fs::remove_dir_all(folder)
+                    .with_context(|| format!("Failed to delete {}: {}", what, folder.display()))
+            }
+            StagingCleanup::SecureShred => {
+                self.logger.info(&format!(
+                    "Shredding {} ({} pass(es)): {}",
+                    what,
+                    shred_passes.max(1),
+                    folder.display()
+                ));
+                let stats = secure_delete_path(folder, shred_passes, &self.logger)?;
+                self.logger.info(&format!(
+                    "Shredded {} files ({} bytes overwritten)",
+                    stats.files_deleted, stats.bytes_overwritten
+                ));
+                if stats.failures.is_empty() {
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!(
+                        "{} items in the {} could not be shredded",
+                        stats.failures.len(),
+                        what
+                    ))
+                }
+            }
+            StagingCleanup::RecycleBin => {
+                self.logger.info(&format!("Moving {} to the recycle bin: {}", what, folder.display()));
+                trash::delete(folder)
+                    .with_context(|| format!("Failed to move {} to the recycle bin: {}", what, folder.display()))
+            }
+        }
+    }
+
+    /// Apply `policy` to archives that were extracted inside the staging folder.
+    ///
+    /// Returns each handled archive with its new location (`None` when deleted).
+    /// Archives outside `working_path` are never touched.
+    pub fn dispose_extracted_archives(
+        &self,
+        working_path: &Path,
+        archives: &[PathBuf],
+        policy: ExtractedArchivePolicy,
+    ) -> Result<Vec<(PathBuf, Option<PathBuf>)>> {
+        let mut disposed = Vec::new();
+        if policy == ExtractedArchivePolicy::Keep {
+            return Ok(disposed);
+        }
+
+        for archive in archives {
+            let Ok(relative) = archive.strip_prefix(working_path) else {
+                continue;
+            };
+            if !archive.is_file() {
+                continue;
+            }
+            match policy {
+                ExtractedArchivePolicy::Keep => {}
+                ExtractedArchivePolicy::MoveToArchivesFolder => {
+                    let target = working_path.join(ARCHIVES_FOLDER_NAME).join(relative);
+                    if let Some(parent) = target.parent() {
+                        fs::create_dir_all(parent)
+                            .with_context(|| format!("Failed to create {}", parent.display()))?;
+                    }
+                    fs::rename(archive, &target)
+                        .with_context(|| format!("Failed to move {} to {}", archive.display(), target.display()))?;
+                    self.logger.debug(&format!("Moved extracted archive to {}", target.display()));
+                    disposed.push((archive.clone(), Some(target)));
+                }
+                ExtractedArchivePolicy::Delete => {
+                    fs::remove_file(archive)
+                        .with_context(|| format!("Failed to delete {}", archive.display()))?;
+                    self.logger.debug(&format!("Deleted extracted archive {}", archive.display()));
+                    disposed.push((archive.clone(), None));
+                }
+            }
+        }
+
+        self.logger.info(&format!(
+            "{} {} extracted archives in staging",
+            if policy == ExtractedArchivePolicy::Delete { "Deleted" } else { "Moved" },
+            disposed.len()
+        ));
+        Ok(disposed)
+    }
+}
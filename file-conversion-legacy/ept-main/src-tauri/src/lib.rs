@@ -1,26 +1,14 @@
-mod ept_logger;
-mod report_model;
-mod decompression_engine;
-mod process_controller;
-mod hashing_service;
-mod conversion_engine;
-mod llm_export_engine;
-mod report_writer;
-mod file_scanner;
-
-use conversion_engine::ConversionEngine;
-use ept_logger::{EPTLogger, LogEntry};
-use process_controller::{ProcessController, ProcessingResult};
-use serde::{Deserialize, Serialize};
+mod tauri_events;
+
+use auditor_pipeline::conversion_engine::ConversionEngine;
+use auditor_pipeline::ept_logger::{EPTLogger, LogEntry};
+use auditor_pipeline::process_controller::{ProcessController, ProcessingResult};
+use auditor_pipeline::settings::ProcessingSettings;
+use serde::Serialize;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ProgressUpdate {
-    pub current: usize,
-    pub total: usize,
-    pub task_category: String,
-}
+use tauri::Emitter;
+use tauri_events::TauriEventSink;
 
 // Global state for the logger and process controller
 struct AppState {
@@ -50,19 +38,33 @@ fn handle_user_input(path: String) -> Result<String, String> {
     Ok(path)
 }
 
+/// Payload of the `processing-complete` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessingComplete {
+    pub run_id: String,
+    pub result: ProcessingResult,
+}
+
+/// Payload of the `processing-failed` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessingFailed {
+    pub run_id: String,
+    pub error: String,
+}
+
+/// Start processing on a background task and return its run ID immediately.
+/// The outcome is delivered via the `processing-complete` / `processing-failed` events.
 #[tauri::command]
 async fn start_processing(
     input_path: String,
     state: tauri::State<'_, AppState>,
-) -> Result<ProcessingResult, String> {
+) -> Result<String, String> {
     let path = PathBuf::from(&input_path);
     
     if !path.exists() {
         return Err(format!("Path does not exist: {}", input_path));
     }
     
-    state.logger.info(&format!("Starting processing for: {}", input_path));
-    
     let app_handle = {
         if let Ok(handle) = state.app_handle.lock() {
             handle.clone()
@@ -71,26 +73,57 @@ async fn start_processing(
         }
     };
     
-    let app_handle_clone = app_handle.ok_or("App handle not initialized".to_string())?;
-    let mut controller = ProcessController::new(state.logger.clone(), app_handle_clone);
-    
-    match controller.start_processing(&path) {
-        Ok(result) => {
-            state.logger.info("Processing completed successfully");
-            Ok(result)
-        }
-        Err(e) => {
-            // detailed chain of errors
-            let error_chain = e.chain()
-                .map(|e| e.to_string())
-                .collect::<Vec<_>>()
-                .join(" -> ");
-            
-            let error_msg = format!("Processing failed: {}", error_chain);
-            state.logger.error(&error_msg);
-            Err(error_msg)
+    let app_handle = app_handle.ok_or("App handle not initialized".to_string())?;
+    let logger = state.logger.clone();
+
+    let run_id = format!("run-{}", chrono::Local::now().format("%Y%m%d_%H%M%S%3f"));
+    logger.info(&format!("Starting processing run {} for: {}", run_id, input_path));
+
+    let run_id_for_task = run_id.clone();
+    tauri::async_runtime::spawn(async move {
+        let events = Arc::new(TauriEventSink::new(app_handle.clone()));
+        let logger_for_controller = logger.clone();
+
+        let result = tokio::task::spawn_blocking(move || {
+            let mut controller = ProcessController::new(logger_for_controller, events, ProcessingSettings::default());
+            controller.start_processing(&path)
+        })
+        .await;
+
+        match result {
+            Ok(Ok(result)) => {
+                logger.info("Processing completed successfully");
+                let _ = app_handle.emit("processing-complete", &ProcessingComplete {
+                    run_id: run_id_for_task,
+                    result,
+                });
+            }
+            Ok(Err(e)) => {
+                // detailed chain of errors
+                let error_chain = e.chain()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                
+                let error_msg = format!("Processing failed: {}", error_chain);
+                logger.error(&error_msg);
+                let _ = app_handle.emit("processing-failed", &ProcessingFailed {
+                    run_id: run_id_for_task,
+                    error: error_msg,
+                });
+            }
+            Err(e) => {
+                let error_msg = format!("Task join error: {}", e);
+                logger.error(&error_msg);
+                let _ = app_handle.emit("processing-failed", &ProcessingFailed {
+                    run_id: run_id_for_task,
+                    error: error_msg,
+                });
+            }
         }
-    }
+    });
+
+    Ok(run_id)
 }
 
 /// SECURITY: Validate path before opening to prevent command injection
@@ -214,7 +247,7 @@ pub fn run() {
             app_handle: app_handle_clone,
         })
         .setup(move |app| {
-            logger.set_app_handle(app.handle().clone());
+            logger.set_sink(Arc::new(TauriEventSink::new(app.handle().clone())));
             if let Ok(mut handle) = app_handle.lock() {
                 *handle = Some(app.handle().clone());
             }
@@ -0,0 +1,24 @@
+use auditor_pipeline::ept_logger::LogEntry;
+use auditor_pipeline::events::{EventSink, ProgressUpdate};
+use tauri::Emitter;
+
+/// Forwards pipeline events to the webview as Tauri events.
+pub struct TauriEventSink {
+    app_handle: tauri::AppHandle,
+}
+
+impl TauriEventSink {
+    pub fn new(app_handle: tauri::AppHandle) -> Self {
+        Self { app_handle }
+    }
+}
+
+impl EventSink for TauriEventSink {
+    fn emit_log(&self, entry: &LogEntry) {
+        let _ = self.app_handle.emit("log-entry", entry);
+    }
+
+    fn emit_progress(&self, update: &ProgressUpdate) {
+        let _ = self.app_handle.emit("progress-update", update);
+    }
+}
@@ -4,10 +4,30 @@ export interface LogEntry {
   timestamp: string;
 }
 
+export type FailureStage =
+  | "extraction"
+  | "path_validation"
+  | "missing"
+  | "hashing"
+  | "conversion"
+  | "export";
+
+export type FileStatus =
+  | { kind: "pending" }
+  | { kind: "converted" }
+  | { kind: "copied_as_is" }
+  | { kind: "skipped_duplicate" }
+  | { kind: "skipped_encrypted" }
+  | { kind: "suppressed_in_thread" }
+  | { kind: "failed"; stage: FailureStage }
+  | { kind: "excluded" }
+  | { kind: "quarantined" }
+  | { kind: "inventory_only" };
+
 export interface ReportModel {
   file_name: string;
   sha512: string | null;
-  processed: string;
+  status: FileStatus;
   skip_reason: string | null;
   relative_path: string;
   file_type: string;
@@ -17,11 +37,54 @@ export interface ReportModel {
   created_time: string;
 }
 
+export interface RunSummary {
+  total_files: number;
+  status_counts: Record<string, number>;
+  file_type_counts: Record<string, number>;
+  total_bytes_in: number;
+  total_bytes_out: number;
+  exported_files: number;
+  duplicates_collapsed: number;
+  indicator_matches: number;
+  stage_durations_ms: Record<string, number>;
+  total_duration_ms: number;
+}
+
+export type ArchiveOutcome =
+  | "extracted"
+  | "failed"
+  | "skipped_nesting_depth"
+  | "skipped_already_processed";
+
+export interface ArchiveRecord {
+  path: string;
+  format: string;
+  sha512: string | null;
+  nesting_depth: number;
+  entry_count: number;
+  bytes_extracted: number;
+  outcome: ArchiveOutcome;
+  detail: string | null;
+}
+
 export interface ProcessingResult {
   entries: ReportModel[];
   staging_path: string;
   llm_output_path: string;
   report_path: string;
+  search_index_path: string | null;
+  archives: ArchiveRecord[];
+  summary: RunSummary;
+}
+
+export interface ProcessingComplete {
+  run_id: string;
+  result: ProcessingResult;
+}
+
+export interface ProcessingFailed {
+  run_id: string;
+  error: string;
 }
 
 export interface ProgressUpdate {
@@ -1,8 +1,8 @@
 import { invoke } from "@tauri-apps/api/core";
-import { listen } from "@tauri-apps/api/event";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import { open } from "@tauri-apps/plugin-dialog";
 import { getCurrentWindow } from "@tauri-apps/api/window";
-import { AppState, LogEntry, ProcessingResult, ProgressUpdate } from "./AppState";
+import { AppState, LogEntry, ProcessingComplete, ProcessingFailed, ProcessingResult, ProgressUpdate } from "./AppState";
 import { LogPanel } from "./LogPanel";
 import { ProgressPanel } from "./ProgressPanel";
 
@@ -10,6 +10,14 @@ export class EPTAppShell {
   private appState: AppState;
   private logPanel: LogPanel;
   private progressPanel: ProgressPanel;
+  private activeRunId: string | null = null;
+  // A run can end before start_processing returns its ID, so outcomes that
+  // arrive while the ID is awaited are kept until it does
+  private awaitingRunId = false;
+  private earlyOutcomes = new Map<string, () => void>();
+  // Backend event listeners, removed by destroy()
+  private unlisteners: UnlistenFn[] = [];
+  private destroyed = false;
 
   constructor() {
     this.appState = new AppState();
@@ -42,7 +50,23 @@ export class EPTAppShell {
     this.setupEventListeners();
     this.setupLogListener();
     this.setupProgressListener();
+    this.setupCompletionListeners();
     this.setupTauriDragDrop();
+    window.addEventListener("beforeunload", () => this.destroy());
+  }
+
+  /** Remove the backend event listeners */
+  public destroy(): void {
+    this.destroyed = true;
+    this.unlisteners.splice(0).forEach((unlisten) => unlisten());
+  }
+
+  private trackListener(unlisten: UnlistenFn): void {
+    if (this.destroyed) {
+      unlisten();
+    } else {
+      this.unlisteners.push(unlisten);
+    }
   }
 
   private renderLibreOfficeError(): void {
@@ -339,9 +363,9 @@ export class EPTAppShell {
 
   private async setupLogListener(): Promise<void> {
     try {
-      await listen<LogEntry>("log-entry", (event) => {
+      this.trackListener(await listen<LogEntry>("log-entry", (event) => {
         this.appState.addLog(event.payload.level, event.payload.message);
-      });
+      }));
     } catch (error) {
       console.error("Failed to set up log listener:", error);
     }
@@ -349,20 +373,51 @@ export class EPTAppShell {
 
   private async setupProgressListener(): Promise<void> {
     try {
-      await listen<ProgressUpdate>("progress-update", (event) => {
+      this.trackListener(await listen<ProgressUpdate>("progress-update", (event) => {
         this.appState.updateProgress(event.payload.current, event.payload.total, event.payload.task_category);
-      });
+      }));
     } catch (error) {
       console.error("Failed to set up progress listener:", error);
     }
   }
 
+  private async setupCompletionListeners(): Promise<void> {
+    try {
+      this.trackListener(await listen<ProcessingComplete>("processing-complete", (event) => {
+        this.handleRunOutcome(event.payload.run_id, () => {
+          this.handleProcessingResult(event.payload.result);
+          this.appState.setProcessing(false);
+        });
+      }));
+      this.trackListener(await listen<ProcessingFailed>("processing-failed", (event) => {
+        this.handleRunOutcome(event.payload.run_id, () => {
+          this.appState.addLog("ERROR", event.payload.error);
+          this.appState.setStatusMessage(`Error: ${event.payload.error}`);
+          this.appState.setProcessing(false);
+        });
+      }));
+    } catch (error) {
+      console.error("Failed to set up completion listeners:", error);
+    }
+  }
+
+  /** Apply the outcome of `runId` if it is the active run, or keep it if
+   * the ID of the run just started hasn't arrived yet */
+  private handleRunOutcome(runId: string, apply: () => void): void {
+    if (runId === this.activeRunId) {
+      this.activeRunId = null;
+      apply();
+    } else if (this.awaitingRunId) {
+      this.earlyOutcomes.set(runId, apply);
+    }
+  }
+
   private async setupTauriDragDrop(): Promise<void> {
     try {
       const window = getCurrentWindow();
       const dropZone = document.getElementById("drop-zone");
       
-      await window.onDragDropEvent(async (event) => {
+      const unlisten = await window.onDragDropEvent(async (event) => {
         const dropEvent = event.payload;
         
         if (dropEvent.type === "enter" || dropEvent.type === "over") {
@@ -381,6 +436,7 @@ export class EPTAppShell {
           }
         }
       });
+      this.trackListener(unlisten);
     } catch (error) {
       console.error("Failed to set up Tauri drag and drop:", error);
       this.appState.addLog("WARN", "Tauri drag and drop not available, falling back to web API");
@@ -406,30 +462,45 @@ export class EPTAppShell {
     this.appState.addLog("INFO", "Starting processing...");
 
     try {
-      const result = await invoke<ProcessingResult>("start_processing", {
+      // Processing runs in the background; the outcome arrives via the
+      // processing-complete / processing-failed events.
+      this.awaitingRunId = true;
+      const runId = await invoke<string>("start_processing", {
         inputPath: this.appState.selectedPath,
       });
-
-      this.appState.setProcessingResult(result);
-      const processedCount = result.entries.filter(e => e.processed === "Yes").length;
-      
-      this.appState.addLog("INFO", `Processing complete. Found ${result.entries.length} files, ${processedCount} processed.`);
-      
-      // Build status message with paths
-      const statusMessage = `Processed ${processedCount} of ${result.entries.length} files.\n\n` +
-        `<strong>Staging Folder:</strong> ${result.staging_path}\n` +
-        `<strong>LLM Output Folder:</strong> ${result.llm_output_path}\n` +
-        `<strong>Report File:</strong> ${result.report_path}`;
-      this.appState.setStatusMessage(statusMessage);
-      
+      this.awaitingRunId = false;
+      const earlyOutcome = this.earlyOutcomes.get(runId);
+      this.earlyOutcomes.clear();
+      if (earlyOutcome) {
+        earlyOutcome();
+      } else {
+        this.activeRunId = runId;
+      }
     } catch (error) {
+      this.awaitingRunId = false;
+      this.earlyOutcomes.clear();
       this.appState.addLog("ERROR", `Processing failed: ${error}`);
       this.appState.setStatusMessage(`Error: ${error}`);
-    } finally {
       this.appState.setProcessing(false);
     }
   }
 
+  private handleProcessingResult(result: ProcessingResult): void {
+    this.appState.setProcessingResult(result);
+    const { summary } = result;
+    const processedCount =
+      (summary.status_counts["converted"] ?? 0) + (summary.status_counts["copied_as_is"] ?? 0);
+    
+    this.appState.addLog("INFO", `Processing complete. Found ${summary.total_files} files, ${processedCount} processed.`);
+    
+    // Build status message with paths
+    const statusMessage = `Processed ${processedCount} of ${summary.total_files} files.\n\n` +
+      `<strong>Staging Folder:</strong> ${result.staging_path}\n` +
+      `<strong>LLM Output Folder:</strong> ${result.llm_output_path}\n` +
+      `<strong>Report File:</strong> ${result.report_path}`;
+    this.appState.setStatusMessage(statusMessage);
+  }
+
   private resetState(): void {
     this.appState.reset();
   }
@@ -0,0 +1,116 @@
+use auditor_pipeline::engagement::{Engagement, EngagementStore};
+use auditor_pipeline::ept_logger::EPTLogger;
+use auditor_pipeline::retention::{self, ExpiredRun, PurgedRun};
+use chrono::NaiveDate;
+use std::path::PathBuf;
+use tauri::Manager;
+
+use crate::AppState;
+
+/// Engagements live in the app data folder, one subfolder each.
+pub fn engagement_store(app_handle: &tauri::AppHandle) -> Result<EngagementStore, String> {
+    let data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data folder: {}", e))?;
+    Ok(EngagementStore::new(data_dir.join("engagements")))
+}
+
+fn app_handle(state: &tauri::State<'_, AppState>) -> Result<tauri::AppHandle, String> {
+    state
+        .app_handle
+        .lock()
+        .map_err(|_| "Failed to get app handle".to_string())?
+        .clone()
+        .ok_or("App handle not initialized".to_string())
+}
+
+/// Create an engagement; `retention_date` is `YYYY-MM-DD` when given.
+#[tauri::command]
+pub fn create_engagement(
+    name: String,
+    client_code: String,
+    retention_date: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Engagement, String> {
+    let retention_date = match retention_date.as_deref().map(str::trim) {
+        Some(date) if !date.is_empty() => Some(
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| format!("Invalid retention date (expected YYYY-MM-DD): {}", date))?,
+        ),
+        _ => None,
+    };
+
+    let store = engagement_store(&app_handle(&state)?)?;
+    let engagement = store
+        .create(&name, &client_code, retention_date)
+        .map_err(|e| e.to_string())?;
+    state.logger.info(&format!("Created engagement {}", engagement.id));
+    Ok(engagement)
+}
+
+#[tauri::command]
+pub fn list_engagements(state: tauri::State<'_, AppState>) -> Result<Vec<Engagement>, String> {
+    let store = engagement_store(&app_handle(&state)?)?;
+    store.list().map_err(|e| e.to_string())
+}
+
+/// Runs past their engagement's retention date that still have data on disk
+#[tauri::command]
+pub fn list_expired_runs(state: tauri::State<'_, AppState>) -> Result<Vec<ExpiredRun>, String> {
+    let store = engagement_store(&app_handle(&state)?)?;
+    retention::expired_runs(&store, chrono::Local::now().date_naive()).map_err(|e| e.to_string())
+}
+
+/// Securely delete the staging and export folders of every expired run,
+/// writing a deletion certificate per run into its engagement folder.
+#[tauri::command]
+pub async fn purge_expired(state: tauri::State<'_, AppState>) -> Result<Vec<PurgedRun>, String> {
+    let store = engagement_store(&app_handle(&state)?)?;
+    let logger = state.logger.clone();
+    tokio::task::spawn_blocking(move || {
+        retention::purge_expired(&store, &logger, chrono::Local::now().date_naive())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+    .map_err(|e| e.to_string())
+}
+
+/// Export folder of an engagement run, if some engagement recorded it
+pub fn find_run_export(state: &tauri::State<'_, AppState>, run_id: &str) -> Result<Option<PathBuf>, String> {
+    let store = engagement_store(&app_handle(state)?)?;
+    let engagements = store.list().map_err(|e| e.to_string())?;
+    Ok(engagements
+        .into_iter()
+        .flat_map(|engagement| engagement.runs)
+        .find(|run| run.run_id == run_id && run.purged_at.is_none())
+        .and_then(|run| run.llm_output_path)
+        .map(PathBuf::from))
+}
+
+/// Staging folder of an engagement run, if some engagement recorded it
+pub fn find_run_staging(state: &tauri::State<'_, AppState>, run_id: &str) -> Result<Option<PathBuf>, String> {
+    let store = engagement_store(&app_handle(state)?)?;
+    let engagements = store.list().map_err(|e| e.to_string())?;
+    Ok(engagements
+        .into_iter()
+        .flat_map(|engagement| engagement.runs)
+        .find(|run| run.run_id == run_id && run.purged_at.is_none())
+        .and_then(|run| run.staging_path)
+        .map(PathBuf::from))
+}
+
+/// Log a reminder at startup when runs are past retention.
+pub fn remind_expired_runs(app_handle: &tauri::AppHandle, logger: &EPTLogger) {
+    let Ok(store) = engagement_store(app_handle) else {
+        return;
+    };
+    match retention::expired_runs(&store, chrono::Local::now().date_naive()) {
+        Ok(expired) if !expired.is_empty() => logger.warning(&format!(
+            "{} run(s) are past their retention date and should be purged",
+            expired.len()
+        )),
+        Ok(_) => {}
+        Err(e) => logger.warning(&format!("Failed to check retention dates: {}", e)),
+    }
+}
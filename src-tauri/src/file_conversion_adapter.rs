@@ -1,29 +1,62 @@
-use crate::process_controller::{ProcessController, ProcessingResult};
+use crate::engagements::{self, engagement_store};
+use crate::tauri_events::TauriEventSink;
+use auditor_pipeline::engagement::EngagementRun;
+use auditor_pipeline::events::ProgressTracker;
+use auditor_pipeline::process_controller::{ProcessController, ProcessingResult};
+use auditor_pipeline::remediation::RemediationSummary;
+use auditor_pipeline::run_state;
+use auditor_pipeline::run_summary::RunSummary;
+use auditor_pipeline::settings::ProcessingSettings;
 use serde::Serialize;
-use std::path::PathBuf;
-use tauri::State;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{Emitter, Manager, State};
 
 // Import AppState from main module
-use crate::AppState;
+use crate::{AppState, CompletedRun};
+
+/// Event emitted when a background conversion run finishes successfully.
+pub const CONVERSION_COMPLETE_EVENT: &str = "file-conversion-complete";
+/// Event emitted when a background conversion run fails.
+pub const CONVERSION_FAILED_EVENT: &str = "file-conversion-failed";
 
 /// Result shape returned to the frontend for a File Conversion request.
-#[derive(Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FileConversionResult {
+    pub run_id: String,
     pub status: String,
     pub staging_path: Option<String>,
     pub llm_output_path: Option<String>,
     pub report_path: Option<String>,
+    pub search_index_path: Option<String>,
+    pub summary: Option<RunSummary>,
+    /// Next steps for files that weren't exported
+    pub remediations: Vec<RemediationSummary>,
+}
+
+/// Payload of the failure event for a File Conversion run.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileConversionFailure {
+    pub run_id: String,
+    pub error: String,
 }
 
 /// Adapter entrypoint for File Conversion (async version).
 ///
 /// This adapter delegates to the ProcessController which handles the full
 /// file conversion pipeline: decompression, scanning, conversion, hashing,
-/// and report generation. Runs in a background task to allow real-time event processing.
+/// and report generation. The pipeline runs on a background task and this
+/// function returns the run ID immediately; the outcome is delivered via the
+/// `file-conversion-complete` / `file-conversion-failed` events.
+///
+/// With an `engagement_id` the export and report are filed under the
+/// engagement's run folder and the run is recorded on the engagement.
 pub async fn start_file_conversion_async(
     input_path: String,
-    state: tauri::State<'_, AppState>,
-) -> Result<FileConversionResult, String> {
+    mut settings: ProcessingSettings,
+    engagement_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
     if input_path.trim().is_empty() {
         return Err("Input path must not be empty.".to_string());
     }
@@ -34,8 +67,6 @@ pub async fn start_file_conversion_async(
         return Err(format!("Path does not exist: {}", input_path));
     }
     
-    state.logger.info(&format!("Starting conversion for: {}", input_path));
-    
     // Get app handle for ProcessController
     let app_handle_clone = {
         if let Ok(handle) = state.app_handle.lock() {
@@ -45,45 +76,339 @@ pub async fn start_file_conversion_async(
         }
     };
     
-    let app_handle_for_controller = app_handle_clone.ok_or("App handle not initialized".to_string())?;
+    let app_handle = app_handle_clone.ok_or("App handle not initialized".to_string())?;
     let logger = state.logger.clone();
-    
-    // Spawn the processing in a blocking task so events can be processed in real-time
-    // Move path into the closure
-    let result = tokio::task::spawn_blocking(move || {
-        let mut controller = ProcessController::new(logger.clone(), app_handle_for_controller);
-        controller.start_processing(&path)
+    let progress = state.progress.clone();
+
+    let run_id = generate_run_id();
+    settings.run_id = Some(run_id.clone());
+
+    // Reprocessed evidence folders reuse earlier conversions from the app data folder
+    if settings.conversion_cache_dir.is_none() {
+        settings.conversion_cache_dir = app_handle
+            .path()
+            .app_data_dir()
+            .ok()
+            .map(|data_dir| data_dir.join("conversion_cache"));
+    }
+
+    let engagement = match engagement_id {
+        Some(id) => {
+            let store = engagement_store(&app_handle)?;
+            let engagement = store.load(&id).map_err(|e| e.to_string())?;
+            let retention_date = engagement.retention_date;
+            settings.output_root = Some(store.run_dir(&id, &run_id));
+            settings.engagement_code = Some(engagement.client_code);
+            Some((store, id, retention_date))
+        }
+        None => None,
+    };
+    let started_at = chrono::Local::now().to_rfc3339();
+
+    logger.info(&format!("Starting conversion run {} for: {}", run_id, input_path));
+
+    claim_progress(&progress)?;
+    let run_id_for_task = run_id.clone();
+    tauri::async_runtime::spawn(async move {
+        let events = Arc::new(TauriEventSink::new(app_handle.clone()));
+        let logger_for_controller = logger.clone();
+        let progress_for_controller = progress.clone();
+
+        // Run the pipeline in a blocking task so the invoke thread stays free
+        // and events can be processed in real-time
+        let result = tokio::task::spawn_blocking(move || {
+            let mut controller = ProcessController::new(logger_for_controller, events, settings)
+                .with_progress_tracker(progress_for_controller);
+            controller.start_processing(&path)
+        })
+        .await;
+        // A panic skips the controller's own bookkeeping
+        if result.is_err() {
+            progress.finish_run();
+        }
+
+        if let Some((store, id, retention_date)) = &engagement {
+            let mut run = EngagementRun {
+                run_id: run_id_for_task.clone(),
+                started_at,
+                input_path,
+                status: "failed".to_string(),
+                staging_path: None,
+                llm_output_path: None,
+                report_path: None,
+                error: None,
+                delete_after: *retention_date,
+                purged_at: None,
+            };
+            match &result {
+                Ok(Ok(processing_result)) => {
+                    run.status = "completed".to_string();
+                    run.staging_path = Some(processing_result.staging_path.clone());
+                    run.llm_output_path = Some(processing_result.llm_output_path.clone());
+                    run.report_path = Some(processing_result.report_path.clone());
+                }
+                Ok(Err(e)) => run.error = Some(e.to_string()),
+                Err(e) => run.error = Some(e.to_string()),
+            }
+            if let Err(e) = store.record_run(id, run) {
+                logger.warning(&format!("Failed to record run on engagement {}: {}", id, e));
+            }
+        }
+
+        match result {
+            Ok(Ok(ProcessingResult {
+                staging_path,
+                llm_output_path,
+                report_path,
+                search_index_path,
+                summary,
+                remediations,
+                ..
+            })) => {
+                logger.info(&format!("Conversion run {} completed.", run_id_for_task));
+                let state = app_handle.state::<AppState>();
+                if let Ok(mut runs) = state.completed_runs.lock() {
+                    runs.insert(run_id_for_task.clone(), CompletedRun {
+                        staging_path: PathBuf::from(&staging_path),
+                        llm_output_path: PathBuf::from(&llm_output_path),
+                    });
+                }
+                let _ = app_handle.emit(CONVERSION_COMPLETE_EVENT, &FileConversionResult {
+                    run_id: run_id_for_task,
+                    status: "completed".to_string(),
+                    staging_path: Some(staging_path),
+                    llm_output_path: Some(llm_output_path),
+                    report_path: Some(report_path),
+                    search_index_path,
+                    summary: Some(summary),
+                    remediations,
+                });
+            }
+            Ok(Err(e)) => {
+                // detailed chain of errors
+                let error_chain = e.chain()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                
+                let error_msg = format!("Processing failed: {}", error_chain);
+                logger.error(&error_msg);
+                let _ = app_handle.emit(CONVERSION_FAILED_EVENT, &FileConversionFailure {
+                    run_id: run_id_for_task,
+                    error: error_msg,
+                });
+            }
+            Err(e) => {
+                let error_msg = format!("Task join error: {}", e);
+                logger.error(&error_msg);
+                let _ = app_handle.emit(CONVERSION_FAILED_EVENT, &FileConversionFailure {
+                    run_id: run_id_for_task,
+                    error: error_msg,
+                });
+            }
+        }
+    });
+
+    Ok(run_id)
+}
+
+/// Redo the export and report of a finished run with its own settings,
+/// changed by `overrides`.
+///
+/// The run's staging folder (found through this session's runs or the
+/// engagement it was filed under) must still exist. Unlike a full run this
+/// waits for the result, as only the export and report are redone.
+pub async fn rerun_finalize_async(
+    run_id: String,
+    overrides: Option<serde_json::Value>,
+    state: State<'_, AppState>,
+) -> Result<FileConversionResult, String> {
+    state.logger.info(&format!("Redoing export and report of run {}", run_id));
+    redo_run_async(run_id, overrides, state, |controller, staging_path| {
+        controller.rerun_finalize(staging_path).map_err(|e| format!("{:#}", e))
     })
     .await
-    .map_err(|e| format!("Task join error: {}", e))?;
-    
-    match result {
-        Ok(ProcessingResult {
-            staging_path,
-            llm_output_path,
-            report_path,
-            ..
-        }) => {
-            state.logger.info("Conversion request completed.");
-            Ok(FileConversionResult {
-                status: "completed".to_string(),
-                staging_path: Some(staging_path),
-                llm_output_path: Some(llm_output_path),
-                report_path: Some(report_path),
+}
+
+/// Process the entries `entry_ids` (original relative paths, as in the
+/// report) of a finished run again with `settings`, then redo its export
+/// and report. The other entries keep their outcome.
+pub async fn reprocess_entries_async(
+    run_id: String,
+    entry_ids: Vec<String>,
+    overrides: Option<serde_json::Value>,
+    state: State<'_, AppState>,
+) -> Result<FileConversionResult, String> {
+    if entry_ids.is_empty() {
+        return Err("No entries selected for reprocessing".to_string());
+    }
+    state.logger.info(&format!("Reprocessing {} entries of run {}", entry_ids.len(), run_id));
+    redo_run_async(run_id, overrides, state, move |controller, staging_path| {
+        controller.reprocess_entries(staging_path, &entry_ids).map_err(|e| format!("{:#}", e))
+    })
+    .await
+}
+
+/// Continue a run that was interrupted (e.g. by a crash) during the file
+/// stages, from the checkpoint in its staging folder. Files the run had
+/// already hashed or processed are not redone.
+pub async fn resume_file_conversion_async(
+    staging_path: String,
+    overrides: Option<serde_json::Value>,
+    state: State<'_, AppState>,
+) -> Result<FileConversionResult, String> {
+    let staging_path = PathBuf::from(staging_path);
+    if !staging_path.is_dir() {
+        return Err(format!("Staging folder does not exist: {}", staging_path.display()));
+    }
+    state.logger.info(&format!("Resuming the run in {}", staging_path.display()));
+    run_in_staging_async(staging_path, overrides, state, |controller, staging_path| {
+        controller.resume_processing(staging_path).map_err(|e| format!("{:#}", e))
+    })
+    .await
+}
+
+/// Run `redo` against the kept staging folder of `run_id` and record the
+/// new export as the run's
+async fn redo_run_async<F>(
+    run_id: String,
+    overrides: Option<serde_json::Value>,
+    state: State<'_, AppState>,
+    redo: F,
+) -> Result<FileConversionResult, String>
+where
+    F: FnOnce(&mut ProcessController, &Path) -> Result<ProcessingResult, String> + Send + 'static,
+{
+    let session_run = state
+        .completed_runs
+        .lock()
+        .map_err(|_| "Failed to read completed runs".to_string())?
+        .get(&run_id)
+        .map(|run| run.staging_path.clone());
+    let staging_path = match session_run {
+        Some(path) => path,
+        None => engagements::find_run_staging(&state, &run_id)?
+            .ok_or_else(|| format!("Unknown run: {}", run_id))?,
+    };
+    if !staging_path.is_dir() {
+        return Err(format!("Staging folder of run {} no longer exists: {}", run_id, staging_path.display()));
+    }
+    run_in_staging_async(staging_path, overrides, state, redo).await
+}
+
+/// Run `redo` against `staging_path` with the settings saved there changed
+/// by `overrides`, and record the export as that of the run the staging
+/// folder belongs to
+async fn run_in_staging_async<F>(
+    staging_path: PathBuf,
+    overrides: Option<serde_json::Value>,
+    state: State<'_, AppState>,
+    redo: F,
+) -> Result<FileConversionResult, String>
+where
+    F: FnOnce(&mut ProcessController, &Path) -> Result<ProcessingResult, String> + Send + 'static,
+{
+    let saved = run_state::saved_settings(&staging_path).unwrap_or_else(|| {
+        state.logger.warning("The run saved no settings; starting from the defaults");
+        ProcessingSettings::default()
+    });
+    let settings = match overrides {
+        Some(overrides) => saved.with_overrides(&overrides).map_err(|e| format!("{:#}", e))?,
+        None => saved,
+    };
+    let saved_run_id = settings.run_id.clone();
+    let app_handle = state
+        .app_handle
+        .lock()
+        .map_err(|_| "Failed to get app handle".to_string())?
+        .clone()
+        .ok_or("App handle not initialized".to_string())?;
+    let logger = state.logger.clone();
+    let progress = state.progress.clone();
+
+    let events = Arc::new(TauriEventSink::new(app_handle));
+    claim_progress(&progress)?;
+    let progress_for_controller = progress.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let mut controller =
+            ProcessController::new(logger, events, settings).with_progress_tracker(progress_for_controller);
+        redo(&mut controller, &staging_path)
+    })
+    .await;
+    // A panic skips the controller's own bookkeeping
+    if result.is_err() {
+        progress.finish_run();
+    }
+    let result = result
+        .map_err(|e| format!("Task join error: {}", e))
+        .and_then(|result| result.map_err(|e| format!("Rerun failed: {}", e)));
+    if let Some(run_id) = &saved_run_id {
+        update_engagement_run(&state, run_id, &result);
+    }
+    let result = result?;
+
+    // The controller takes over the run ID saved in staging
+    let run_id = result.settings_snapshot.settings.run_id.clone().unwrap_or_else(generate_run_id);
+    if let Ok(mut runs) = state.completed_runs.lock() {
+        runs.insert(run_id.clone(), CompletedRun {
+            staging_path: PathBuf::from(&result.staging_path),
+            llm_output_path: PathBuf::from(&result.llm_output_path),
+        });
+    }
+    Ok(FileConversionResult {
+        run_id,
+        status: "completed".to_string(),
+        staging_path: Some(result.staging_path),
+        llm_output_path: Some(result.llm_output_path),
+        report_path: Some(result.report_path),
+        search_index_path: result.search_index_path,
+        summary: Some(result.summary),
+        remediations: result.remediations,
+    })
+}
+
+/// Point the engagement record of `run_id`, if the run was filed under an
+/// engagement, at the outcome of redoing it. A failure is recorded too, as
+/// the redo may already have removed the earlier export.
+fn update_engagement_run(state: &State<'_, AppState>, run_id: &str, result: &Result<ProcessingResult, String>) {
+    let store = match state.app_handle.lock().ok().and_then(|handle| handle.clone()) {
+        Some(app_handle) => engagement_store(&app_handle),
+        None => Err("App handle not initialized".to_string()),
+    };
+    let updated = store.and_then(|store| {
+        store
+            .update_run(run_id, |run| match result {
+                Ok(processing_result) => {
+                    run.status = "completed".to_string();
+                    run.staging_path = Some(processing_result.staging_path.clone());
+                    run.llm_output_path = Some(processing_result.llm_output_path.clone());
+                    run.report_path = Some(processing_result.report_path.clone());
+                    run.error = None;
+                }
+                Err(e) => {
+                    run.status = "failed".to_string();
+                    run.error = Some(e.clone());
+                }
             })
-        }
-        Err(e) => {
-            // detailed chain of errors
-            let error_chain = e.chain()
-                .map(|e| e.to_string())
-                .collect::<Vec<_>>()
-                .join(" -> ");
-            
-            let error_msg = format!("Processing failed: {}", error_chain);
-            state.logger.error(&error_msg);
-            Err(error_msg)
-        }
+            .map_err(|e| format!("{:#}", e))
+    });
+    if let Err(e) = updated {
+        state.logger.warning(&format!("Failed to update run {} on its engagement: {}", run_id, e));
     }
 }
 
+/// Generate a run identifier unique enough to correlate events within a session.
+fn generate_run_id() -> String {
+    format!("run-{}", chrono::Local::now().format("%Y%m%d_%H%M%S%3f"))
+}
 
+/// Mark a run as started on the app's progress tracker. The tracker, and
+/// with it progress and pause, is shared by all runs, so a run is rejected
+/// while another is in progress.
+fn claim_progress(progress: &ProgressTracker) -> Result<(), String> {
+    if progress.try_start_run() {
+        Ok(())
+    } else {
+        Err("Another conversion is still running; start this one once it has finished.".to_string())
+    }
+}
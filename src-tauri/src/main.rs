@@ -3,33 +3,35 @@
 // Minimal Tauri 2 entrypoint for the auditor-tools shell.
 // Integrates File Conversion functionality from the legacy application.
 
+mod engagements;
 mod file_conversion_adapter;
-mod ept_logger;
-mod report_model;
-mod decompression_engine;
-mod process_controller;
-mod hashing_service;
-mod conversion_engine;
-mod llm_export_engine;
-mod report_writer;
-mod file_scanner;
-
-use file_conversion_adapter::FileConversionResult;
-use ept_logger::{EPTLogger, LogEntry};
-use serde::{Deserialize, Serialize};
+mod tauri_events;
+
+use auditor_pipeline::corpus_stats::CorpusStats;
+use auditor_pipeline::ept_logger::{EPTLogger, LogEntry};
+use auditor_pipeline::events::{ProgressSnapshot, ProgressTracker, ProgressUpdate};
+use auditor_pipeline::search_index::{SearchHit, SearchIndex};
+use auditor_pipeline::settings::ProcessingSettings;
+use auditor_pipeline::verification::{self, VerificationReport};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use tauri::Builder;
+use tauri::{Builder, Emitter};
+use tauri_events::TauriEventSink;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ProgressUpdate {
-    pub current: usize,
-    pub total: usize,
-    pub task_category: String,
+/// Folders of a run completed this session
+#[derive(Debug, Clone)]
+pub struct CompletedRun {
+    pub staging_path: PathBuf,
+    pub llm_output_path: PathBuf,
 }
 
 // Global state for the logger
 pub struct AppState {
     pub logger: EPTLogger,
+    pub progress: ProgressTracker,
+    /// Each run completed this session, by run ID
+    pub completed_runs: Mutex<HashMap<String, CompletedRun>>,
     pub app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
 }
 
@@ -39,8 +41,158 @@ fn ping() -> String {
 }
 
 #[tauri::command]
-async fn start_file_conversion(input_path: String, state: tauri::State<'_, AppState>) -> Result<FileConversionResult, String> {
-    file_conversion_adapter::start_file_conversion_async(input_path, state).await
+async fn start_file_conversion(
+    input_path: String,
+    settings: Option<ProcessingSettings>,
+    engagement_id: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    file_conversion_adapter::start_file_conversion_async(
+        input_path,
+        settings.unwrap_or_default(),
+        engagement_id,
+        state,
+    )
+    .await
+}
+
+/// Query the full-text index of a finished run's LLM export folder
+#[tauri::command]
+async fn search_corpus(
+    llm_output_path: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, String> {
+    tokio::task::spawn_blocking(move || {
+        let export_path = Path::new(&llm_output_path);
+        let index = SearchIndex::load(export_path).map_err(|e| format!("{:#}", e))?;
+        index
+            .search(export_path, &query, limit.unwrap_or(50))
+            .map_err(|e| format!("{:#}", e))
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Token, type, size and language statistics of a run's export, computed
+/// from its manifest. Runs from earlier sessions are found through the
+/// engagements they were filed under.
+#[tauri::command]
+async fn get_corpus_stats(run_id: String, state: tauri::State<'_, AppState>) -> Result<CorpusStats, String> {
+    let session_run = state
+        .completed_runs
+        .lock()
+        .map_err(|_| "Failed to read completed runs".to_string())?
+        .get(&run_id)
+        .map(|run| run.llm_output_path.clone());
+    let export_path = match session_run {
+        Some(path) => path,
+        None => engagements::find_run_export(&state, &run_id)?
+            .ok_or_else(|| format!("Unknown run: {}", run_id))?,
+    };
+    // Reads every exported file, so it stays off the async runtime
+    tokio::task::spawn_blocking(move || CorpusStats::compute(&export_path).map_err(|e| format!("{:#}", e)))
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Redo only the export and report of a finished run, reusing its kept
+/// staging folder instead of reprocessing the evidence. `new_options` holds
+/// only the settings to change; the rest are the run's own.
+#[tauri::command]
+async fn rerun_finalize(
+    run_id: String,
+    new_options: Option<serde_json::Value>,
+    state: tauri::State<'_, AppState>,
+) -> Result<file_conversion_adapter::FileConversionResult, String> {
+    file_conversion_adapter::rerun_finalize_async(run_id, new_options, state).await
+}
+
+/// Process selected entries of a finished run again, e.g. after installing
+/// OCR or supplying a document password, and redo its export and report.
+/// `new_options` holds only the settings to change, as for `rerun_finalize`.
+#[tauri::command]
+async fn reprocess_entries(
+    run_id: String,
+    entry_ids: Vec<String>,
+    new_options: Option<serde_json::Value>,
+    state: tauri::State<'_, AppState>,
+) -> Result<file_conversion_adapter::FileConversionResult, String> {
+    file_conversion_adapter::reprocess_entries_async(run_id, entry_ids, new_options, state).await
+}
+
+/// Continue a run interrupted during the file stages from the checkpoint in
+/// its staging folder, skipping files it had already hashed or processed.
+/// `new_options` holds only the settings to change, as for `rerun_finalize`.
+#[tauri::command]
+async fn resume_file_conversion(
+    staging_path: String,
+    new_options: Option<serde_json::Value>,
+    state: tauri::State<'_, AppState>,
+) -> Result<file_conversion_adapter::FileConversionResult, String> {
+    file_conversion_adapter::resume_file_conversion_async(staging_path, new_options, state).await
+}
+
+/// Pause the running conversion once the files in progress finish; the
+/// paused state goes out as a progress update
+#[tauri::command]
+fn pause_file_conversion(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let update = state.progress.pause().ok_or("No conversion is running")?;
+    state.logger.info("Processing paused after the files in progress");
+    emit_progress_update(&state, &update);
+    Ok(())
+}
+
+/// Continue a conversion paused with `pause_file_conversion`. Unlike
+/// `resume_file_conversion`, which restarts an interrupted run from its
+/// checkpoint, this releases a run that is still in progress.
+#[tauri::command]
+fn continue_file_conversion(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    let update = state.progress.resume().ok_or("No conversion is running")?;
+    state.logger.info("Processing continued");
+    emit_progress_update(&state, &update);
+    Ok(())
+}
+
+fn emit_progress_update(state: &AppState, update: &ProgressUpdate) {
+    if let Ok(handle) = state.app_handle.lock() {
+        if let Some(handle) = handle.as_ref() {
+            let _ = handle.emit("progress-update", update);
+        }
+    }
+}
+
+/// Re-hash an output folder against the manifest, or JSON report, of the
+/// run that produced it and report missing, modified and extra files; the
+/// report is also saved as JSON when `report_path` is given
+#[tauri::command]
+async fn verify_run(
+    reference_path: String,
+    output_path: String,
+    report_path: Option<String>,
+) -> Result<VerificationReport, String> {
+    tokio::task::spawn_blocking(move || {
+        let report = verification::verify_run(Path::new(&reference_path), Path::new(&output_path))
+            .map_err(|e| format!("{:#}", e))?;
+        if let Some(report_path) = report_path {
+            report.save(Path::new(&report_path)).map_err(|e| format!("{:#}", e))?;
+        }
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Load processing settings from a TOML profile
+#[tauri::command]
+fn load_settings_profile(path: String) -> Result<ProcessingSettings, String> {
+    ProcessingSettings::load_profile(Path::new(&path)).map_err(|e| format!("{:#}", e))
+}
+
+/// Save processing settings as a TOML profile
+#[tauri::command]
+fn save_settings_profile(path: String, settings: ProcessingSettings) -> Result<(), String> {
+    settings.save_profile(Path::new(&path)).map_err(|e| format!("{:#}", e))
 }
 
 #[tauri::command]
@@ -48,6 +200,13 @@ fn get_logs(state: tauri::State<'_, AppState>) -> Vec<LogEntry> {
     state.logger.get_logs()
 }
 
+/// Latest progress of the running (or last) conversion, so a reloaded
+/// webview can resynchronize without waiting for the next event
+#[tauri::command]
+fn get_current_progress(state: tauri::State<'_, AppState>) -> ProgressSnapshot {
+    state.progress.snapshot()
+}
+
 fn main() {
     let logger = EPTLogger::new();
     let logger_clone = logger.clone();
@@ -58,10 +217,13 @@ fn main() {
         .plugin(tauri_plugin_dialog::init())
         .manage(AppState {
             logger: logger_clone,
+            progress: ProgressTracker::new(),
+            completed_runs: Mutex::new(HashMap::new()),
             app_handle: app_handle_clone,
         })
         .setup(move |app| {
-            logger.set_app_handle(app.handle().clone());
+            logger.set_sink(Arc::new(TauriEventSink::new(app.handle().clone())));
+            engagements::remind_expired_runs(app.handle(), &logger);
             if let Ok(mut handle) = app_handle.lock() {
                 *handle = Some(app.handle().clone());
             }
@@ -70,7 +232,22 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             ping,
             start_file_conversion,
-            get_logs
+            search_corpus,
+            get_corpus_stats,
+            rerun_finalize,
+            reprocess_entries,
+            resume_file_conversion,
+            pause_file_conversion,
+            continue_file_conversion,
+            verify_run,
+            load_settings_profile,
+            save_settings_profile,
+            engagements::create_engagement,
+            engagements::list_engagements,
+            engagements::list_expired_runs,
+            engagements::purge_expired,
+            get_logs,
+            get_current_progress
         ])
         .run(tauri::generate_context!())
         .expect("error while running auditor-tools application");
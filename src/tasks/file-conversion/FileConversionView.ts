@@ -1,6 +1,6 @@
 import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
-import { listen } from "@tauri-apps/api/event";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 
 export interface FileConversionViewOptions {
   onBackToDashboard: () => void;
@@ -215,7 +215,24 @@ export function renderFileConversionView(
     logPanel.scrollTop = logPanel.scrollHeight;
   };
 
+  // Backend event listeners, removed when leaving the view so they don't
+  // pile up each time it is rendered again
+  const unlisteners: UnlistenFn[] = [];
+  let tornDown = false;
+  const trackListener = (unlisten: UnlistenFn) => {
+    if (tornDown) {
+      unlisten();
+    } else {
+      unlisteners.push(unlisten);
+    }
+  };
+  const teardown = () => {
+    tornDown = true;
+    unlisteners.splice(0).forEach((unlisten) => unlisten());
+  };
+
   backButton?.addEventListener("click", () => {
+    teardown();
     options.onBackToDashboard();
   });
 
@@ -242,9 +259,7 @@ export function renderFileConversionView(
       // Add log entry immediately for real-time display
       allLogs.push(entry);
       updateLogDisplay();
-    }).then(() => {
-      // Listener set up successfully
-    }).catch((error) => {
+    }).then(trackListener).catch((error) => {
       console.error("Failed to set up log listener:", error);
       appendLog(`Failed to set up log listener: ${error}`, "ERROR");
     });
@@ -317,6 +332,52 @@ export function renderFileConversionView(
     }
   });
 
+  interface FileConversionResult {
+    run_id: string;
+    status: string;
+    staging_path: string | null;
+    llm_output_path: string | null;
+    report_path: string | null;
+    search_index_path: string | null;
+  }
+  interface FileConversionFailure {
+    run_id: string;
+    error: string;
+  }
+
+  // Run currently in flight; completion events for other runs are ignored
+  let activeRunId: string | null = null;
+  // A run can finish or fail before start_file_conversion returns its ID,
+  // so runs that end while the ID is awaited are kept until it arrives
+  let awaitingRunId = false;
+  const endedEarly = new Set<string>();
+
+  const finishRun = () => {
+    activeRunId = null;
+    if (startButton) startButton.disabled = false;
+  };
+
+  const handleRunEnded = (runId: string) => {
+    if (runId === activeRunId) {
+      finishRun();
+    } else if (awaitingRunId) {
+      endedEarly.add(runId);
+    }
+  };
+
+  listen<FileConversionResult>("file-conversion-complete", (event) => {
+    handleRunEnded(event.payload.run_id);
+  }).then(trackListener).catch((error) => {
+    console.error("Failed to set up completion listener:", error);
+  });
+
+  listen<FileConversionFailure>("file-conversion-failed", (event) => {
+    // The backend already logs the failure via log-entry events
+    handleRunEnded(event.payload.run_id);
+  }).then(trackListener).catch((error) => {
+    console.error("Failed to set up failure listener:", error);
+  });
+
   startButton?.addEventListener("click", async () => {
     if (!inputField) return;
     const value = inputField.value.trim();
@@ -335,20 +396,29 @@ export function renderFileConversionView(
     try {
       // Adapter command; backend is responsible for delegating to the legacy
       // File Conversion logic without duplicating it.
-      // All logs will come through the event listener in real-time
-      await invoke("start_file_conversion", { inputPath: value });
-      // Backend will send completion log via event
+      // The command returns a run ID immediately; completion arrives via
+      // file-conversion-complete / file-conversion-failed events.
+      awaitingRunId = true;
+      const runId = await invoke<string>("start_file_conversion", {
+        inputPath: value,
+      });
+      awaitingRunId = false;
+      if (endedEarly.has(runId)) {
+        finishRun();
+      } else {
+        activeRunId = runId;
+      }
+      endedEarly.clear();
     } catch (error) {
+      awaitingRunId = false;
+      endedEarly.clear();
       // Only log errors that aren't already logged by backend
       const errorMsg = error instanceof Error ? error.message : String(error);
       appendLog(
         `Conversion failed: ${errorMsg}`,
         "ERROR"
       );
-    } finally {
       startButton.disabled = false;
     }
   });
 }
-
-